@@ -1,77 +1,470 @@
 //! 调用 ASR 语音转写 API 的封装，支持自定义 API 地址和模型。
 
+use crate::tr;
 use anyhow::{anyhow, Result};
 use reqwest::{Client, StatusCode};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::collections::HashMap;
 use std::path::Path;
+use std::time::Duration;
 use tokio::fs::File;
 use tokio_util::codec::{BytesCodec, FramedRead};
 
+/// 建立连接的超时，与整个请求的超时分开，避免网络不通时长时间等待。
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+/// 校验 API 密钥的请求超时，校验只是轻量请求，不应长时间拖住扫描的启动。
+const KEY_CHECK_TIMEOUT: Duration = Duration::from_secs(15);
+/// 服务端建议的等待时间上限，异常的超大值按此截断。
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(3600);
+
+/// 转写接口的协议类型，决定请求参数与响应解析方式。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ApiFlavor {
+    /// SiliconFlow 风格：只返回整段文本。
+    #[default]
+    #[serde(rename = "siliconflow")]
+    SiliconFlow,
+    /// OpenAI Whisper 兼容接口（whisper.cpp、faster-whisper 等）：请求 `verbose_json` 以获取分段时间戳。
+    #[serde(rename = "openai_whisper")]
+    OpenAiWhisper,
+}
+
+impl ApiFlavor {
+    pub const ALL: [ApiFlavor; 2] = [ApiFlavor::SiliconFlow, ApiFlavor::OpenAiWhisper];
+}
+
+impl std::fmt::Display for ApiFlavor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ApiFlavor::SiliconFlow => write!(f, "SiliconFlow"),
+            ApiFlavor::OpenAiWhisper => f.write_str(tr!("OpenAI Whisper 兼容")),
+        }
+    }
+}
+
+/// 转写接口的错误类别，调用方据此区分鉴权失败、限流、网络与解析错误；`Display` 输出面向用户的日志文本。
+#[derive(Debug)]
+pub enum ApiError {
+    /// HTTP 401/403：密钥无效、过期或无权访问该模型，重试其他文件也不会成功。
+    Unauthorized { message: String },
+    /// HTTP 429 限流；`retry_after` 为服务端通过 `Retry-After` 头或响应体给出的等待时间。
+    RateLimited {
+        retry_after: Option<Duration>,
+        message: String,
+    },
+    /// 其他非成功的 HTTP 状态及原始响应体。
+    ServerError { status: StatusCode, body: String },
+    /// 连接、超时等网络错误。
+    Network(reqwest::Error),
+    /// 响应体无法解析，或请求参数（如 MIME 类型）无效。
+    Parse(String),
+    /// 读取待上传的音频文件失败。
+    Io(std::io::Error),
+}
+
+impl ApiError {
+    /// 按状态码归类错误响应，`retry_after_header` 为响应的 `Retry-After` 头。
+    fn from_response(status: StatusCode, retry_after_header: Option<&str>, body: &str) -> Self {
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            ApiError::RateLimited {
+                retry_after: parse_retry_after(retry_after_header, body),
+                message: format_api_error(status, body),
+            }
+        } else if is_auth_rejected(status) {
+            ApiError::Unauthorized {
+                message: format_api_error(status, body),
+            }
+        } else {
+            ApiError::ServerError {
+                status,
+                body: body.to_string(),
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ApiError::RateLimited {
+                retry_after: Some(wait),
+                message,
+            } => write!(
+                f,
+                "{}",
+                tr!(
+                    "{}（服务端建议 {:.1} 秒后重试）",
+                    message,
+                    wait.as_secs_f64()
+                )
+            ),
+            ApiError::RateLimited { message, .. }
+            | ApiError::Unauthorized { message }
+            | ApiError::Parse(message) => write!(f, "{}", message),
+            ApiError::ServerError { status, body } => {
+                write!(f, "{}", format_api_error(*status, body))
+            }
+            ApiError::Network(err) => write!(f, "{}", err),
+            ApiError::Io(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for ApiError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ApiError::Network(err) => Some(err),
+            ApiError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for ApiError {
+    fn from(err: reqwest::Error) -> Self {
+        ApiError::Network(err)
+    }
+}
+
+impl From<std::io::Error> for ApiError {
+    fn from(err: std::io::Error) -> Self {
+        ApiError::Io(err)
+    }
+}
+
+/// 一次转写的结果：整段文本，以及服务端提供的分段时间戳（可能为空）。
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Transcription {
+    pub text: String,
+    /// 服务端报告的音频时长（秒），用于估算费用；未提供时为 `None`。
+    pub duration: Option<f64>,
+    /// 服务端返回的分段，时间相对于上传音频的开头；未提供时为空。
+    pub segments: Vec<TimedSegment>,
+    /// 服务端返回的逐词时间戳，只有请求了词级粒度且服务端支持时才非空。
+    pub words: Vec<TimedWord>,
+}
+
+/// 带起止时间（秒）的识别片段。
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct TimedSegment {
+    pub start: f64,
+    pub end: f64,
+    pub text: String,
+    /// 支持说话人分离的服务（如 WhisperX）返回的说话人标签，例如 `SPEAKER_00`。
+    #[serde(default)]
+    pub speaker: Option<String>,
+}
+
+/// 带起止时间（秒）的单个词，中文通常一个字一条。
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct TimedWord {
+    pub start: f64,
+    pub end: f64,
+    pub word: String,
+}
+
+/// OpenAI `verbose_json` 响应结构，只保留用到的字段。
+#[derive(Deserialize, Debug)]
+pub struct VerboseResponse {
+    #[serde(default)]
+    pub text: Option<String>,
+    #[serde(default)]
+    pub duration: Option<f64>,
+    #[serde(default)]
+    pub usage: Option<Usage>,
+    #[serde(default)]
+    pub segments: Vec<TimedSegment>,
+    #[serde(default)]
+    pub words: Vec<TimedWord>,
+}
+
 /// SiliconFlow 返回的成功响应结构。
 #[derive(Deserialize, Debug)]
 pub struct SuccessResponse {
-    /// 服务端返回的完整转写文本。
-    pub text: String,
+    /// 服务端返回的完整转写文本，部分服务在无结果时返回 `null` 或直接省略。
+    #[serde(default)]
+    pub text: Option<String>,
+    /// 部分服务直接返回的音频时长（秒）。
+    #[serde(default)]
+    pub duration: Option<f64>,
+    /// 计费用量信息，字段缺失时为 `None`。
+    #[serde(default)]
+    pub usage: Option<Usage>,
+}
+
+/// 响应中的计费用量，目前只关心按时长计费的秒数。
+#[derive(Deserialize, Debug, Default)]
+pub struct Usage {
+    #[serde(default)]
+    pub seconds: Option<f64>,
+}
+
+/// 优先使用 `duration`，其次使用 `usage.seconds`，忽略负数等无效值。
+fn reported_duration(duration: Option<f64>, usage: Option<&Usage>) -> Option<f64> {
+    duration
+        .or_else(|| usage.and_then(|u| u.seconds))
+        .filter(|secs| secs.is_finite() && *secs >= 0.0)
 }
 
-/// 上传单个音频文件并返回识别文本，自动推断常见 MIME 类型。
+/// 构造一次扫描共用的 HTTP 客户端；配置了代理时所有请求都经由该代理发出。
+pub fn build_client(proxy_url: Option<&str>) -> Result<Client> {
+    let mut builder = Client::builder().connect_timeout(CONNECT_TIMEOUT);
+    if let Some(url) = proxy_url {
+        let proxy = reqwest::Proxy::all(url).map_err(|e| anyhow!(tr!("代理地址无效：{}", e)))?;
+        builder = builder.proxy(proxy);
+    }
+    Ok(builder.build()?)
+}
+
+/// 按扩展名推断上传文件的 MIME 类型：`overrides`（键为小写、不带点的扩展名）优先，
+/// 未覆盖的扩展名使用内置表，无法识别时按 `audio/mpeg` 处理。
+pub fn upload_mime_type<'a>(file_path: &Path, overrides: &'a HashMap<String, String>) -> &'a str {
+    let ext = file_path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    if let Some(mime) = overrides.get(&ext) {
+        return mime;
+    }
+    match ext.as_str() {
+        "wav" => "audio/wav",
+        "ogg" | "opus" => "audio/ogg",
+        "mp3" => "audio/mpeg",
+        "m4a" => "audio/mp4",
+        "flac" => "audio/flac",
+        "aac" => "audio/aac",
+        _ => "audio/mpeg", // Fallback
+    }
+}
+
+/// 使用调用方共享的客户端上传单个音频文件并返回识别结果，MIME 类型见 [`upload_mime_type`]。
+///
+/// `word_timestamps` 时向 Whisper 兼容接口额外请求词级时间戳；其他接口忽略该参数。
+/// `timeout` 为 `None` 时不限制请求总时长，只保留建立连接的超时。
+#[allow(clippy::too_many_arguments)]
 pub async fn transcribe_file(
+    client: &Client,
     api_key: &str,
     api_url: &str,
     model_name: &str,
+    flavor: ApiFlavor,
+    timeout: Option<Duration>,
+    mime_overrides: &HashMap<String, String>,
+    word_timestamps: bool,
     file_path: &Path,
-) -> Result<String> {
-    let client = Client::new();
-
+) -> Result<Transcription, ApiError> {
     let file_name = file_path
         .file_name()
         .unwrap_or_default()
         .to_string_lossy()
         .to_string();
-
-    // 简易 MIME 类型检测
-    let mime_type = if let Some(ext) = file_path.extension() {
-        let ext_str = ext.to_string_lossy().to_lowercase();
-        match ext_str.as_str() {
-            "wav" => "audio/wav",
-            "ogg" | "opus" => "audio/ogg",
-            "mp3" => "audio/mpeg",
-            "m4a" => "audio/mp4",
-            _ => "audio/mpeg", // Fallback
-        }
-    } else {
-        "audio/mpeg"
-    };
+    let mime_type = upload_mime_type(file_path, mime_overrides);
 
     let file = File::open(file_path).await?;
     let stream = FramedRead::new(file, BytesCodec::new());
     let file_part = reqwest::multipart::Part::stream(reqwest::Body::wrap_stream(stream))
         .file_name(file_name)
-        .mime_str(mime_type)?;
+        .mime_str(mime_type)
+        .map_err(|e| {
+            ApiError::Parse(tr!("MIME 类型 {} 无效：{}", format!("{:?}", mime_type), e))
+        })?;
 
-    let form = reqwest::multipart::Form::new()
+    let mut form = reqwest::multipart::Form::new()
         .text("model", model_name.to_string())
         .part("file", file_part);
+    if flavor == ApiFlavor::OpenAiWhisper {
+        form = form.text("response_format", "verbose_json");
+        if word_timestamps {
+            form = form
+                .text("timestamp_granularities[]", "word")
+                .text("timestamp_granularities[]", "segment");
+        }
+    }
 
-    let response = client
+    let mut request = client
         .post(api_url)
         .header("Authorization", format!("Bearer {}", api_key))
-        .multipart(form)
-        .timeout(std::time::Duration::from_secs(3600)) // 大文件需要更长超时
-        .send()
-        .await?;
+        .multipart(form);
+    if let Some(timeout) = timeout {
+        request = request.timeout(timeout);
+    }
+    let response = request.send().await?;
 
     let status = response.status();
+    let retry_after = response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
     let text = response.text().await?;
 
     if status.is_success() {
-        return serde_json::from_str::<SuccessResponse>(&text)
-            .map(|succ| succ.text)
-            .map_err(|_| anyhow!("解析成功响应失败：{}", text));
+        return match flavor {
+            ApiFlavor::SiliconFlow => parse_success_body(&text),
+            ApiFlavor::OpenAiWhisper => parse_verbose_body(&text),
+        };
+    }
+
+    Err(ApiError::from_response(
+        status,
+        retry_after.as_deref(),
+        &text,
+    ))
+}
+
+/// 用一次轻量的鉴权请求（GET 同一服务的模型列表）校验 API 密钥，不上传任何音频。
+///
+/// 只有 HTTP 401/403 视为密钥被拒绝并返回 `Ok(false)`；其余状态（如未提供模型列表的
+/// 自建服务返回的 404）无法据此判断，按可用处理。网络错误返回 `Err`。
+pub async fn validate_api_key(client: &Client, api_key: &str, api_url: &str) -> Result<bool> {
+    let response = client
+        .get(models_url(api_url))
+        .header("Authorization", format!("Bearer {}", api_key))
+        .timeout(KEY_CHECK_TIMEOUT)
+        .send()
+        .await?;
+    Ok(!is_auth_rejected(response.status()))
+}
+
+/// 由转写地址推导模型列表地址：`…/v1/audio/transcriptions` → `…/v1/models`。
+/// 无法识别的地址原样返回，服务端通常也会先校验鉴权。
+fn models_url(api_url: &str) -> String {
+    let trimmed = api_url.trim().trim_end_matches('/');
+    match trimmed.strip_suffix("/audio/transcriptions") {
+        Some(base) => format!("{}/models", base),
+        None => trimmed.to_string(),
+    }
+}
+
+fn is_auth_rejected(status: StatusCode) -> bool {
+    matches!(status, StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN)
+}
+
+/// 解析 HTTP 成功响应体；缺失或为 `null` 的 `text` 视为空结果，交由调用方按空结果处理。
+fn parse_success_body(body: &str) -> Result<Transcription, ApiError> {
+    serde_json::from_str::<SuccessResponse>(body)
+        .map(|succ| Transcription {
+            duration: reported_duration(succ.duration, succ.usage.as_ref()),
+            text: succ.text.unwrap_or_default(),
+            segments: Vec::new(),
+            words: Vec::new(),
+        })
+        .map_err(|_| invalid_response(body))
+}
+
+/// 解析 `verbose_json` 响应，丢弃空白或时间无效的分段与词。
+fn parse_verbose_body(body: &str) -> Result<Transcription, ApiError> {
+    let response =
+        serde_json::from_str::<VerboseResponse>(body).map_err(|_| invalid_response(body))?;
+    let segments = response
+        .segments
+        .into_iter()
+        .filter(|seg| !seg.text.trim().is_empty() && seg.end >= seg.start && seg.start >= 0.0)
+        .map(|seg| TimedSegment {
+            text: seg.text.trim().to_string(),
+            ..seg
+        })
+        .collect();
+    let words = response
+        .words
+        .into_iter()
+        .filter(|word| !word.word.trim().is_empty() && word.end >= word.start && word.start >= 0.0)
+        .collect();
+    Ok(Transcription {
+        duration: reported_duration(response.duration, response.usage.as_ref()),
+        text: response.text.unwrap_or_default(),
+        segments,
+        words,
+    })
+}
+
+fn invalid_response(body: &str) -> ApiError {
+    ApiError::Parse(tr!("解析成功响应失败：{}", body))
+}
+
+/// 限流响应建议的等待时间：优先读取 `Retry-After` 头（秒数），其次是响应体 JSON 中的
+/// `retry_after`/`retryAfter`（秒）或 `retry_after_ms`（毫秒，可位于 `error`、`data` 内），
+/// 最后在错误文本中查找 “retry after 20s”“try again in 1.5 seconds”“20 秒后” 这类提示。
+fn parse_retry_after(header: Option<&str>, body: &str) -> Option<Duration> {
+    let valid = |secs: f64| {
+        (secs.is_finite() && secs >= 0.0)
+            .then(|| Duration::try_from_secs_f64(secs).unwrap_or(MAX_RETRY_AFTER))
+            .map(|wait| wait.min(MAX_RETRY_AFTER))
+    };
+    if let Some(secs) = header.and_then(|value| value.trim().parse::<f64>().ok()) {
+        return valid(secs);
+    }
+    if let Ok(value) = serde_json::from_str::<Value>(body) {
+        if let Some(secs) = retry_after_from_json(&value) {
+            return valid(secs);
+        }
+        if let Some(secs) = retry_after_from_text(&value.to_string()) {
+            return valid(secs);
+        }
+        return None;
+    }
+    retry_after_from_text(body).and_then(valid)
+}
+
+/// 在 JSON 对象（及其 `error`、`data` 子对象）中查找等待秒数。
+fn retry_after_from_json(value: &Value) -> Option<f64> {
+    let number = |v: &Value| v.as_f64().or_else(|| v.as_str()?.trim().parse().ok());
+    let obj = value.as_object()?;
+    obj.get("retry_after")
+        .or_else(|| obj.get("retryAfter"))
+        .and_then(number)
+        .or_else(|| {
+            obj.get("retry_after_ms")
+                .and_then(number)
+                .map(|ms| ms / 1000.0)
+        })
+        .or_else(|| {
+            ["error", "data"]
+                .iter()
+                .find_map(|key| obj.get(*key).and_then(retry_after_from_json))
+        })
+}
+
+/// 在错误文本中查找等待时间提示，单位缺省为秒。
+fn retry_after_from_text(text: &str) -> Option<f64> {
+    let lower = text.to_lowercase();
+    for marker in ["retry after", "try again in", "retry in"] {
+        if let Some(pos) = lower.find(marker) {
+            if let Some(secs) = leading_duration(&lower[pos + marker.len()..]) {
+                return Some(secs);
+            }
+        }
     }
+    let pos = lower.find("秒后")?;
+    let digits: String = lower[..pos]
+        .trim_end()
+        .chars()
+        .rev()
+        .take_while(|c| c.is_ascii_digit() || *c == '.')
+        .collect::<Vec<_>>()
+        .into_iter()
+        .rev()
+        .collect();
+    digits.parse().ok()
+}
 
-    Err(anyhow!(format_api_error(status, &text)))
+/// 解析文本开头形如 `20s`、`1.5 seconds`、`500ms`、`2 minutes` 的时长（秒）。
+fn leading_duration(text: &str) -> Option<f64> {
+    let text = text.trim_start();
+    let end = text
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(text.len());
+    let value: f64 = text[..end].parse().ok()?;
+    let unit = text[end..].trim_start();
+    let scale = if unit.starts_with("ms") || unit.starts_with("millisecond") {
+        0.001
+    } else if unit.starts_with("min") || unit.starts_with('m') && !unit.starts_with("ms") {
+        60.0
+    } else {
+        1.0
+    };
+    Some(value * scale)
 }
 
 /// 将 API 错误响应格式化为易读的日志文本。
@@ -83,10 +476,10 @@ fn format_api_error(status: StatusCode, body: &str) -> String {
             let data = obj.get("data").and_then(|v| v.as_str());
 
             if code.is_some() || message.is_some() || data.is_some() {
-                return format!(
-                    "API 错误（HTTP {}，code {:?}）：{} {}",
+                return tr!(
+                    "API 错误（HTTP {}，code {}）：{} {}",
                     status,
-                    code,
+                    format!("{:?}", code),
                     message.unwrap_or(""),
                     data.unwrap_or("")
                 )
@@ -94,14 +487,232 @@ fn format_api_error(status: StatusCode, body: &str) -> String {
                 .to_string();
             }
         } else if let Some(text) = value.as_str() {
-            return format!("API 错误（HTTP {}）：{}", status, text);
+            return tr!("API 错误（HTTP {}）：{}", status, text);
         }
     }
 
     // 429 specific plain message
     if status == StatusCode::TOO_MANY_REQUESTS {
-        return format!("已被限流（HTTP 429）：{}", body);
+        return tr!("已被限流（HTTP 429）：{}", body);
+    }
+
+    tr!("API 错误（HTTP {}）：{}", status, body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_check_uses_models_endpoint_and_only_rejects_auth_errors() {
+        assert_eq!(
+            models_url("https://api.siliconflow.cn/v1/audio/transcriptions"),
+            "https://api.siliconflow.cn/v1/models"
+        );
+        assert_eq!(
+            models_url(" http://127.0.0.1:8000/v1/audio/transcriptions/ "),
+            "http://127.0.0.1:8000/v1/models"
+        );
+        assert_eq!(models_url("http://asr.local/run"), "http://asr.local/run");
+
+        assert!(is_auth_rejected(StatusCode::UNAUTHORIZED));
+        assert!(is_auth_rejected(StatusCode::FORBIDDEN));
+        assert!(!is_auth_rejected(StatusCode::OK));
+        assert!(!is_auth_rejected(StatusCode::NOT_FOUND));
+    }
+
+    #[test]
+    fn success_body_with_text() {
+        let result = parse_success_body(r#"{"text": "你好"}"#).unwrap();
+        assert_eq!(result.text, "你好");
+        assert_eq!(result.duration, None);
+    }
+
+    #[test]
+    fn success_body_without_text_is_empty() {
+        assert_eq!(
+            parse_success_body(r#"{"error": null, "text": null}"#)
+                .unwrap()
+                .text,
+            ""
+        );
+        assert_eq!(parse_success_body("{}").unwrap().text, "");
+    }
+
+    #[test]
+    fn verbose_body_yields_trimmed_segments() {
+        let body = r#"{
+            "text": "你好。世界。",
+            "language": "zh",
+            "segments": [
+                {"id": 0, "start": 0.0, "end": 1.5, "text": " 你好。"},
+                {"id": 1, "start": 1.5, "end": 1.5, "text": "  "},
+                {"id": 2, "start": 1.8, "end": 3.2, "text": "世界。"}
+            ]
+        }"#;
+        let result = parse_verbose_body(body).unwrap();
+        assert_eq!(result.text, "你好。世界。");
+        assert_eq!(
+            result.segments,
+            vec![
+                TimedSegment {
+                    start: 0.0,
+                    end: 1.5,
+                    text: "你好。".to_string(),
+                    speaker: None,
+                },
+                TimedSegment {
+                    start: 1.8,
+                    end: 3.2,
+                    text: "世界。".to_string(),
+                    speaker: None,
+                },
+            ]
+        );
+        assert!(parse_verbose_body(r#"{"text": "仅文本"}"#)
+            .unwrap()
+            .segments
+            .is_empty());
     }
 
-    format!("API 错误（HTTP {}）：{}", status, body)
+    #[test]
+    fn verbose_body_keeps_word_timestamps() {
+        let body = r#"{
+            "text": "Hello world",
+            "words": [
+                {"word": "Hello", "start": 0.0, "end": 0.4},
+                {"word": " ", "start": 0.4, "end": 0.5},
+                {"word": "world", "start": 0.5, "end": 0.9}
+            ]
+        }"#;
+        let words = parse_verbose_body(body).unwrap().words;
+        assert_eq!(
+            words,
+            vec![
+                TimedWord {
+                    start: 0.0,
+                    end: 0.4,
+                    word: "Hello".to_string(),
+                },
+                TimedWord {
+                    start: 0.5,
+                    end: 0.9,
+                    word: "world".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn reported_duration_prefers_duration_then_usage() {
+        let body = r#"{"text": "好", "duration": 12.5, "usage": {"seconds": 13}}"#;
+        assert_eq!(parse_success_body(body).unwrap().duration, Some(12.5));
+        let body = r#"{"text": "好", "usage": {"type": "duration", "seconds": 13}}"#;
+        assert_eq!(parse_success_body(body).unwrap().duration, Some(13.0));
+        let body = r#"{"text": "好", "usage": {"total_tokens": 40}}"#;
+        assert_eq!(parse_success_body(body).unwrap().duration, None);
+        let body = r#"{"text": "好", "duration": 3.0, "segments": []}"#;
+        assert_eq!(parse_verbose_body(body).unwrap().duration, Some(3.0));
+    }
+
+    #[test]
+    fn client_builds_with_and_without_proxy() {
+        assert!(build_client(None).is_ok());
+        assert!(build_client(Some("http://127.0.0.1:7890")).is_ok());
+    }
+
+    #[test]
+    fn malformed_success_body_is_error() {
+        let err = parse_success_body("<html>502 Bad Gateway</html>").unwrap_err();
+        assert!(err.to_string().contains("解析成功响应失败"));
+        assert!(parse_success_body(r#"{"text": 42}"#).is_err());
+    }
+
+    #[test]
+    fn mime_overrides_take_precedence_over_defaults() {
+        let mut overrides = HashMap::new();
+        assert_eq!(
+            upload_mime_type(Path::new("a.OPUS"), &overrides),
+            "audio/ogg"
+        );
+        assert_eq!(
+            upload_mime_type(Path::new("a.wav"), &overrides),
+            "audio/wav"
+        );
+        assert_eq!(upload_mime_type(Path::new("a"), &overrides), "audio/mpeg");
+
+        overrides.insert("opus".to_string(), "audio/opus".to_string());
+        assert_eq!(
+            upload_mime_type(Path::new("a.Opus"), &overrides),
+            "audio/opus"
+        );
+        assert_eq!(
+            upload_mime_type(Path::new("a.ogg"), &overrides),
+            "audio/ogg"
+        );
+    }
+
+    #[test]
+    fn rate_limit_errors_carry_the_wait_hint() {
+        let error = ApiError::from_response(StatusCode::TOO_MANY_REQUESTS, Some("12"), "busy");
+        assert!(matches!(
+            &error,
+            ApiError::RateLimited { retry_after: Some(wait), message }
+                if *wait == Duration::from_secs(12) && message == "已被限流（HTTP 429）：busy"
+        ));
+        assert!(error.to_string().contains("12.0 秒后重试"));
+
+        let body =
+            r#"{"code": 50603, "message": "System is too busy", "data": {"retry_after": 2.5}}"#;
+        assert_eq!(
+            parse_retry_after(None, body),
+            Some(Duration::from_millis(2500))
+        );
+        let body = r#"{"error": {"message": "Rate limit reached", "retry_after_ms": 800}}"#;
+        assert_eq!(
+            parse_retry_after(None, body),
+            Some(Duration::from_millis(800))
+        );
+        let body = r#"{"message": "Rate limit reached. Please try again in 1.5s."}"#;
+        assert_eq!(
+            parse_retry_after(None, body),
+            Some(Duration::from_millis(1500))
+        );
+        assert_eq!(
+            parse_retry_after(None, "请求过于频繁，请 30 秒后重试"),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(
+            parse_retry_after(None, "Retry after 2 minutes"),
+            Some(Duration::from_secs(120))
+        );
+        assert_eq!(parse_retry_after(None, "slow down"), None);
+        assert_eq!(
+            parse_retry_after(None, r#"{"retry_after": 1e30}"#),
+            Some(MAX_RETRY_AFTER)
+        );
+        assert_eq!(parse_retry_after(Some("7200"), ""), Some(MAX_RETRY_AFTER));
+    }
+
+    #[test]
+    fn api_errors_are_classified_by_status() {
+        assert!(matches!(
+            ApiError::from_response(
+                StatusCode::UNAUTHORIZED,
+                None,
+                r#"{"message": "Invalid token"}"#
+            ),
+            ApiError::Unauthorized { .. }
+        ));
+        assert!(matches!(
+            ApiError::from_response(StatusCode::BAD_REQUEST, None, "bad"),
+            ApiError::ServerError {
+                status: StatusCode::BAD_REQUEST,
+                ..
+            }
+        ));
+        let err = parse_success_body("<html>502</html>").unwrap_err();
+        assert!(matches!(err, ApiError::Parse(_)));
+        assert_eq!(err.to_string(), "解析成功响应失败：<html>502</html>");
+    }
 }
@@ -0,0 +1,406 @@
+//! 无界面的命令行模式，供没有图形环境的 NAS、服务器或 cron 任务使用。
+//!
+//! 带 `--scan <目录>` 启动时完全绕过 Iced：读取已保存的配置，用命令行参数覆盖后
+//! 直接调用 [`process_directories`]，日志实时输出到标准输出。
+
+use crate::api::{build_client, validate_api_key};
+use crate::config::{self, AppConfig};
+use crate::i18n;
+use crate::log_file;
+use crate::scanner::{
+    display_path, process_directories, validate_export_bitrate, validate_include_patterns,
+    validate_text_replacements, validate_track_selection, validate_vad_format, RunReport,
+    ScanEvent, ScanLog, ScanLogLevel, SegmentMode,
+};
+use crate::tr;
+use anyhow::{anyhow, bail, Result};
+use chrono::{Datelike, Local, NaiveDateTime, NaiveTime};
+use std::path::PathBuf;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+/// `--help` 输出的用法说明。
+pub const USAGE: &str = "\
+用法：autoasr [--scan <目录> [选项]]
+
+不带 --scan 时启动图形界面。
+
+选项：
+  --scan <目录>        扫描并转写该目录后退出（无界面模式），可重复指定多个目录
+  --api-key <密钥>     API 密钥，未提供时使用配置文件中的值
+  --model <名称>       ASR 模型名称
+  --vad                启用 VAD 分段
+  --no-vad             整段上传，不做分段
+  --threshold <0-1>    VAD 语音概率阈值
+  --schedule <HH:MM>   按时间每天执行，可重复或用逗号分隔多个时间；
+                       提供后进程常驻，按 Ctrl+C 退出
+  -h, --help           显示本说明";
+
+/// 解析后的启动方式。
+#[derive(Debug, PartialEq)]
+pub enum CliCommand {
+    /// 未指定 `--scan`，正常启动图形界面。
+    Gui,
+    /// 输出用法说明后退出。
+    Help,
+    /// 无界面扫描。
+    Scan(CliArgs),
+}
+
+/// 无界面模式的参数，未提供的项沿用配置文件。
+#[derive(Debug, Default, PartialEq)]
+pub struct CliArgs {
+    pub directories: Vec<PathBuf>,
+    pub api_key: Option<String>,
+    pub model: Option<String>,
+    /// `--vad` 为 `Some(true)`，`--no-vad` 为 `Some(false)`，均未提供时沿用配置。
+    pub vad: Option<bool>,
+    pub threshold: Option<f32>,
+    pub schedule: Vec<String>,
+}
+
+/// 解析命令行参数（不含程序名）。
+pub fn parse_args<I>(args: I) -> Result<CliCommand>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut args = args.into_iter();
+    let mut parsed = CliArgs::default();
+    let mut any_option = false;
+
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| {
+            args.next()
+                .ok_or_else(|| anyhow!(tr!("参数 {} 缺少取值。", name)))
+        };
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(CliCommand::Help),
+            "--scan" => parsed.directories.push(PathBuf::from(value("--scan")?)),
+            "--api-key" => parsed.api_key = Some(value("--api-key")?),
+            "--model" => parsed.model = Some(value("--model")?),
+            "--vad" => parsed.vad = Some(true),
+            "--no-vad" => parsed.vad = Some(false),
+            "--threshold" => {
+                let raw = value("--threshold")?;
+                let threshold: f32 = raw.parse().map_err(|_| {
+                    anyhow!(tr!("--threshold 必须是数字：{}", format!("{:?}", raw)))
+                })?;
+                if !(0.0..=1.0).contains(&threshold) {
+                    bail!(tr!("--threshold 必须在 0 到 1 之间：{}", threshold));
+                }
+                parsed.threshold = Some(threshold);
+            }
+            "--schedule" => parsed.schedule.extend(
+                value("--schedule")?
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|time| !time.is_empty())
+                    .map(str::to_string),
+            ),
+            other => bail!(tr!("未知参数：{}", other)),
+        }
+        any_option = true;
+    }
+
+    if !parsed.directories.is_empty() {
+        Ok(CliCommand::Scan(parsed))
+    } else if any_option {
+        bail!(tr!("命令行模式需要通过 --scan 指定目录。"))
+    } else {
+        Ok(CliCommand::Gui)
+    }
+}
+
+impl CliArgs {
+    /// 用命令行参数覆盖配置文件中的对应项。
+    fn apply(&self, config: &mut AppConfig) {
+        config.directories = self
+            .directories
+            .iter()
+            .map(|dir| dir.to_string_lossy().to_string())
+            .collect();
+        if let Some(key) = &self.api_key {
+            config.api_key = key.clone();
+        }
+        if let Some(model) = &self.model {
+            config.model_name = model.clone();
+        }
+        match self.vad {
+            Some(true) => config.segment_mode = SegmentMode::Vad,
+            Some(false) => config.segment_mode = SegmentMode::Whole,
+            None => {}
+        }
+        if let Some(threshold) = self.threshold {
+            config.vad_threshold = threshold;
+        }
+        if !self.schedule.is_empty() {
+            config.schedule_times = self.schedule.clone();
+        }
+    }
+}
+
+/// 运行无界面模式：单次扫描完成后返回；指定 `--schedule` 时常驻直到 Ctrl+C。
+pub fn run(args: CliArgs) -> Result<()> {
+    let (mut config, load_error) = AppConfig::load_or_default();
+    if let Some(note) = load_error {
+        print_log(ScanLogLevel::Error, note);
+    }
+    args.apply(&mut config);
+    i18n::set_language(config.language);
+
+    if !args.directories.iter().any(|dir| dir.is_dir()) {
+        bail!(tr!("指定的目录均不存在：{}", join_paths(&args.directories)));
+    }
+    if config.api_key.trim().is_empty() {
+        bail!(tr!("需要通过 --api-key 或配置文件提供 API 密钥。"));
+    }
+    config::validate_api_url(&config.api_url)?;
+    if let Some(proxy) = config.proxy_url() {
+        config::validate_proxy_url(&proxy)?;
+    }
+    validate_export_bitrate(&config.export_bitrate)?;
+    validate_track_selection(&config.track_selection)?;
+    validate_include_patterns(&config.include_patterns)?;
+    validate_text_replacements(&config.text_replacements)?;
+    validate_vad_format(config.vad_sample_rate, config.vad_chunk_size)?;
+    let times = if args.schedule.is_empty() {
+        None
+    } else {
+        Some(config.parsed_schedule_times()?)
+    };
+
+    let runtime = tokio::runtime::Runtime::new()?;
+    runtime.block_on(async move {
+        let cancel = CancellationToken::new();
+        let ctrl_c = cancel.clone();
+        tokio::spawn(async move {
+            if tokio::signal::ctrl_c().await.is_ok() {
+                ctrl_c.cancel();
+            }
+        });
+
+        let client = build_client(config.proxy_url().as_deref())?;
+        match validate_api_key(&client, &config.api_key, config.api_url.trim()).await {
+            Ok(true) => {}
+            Ok(false) => bail!(tr!(
+                "API 密钥被服务端拒绝（HTTP 401/403），请检查密钥后重试。"
+            )),
+            Err(e) => print_log(
+                ScanLogLevel::Info,
+                tr!("无法校验 API 密钥：{}，仍继续执行。", e),
+            ),
+        }
+
+        let Some(times) = times else {
+            let report = scan_once(&args.directories, &config, cancel).await?;
+            if !report.failed.is_empty() {
+                bail!(tr!("{} 个文件转写失败。", report.failed.len()));
+            }
+            return Ok(());
+        };
+
+        loop {
+            let now = Local::now().naive_local();
+            let next = next_run(&config, &times, now)
+                .ok_or_else(|| anyhow!(tr!("未来一周内没有可执行的定时时间。")))?;
+            print_log(ScanLogLevel::Info, tr!("下一次扫描：{}", next));
+            let wait = (next - now).to_std().unwrap_or_default();
+            tokio::select! {
+                _ = tokio::time::sleep(wait) => {}
+                _ = cancel.cancelled() => return Ok(()),
+            }
+            // 单次扫描失败只记录，不结束常驻进程。
+            if let Err(e) = scan_once(&args.directories, &config, cancel.clone()).await {
+                print_log(ScanLogLevel::Error, tr!("扫描过程中出现错误：{}", e));
+            }
+            if cancel.is_cancelled() {
+                return Ok(());
+            }
+        }
+    })
+}
+
+/// 扫描一次全部目录，实时打印日志并在结束时输出汇总。
+async fn scan_once(
+    dirs: &[PathBuf],
+    config: &AppConfig,
+    cancel: CancellationToken,
+) -> Result<RunReport> {
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let printer = tokio::spawn(async move {
+        while let Some(event) = rx.recv().await {
+            if let ScanEvent::Log(log) = event {
+                print!("{}", log_file::format_line(Local::now(), &log));
+            }
+        }
+    });
+
+    print_log(ScanLogLevel::Info, tr!("开始扫描 {}……", join_paths(dirs)));
+    let result = process_directories(dirs.to_vec(), config.scanner_options(cancel), Some(tx)).await;
+    let _ = printer.await;
+    let report = result?;
+
+    let headline = if report.cancelled {
+        tr!("扫描已取消")
+    } else {
+        tr!("扫描流程完成")
+    };
+    print_log(
+        ScanLogLevel::Success,
+        tr!(
+            "{}：成功 {}，失败 {}，跳过 {}，共 {} 个分段，用时 {} 秒。",
+            headline,
+            report.succeeded.len(),
+            report.failed.len(),
+            report.skipped,
+            report.total_segments,
+            report.elapsed.as_secs()
+        ),
+    );
+    Ok(report)
+}
+
+/// 以界面一致的形式列出多个目录，避免 Debug 输出在 Windows 上双写反斜杠。
+fn join_paths(paths: &[PathBuf]) -> String {
+    paths
+        .iter()
+        .map(|path| display_path(path).into_owned())
+        .collect::<Vec<_>>()
+        .join("、")
+}
+
+fn print_log(level: ScanLogLevel, message: impl Into<String>) {
+    print!(
+        "{}",
+        log_file::format_line(Local::now(), &ScanLog::new(level, message))
+    );
+}
+
+/// 计算 `now` 之后最近的一次执行时间，遵循配置中的执行星期。
+fn next_run(config: &AppConfig, times: &[NaiveTime], now: NaiveDateTime) -> Option<NaiveDateTime> {
+    (0..=7)
+        .filter_map(|offset| now.date().checked_add_days(chrono::Days::new(offset)))
+        .filter(|date| config.runs_on(date.weekday()))
+        .flat_map(|date| times.iter().map(move |time| date.and_time(*time)))
+        .filter(|candidate| *candidate > now)
+        .min()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveDate, Weekday};
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn parses_scan_flags_and_falls_back_to_gui() {
+        assert_eq!(parse_args(args(&[])).unwrap(), CliCommand::Gui);
+        assert_eq!(parse_args(args(&["--help"])).unwrap(), CliCommand::Help);
+
+        let parsed = parse_args(args(&[
+            "--scan",
+            "/media",
+            "--scan",
+            "/shows",
+            "--api-key",
+            "sk-1",
+            "--vad",
+            "--threshold",
+            "0.4",
+            "--model",
+            "m",
+            "--schedule",
+            "02:00, 14:30",
+            "--schedule",
+            "20:00",
+        ]))
+        .unwrap();
+        assert_eq!(
+            parsed,
+            CliCommand::Scan(CliArgs {
+                directories: vec![PathBuf::from("/media"), PathBuf::from("/shows")],
+                api_key: Some("sk-1".into()),
+                model: Some("m".into()),
+                vad: Some(true),
+                threshold: Some(0.4),
+                schedule: args(&["02:00", "14:30", "20:00"]),
+            })
+        );
+
+        assert!(matches!(
+            parse_args(args(&["--scan", "/m", "--vad", "--no-vad"])).unwrap(),
+            CliCommand::Scan(CliArgs {
+                vad: Some(false),
+                ..
+            })
+        ));
+        assert!(parse_args(args(&["--vad"])).is_err());
+        assert!(parse_args(args(&["--scan"])).is_err());
+        assert!(parse_args(args(&["--scan", "/m", "--threshold", "2"])).is_err());
+        assert!(parse_args(args(&["--scan", "/m", "--bogus"])).is_err());
+    }
+
+    #[test]
+    fn cli_args_override_config() {
+        let mut config = AppConfig::default();
+        let cli = CliArgs {
+            directories: vec![PathBuf::from("/media")],
+            api_key: Some("sk-1".into()),
+            vad: Some(true),
+            threshold: Some(0.3),
+            ..CliArgs::default()
+        };
+        let model = config.model_name.clone();
+        cli.apply(&mut config);
+        assert_eq!(config.directories, vec!["/media".to_string()]);
+        assert_eq!(config.api_key, "sk-1");
+        assert_eq!(config.segment_mode, SegmentMode::Vad);
+        assert_eq!(config.vad_threshold, 0.3);
+        assert_eq!(config.model_name, model);
+
+        let whole = CliArgs {
+            directories: vec![PathBuf::from("/media")],
+            vad: Some(false),
+            ..CliArgs::default()
+        };
+        whole.apply(&mut config);
+        assert_eq!(config.segment_mode, SegmentMode::Whole);
+        assert_eq!(join_paths(&whole.directories), "/media");
+    }
+
+    #[test]
+    fn next_run_respects_times_and_weekdays() {
+        let mut config = AppConfig::default();
+        let times = [
+            NaiveTime::from_hms_opt(2, 0, 0).unwrap(),
+            NaiveTime::from_hms_opt(14, 30, 0).unwrap(),
+        ];
+        // 2024-05-01 是星期三。
+        let now = NaiveDate::from_ymd_opt(2024, 5, 1)
+            .unwrap()
+            .and_hms_opt(10, 0, 0)
+            .unwrap();
+        let day = |d| NaiveDate::from_ymd_opt(2024, 5, d).unwrap();
+
+        assert_eq!(
+            next_run(&config, &times, now),
+            Some(day(1).and_time(times[1]))
+        );
+
+        config.schedule_weekdays = vec![Weekday::Mon];
+        assert_eq!(
+            next_run(&config, &times, now),
+            Some(day(6).and_time(times[0]))
+        );
+
+        config.schedule_weekdays = vec![Weekday::Wed];
+        let late = day(1).and_hms_opt(15, 0, 0).unwrap();
+        assert_eq!(
+            next_run(&config, &times, late),
+            Some(day(8).and_time(times[0]))
+        );
+    }
+}
@@ -0,0 +1,647 @@
+//! 界面与日志文本的多语言支持。
+//!
+//! 源代码中的中文文本本身就是查找键：中文界面直接使用键，英文界面在 [`EN`] 表中查找译文，
+//! 未收录的文本原样显示中文。带参数的文本用 [`tr!`](crate::tr) 按 `{}` 依次填入参数。
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::{Display, Write as _};
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::OnceLock;
+
+/// 界面语言。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Language {
+    /// 简体中文（源语言）。
+    #[default]
+    Zh,
+    /// 英文。
+    En,
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::Zh, Language::En];
+}
+
+impl Display for Language {
+    /// 始终以该语言自身的名称显示，切换到不熟悉的语言后仍能找回。
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Language::Zh => f.write_str("简体中文"),
+            Language::En => f.write_str("English"),
+        }
+    }
+}
+
+/// 当前界面语言，GUI、CLI 与扫描任务共用，扫描日志随之使用同一语言。
+static CURRENT: AtomicU8 = AtomicU8::new(0);
+
+/// 切换当前语言，之后生成的界面与日志文本都使用新语言。
+pub fn set_language(language: Language) {
+    CURRENT.store(language as u8, Ordering::Relaxed);
+}
+
+/// 当前语言。
+pub fn language() -> Language {
+    match CURRENT.load(Ordering::Relaxed) {
+        1 => Language::En,
+        _ => Language::Zh,
+    }
+}
+
+/// 按当前语言查找文本，见 [`lookup`]。
+pub fn tr(key: &'static str) -> &'static str {
+    lookup(language(), key)
+}
+
+/// 指定语言下 `key` 对应的文本；中文或未收录译文时返回 `key` 本身。
+pub fn lookup(language: Language, key: &'static str) -> &'static str {
+    match language {
+        Language::Zh => key,
+        Language::En => en_table().get(key).copied().unwrap_or(key),
+    }
+}
+
+fn en_table() -> &'static HashMap<&'static str, &'static str> {
+    static TABLE: OnceLock<HashMap<&'static str, &'static str>> = OnceLock::new();
+    TABLE.get_or_init(|| EN.iter().copied().collect())
+}
+
+/// 依次用 `args` 替换模板中的占位符，支持 `{}`、`{:#}` 与 `{:.N}`，`{{`/`}}` 为字面花括号。
+pub fn fill(template: &str, args: &[&dyn Display]) -> String {
+    let mut out = String::with_capacity(template.len() + 16 * args.len());
+    let mut args = args.iter();
+    let mut rest = template;
+    while let Some(pos) = rest.find(['{', '}']) {
+        out.push_str(&rest[..pos]);
+        let tail = &rest[pos..];
+        if tail.starts_with("{{") || tail.starts_with("}}") {
+            out.push_str(&tail[..1]);
+            rest = &tail[2..];
+            continue;
+        }
+        let Some(end) = tail.find('}').filter(|_| tail.starts_with('{')) else {
+            out.push_str(&tail[..1]);
+            rest = &tail[1..];
+            continue;
+        };
+        let spec = tail[1..end].trim_start_matches(':');
+        if let Some(arg) = args.next() {
+            let _ = match spec.strip_prefix('.').and_then(|p| p.parse::<usize>().ok()) {
+                Some(precision) => write!(out, "{:.*}", precision, arg),
+                None if spec == "#" => write!(out, "{:#}", arg),
+                None => write!(out, "{}", arg),
+            };
+        }
+        rest = &tail[end + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// 翻译界面或日志文本：`tr!("文本")` 返回 `&'static str`，带参数时按占位符填入并返回 `String`。
+#[macro_export]
+macro_rules! tr {
+    ($key:literal) => {
+        $crate::i18n::tr($key)
+    };
+    ($key:literal, $($arg:expr),+ $(,)?) => {{
+        // 参数数组只活到本语句结束，避免 `&dyn Display` 跨越 `.await` 使 future 失去 `Send`。
+        let text = $crate::i18n::fill(
+            $crate::i18n::tr($key),
+            &[$(&$arg as &dyn ::std::fmt::Display),+],
+        );
+        text
+    }};
+}
+
+/// 英文译文表，键为源代码中的中文文本（含占位符），按所在模块分组。
+static EN: &[(&str, &str)] = &[
+    // main.rs
+    ("错误：{:#}", "Error: {:#}"),
+    ("应用已启动。", "Application started."),
+    ("创建系统托盘图标失败：{}", "Failed to create the tray icon: {}"),
+    ("更新系统托盘图标失败：{}", "Failed to update the tray icon: {}"),
+    ("已开启启动时自动开始定时，正在启动定时任务……", "Auto-start schedule is enabled, starting the schedule..."),
+    ("自动启动定时任务失败：{}", "Failed to start the schedule automatically: {}"),
+    ("AutoASR - 语音转写助手", "AutoASR - Speech Transcription Assistant"),
+    ("选择要转写的媒体文件", "Choose a media file to transcribe"),
+    ("已有扫描任务在进行中，请稍候。", "A scan is already in progress, please wait."),
+    ("正在校验 API 密钥，请稍候。", "Validating the API key, please wait."),
+    ("预览开始，仅检查所选文件……", "Preview started, checking the chosen file only..."),
+    ("已忽略拖入的 {}：不是文件夹或支持的媒体文件。", "Ignored dropped {}: not a folder or a supported media file."),
+    ("已移除目录：{}", "Removed directory: {}"),
+    ("保存窗口位置失败：{}", "Failed to save the window position: {}"),
+    ("打开目录 {} 失败：{}", "Failed to open directory {}: {}"),
+    ("已停止监视目录。", "Stopped watching directories."),
+    ("无法开始监视：请先关闭预览模式。", "Cannot start watching: turn off preview mode first."),
+    ("开始监视 {} 个目录。", "Watching {} directories."),
+    ("无法开始监视：{}", "Cannot start watching: {}"),
+    ("扫描报告将写入：{}", "Scan reports will be written to: {}"),
+    ("日志将写入：{}", "Logs will be written to: {}"),
+    ("发送桌面通知失败：{}", "Failed to send the desktop notification: {}"),
+    ("已找到 FFmpeg：{}", "Found FFmpeg: {}"),
+    ("FFmpeg 不可用（{}），请安装 FFmpeg 或在设置中指定其路径。", "FFmpeg is unavailable ({}). Install FFmpeg or set its path in the settings."),
+    ("分段方式已切换为{}。", "Segmentation switched to {}."),
+    ("VAD 预览失败：{}", "VAD preview failed: {}"),
+    ("定时任务已停止。", "Schedule stopped."),
+    ("无法启动定时任务：{}", "Cannot start the schedule: {}"),
+    ("预览开始，仅列出待处理的文件……", "Preview started, listing pending files only..."),
+    ("API 密钥校验通过。", "API key is valid."),
+    ("API 密钥被服务端拒绝（HTTP 401/403），请检查密钥后重试。", "The server rejected the API key (HTTP 401/403). Check the key and try again."),
+    ("无法校验 API 密钥：{}，仍继续执行。", "Could not validate the API key: {}. Continuing anyway."),
+    ("定时任务已启动。", "Schedule started."),
+    ("立即扫描开始……", "Scan started..."),
+    ("开始转写单个文件 {}……", "Transcribing single file {}..."),
+    ("开始转写 {} 个文件……", "Transcribing {} files..."),
+    (
+        "已加入待转写列表：{}（共 {} 个），点击“转写拖入的文件”开始。",
+        "Added to the pending list: {} ({} in total). Click \"Transcribe dropped files\" to start.",
+    ),
+    ("转写拖入的文件（{} 个）", "Transcribe dropped files ({})"),
+    ("清空拖入列表", "Clear dropped files"),
+    ("正在取消扫描，当前分段结束后停止……", "Cancelling the scan, stopping after the current segment..."),
+    ("扫描已暂停：进行中的转码与上传完成后不再开始新的工作。", "Scan paused: no new work starts after the running conversions and uploads finish."),
+    ("扫描已继续。", "Scan resumed."),
+    ("日志已清空。", "Log cleared."),
+    ("保存配置失败：{}", "Failed to save settings: {}"),
+    ("配置已保存（配置档：{}）。", "Settings saved (profile: {})."),
+    ("已切换到配置档：{}", "Switched to profile: {}"),
+    ("加载配置档 {} 失败：{}", "Failed to load profile {}: {}"),
+    ("无法另存配置档：{}", "Cannot save the profile: {}"),
+    ("已另存为配置档：{}", "Saved as profile: {}"),
+    ("另存配置档失败：{}", "Failed to save the profile: {}"),
+    ("已删除配置档 {}，已切换回默认配置。", "Deleted profile {} and switched back to the default profile."),
+    ("删除配置档 {} 失败：{}", "Failed to delete profile {}: {}"),
+    ("无法重命名配置档：{}", "Cannot rename the profile: {}"),
+    ("已将配置档 {} 重命名为 {}。", "Renamed profile {} to {}."),
+    ("重命名配置档 {} 失败：{}", "Failed to rename profile {}: {}"),
+    ("时间格式无效，已停止定时任务。", "Invalid time format, the schedule has been stopped."),
+    ("到达定时时间但尚未选择目录。", "Scheduled time reached but no directory is selected."),
+    ("到达定时时间，开始扫描……", "Scheduled time reached, starting the scan..."),
+    ("预览完成：待转写 {} 个音轨，跳过 {} 个。", "Preview finished: {} tracks to transcribe, {} skipped."),
+    ("扫描已取消", "Scan cancelled"),
+    ("扫描流程完成", "Scan finished"),
+    ("{}：成功 {}，失败 {}，跳过 {}，共 {} 个分段，用时 {} 秒。", "{}: {} succeeded, {} failed, {} skipped, {} segments in total, took {} s."),
+    ("AutoASR 定时扫描已取消", "AutoASR scheduled scan cancelled"),
+    ("AutoASR 定时扫描完成", "AutoASR scheduled scan finished"),
+    ("AutoASR 定时扫描完成，但有文件失败", "AutoASR scheduled scan finished with failures"),
+    ("成功 {} 个，失败 {} 个，跳过 {} 个。", "{} succeeded, {} failed, {} skipped."),
+    ("扫描过程中出现错误：{}", "Error during the scan: {}"),
+    ("AutoASR 定时扫描出错", "AutoASR scheduled scan failed"),
+    ("AutoASR 语音转写助手", "AutoASR Speech Transcription Assistant"),
+    ("尚未选择目录", "No directory selected"),
+    ("移除", "Remove"),
+    ("添加目录", "Add directory"),
+    ("最近使用的目录", "Recent directories"),
+    ("请输入 API 密钥", "Enter the API key"),
+    ("API 地址", "API URL"),
+    ("代理地址（可选，如 http://127.0.0.1:7890）", "Proxy URL (optional, e.g. http://127.0.0.1:7890)"),
+    ("模型名称", "Model name"),
+    ("常用模型", "Common models"),
+    ("删除", "Delete"),
+    ("执行时间（HH:MM）", "Run time (HH:MM)"),
+    ("添加时间", "Add time"),
+    ("例如 **/backup/** 或 *.sample.mp4", "e.g. **/backup/** or *.sample.mp4"),
+    ("添加排除规则", "Add exclude pattern"),
+    ("例如 *.interview.mp4", "e.g. *.interview.mp4"),
+    ("添加包含规则", "Add include pattern"),
+    ("例如 zh,chi,chs，留空不检查", "e.g. zh,chi,chs; leave empty to skip the check"),
+    ("留空不限制，1 仅扫描所选目录", "Empty for no limit, 1 scans only the chosen directory"),
+    ("留空不限制", "Empty for no limit"),
+    ("周一", "Mon"),
+    ("周二", "Tue"),
+    ("周三", "Wed"),
+    ("周四", "Thu"),
+    ("周五", "Fri"),
+    ("周六", "Sat"),
+    ("周日", "Sun"),
+    ("上传请求超时（秒，0 为不限制）", "Upload request timeout (s, 0 for no limit)"),
+    ("FFmpeg/ffprobe 超时（秒，0 为不限制）", "FFmpeg/ffprobe timeout (s, 0 for no limit)"),
+    ("留空则自动查找", "Empty to search automatically"),
+    ("浏览", "Browse"),
+    ("FFmpeg 路径：", "FFmpeg path:"),
+    ("ffprobe 路径：", "ffprobe path:"),
+    ("结果输出目录：", "Output directory:"),
+    ("留空则写在媒体文件旁边", "Empty to write next to the media file"),
+    ("同时转写数量（1 为逐个处理）", "Concurrent transcriptions (1 for one at a time)"),
+    (
+        "同时转码数量（0 表示随转写并发）",
+        "Concurrent FFmpeg conversions (0 to follow transcriptions)",
+    ),
+    ("all、first 或音轨编号（如 1,3）", "all, first or track numbers (e.g. 1,3)"),
+    ("留空或 0 不中止", "Empty or 0 never aborts"),
+    ("留空不估算", "Empty to skip estimates"),
+    ("ASS 字体：", "ASS font:"),
+    ("字号：", "Size:"),
+    ("清理结果文件名中的不安全字符", "Replace unsafe characters in result file names"),
+    ("默认码率", "Default bitrate"),
+    ("分段方式", "Segmentation"),
+    ("间隔秒数", "Interval seconds"),
+    ("秒/段", "s/segment"),
+    ("{} 块", "{} chunks"),
+    ("关闭", "Off"),
+    ("不限制", "No limit"),
+    ("采样率 / 分块大小", "Sample rate / chunk size"),
+    ("转写前进行响度归一化（改善小声录音）", "Normalize loudness before transcribing (helps quiet recordings)"),
+    ("裁掉 VAD 分段首尾的静音（减少幻听）", "Trim silence around VAD segments (fewer hallucinations)"),
+    ("取消扫描时丢弃未完成的字幕", "Discard unfinished subtitles when cancelling"),
+    ("VAD 预览", "VAD preview"),
+    ("选择预览文件", "Choose preview file"),
+    ("只分析开头 {:.0} 秒，不上传", "Analyzes the first {:.0} s only, nothing is uploaded"),
+    ("自适应阈值（按录音电平微调）", "Adaptive threshold (tuned to the recording level)"),
+    ("定时扫描完成后发送桌面通知", "Send a desktop notification after scheduled scans"),
+    ("同时将日志写入文件", "Also write logs to a file"),
+    ("每次扫描后写出汇总报告", "Write a summary report after each scan"),
+    ("程序启动后自动开始定时任务", "Start the schedule when the app launches"),
+    ("关闭窗口时最小化到系统托盘", "Minimize to the tray when closing the window"),
+    ("未启用 VAD 时按句子拆分字幕", "Split subtitles by sentence when VAD is off"),
+    ("去掉字幕末尾的标点", "Trim trailing punctuation from subtitles"),
+    ("合并字幕中的连续空白", "Collapse repeated whitespace in subtitles"),
+    ("标注说话人（实验性）", "Label speakers (experimental)"),
+    ("按逐词时间戳生成更细的字幕（Whisper 兼容接口）", "Build finer subtitles from word timestamps (Whisper-compatible API)"),
+    ("保留中间音频文件（调试用）", "Keep intermediate audio files (for debugging)"),
+    ("视频已有内嵌字幕时直接提取，不再转写", "Extract embedded subtitles instead of transcribing"),
+    ("多音轨结果按语言命名（如 video.jpn.srt）", "Name multi-track results by language (e.g. video.jpn.srt)"),
+    ("探测没有扩展名的文件（较慢）", "Probe files without an extension (slower)"),
+    ("按文件名顺序把每个目录的字幕合并为一个 SRT", "Merge each directory's subtitles into one SRT by file name"),
+    ("转写完成后把字幕封装回视频（MKV/MP4/MOV/WebM）", "Mux subtitles back into the video (MKV/MP4/MOV/WebM)"),
+    ("直接替换原视频（否则另存为 .subtitled）", "Replace the original video (otherwise save as .subtitled)"),
+    ("忽略已有结果，全部重新转写", "Ignore existing results and transcribe everything again"),
+    ("对增长中的文件仅转写新增部分并追加", "Only transcribe and append new parts of growing files"),
+    ("VAD 阈值", "VAD threshold"),
+    ("最短片段（秒）", "Min segment (s)"),
+    ("{:.1}秒", "{:.1} s"),
+    ("合并间隔（秒）", "Merge gap (s)"),
+    ("{:.2}秒", "{:.2} s"),
+    ("字幕提前（秒）", "Cue lead (s)"),
+    ("字幕延后（秒）", "Cue tail (s)"),
+    ("概率平滑", "Probability smoothing"),
+    ("语音段间的静音", "Silence between speech"),
+    ("最长片段（秒）", "Max segment (s)"),
+    ("{:.0}秒", "{:.0} s"),
+    ("音频加速", "Audio speed-up"),
+    ("停止定时", "Stop schedule"),
+    ("启动定时", "Start schedule"),
+    ("立即扫描", "Scan now"),
+    ("转写单个文件", "Transcribe file"),
+    ("保存设置", "Save settings"),
+    ("预览", "Preview"),
+    ("取消扫描", "Cancel scan"),
+    ("继续扫描", "Resume scan"),
+    ("暂停扫描", "Pause scan"),
+    ("打开结果目录", "Open output directory"),
+    ("停止监视", "Stop watching"),
+    ("开始监视", "Watch"),
+    ("快捷键：Ctrl+R 立即扫描　Ctrl+S 保存设置　空格 启动/停止定时", "Shortcuts: Ctrl+R scan now, Ctrl+S save settings, Space start/stop schedule"),
+    ("新配置档名称", "New profile name"),
+    ("另存为", "Save as"),
+    ("重命名", "Rename"),
+    ("删除配置档", "Delete profile"),
+    ("配置档：", "Profile:"),
+    ("主题：", "Theme:"),
+    ("语言：", "Language:"),
+    ("API 地址：", "API URL:"),
+    ("代理：", "Proxy:"),
+    ("模型名称：", "Model:"),
+    ("API 密钥：", "API key:"),
+    ("执行时间：", "Run times:"),
+    ("执行星期（全不选表示每天）：", "Weekdays (none selected means every day):"),
+    ("FFmpeg/ffprobe 超时（秒，0 为不限制）：", "FFmpeg/ffprobe timeout (s, 0 for no limit):"),
+    ("上传请求超时（秒，0 为不限制）：", "Upload request timeout (s, 0 for no limit):"),
+    ("单个上传文件大小上限（MB）：", "Max upload file size (MB):"),
+    ("扫描深度：", "Scan depth:"),
+    ("包含规则（留空处理全部文件）：", "Include patterns (empty processes all files):"),
+    ("排除规则（相对媒体目录的 glob）：", "Exclude patterns (globs relative to the media directory):"),
+    ("已有外挂字幕的语言后缀（逗号分隔）：", "Language suffixes of existing subtitles (comma-separated):"),
+    ("同时转写数量：", "Concurrent transcriptions:"),
+    ("同时转码数量：", "Concurrent FFmpeg conversions:"),
+    ("每分钟请求上限：", "Requests per minute limit:"),
+    ("连续失败多少个音轨后中止扫描：", "Abort the scan after this many consecutive failed tracks:"),
+    ("每分钟音频费用（用于估算）：", "Cost per audio minute (for estimates):"),
+    ("转写的视频音轨：", "Video tracks to transcribe:"),
+    ("输出格式：", "Output format:"),
+    ("替换为：", "Replace with:"),
+    ("上传音频编码：", "Upload audio codec:"),
+    ("码率：", "Bitrate:"),
+    ("多声道混音：", "Multichannel downmix:"),
+    ("进度：{} / {} 个音轨，已暂停", "Progress: {} / {} tracks, paused"),
+    ("计算中…", "calculating…"),
+    ("进度：{} / {} 个音轨，预计剩余 {}", "Progress: {} / {} tracks, {} remaining"),
+    ("进度：{} / {} 个音轨", "Progress: {} / {} tracks"),
+    ("进度：空闲", "Progress: idle"),
+    ("上次扫描：{}", "Last scan: {}"),
+    ("{} 条音轨", "{} tracks"),
+    ("文件状态（完成 {} / {}）", "File status ({} / {} done)"),
+    ("日志", "Log"),
+    ("清空日志", "Clear log"),
+    ("正在预览 {} 前 {:.0} 秒的 VAD 分段……", "Previewing VAD segments of {} for the first {:.0} s..."),
+    ("读取配置档列表失败：{}", "Failed to read the profile list: {}"),
+    ("写入日志文件失败：{}", "Failed to write the log file: {}"),
+    ("信息", "Info"),
+    ("成功", "Success"),
+    ("错误", "Error"),
+    ("约 {} 小时 {} 分", "about {} h {} min"),
+    ("约 {} 分 {} 秒", "about {} min {} s"),
+    ("约 {} 秒", "about {} s"),
+    ("分段 {} / {}", "segment {} / {}"),
+    ("→ {} 个结果文件", "→ {} result files"),
+    ("需要填写 API 密钥。", "An API key is required."),
+    ("目录已在列表中：{}", "Directory is already in the list: {}"),
+    ("已添加目录：{}", "Added directory: {}"),
+    ("所选文件不存在：{}", "The chosen file does not exist: {}"),
+    ("请先添加至少一个媒体目录。", "Add at least one media directory first."),
+    ("所选目录均不存在。", "None of the chosen directories exist."),
+    ("正在校验 API 密钥……", "Validating the API key..."),
+    ("无法转写新文件：{}", "Cannot transcribe new files: {}"),
+    ("检测到 {} 个新文件，开始转写……", "Detected {} new files, transcribing..."),
+    // scanner.rs
+    ("等待", "Pending"),
+    ("处理中", "Processing"),
+    ("完成", "Done"),
+    ("失败", "Failed"),
+    ("预估费用 {:.2}，实际费用 {:.2}", "estimated cost {:.2}, actual cost {:.2}"),
+    ("预估费用 {:.2}", "estimated cost {:.2}"),
+    ("实际费用 {:.2}", "actual cost {:.2}"),
+    ("无法运行 {}：{}", "Cannot run {}: {}"),
+    ("{} -version 执行失败，退出状态：{}", "{} -version failed, exit status: {}"),
+    ("平均混合（默认）", "Average mix (default)"),
+    ("突出中置对白（5.1/7.1）", "Emphasize center dialogue (5.1/7.1)"),
+    ("仅左声道", "Left channel only"),
+    ("仅右声道", "Right channel only"),
+    (
+        "{} 只有 {} 个声道，不应用混音预设“{}”。",
+        "{} has only {} channel(s); downmix preset \"{}\" is not applied.",
+    ),
+    (
+        "无法读取 {} 的声道数，不应用混音预设“{}”。",
+        "Could not read the channel count of {}; downmix preset \"{}\" is not applied.",
+    ),
+    ("VAD 采样率 {} Hz 与分块大小 {} 不匹配，仅支持：{}。", "VAD sample rate {} Hz does not match chunk size {}. Supported: {}."),
+    ("导出码率 {} 无效，应形如 64k 或 128k。", "Export bitrate {} is invalid, expected something like 64k or 128k."),
+    ("音轨选择 {} 无效，应为 all、first 或逗号分隔的音轨编号（如 1,3）。", "Track selection {} is invalid, expected all, first or comma-separated track numbers (e.g. 1,3)."),
+    ("音轨选择 {} 未列出任何音轨编号。", "Track selection {} lists no track numbers."),
+    ("文本替换规则 {} 无效：{}", "Text replacement rule {} is invalid: {}"),
+    ("{}，已跳过该规则，其余规则照常生效。", "{}; the rule was skipped and the other rules still apply."),
+    ("SRT 字幕", "SRT subtitles"),
+    ("纯文本 TXT", "Plain text TXT"),
+    ("ASS 字幕", "ASS subtitles"),
+    ("创建输出目录 {} 失败", "Failed to create output directory {}"),
+    ("已达到每分钟 {} 次请求的上限，{} 将在 {:.1} 秒后上传。", "Reached the limit of {} requests per minute, {} will be uploaded in {:.1} s."),
+    ("ffprobe/ffmpeg 超时，已跳过 {}", "ffprobe/ffmpeg timed out, skipped {}"),
+    ("认证失败，已中止本次扫描，请检查 API 密钥后重试。", "Authentication failed and the scan was aborted. Check the API key and try again."),
+    ("连续 {} 个音轨转写失败，已中止本次扫描，请检查网络或 API 服务。", "{} tracks failed in a row and the scan was aborted. Check the network or the API service."),
+    ("{}，退出状态：{}\n{}", "{}, exit status: {}\n{}"),
+    ("{}，退出状态：{}", "{}, exit status: {}"),
+    ("跳过", "Skip"),
+    ("照常转写", "Transcribe anyway"),
+    ("写入空字幕", "Write empty subtitles"),
+    ("整段上传", "Whole file"),
+    ("固定间隔分段", "Fixed interval"),
+    ("VAD 语音分段", "VAD speech segments"),
+    ("FFmpeg 转换音频用于 VAD 时失败", "FFmpeg failed to convert audio for VAD"),
+    ("FFmpeg 裁剪语音片段失败", "FFmpeg failed to cut the speech segment"),
+    ("目录不存在，已跳过：{}", "Directory does not exist, skipped: {}"),
+    ("扫描 {} 失败，已跳过：{}", "Scanning {} failed, skipped: {}"),
+    ("文件不存在：{}", "File does not exist: {}"),
+    ("不是支持的媒体文件：{}", "Not a supported media file: {}"),
+    ("无法确定 {} 所在的目录", "Cannot determine the directory of {}"),
+    ("API Key 为空，请在设置中填写后再运行。", "The API key is empty. Fill it in the settings and run again."),
+    ("目录不存在：{}", "Directory does not exist: {}"),
+    ("包含", "Include"),
+    ("排除", "Exclude"),
+    ("已开启强制重新转写，忽略所有已有结果。", "Forced re-transcription is on, ignoring all existing results."),
+    ("转写结果输出到 {}。", "Writing results to {}."),
+    ("视频", "video"),
+    ("音频", "audio"),
+    ("{} 没有扩展名，探测为{}文件。", "{} has no extension, probed as a {} file."),
+    ("跳过 {}：已有外挂字幕 {}。", "Skipped {}: subtitles {} already exist."),
+    ("跳过 {}：视频中未检测到音轨。", "Skipped {}: no audio tracks found in the video."),
+    ("跳过 {}：{} 条音轨均不在音轨选择范围内。", "Skipped {}: none of its {} tracks match the track selection."),
+    ("跳过 {}：所有音轨均已转写。", "Skipped {}: all tracks are already transcribed."),
+    ("读取 {} 音轨失败：{}", "Failed to read the tracks of {}: {}"),
+    ("跳过 {}：文件大小 {} MB 超过上限 {} MB。", "Skipped {}: file size {} MB exceeds the {} MB limit."),
+    (
+        "包含规则均无效（如 {}），请修正后再扫描。",
+        "None of the include patterns are valid (e.g. {}); fix them before scanning.",
+    ),
+    ("包含规则匹配了 {} 个媒体文件，另有 {} 个未匹配，已跳过。", "Include patterns matched {} media files, {} others did not match and were skipped."),
+    ("根据排除规则跳过了 {} 个媒体文件。", "Skipped {} media files by exclude patterns."),
+    ("没有检测到新的待转写文件。", "No new files to transcribe."),
+    ("待处理音轨总数：{}。", "Tracks to process: {}."),
+    ("预计上传音频约 {:.1} 分钟，按每分钟 {} 估算费用约 {:.2}。", "About {:.1} minutes of audio to upload; at {} per minute the estimated cost is about {:.2}."),
+    ("{} 个文件无法读取时长，未计入预估。", "{} files have no readable duration and are not included in the estimate."),
+    ("预览模式：未执行转码与转写。", "Preview mode: nothing was converted or transcribed."),
+    ("最多同时处理 {} 个音轨。", "Processing up to {} tracks at a time."),
+    (
+        "最多同时运行 {} 个 FFmpeg 转码、{} 个上传。",
+        "Running up to {} FFmpeg conversions and {} uploads at a time.",
+    ),
+    ("转写任务异常退出：{}", "Transcription task exited abnormally: {}"),
+    ("写入 {} 的转写状态失败：{}", "Failed to write the transcription state of {}: {}"),
+    ("扫描已中止，剩余 {} 个音轨未处理。", "Scan aborted, {} tracks left unprocessed."),
+    ("扫描已取消，剩余 {} 个音轨未处理。", "Scan cancelled, {} tracks left unprocessed."),
+    ("API 报告的转写时长合计 {:.1} 秒（约 {:.1} 分钟），可据此估算费用。", "The API reported {:.1} s of transcribed audio in total (about {:.1} minutes), usable for cost estimates."),
+    ("按每分钟 {} 计算，本次实际费用约 {:.2}。", "At {} per minute, the actual cost of this run is about {:.2}."),
+    ("扫描报告已写入 {}", "Scan report written to {}"),
+    ("写入扫描报告失败：{}", "Failed to write the scan report: {}"),
+    ("跳过封装 {}：该容器不支持软字幕，仅支持 MKV、MP4、MOV 与 WebM。", "Skipped muxing {}: the container does not support soft subtitles, only MKV, MP4, MOV and WebM do."),
+    ("已把 {} 条字幕封装到 {}", "Muxed {} subtitle tracks into {}"),
+    ("封装 {} 的字幕失败：{}", "Failed to mux the subtitles of {}: {}"),
+    ("FFmpeg 封装字幕失败", "FFmpeg failed to mux the subtitles"),
+    ("用封装后的视频替换 {} 失败：{}", "Failed to replace {} with the muxed video: {}"),
+    ("合并 {} 的字幕失败：{}", "Failed to merge the subtitles of {}: {}"),
+    ("读取 {} 失败", "Failed to read {}"),
+    ("合并字幕时 {} 没有可用的 SRT，仅计入时长。", "{} has no usable SRT while merging, only its duration is counted."),
+    ("已按文件名顺序将 {} 个字幕合并为 {}（共 {} 条）。", "Merged {} subtitles by file name into {} ({} cues)."),
+    ("（从 {} 起追加）", " (appending from {})"),
+    ("待转写音频：{}{}", "Audio to transcribe: {}{}"),
+    ("待转写视频：{}，音轨 {}", "Video to transcribe: {}, tracks {}"),
+    ("{}规则 {} 无效，已忽略：{}", "{} pattern {} is invalid and was ignored: {}"),
+    ("FFmpeg 转码音轨失败", "FFmpeg failed to convert the track"),
+    ("重新转写 {}：{}。", "Transcribing {} again: {}."),
+    ("已有结果为空", "the existing result is empty"),
+    ("源文件在上次转写后被修改", "the source file changed since the last transcription"),
+    ("{} 自上次转写后增长了 {:.1} 秒，将仅转写新增部分并追加到 {}。", "{} grew by {:.1} s since the last transcription; only the new part will be transcribed and appended to {}."),
+    ("{} 使用 Whisper 接口返回的分段时间戳，跳过本地 VAD。", "{} uses the segment timestamps returned by the Whisper API and skips local VAD."),
+    ("VAD 分段失败（{}），回退整段上传：{}", "VAD segmentation failed ({}), uploading the whole file: {}"),
+    ("准备 {} 音频失败：{}", "Failed to prepare the audio of {}: {}"),
+    ("开始转写 {}，音频源 {}", "Transcribing {}, audio source {}"),
+    ("已保留临时音轨：{}", "Kept temporary track: {}"),
+    ("清理临时音轨失败：{}", "Failed to clean up the temporary track: {}"),
+    ("{} 待上传音频 {} MB 超过上限 {} MB，已跳过。", "Audio of {} to upload is {} MB, over the {} MB limit; skipped."),
+    ("调用 API 转写 {} 失败：{}", "API transcription of {} failed: {}"),
+    ("{} 的识别结果为空，跳过写入。", "Recognition result of {} is empty, nothing written."),
+    ("无法获取 {} 的时长（{}），使用估算值。", "Cannot get the duration of {} ({}), using an estimate."),
+    ("{} 的接口未返回逐词时间戳，按分段时间生成字幕。", "The API returned no word timestamps for {}, building subtitles from segment timing."),
+    ("写入 {} 失败：{}", "Failed to write {}: {}"),
+    ("写入 {} 的 JSON 失败：{}", "Failed to write the JSON of {}: {}"),
+    ("完成 {}，结果输出 {}", "Finished {}, result written to {}"),
+    ("已保留 VAD 用 PCM 音频：{}", "Kept the PCM audio used for VAD: {}"),
+    ("{} 自适应阈值：语音电平 {:.1} dBFS，噪声底 {:.1} dBFS，阈值 {:.2} → {:.2}。", "{} adaptive threshold: speech level {:.1} dBFS, noise floor {:.1} dBFS, threshold {:.2} → {:.2}."),
+    ("VAD 预览 {}（前 {:.0} 秒，阈值 {:.2}）：检测到 {} 段语音，共 {:.1} 秒。", "VAD preview of {} (first {:.0} s, threshold {:.2}): {} speech segments, {:.1} s in total."),
+    ("  {}. {} - {}（{:.1} 秒）", "  {}. {} - {} ({:.1} s)"),
+    ("{} 中没有音轨", "{} has no audio track"),
+    ("{} 启用 VAD，准备语音分段。", "{} uses VAD, preparing speech segments."),
+    ("{} 复用上次的 VAD 检测结果。", "{} reuses the previous VAD result."),
+    ("写入 VAD 缓存失败：{}", "Failed to write the VAD cache: {}"),
+    ("未检测到有效语音", "No speech detected"),
+    ("合并间隔小于 {:.2} 秒的相邻语音段：{} 段 → {} 段。", "Merged speech segments closer than {:.2} s: {} segments → {} segments."),
+    ("检测到 {} 段语音，{} 个静音区写为空字幕。", "Detected {} speech segments, {} silent gaps written as empty subtitles."),
+    ("检测到 {} 段语音，额外包含 {} 个静音覆盖区。", "Detected {} speech segments plus {} silent gap regions."),
+    ("检测到 {} 段语音，逐段上传。", "Detected {} speech segments, uploading one by one."),
+    ("获取 {} 的时长失败，无法按固定间隔分段：{}", "Failed to get the duration of {}, cannot split at fixed intervals: {}"),
+    ("{} 没有可转写的音频", "{} has no audio to transcribe"),
+    ("{} 按每 {:.0} 秒固定分段，共 {} 段，逐段上传。", "{} split every {:.0} s into {} segments, uploading one by one."),
+    ("{} 从分段缓存恢复 {} 段，仅上传其余 {} 段。", "{} restored {} segments from the cache, uploading the remaining {}."),
+    ("分段 {} 导出音频失败：{}", "Segment {} failed to export audio: {}"),
+    ("分段 {}", "segment {}"),
+    ("分段 {} 调用 API 失败：{}", "Segment {} API call failed: {}"),
+    ("写入分段缓存失败：{}", "Failed to write the segment cache: {}"),
+    ("分段 {} 结果为空，已跳过。", "Segment {} result is empty, skipped."),
+    ("语音", "speech"),
+    ("补间", "gap"),
+    ("分段 {} [{}] 完成（{} - {}）。", "Segment {} [{}] done ({} - {})."),
+    ("{} 已保留 {} 个分段音频：{}", "{} kept {} segment audio files: {}"),
+    ("所有分段均转写失败", "All segments failed to transcribe"),
+    ("{} 分段转写完成，结果输出 {}", "{} segment transcription finished, result written to {}"),
+    ("{} 已取消，无需清理中间文件。", "{} cancelled, no intermediate files to clean up."),
+    ("{} 已取消，已清理：{}", "{} cancelled, cleaned up: {}"),
+    (
+        "{} 已完成的分段保留在 {}，下次扫描将续传其余分段。",
+        "{} finished segments are kept in {}; the next scan resumes the remaining segments.",
+    ),
+    ("生成的 WAV 格式不符合 VAD 要求", "The generated WAV does not meet the VAD requirements"),
+    ("语音活动检测器初始化失败", "Failed to initialize the voice activity detector"),
+    ("ffprobe 解析音轨失败，退出状态：{}", "ffprobe failed to list the tracks, exit status: {}"),
+    ("无法解析 ffprobe 音轨信息：{}", "Cannot parse the ffprobe track info: {}"),
+    ("ffprobe 无法识别 {}，退出状态：{}", "ffprobe cannot recognize {}, exit status: {}"),
+    ("ffprobe 解析字幕流失败，退出状态：{}", "ffprobe failed to list the subtitle streams, exit status: {}"),
+    ("读取 {} 字幕流失败，改为转写音轨：{}", "Failed to read the subtitle streams of {}, transcribing the audio instead: {}"),
+    ("跳过 {}：内嵌字幕已提取到 {}。", "Skipped {}: embedded subtitles already extracted to {}."),
+    ("待提取内嵌字幕：{}（字幕流 {}，{}）", "Embedded subtitles to extract: {} (subtitle stream {}, {})"),
+    ("{}，改为转写音轨。", "{}, transcribing the audio instead."),
+    ("FFmpeg 提取字幕失败", "FFmpeg failed to extract the subtitles"),
+    ("{} 已包含字幕流 {}（{}），已提取到 {}，未调用 ASR 转写。", "{} already has subtitle stream {} ({}), extracted to {} without ASR."),
+    ("提取 {} 的内嵌字幕失败，改为转写音轨：{}", "Failed to extract the embedded subtitles of {}, transcribing the audio instead: {}"),
+    ("无法解析 {} 的时长", "Cannot parse the duration of {}"),
+    ("ffprobe 读取 {} 时长失败，退出状态：{}", "ffprobe failed to read the duration of {}, exit status: {}"),
+    ("（音轨 {} · 片段 {}）", " (track {} · chunk {})"),
+    ("（音轨 {}）", " (track {})"),
+    ("（片段 {}）", " (chunk {})"),
+    // api.rs
+    ("OpenAI Whisper 兼容", "OpenAI Whisper compatible"),
+    ("{}（服务端建议 {:.1} 秒后重试）", "{} (the server suggests retrying in {:.1} s)"),
+    ("代理地址无效：{}", "Invalid proxy URL: {}"),
+    ("MIME 类型 {} 无效：{}", "Invalid MIME type {}: {}"),
+    ("解析成功响应失败：{}", "Failed to parse the successful response: {}"),
+    ("API 错误（HTTP {}，code {}）：{} {}", "API error (HTTP {}, code {}): {} {}"),
+    ("API 错误（HTTP {}）：{}", "API error (HTTP {}): {}"),
+    ("已被限流（HTTP 429）：{}", "Rate limited (HTTP 429): {}"),
+    // config.rs
+    ("浅色", "Light"),
+    ("深色", "Dark"),
+    ("至少需要一个执行时间。", "At least one run time is required."),
+    ("执行时间 {} 必须符合 HH:MM 格式。", "Run time {} must use the HH:MM format."),
+    ("配置中的 VAD 阈值 {} 超出范围 {}，已按 {} 显示和使用。", "The configured VAD threshold {} is outside {}, using {} instead."),
+    ("配置中的最短语音段 {} 秒超出范围 {}，已按 {} 秒显示和使用。", "The configured minimum speech segment of {} s is outside {}, using {} s instead."),
+    ("无法定位配置文件，已使用默认值：{}", "Cannot locate the config file, using defaults: {}"),
+    ("{}，已使用默认值；原文件已另存为 {}，修复后可复制回原位置。", "{}. Using defaults; the original file was saved as {} and can be copied back after fixing it."),
+    ("{}，已使用默认值。", "{}. Using defaults."),
+    ("读取配置文件 {} 失败：{}", "Failed to read config file {}: {}"),
+    ("配置文件 {} 解析失败（{}），已改用备份 {}", "Failed to parse config file {} ({}), using backup {}"),
+    ("配置文件 {} 解析失败：{}", "Failed to parse config file {}: {}"),
+    ("读取配置档 {} 的 API 密钥失败：{}", "Failed to read the API key of profile {}: {}"),
+    ("无法写入系统钥匙串，API 密钥将以明文保存在配置文件中：{}", "Cannot write to the system keychain, the API key will be stored in plain text in the config file: {}"),
+    ("默认配置档不可删除", "The default profile cannot be deleted"),
+    ("删除配置档 {} 的 API 密钥失败：{}", "Failed to delete the API key of profile {}: {}"),
+    ("默认配置档不可重命名", "The default profile cannot be renamed"),
+    ("配置档 {} 已存在", "Profile {} already exists"),
+    (
+        "无法从系统钥匙串读取配置档 {} 的 API 密钥，暂不能重命名",
+        "Cannot read the API key of profile {} from the system keychain, so it cannot be renamed now",
+    ),
+    ("配置档名称不能为空", "Profile name cannot be empty"),
+    ("配置档名称包含非法字符：{}", "Profile name contains invalid characters: {}"),
+    ("需要填写 API 地址。", "An API URL is required."),
+    ("API 地址必须以 http:// 或 https:// 开头。", "The API URL must start with http:// or https://."),
+    ("代理地址必须以 http:// 或 https:// 开头。", "The proxy URL must start with http:// or https://."),
+    // tray.rs
+    ("显示窗口", "Show window"),
+    ("退出", "Quit"),
+    ("AutoASR - 正在扫描", "AutoASR - Scanning"),
+    ("当前平台暂不支持系统托盘", "The system tray is not supported on this platform"),
+    // cli.rs
+    ("参数 {} 缺少取值。", "Option {} is missing a value."),
+    ("--threshold 必须是数字：{}", "--threshold must be a number: {}"),
+    ("--threshold 必须在 0 到 1 之间：{}", "--threshold must be between 0 and 1: {}"),
+    ("未知参数：{}", "Unknown option: {}"),
+    ("命令行模式需要通过 --scan 指定目录。", "Command-line mode needs a directory given with --scan."),
+    ("指定的目录均不存在：{}", "None of the given directories exist: {}"),
+    ("需要通过 --api-key 或配置文件提供 API 密钥。", "An API key is required, via --api-key or the config file."),
+    ("{} 个文件转写失败。", "{} files failed to transcribe."),
+    ("未来一周内没有可执行的定时时间。", "No scheduled run time in the coming week."),
+    ("下一次扫描：{}", "Next scan: {}"),
+    ("开始扫描 {}……", "Scanning {}..."),
+    // scan_report.rs
+    ("已取消", "Cancelled"),
+    ("AutoASR 扫描报告", "AutoASR scan report"),
+    ("时间：{}", "Time: {}"),
+    ("目录：{}", "Directory: {}"),
+    ("状态：{}", "Status: {}"),
+    ("耗时：{:.1} 秒", "Elapsed: {:.1} s"),
+    ("发现媒体文件：{}", "Media files found: {}"),
+    ("转写成功：{}", "Transcribed: {}"),
+    ("跳过：{}", "Skipped: {}"),
+    ("失败：{}", "Failed: {}"),
+    ("处理音频时长：{:.1} 秒（约 {:.1} 分钟）", "Audio processed: {:.1} s (about {:.1} minutes)"),
+    ("API 报告的转写时长：{:.1} 秒", "Transcribed duration reported by the API: {:.1} s"),
+    ("失败明细：", "Failures:"),
+    ("其他错误：", "Other errors:"),
+    // keychain.rs
+    ("访问系统钥匙串的线程异常退出", "The keychain thread exited abnormally"),
+    ("访问系统钥匙串失败：{}", "Failed to access the system keychain: {}"),
+    // watch.rs
+    ("监视目录 {} 失败：{}", "Failed to watch directory {}: {}"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fill_handles_precision_alternate_and_escapes() {
+        let err = anyhow::anyhow!("外层").context("内层");
+        assert_eq!(
+            fill("{} 用时 {:.1} 秒 {{x}}：{:#}", &[&"a.mp3", &1.25, &err]),
+            "a.mp3 用时 1.2 秒 {x}：内层: 外层"
+        );
+        assert_eq!(fill("缺少参数 {}", &[]), "缺少参数 ");
+    }
+
+    #[test]
+    fn english_table_covers_placeholders_and_falls_back_to_chinese() {
+        assert_eq!(lookup(Language::Zh, "立即扫描"), "立即扫描");
+        assert_eq!(lookup(Language::En, "立即扫描"), "Scan now");
+        assert_eq!(lookup(Language::En, "未收录的文本"), "未收录的文本");
+
+        let mut seen = std::collections::HashSet::new();
+        let placeholders = |text: &str| -> Vec<String> {
+            let mut found = Vec::new();
+            let mut rest = text.replace("{{", "").replace("}}", "");
+            while let Some(start) = rest.find('{') {
+                let end = rest[start..]
+                    .find('}')
+                    .map_or(rest.len(), |e| start + e + 1);
+                found.push(rest[start..end].to_string());
+                rest = rest[end..].to_string();
+            }
+            found
+        };
+        for (key, en) in EN {
+            assert!(seen.insert(*key), "重复的键：{}", key);
+            assert_eq!(placeholders(key), placeholders(en), "占位符不一致：{}", key);
+        }
+    }
+}
@@ -0,0 +1,53 @@
+//! 把 API 密钥保存在系统凭据存储中：Windows 凭据管理器、macOS 钥匙串或 Linux Secret Service。
+//!
+//! 每个配置档对应一条凭据，服务名固定为 [`SERVICE`]、账户名为配置档名称。
+
+use crate::tr;
+use anyhow::{anyhow, Result};
+use keyring::Entry;
+
+/// 凭据存储中的服务名。
+const SERVICE: &str = "AutoASR";
+
+/// 把 `key` 写入配置档 `profile` 对应的凭据。
+pub fn store(profile: &str, key: &str) -> Result<()> {
+    let profile = profile.to_string();
+    let key = key.to_string();
+    on_thread(move || Entry::new(SERVICE, &profile)?.set_password(&key))
+}
+
+/// 读取配置档 `profile` 保存的密钥，从未保存过时返回 `None`。
+pub fn load(profile: &str) -> Result<Option<String>> {
+    let profile = profile.to_string();
+    on_thread(
+        move || match Entry::new(SERVICE, &profile)?.get_password() {
+            Ok(key) => Ok(Some(key)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(e),
+        },
+    )
+}
+
+/// 删除配置档 `profile` 的凭据，不存在时视为成功。
+pub fn delete(profile: &str) -> Result<()> {
+    let profile = profile.to_string();
+    on_thread(
+        move || match Entry::new(SERVICE, &profile)?.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(e),
+        },
+    )
+}
+
+/// Linux 上的异步 Secret Service 后端在 Tokio 运行时所在线程调用可能死锁，
+/// 因此每次访问都放到独立线程中执行。
+fn on_thread<T, F>(f: F) -> Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> keyring::Result<T> + Send + 'static,
+{
+    std::thread::spawn(f)
+        .join()
+        .map_err(|_| anyhow!(tr!("访问系统钥匙串的线程异常退出")))?
+        .map_err(|e| anyhow!(tr!("访问系统钥匙串失败：{}", e)))
+}
@@ -0,0 +1,237 @@
+//! 按日期滚动的日志文件，便于事后审计夜间无人值守的扫描。
+
+use crate::scanner::{ScanLog, ScanLogLevel};
+use crate::tr;
+use anyhow::Result;
+use chrono::{DateTime, Local, NaiveDate};
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver, Sender},
+    time::Duration,
+};
+use tokio::sync::oneshot;
+
+/// 日志文件名前缀，完整文件名形如 `autoasr-2024-05-01.log`。
+const LOG_FILE_PREFIX: &str = "autoasr-";
+/// 保留最近多少天的日志文件，更早的在打开新一天的日志时删除。
+const LOG_RETENTION_DAYS: i64 = 30;
+
+/// 指定日期对应的日志文件路径。
+pub fn log_file_path(dir: &Path, date: NaiveDate) -> PathBuf {
+    dir.join(format!(
+        "{}{}.log",
+        LOG_FILE_PREFIX,
+        date.format("%Y-%m-%d")
+    ))
+}
+
+/// 把一条日志格式化为带时间戳的单行文本，消息中的换行会缩进续行。
+pub fn format_line(time: DateTime<Local>, log: &ScanLog) -> String {
+    let level = match log.level {
+        ScanLogLevel::Info => tr!("信息"),
+        ScanLogLevel::Success => tr!("成功"),
+        ScanLogLevel::Error => tr!("错误"),
+    };
+    format!(
+        "[{}] [{}] {}\n",
+        time.format("%Y-%m-%d %H:%M:%S"),
+        level,
+        log.message.replace('\n', "\n    ")
+    )
+}
+
+/// 以追加方式打开某天的日志文件；当天第一次写入时创建目录并顺带清理过期日志，避免目录无限增长。
+fn open_day(dir: &Path, date: NaiveDate) -> Result<BufWriter<File>> {
+    let path = log_file_path(dir, date);
+    if !path.exists() {
+        fs::create_dir_all(dir)?;
+        prune(dir, date)?;
+    }
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    Ok(BufWriter::new(file))
+}
+
+/// 等待写入线程落盘的最长时间，避免日志目录异常时拖住扫描收尾。
+const FLUSH_TIMEOUT: Duration = Duration::from_secs(2);
+
+enum WriterMessage {
+    Line(DateTime<Local>, ScanLog),
+    /// 写完此前的日志并刷新到磁盘后回复。
+    Flush(oneshot::Sender<()>),
+}
+
+/// 在独立线程中写日志文件：当天的文件保持打开并经缓冲写入，调用方只把日志发进通道，
+/// 不在界面线程或异步运行时上做阻塞 IO。克隆后共用同一个写入线程，全部句柄丢弃后线程写完剩余日志退出。
+#[derive(Clone)]
+pub struct LogWriter {
+    sender: Sender<WriterMessage>,
+}
+
+impl LogWriter {
+    /// 启动写入线程；写入失败时以本地化的错误说明调用 `on_error`（连续失败只报告一次），
+    /// 由调用方作为日志事件显示。
+    pub fn spawn(dir: PathBuf, on_error: impl FnMut(String) + Send + 'static) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || run_writer(&dir, receiver, on_error));
+        Self { sender }
+    }
+
+    /// 把日志交给写入线程，立即返回。
+    pub fn write(&self, log: &ScanLog) {
+        let _ = self
+            .sender
+            .send(WriterMessage::Line(Local::now(), log.clone()));
+    }
+
+    /// 等待此前交给写入线程的日志全部落盘，最多等待 [`FLUSH_TIMEOUT`]；等待期间不占用运行时线程。
+    pub async fn flush(&self) {
+        let (done, flushed) = oneshot::channel();
+        if self.sender.send(WriterMessage::Flush(done)).is_ok() {
+            let _ = tokio::time::timeout(FLUSH_TIMEOUT, flushed).await;
+        }
+    }
+}
+
+/// 写入线程主循环：一次取走通道中积压的全部日志再刷新，突发的大量日志只触发一次落盘。
+/// 写日志文件失败不应中断扫描，只通过 `on_error` 报告，恢复正常后再次失败时重新报告。
+fn run_writer(dir: &Path, receiver: Receiver<WriterMessage>, mut on_error: impl FnMut(String)) {
+    let mut current: Option<(NaiveDate, BufWriter<File>)> = None;
+    let mut failing = false;
+    while let Ok(first) = receiver.recv() {
+        let mut waiting = Vec::new();
+        let mut result = Ok(());
+        for message in std::iter::once(first).chain(receiver.try_iter()) {
+            match message {
+                WriterMessage::Line(time, log) => {
+                    result = result.and(write_line(dir, &mut current, time, &log));
+                }
+                WriterMessage::Flush(done) => waiting.push(done),
+            }
+        }
+        if let Some((_, file)) = &mut current {
+            result = result.and(file.flush().map_err(Into::into));
+        }
+        match result {
+            Err(e) if !failing => {
+                failing = true;
+                on_error(tr!("写入日志文件失败：{}", e));
+            }
+            Err(_) => {}
+            Ok(()) => failing = false,
+        }
+        for done in waiting {
+            let _ = done.send(());
+        }
+    }
+}
+
+/// 写入一行日志，跨过零点时切换到新一天的文件。
+fn write_line(
+    dir: &Path,
+    current: &mut Option<(NaiveDate, BufWriter<File>)>,
+    time: DateTime<Local>,
+    log: &ScanLog,
+) -> Result<()> {
+    let date = time.date_naive();
+    let file = match current {
+        Some((open_date, file)) if *open_date == date => file,
+        _ => {
+            if let Some((_, mut previous)) = current.take() {
+                previous.flush()?;
+            }
+            &mut current.insert((date, open_day(dir, date)?)).1
+        }
+    };
+    file.write_all(format_line(time, log).as_bytes())?;
+    Ok(())
+}
+
+/// 删除早于保留期限的日志文件，只处理符合命名规则的文件。
+pub fn prune(dir: &Path, today: NaiveDate) -> Result<()> {
+    let cutoff = today - chrono::Duration::days(LOG_RETENTION_DAYS);
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let date = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.strip_prefix(LOG_FILE_PREFIX))
+            .and_then(|name| name.strip_suffix(".log"))
+            .and_then(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok());
+        if matches!(date, Some(date) if date < cutoff) {
+            fs::remove_file(&path)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn line_has_timestamp_level_and_indented_continuation() {
+        let time = Local.with_ymd_and_hms(2024, 5, 1, 2, 3, 4).unwrap();
+        let log = ScanLog::new(ScanLogLevel::Error, "转写失败\n详情");
+        assert_eq!(
+            format_line(time, &log),
+            "[2024-05-01 02:03:04] [错误] 转写失败\n    详情\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn writer_keeps_file_open_and_flushes_on_request() {
+        let dir = std::env::temp_dir().join(format!("auto_asr_log_writer_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let writer = LogWriter::spawn(dir.clone(), |e| panic!("{}", e));
+        let clone = writer.clone();
+        writer.write(&ScanLog::new(ScanLogLevel::Info, "开始扫描"));
+        clone.write(&ScanLog::new(ScanLogLevel::Success, "转写完成"));
+        writer.flush().await;
+
+        let content = fs::read_to_string(log_file_path(&dir, Local::now().date_naive())).unwrap();
+        let first = content.find("开始扫描").unwrap();
+        assert!(first < content.find("转写完成").unwrap());
+        let _ = fs::remove_dir_all(&dir);
+
+        // 日志目录无法创建时通过回调报告一次，而不是写到标准错误输出。
+        let blocker =
+            std::env::temp_dir().join(format!("auto_asr_log_blocked_{}", std::process::id()));
+        fs::write(&blocker, "file").unwrap();
+        let (errors, reported) = std::sync::mpsc::channel();
+        let writer = LogWriter::spawn(blocker.join("logs"), move |e| {
+            let _ = errors.send(e);
+        });
+        writer.write(&ScanLog::new(ScanLogLevel::Info, "一"));
+        writer.flush().await;
+        writer.write(&ScanLog::new(ScanLogLevel::Info, "二"));
+        writer.flush().await;
+        let reported: Vec<String> = reported.try_iter().collect();
+        assert_eq!(reported.len(), 1);
+        assert!(reported[0].contains("写入日志文件失败"));
+        let _ = fs::remove_file(&blocker);
+    }
+
+    #[test]
+    fn prune_removes_only_expired_log_files() {
+        let dir = std::env::temp_dir().join(format!("auto_asr_log_prune_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let today = NaiveDate::from_ymd_opt(2024, 5, 31).unwrap();
+        let old = log_file_path(&dir, NaiveDate::from_ymd_opt(2024, 4, 1).unwrap());
+        let recent = log_file_path(&dir, NaiveDate::from_ymd_opt(2024, 5, 30).unwrap());
+        let unrelated = dir.join("notes.log");
+        for path in [&old, &recent, &unrelated] {
+            fs::write(path, "x").unwrap();
+        }
+
+        prune(&dir, today).unwrap();
+
+        assert!(!old.exists());
+        assert!(recent.exists());
+        assert!(unrelated.exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -1,26 +1,75 @@
 //! Iced GUI 入口，负责状态管理、调度以及用户交互。
 
-use crate::config::AppConfig;
-use crate::scanner::{process_directory, ScanLog, ScanLogLevel, ScannerOptions, VadConfig};
-use chrono::{Local, NaiveTime, Timelike};
+use crate::api::{build_client, validate_api_key, ApiFlavor};
+use crate::config::{AppConfig, AppTheme, DEFAULT_PROFILE, MAX_CONCURRENCY, SUGGESTED_MODELS};
+use crate::i18n::Language;
+use crate::log_file::LogWriter;
+use crate::scanner::{
+    check_ffmpeg, display_path, has_media_extension, preview_vad, process_directories,
+    process_files, validate_export_bitrate, validate_include_patterns, validate_text_replacements,
+    validate_track_selection, validate_vad_format, DownmixFilter, ExportCodec, FileState,
+    GapPolicy, OutputFormat, PauseToken, RunReport, ScanEvent, ScanLog, ScanLogLevel,
+    ScannerOptions, SegmentMode, VadFormat, VadPreview, DEFAULT_SEGMENT_INTERVAL_SECS,
+    VAD_MIN_SEGMENT_RANGE, VAD_PREVIEW_SECS, VAD_SMOOTHING_WINDOW_MAX, VAD_THRESHOLD_RANGE,
+};
+use crate::tray::{Tray, TrayAction};
+use crate::watch::WatchEvent;
+use chrono::{Datelike, Local, NaiveTime, Timelike, Weekday};
 use iced::{
-    executor, time,
-    widget::{button, checkbox, scrollable, slider, text, text_input, Column, Container, Row},
-    Alignment, Application, Color, Command, Element, Font, Length, Settings, Subscription, Theme,
+    event, executor, keyboard, time,
+    widget::{
+        button, checkbox, container, pick_list, progress_bar, scrollable, slider, text, text_input,
+        Column, Container, Row,
+    },
+    window, Alignment, Application, Color, Command, Element, Event, Font, Length, Point, Settings,
+    Size, Subscription, Theme,
 };
 use std::{
+    collections::{HashMap, HashSet},
     path::{Path, PathBuf},
     sync::Arc,
 };
 use tokio::sync::{mpsc, Mutex};
+use tokio_util::sync::CancellationToken;
 
 mod api;
+mod cli;
 mod config;
+mod i18n;
+mod keychain;
+mod log_file;
+mod scan_report;
 mod scanner;
+mod tray;
+mod watch;
+
+/// 窗口尺寸的下限，避免恢复出无法操作的窗口。
+const MIN_WINDOW_SIZE: Size = Size::new(480.0, 360.0);
 
-/// 程序入口，启动 Iced 应用。
+/// 程序入口：带 `--scan` 时以无界面模式运行，否则读取配置并按上次的窗口尺寸与位置启动 Iced 应用。
 pub fn main() -> iced::Result {
-    AutoAsrApp::run(Settings::default())
+    match cli::parse_args(std::env::args().skip(1)) {
+        Ok(cli::CliCommand::Gui) => {}
+        Ok(cli::CliCommand::Help) => {
+            println!("{}", cli::USAGE);
+            return Ok(());
+        }
+        Ok(cli::CliCommand::Scan(args)) => {
+            if let Err(e) = cli::run(args) {
+                eprintln!("{}", tr!("错误：{:#}", e));
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, cli::USAGE);
+            std::process::exit(2);
+        }
+    }
+
+    let mut settings = Settings::with_flags(AppConfig::load_or_default());
+    settings.window = AutoAsrApp::window_settings(&settings.flags.0);
+    AutoAsrApp::run(settings)
 }
 
 /// GUI 主体，封装配置、调度状态与日志输出。
@@ -28,9 +77,82 @@ struct AutoAsrApp {
     config: AppConfig,
     is_running: bool,
     logs: Vec<ScanLog>,
-    last_run_date: Option<String>,
+    /// “预览”模式：立即扫描时只列出待处理目标，不实际转写。
+    dry_run: bool,
+    /// 每个执行时间最近一次触发的日期，避免同一天重复运行。
+    last_runs: HashMap<String, String>,
     is_processing: bool,
-    scan_progress_rx: Option<Arc<Mutex<mpsc::UnboundedReceiver<ScanLog>>>>,
+    scan_progress_rx: Option<Arc<Mutex<mpsc::UnboundedReceiver<ScanEvent>>>>,
+    /// 当前扫描已完成与待处理的音轨数，未扫描时为 `None`。
+    scan_progress: Option<(usize, usize)>,
+    /// 当前扫描每个音轨的平均耗时（秒），样本不足时为 `None`。
+    scan_secs_per_target: Option<f64>,
+    profiles: Vec<String>,
+    active_profile: String,
+    profile_name_input: String,
+    /// “每分钟费用”输入框的原始文本，保留小数点等尚未完成的输入。
+    cost_per_minute_input: String,
+    /// 最近一次扫描或预览的费用摘要，未设置单价时为 `None`。
+    cost_summary: Option<String>,
+    cancel_token: Option<CancellationToken>,
+    /// 当前扫描的暂停开关，扫描结束后清空。
+    pause_token: Option<PauseToken>,
+    /// 最近一次扫描写出的最后一个结果文件，供“打开结果目录”使用。
+    last_output: Option<PathBuf>,
+    /// 当前扫描是否由定时任务触发，用于决定结束后是否发送桌面通知。
+    scheduled_scan: bool,
+    /// 系统托盘图标，平台不支持或创建失败时为 `None`。
+    tray: Option<Tray>,
+    /// 是否正在监视所选目录中新出现的媒体文件。
+    is_watching: bool,
+    /// 监视到但尚未转写的文件，当前扫描结束后统一处理。
+    watch_queue: Vec<PathBuf>,
+    /// 是否正在校验 API 密钥，期间忽略重复的启动请求。
+    checking_api_key: bool,
+    /// 当前扫描已通过进度通道实时收到的日志序号，扫描结束时只补上尚未收到的部分。
+    streamed_logs: HashSet<u64>,
+    /// 当前（或最近一次）扫描中每个源文件的处理状态，按列入扫描的顺序排列。
+    file_statuses: Vec<FileStatus>,
+    /// VAD 预览使用的媒体文件，选定后调整参数可直接重新预览。
+    vad_preview_file: Option<PathBuf>,
+    /// 是否正在运行 VAD 预览，期间禁用预览按钮。
+    is_previewing_vad: bool,
+    /// 拖入窗口、等待确认转写的媒体文件。
+    dropped_files: Vec<PathBuf>,
+    /// 界面日志的文件写入线程及其日志目录，目录变化时重新启动。
+    log_writer: Option<(PathBuf, LogWriter)>,
+    /// 写入线程报告的写入失败，每秒取出显示在日志面板中。
+    log_write_errors: Arc<std::sync::Mutex<Vec<String>>>,
+}
+
+/// 文件状态列表中的一行。
+#[derive(Debug, Clone)]
+struct FileStatus {
+    path: PathBuf,
+    state: FileState,
+    tracks: usize,
+    /// VAD 分段进度（已处理, 总数），只在处理中显示。
+    segments: Option<(usize, usize)>,
+    /// 已转写成功的音轨写出的结果文件。
+    outputs: Vec<PathBuf>,
+    /// 最近一条音轨的失败原因。
+    error: Option<String>,
+}
+
+/// 文件状态徽章：圆角色块配白色文字。
+struct StatusBadge(Color);
+
+impl container::StyleSheet for StatusBadge {
+    type Style = Theme;
+
+    fn appearance(&self, _style: &Self::Style) -> container::Appearance {
+        container::Appearance {
+            background: Some(self.0.into()),
+            text_color: Some(Color::WHITE),
+            border: iced::Border::with_radius(4),
+            ..Default::default()
+        }
+    }
 }
 
 /// Iced 消息枚举，覆盖用户交互与后台任务回调。
@@ -38,45 +160,231 @@ struct AutoAsrApp {
 enum Message {
     DirectorySelected(Option<PathBuf>),
     SelectDirectory,
+    SelectFile,
+    FileSelected(Option<PathBuf>),
+    SelectOutputDir,
+    OutputDirSelected(Option<PathBuf>),
+    OutputDirChanged(String),
+    RemoveDirectory(usize),
+    RecentDirectorySelected(String),
+    PathDropped(PathBuf),
+    TranscribeDroppedFiles,
+    ClearDroppedFiles,
+    FfmpegPathChanged(String),
+    FfprobePathChanged(String),
+    BrowseTool(ExternalTool),
+    ToolSelected(ExternalTool, Option<PathBuf>),
+    FfmpegChecked(Result<String, String>),
+    ThemeChanged(AppTheme),
+    LanguageChanged(Language),
+    WindowResized(u32, u32),
+    WindowMoved(i32, i32),
+    WindowCloseRequested(window::Id),
+    MinimizeToTrayToggled(bool),
+    AutoStartToggled(bool),
+    NotifyOnCompletionToggled(bool),
+    LogToFileToggled(bool),
+    WriteReportToggled(bool),
+    OpenLastOutput,
+    ToggleWatch,
+    Watch(WatchEvent),
+    NotificationSent(Result<(), String>),
+    TrayPolled,
+    ShowWindow,
+    QuitApp,
     ApiKeyChanged(String),
     ApiUrlChanged(String),
+    ProxyUrlChanged(String),
     ModelNameChanged(String),
-    ScheduleTimeChanged(String),
-    VadToggled(bool),
+    ApiFlavorSelected(ApiFlavor),
+    ScheduleTimeChanged(usize, String),
+    AddScheduleTime,
+    RemoveScheduleTime(usize),
+    ExcludePatternChanged(usize, String),
+    AddExcludePattern,
+    IncludePatternChanged(usize, String),
+    AddIncludePattern,
+    RemoveIncludePattern(usize),
+    DryRunToggled(bool),
+    SplitWholeCuesToggled(bool),
+    TrimTrailingPunctuationToggled(bool),
+    CollapseWhitespaceToggled(bool),
+    MaxDepthChanged(String),
+    MaxFileSizeChanged(String),
+    RemoveExcludePattern(usize),
+    WeekdayToggled(Weekday, bool),
+    SegmentModeSelected(SegmentMode),
+    SegmentIntervalChanged(String),
     VadThresholdChanged(f32),
     VadMinDurationChanged(f32),
+    VadMergeGapChanged(f32),
+    VadMaxSegmentChanged(f32),
+    VadLeadPaddingChanged(f32),
+    VadTailPaddingChanged(f32),
+    VadSmoothingChanged(u8),
+    VadGapPolicySelected(GapPolicy),
+    VadFormatSelected(VadFormat),
+    PreviewVad,
+    PickVadPreviewFile,
+    VadPreviewFileSelected(Option<PathBuf>),
+    VadPreviewFinished(Result<VadPreview, String>),
+    VadAdaptiveToggled(bool),
+    SpeedupChanged(f32),
+    AudioNormalizeToggled(bool),
+    DownmixFilterSelected(DownmixFilter),
+    TrimSegmentSilenceToggled(bool),
+    ToolTimeoutChanged(String),
+    RequestTimeoutChanged(String),
+    MaxConcurrencyChanged(String),
+    FfmpegConcurrencyChanged(String),
+    RequestsPerMinuteChanged(String),
+    AbortAfterFailuresChanged(String),
+    CostPerMinuteChanged(String),
+    TrackSelectionChanged(String),
+    OutputFormatSelected(OutputFormat),
+    AssFontNameChanged(String),
+    AssFontSizeChanged(String),
+    SanitizeFilenamesToggled(bool),
+    FilenameSubstituteChanged(String),
+    ExportCodecSelected(ExportCodec),
+    ExportBitrateChanged(String),
+    DiscardPartialToggled(bool),
+    AppendGrowingToggled(bool),
+    ForceReprocessToggled(bool),
+    DiarizeToggled(bool),
+    WordTimestampsToggled(bool),
+    KeepIntermediatesToggled(bool),
+    SkipIfEmbeddedSubsToggled(bool),
+    NameTracksByLanguageToggled(bool),
+    SubtitleSuffixesChanged(String),
+    ProbeExtensionlessToggled(bool),
+    CombineOutputsToggled(bool),
+    MuxSubtitlesToggled(bool),
+    MuxReplaceOriginalToggled(bool),
     ToggleRunning,
     RunOnce,
+    CancelScan,
+    PauseScan,
+    ResumeScan,
     Tick(chrono::DateTime<chrono::Local>),
-    ScanFinished(Result<Vec<ScanLog>, String>),
-    ScanProgress(Option<ScanLog>),
+    ScanFinished(Result<RunReport, String>),
+    ScanProgress(Option<ScanEvent>),
     SaveConfig,
-    ConfigSaved(Result<(), String>),
+    ClearLogs,
+    ApiKeyChecked(AfterKeyCheck, Result<bool, String>),
+    ConfigSaved(Result<Option<String>, String>),
+    ProfileSelected(String),
+    ProfileNameChanged(String),
+    SaveProfileAs,
+    ProfileSaved(Result<(String, Option<String>), String>),
+    DeleteProfile,
+    RenameProfile,
+}
+
+/// API 密钥校验通过后要继续执行的操作。
+#[derive(Debug, Clone)]
+enum AfterKeyCheck {
+    StartSchedule,
+    RunNow(Vec<PathBuf>),
+    RunFiles(Vec<PathBuf>),
+}
+
+/// 一次扫描的对象：已配置的目录，或单独选择、拖入的文件。
+#[derive(Debug, Clone)]
+enum ScanTarget {
+    Directories(Vec<PathBuf>),
+    Files(Vec<PathBuf>),
+}
+
+/// 可在设置中指定路径的外部工具。
+#[derive(Debug, Clone, Copy)]
+enum ExternalTool {
+    Ffmpeg,
+    Ffprobe,
 }
 
 impl Application for AutoAsrApp {
     type Executor = executor::Default;
     type Message = Message;
     type Theme = Theme;
-    type Flags = ();
-
-    fn new(_flags: ()) -> (Self, Command<Message>) {
-        let config = AppConfig::load().unwrap_or_default();
-        (
-            Self {
-                config,
-                is_running: false,
-                logs: vec![ScanLog::new(ScanLogLevel::Info, "应用已启动。")],
-                last_run_date: None,
-                is_processing: false,
-                scan_progress_rx: None,
-            },
-            Command::none(),
-        )
+    /// 启动时读取的配置，以及读取失败时需要写入日志的说明。
+    type Flags = (AppConfig, Option<String>);
+
+    fn new((mut config, load_error): (AppConfig, Option<String>)) -> (Self, Command<Message>) {
+        i18n::set_language(config.language);
+        let ffmpeg_check = AutoAsrApp::check_ffmpeg_command(config.ffmpeg_path());
+        let profiles =
+            AppConfig::list_profiles().unwrap_or_else(|_| vec![DEFAULT_PROFILE.to_string()]);
+        let mut logs = vec![ScanLog::new(ScanLogLevel::Info, tr!("应用已启动。"))];
+        if let Some(note) = load_error {
+            logs.push(ScanLog::new(ScanLogLevel::Error, note));
+        }
+        logs.extend(
+            config
+                .clamp_vad_settings()
+                .into_iter()
+                .map(|note| ScanLog::new(ScanLogLevel::Info, note)),
+        );
+        let tray = if tray::SUPPORTED {
+            Tray::new()
+                .map_err(|e| {
+                    logs.push(ScanLog::new(
+                        ScanLogLevel::Error,
+                        tr!("创建系统托盘图标失败：{}", e),
+                    ))
+                })
+                .ok()
+        } else {
+            None
+        };
+        let cost_per_minute_input = Self::cost_per_minute_text(&config);
+        let mut app = Self {
+            config,
+            is_running: false,
+            logs,
+            last_runs: HashMap::new(),
+            dry_run: false,
+            is_processing: false,
+            scan_progress_rx: None,
+            scan_progress: None,
+            scan_secs_per_target: None,
+            profiles,
+            active_profile: DEFAULT_PROFILE.to_string(),
+            profile_name_input: String::new(),
+            cost_per_minute_input,
+            cost_summary: None,
+            cancel_token: None,
+            pause_token: None,
+            scheduled_scan: false,
+            last_output: None,
+            tray,
+            is_watching: false,
+            watch_queue: Vec::new(),
+            checking_api_key: false,
+            streamed_logs: HashSet::new(),
+            file_statuses: Vec::new(),
+            vad_preview_file: None,
+            is_previewing_vad: false,
+            dropped_files: Vec::new(),
+            log_writer: None,
+            log_write_errors: Arc::default(),
+        };
+
+        let mut startup = vec![ffmpeg_check];
+        if app.config.auto_start {
+            match app.validate_ready_state() {
+                Ok(()) => {
+                    app.log_info(tr!("已开启启动时自动开始定时，正在启动定时任务……"));
+                    startup.push(app.check_api_key(AfterKeyCheck::StartSchedule));
+                }
+                Err(err) => app.log_error(tr!("自动启动定时任务失败：{}", err)),
+            }
+        }
+        (app, Command::batch(startup))
     }
 
     fn title(&self) -> String {
-        String::from("AutoASR - 语音转写助手")
+        String::from(tr!("AutoASR - 语音转写助手"))
     }
 
     fn update(&mut self, message: Message) -> Command<Message> {
@@ -92,32 +400,348 @@ impl Application for AutoAsrApp {
                     Message::DirectorySelected,
                 );
             }
+            Message::SelectFile => {
+                return Command::perform(
+                    async {
+                        rfd::AsyncFileDialog::new()
+                            .set_title(tr!("选择要转写的媒体文件"))
+                            .pick_file()
+                            .await
+                            .map(|h| h.path().to_path_buf())
+                    },
+                    Message::FileSelected,
+                );
+            }
+            Message::FileSelected(path) => {
+                if let Some(file) = path {
+                    return self.run_files(vec![file]);
+                }
+            }
             Message::DirectorySelected(path) => {
                 if let Some(p) = path {
-                    self.config.directory = Some(p.to_string_lossy().to_string());
-                    self.log_info(format!("已选择目录：{:?}", p));
+                    self.add_directory(p.to_string_lossy().to_string());
+                }
+            }
+            Message::RecentDirectorySelected(dir) => {
+                self.add_directory(dir);
+            }
+            // 一次拖入多个文件时每个路径各触发一次；文件只加入待转写列表，确认后才开始付费转写。
+            Message::PathDropped(path) => {
+                if path.is_dir() {
+                    return self.update(Message::DirectorySelected(Some(path)));
+                }
+                if path.is_file() && has_media_extension(&path) {
+                    if !self.dropped_files.contains(&path) {
+                        self.log_info(tr!(
+                            "已加入待转写列表：{}（共 {} 个），点击“转写拖入的文件”开始。",
+                            display_path(&path),
+                            self.dropped_files.len() + 1
+                        ));
+                        self.dropped_files.push(path);
+                    }
+                    return Command::none();
+                }
+                self.log_info(tr!(
+                    "已忽略拖入的 {}：不是文件夹或支持的媒体文件。",
+                    display_path(&path)
+                ));
+            }
+            Message::TranscribeDroppedFiles => {
+                if !self.dropped_files.is_empty() && !self.is_processing {
+                    let files = std::mem::take(&mut self.dropped_files);
+                    return self.run_files(files);
                 }
             }
+            Message::ClearDroppedFiles => {
+                self.dropped_files.clear();
+            }
+            Message::RemoveDirectory(idx) => {
+                if idx < self.config.directories.len() {
+                    let dir = self.config.directories.remove(idx);
+                    self.log_info(tr!("已移除目录：{}", display_path(&dir)));
+                }
+            }
+            Message::ThemeChanged(theme) => {
+                self.config.theme = theme;
+            }
+            Message::LanguageChanged(language) => {
+                self.config.language = language;
+                i18n::set_language(language);
+            }
+            Message::WindowResized(width, height) => {
+                // 最小化时部分平台会上报 0 尺寸，忽略以免恢复出不可见的窗口。
+                if width > 0 && height > 0 {
+                    self.config.window_width = Some(width as f32);
+                    self.config.window_height = Some(height as f32);
+                }
+            }
+            Message::WindowMoved(x, y) => {
+                // Windows 最小化时会把窗口移到 (-32000, -32000)，不记录该位置。
+                if x > -10_000 && y > -10_000 {
+                    self.config.window_x = Some(x);
+                    self.config.window_y = Some(y);
+                }
+            }
+            Message::WindowCloseRequested(id) => {
+                if let Err(e) = self.config.save_window_geometry(&self.active_profile) {
+                    self.log_error(tr!("保存窗口位置失败：{}", e));
+                }
+                if self.config.minimize_to_tray && self.tray.is_some() {
+                    return window::change_mode(id, window::Mode::Hidden);
+                }
+                return window::close(id);
+            }
+            Message::AutoStartToggled(enabled) => {
+                self.config.auto_start = enabled;
+            }
+            Message::MinimizeToTrayToggled(enabled) => {
+                self.config.minimize_to_tray = enabled;
+            }
+            Message::NotifyOnCompletionToggled(enabled) => {
+                self.config.notify_on_completion = enabled;
+            }
+            Message::OpenLastOutput => {
+                if let Some(dir) = self.last_output.as_deref().and_then(Path::parent) {
+                    if let Err(e) = opener::open(dir) {
+                        self.log_error(tr!("打开目录 {} 失败：{}", display_path(&dir), e));
+                    }
+                }
+            }
+            Message::ToggleWatch => {
+                if self.is_watching {
+                    self.is_watching = false;
+                    self.watch_queue.clear();
+                    self.log_info(tr!("已停止监视目录。"));
+                } else if self.dry_run {
+                    self.log_error(tr!("无法开始监视：请先关闭预览模式。"));
+                } else {
+                    match self.manual_ready_state() {
+                        Ok(dirs) => {
+                            self.is_watching = true;
+                            self.log_success(tr!("开始监视 {} 个目录。", dirs.len()));
+                        }
+                        Err(err) => self.log_error(tr!("无法开始监视：{}", err)),
+                    }
+                }
+            }
+            Message::Watch(WatchEvent::Ready(files)) => {
+                if !self.is_watching {
+                    return Command::none();
+                }
+                for file in files {
+                    if !self.watch_queue.contains(&file) {
+                        self.watch_queue.push(file);
+                    }
+                }
+                return self.drain_watch_queue();
+            }
+            Message::Watch(WatchEvent::Failed(err)) => {
+                self.is_watching = false;
+                self.watch_queue.clear();
+                self.log_error(err);
+            }
+            Message::WriteReportToggled(enabled) => {
+                self.config.write_report = enabled;
+                if let (true, Ok(dir)) = (enabled, self.config.report_dir()) {
+                    self.log_info(tr!("扫描报告将写入：{}", dir.display()));
+                }
+            }
+            Message::LogToFileToggled(enabled) => {
+                self.config.log_to_file = enabled;
+                if let (true, Ok(dir)) = (enabled, AppConfig::log_dir()) {
+                    self.log_info(tr!("日志将写入：{}", dir.display()));
+                }
+            }
+            Message::NotificationSent(Ok(())) => {}
+            Message::NotificationSent(Err(e)) => {
+                self.log_error(tr!("发送桌面通知失败：{}", e));
+            }
+            Message::TrayPolled => {
+                let Some(tray) = self.tray.as_mut() else {
+                    return Command::none();
+                };
+                let updated = tray.set_busy(self.is_processing);
+                let action = tray.poll();
+                if let Err(e) = updated {
+                    self.log_error(tr!("更新系统托盘图标失败：{}", e));
+                }
+                if let Some(action) = action {
+                    let message = match action {
+                        TrayAction::ShowWindow => Message::ShowWindow,
+                        TrayAction::ScanNow => Message::RunOnce,
+                        TrayAction::Quit => Message::QuitApp,
+                    };
+                    return self.update(message);
+                }
+            }
+            Message::ShowWindow => {
+                return Command::batch(vec![
+                    window::change_mode(window::Id::MAIN, window::Mode::Windowed),
+                    window::gain_focus(window::Id::MAIN),
+                ]);
+            }
+            Message::QuitApp => {
+                if let Err(e) = self.config.save_window_geometry(&self.active_profile) {
+                    self.log_error(tr!("保存窗口位置失败：{}", e));
+                }
+                return window::close(window::Id::MAIN);
+            }
+            Message::SelectOutputDir => {
+                return Command::perform(
+                    async {
+                        rfd::AsyncFileDialog::new()
+                            .pick_folder()
+                            .await
+                            .map(|h| h.path().to_path_buf())
+                    },
+                    Message::OutputDirSelected,
+                );
+            }
+            Message::OutputDirSelected(Some(path)) => {
+                self.config.output_dir = Some(path.to_string_lossy().to_string());
+            }
+            Message::OutputDirSelected(None) => {}
+            Message::OutputDirChanged(path) => {
+                self.config.output_dir = Some(path);
+            }
+            Message::FfmpegPathChanged(path) => {
+                self.config.ffmpeg_path = Some(path);
+            }
+            Message::FfprobePathChanged(path) => {
+                self.config.ffprobe_path = Some(path);
+            }
+            Message::BrowseTool(tool) => {
+                return Command::perform(
+                    async {
+                        rfd::AsyncFileDialog::new()
+                            .pick_file()
+                            .await
+                            .map(|h| h.path().to_path_buf())
+                    },
+                    move |path| Message::ToolSelected(tool, path),
+                );
+            }
+            Message::ToolSelected(tool, Some(path)) => {
+                let value = Some(path.to_string_lossy().to_string());
+                match tool {
+                    ExternalTool::Ffmpeg => {
+                        self.config.ffmpeg_path = value;
+                        return AutoAsrApp::check_ffmpeg_command(self.config.ffmpeg_path());
+                    }
+                    ExternalTool::Ffprobe => self.config.ffprobe_path = value,
+                }
+            }
+            Message::ToolSelected(_, None) => {}
+            Message::FfmpegChecked(Ok(version)) => {
+                self.log_info(tr!("已找到 FFmpeg：{}", version));
+            }
+            Message::FfmpegChecked(Err(e)) => {
+                self.log_error(tr!(
+                    "FFmpeg 不可用（{}），请安装 FFmpeg 或在设置中指定其路径。",
+                    e
+                ));
+            }
             Message::ApiKeyChanged(key) => {
                 self.config.api_key = key;
             }
             Message::ApiUrlChanged(url) => {
                 self.config.api_url = url;
             }
+            Message::ProxyUrlChanged(url) => {
+                self.config.proxy_url = Some(url);
+            }
             Message::ModelNameChanged(name) => {
                 self.config.model_name = name;
             }
-            Message::ScheduleTimeChanged(time) => {
-                self.config.schedule_time = time;
+            Message::ApiFlavorSelected(flavor) => {
+                self.config.api_flavor = flavor;
             }
-            Message::VadToggled(enabled) => {
-                self.config.vad_enabled = enabled;
-                let note = if enabled {
-                    "已启用 VAD 语音分段。"
-                } else {
-                    "已关闭 VAD 语音分段。"
-                };
-                self.log_info(note);
+            Message::ScheduleTimeChanged(idx, time) => {
+                if let Some(slot) = self.config.schedule_times.get_mut(idx) {
+                    *slot = time;
+                }
+            }
+            Message::WeekdayToggled(day, enabled) => {
+                let days = &mut self.config.schedule_weekdays;
+                days.retain(|d| *d != day);
+                if enabled {
+                    days.push(day);
+                    days.sort_by_key(|d| d.num_days_from_monday());
+                }
+            }
+            Message::ExcludePatternChanged(idx, pattern) => {
+                if let Some(slot) = self.config.exclude_patterns.get_mut(idx) {
+                    *slot = pattern;
+                }
+            }
+            Message::IncludePatternChanged(idx, pattern) => {
+                if let Some(slot) = self.config.include_patterns.get_mut(idx) {
+                    *slot = pattern;
+                }
+            }
+            Message::MaxDepthChanged(value) => {
+                let value = value.trim();
+                if value.is_empty() {
+                    self.config.max_depth = None;
+                } else if let Ok(depth) = value.parse::<usize>() {
+                    self.config.max_depth = (depth > 0).then_some(depth);
+                }
+            }
+            Message::MaxFileSizeChanged(value) => {
+                let value = value.trim();
+                if value.is_empty() {
+                    self.config.max_file_size_mb = None;
+                } else if let Ok(size) = value.parse::<u64>() {
+                    self.config.max_file_size_mb = (size > 0).then_some(size);
+                }
+            }
+            Message::SplitWholeCuesToggled(enabled) => {
+                self.config.split_whole_cues = enabled;
+            }
+            Message::TrimTrailingPunctuationToggled(enabled) => {
+                self.config.text_trim_trailing_punctuation = enabled;
+            }
+            Message::CollapseWhitespaceToggled(enabled) => {
+                self.config.text_collapse_whitespace = enabled;
+            }
+            Message::DryRunToggled(enabled) => {
+                self.dry_run = enabled;
+            }
+            Message::AddExcludePattern => {
+                self.config.exclude_patterns.push(String::new());
+            }
+            Message::RemoveExcludePattern(idx) => {
+                if idx < self.config.exclude_patterns.len() {
+                    self.config.exclude_patterns.remove(idx);
+                }
+            }
+            Message::AddIncludePattern => {
+                self.config.include_patterns.push(String::new());
+            }
+            Message::RemoveIncludePattern(idx) => {
+                if idx < self.config.include_patterns.len() {
+                    self.config.include_patterns.remove(idx);
+                }
+            }
+            Message::AddScheduleTime => {
+                self.config.schedule_times.push("12:00".to_string());
+            }
+            Message::RemoveScheduleTime(idx) => {
+                if self.config.schedule_times.len() > 1 && idx < self.config.schedule_times.len() {
+                    self.config.schedule_times.remove(idx);
+                }
+            }
+            Message::SegmentModeSelected(mode) => {
+                self.config.segment_mode = mode;
+                self.log_info(tr!("分段方式已切换为{}。", mode));
+            }
+            Message::SegmentIntervalChanged(value) => {
+                // 范围在扫描时再限制，避免输入过程中被改写。
+                if let Ok(secs) = value.trim().parse::<f64>() {
+                    if secs > 0.0 {
+                        self.config.segment_mode = SegmentMode::FixedInterval(secs);
+                    }
+                }
             }
             Message::VadThresholdChanged(value) => {
                 self.config.vad_threshold = value;
@@ -125,72 +749,475 @@ impl Application for AutoAsrApp {
             Message::VadMinDurationChanged(value) => {
                 self.config.vad_min_segment_secs = value;
             }
+            Message::VadMergeGapChanged(value) => {
+                self.config.vad_merge_gap_secs = value;
+            }
+            Message::VadMaxSegmentChanged(value) => {
+                self.config.vad_max_segment_secs = value;
+            }
+            Message::VadLeadPaddingChanged(value) => {
+                self.config.vad_lead_padding_secs = value;
+            }
+            Message::VadTailPaddingChanged(value) => {
+                self.config.vad_tail_padding_secs = value;
+            }
+            Message::VadGapPolicySelected(policy) => {
+                self.config.vad_gap_policy = policy;
+            }
+            Message::VadFormatSelected(format) => {
+                self.config.vad_sample_rate = format.sample_rate;
+                self.config.vad_chunk_size = format.chunk_size;
+            }
+            Message::PreviewVad => match self.vad_preview_file.clone() {
+                Some(path) => return self.vad_preview_command(path),
+                None => return self.update(Message::PickVadPreviewFile),
+            },
+            Message::PickVadPreviewFile => {
+                return Command::perform(
+                    async {
+                        rfd::AsyncFileDialog::new()
+                            .pick_file()
+                            .await
+                            .map(|h| h.path().to_path_buf())
+                    },
+                    Message::VadPreviewFileSelected,
+                );
+            }
+            Message::VadPreviewFileSelected(Some(path)) => {
+                self.vad_preview_file = Some(path.clone());
+                return self.vad_preview_command(path);
+            }
+            Message::VadPreviewFileSelected(None) => {}
+            Message::VadPreviewFinished(result) => {
+                self.is_previewing_vad = false;
+                match result {
+                    Ok(preview) => {
+                        for line in preview.describe() {
+                            self.log_info(line);
+                        }
+                    }
+                    Err(e) => self.log_error(tr!("VAD 预览失败：{}", e)),
+                }
+            }
+            Message::VadSmoothingChanged(window) => {
+                self.config.vad_smoothing_window = window as usize;
+            }
+            Message::VadAdaptiveToggled(enabled) => {
+                self.config.vad_adaptive_threshold = enabled;
+            }
+            Message::SpeedupChanged(value) => {
+                self.config.speedup_factor = value;
+            }
+            Message::DownmixFilterSelected(filter) => {
+                self.config.downmix_filter = filter;
+            }
+            Message::AudioNormalizeToggled(enabled) => {
+                self.config.audio_normalize = enabled;
+            }
+            Message::TrimSegmentSilenceToggled(enabled) => {
+                self.config.trim_segment_silence = enabled;
+            }
+            Message::DiscardPartialToggled(enabled) => {
+                self.config.discard_partial_on_cancel = enabled;
+            }
+            Message::AppendGrowingToggled(enabled) => {
+                self.config.append_growing_files = enabled;
+            }
+            Message::ForceReprocessToggled(enabled) => {
+                self.config.force_reprocess = enabled;
+            }
+            Message::DiarizeToggled(enabled) => {
+                self.config.diarize = enabled;
+            }
+            Message::WordTimestampsToggled(enabled) => {
+                self.config.word_timestamps = enabled;
+            }
+            Message::KeepIntermediatesToggled(enabled) => {
+                self.config.keep_intermediates = enabled;
+            }
+            Message::SkipIfEmbeddedSubsToggled(enabled) => {
+                self.config.skip_if_embedded_subs = enabled;
+            }
+            Message::NameTracksByLanguageToggled(enabled) => {
+                self.config.name_tracks_by_language = enabled;
+            }
+            Message::ProbeExtensionlessToggled(enabled) => {
+                self.config.probe_extensionless = enabled;
+            }
+            Message::CombineOutputsToggled(enabled) => {
+                self.config.combine_outputs = enabled;
+            }
+            Message::MuxSubtitlesToggled(enabled) => {
+                self.config.mux_subtitles = enabled;
+            }
+            Message::MuxReplaceOriginalToggled(enabled) => {
+                self.config.mux_replace_original = enabled;
+            }
+            Message::SubtitleSuffixesChanged(value) => {
+                // 保留原始输入（含空格与末尾逗号）以便继续编辑，生成扫描参数时再清理。
+                self.config.subtitle_suffixes = value.split(',').map(str::to_string).collect();
+            }
+            Message::RequestTimeoutChanged(value) => {
+                let value = value.trim();
+                if value.is_empty() {
+                    self.config.request_timeout_secs = 0;
+                } else if let Ok(secs) = value.parse::<u64>() {
+                    self.config.request_timeout_secs = secs;
+                }
+            }
+            Message::ToolTimeoutChanged(value) => {
+                let value = value.trim();
+                if value.is_empty() {
+                    self.config.tool_timeout_secs = 0;
+                } else if let Ok(secs) = value.parse::<u64>() {
+                    self.config.tool_timeout_secs = secs;
+                }
+            }
+            Message::MaxConcurrencyChanged(value) => {
+                if let Ok(limit) = value.trim().parse::<usize>() {
+                    self.config.max_concurrency = limit.clamp(1, MAX_CONCURRENCY);
+                }
+            }
+            Message::FfmpegConcurrencyChanged(value) => {
+                if let Ok(limit) = value.trim().parse::<usize>() {
+                    self.config.ffmpeg_concurrency = limit.min(MAX_CONCURRENCY);
+                }
+            }
+            Message::TrackSelectionChanged(value) => {
+                // 保留原始输入以便继续编辑，保存与启动扫描时再校验。
+                self.config.track_selection = value;
+            }
+            Message::RequestsPerMinuteChanged(value) => {
+                let value = value.trim();
+                if value.is_empty() {
+                    self.config.requests_per_minute = None;
+                } else if let Ok(limit) = value.parse::<u32>() {
+                    self.config.requests_per_minute = (limit > 0).then_some(limit);
+                }
+            }
+            Message::AbortAfterFailuresChanged(value) => {
+                let value = value.trim();
+                if value.is_empty() {
+                    self.config.abort_after_consecutive_failures = 0;
+                } else if let Ok(limit) = value.parse::<usize>() {
+                    self.config.abort_after_consecutive_failures = limit;
+                }
+            }
+            Message::CostPerMinuteChanged(value) => {
+                let trimmed = value.trim();
+                if trimmed.is_empty() {
+                    self.config.cost_per_minute = None;
+                } else if let Ok(rate) = trimmed.parse::<f64>() {
+                    self.config.cost_per_minute = (rate.is_finite() && rate > 0.0).then_some(rate);
+                } else {
+                    return Command::none();
+                }
+                self.cost_per_minute_input = value;
+            }
+            Message::ExportCodecSelected(codec) => {
+                self.config.export_codec = codec;
+            }
+            Message::ExportBitrateChanged(bitrate) => {
+                self.config.export_bitrate = bitrate;
+            }
+            Message::OutputFormatSelected(format) => {
+                self.config.output_format = format;
+            }
+            Message::AssFontNameChanged(name) => {
+                self.config.ass_font_name = name;
+            }
+            Message::AssFontSizeChanged(value) => {
+                if let Ok(size) = value.trim().parse::<u32>() {
+                    self.config.ass_font_size = size.clamp(1, 400);
+                }
+            }
+            Message::SanitizeFilenamesToggled(enabled) => {
+                self.config.sanitize_filenames = enabled;
+            }
+            Message::FilenameSubstituteChanged(substitute) => {
+                self.config.filename_substitute = substitute;
+            }
             Message::ToggleRunning => {
                 if self.is_running {
                     self.is_running = false;
-                    self.log_info("定时任务已停止。");
+                    self.log_info(tr!("定时任务已停止。"));
+                } else if self.checking_api_key {
+                    self.log_info(tr!("正在校验 API 密钥，请稍候。"));
                 } else {
                     match self.validate_ready_state() {
-                        Ok(_) => {
-                            self.is_running = true;
-                            self.last_run_date = None;
-                            self.log_success("定时任务已启动。");
-                        }
+                        Ok(_) => return self.check_api_key(AfterKeyCheck::StartSchedule),
                         Err(err) => {
-                            self.log_error(format!("无法启动定时任务：{}", err));
+                            self.log_error(tr!("无法启动定时任务：{}", err));
                         }
                     }
                 }
             }
             Message::RunOnce => {
                 if self.is_processing {
-                    self.log_info("已有扫描任务在进行中，请稍候。");
+                    self.log_info(tr!("已有扫描任务在进行中，请稍候。"));
+                } else if self.checking_api_key {
+                    self.log_info(tr!("正在校验 API 密钥，请稍候。"));
                 } else {
                     match self.manual_ready_state() {
+                        Ok(dir_path) if self.dry_run => {
+                            return self.start_scan(
+                                ScanTarget::Directories(dir_path),
+                                tr!("预览开始，仅列出待处理的文件……").to_string(),
+                                true,
+                                None,
+                            );
+                        }
                         Ok(dir_path) => {
-                            return self.start_scan(dir_path, "立即扫描开始……".to_string());
+                            return self.check_api_key(AfterKeyCheck::RunNow(dir_path));
                         }
                         Err(err) => self.log_error(err),
                     }
                 }
             }
+            Message::ApiKeyChecked(after, result) => {
+                self.checking_api_key = false;
+                match result {
+                    Ok(true) => self.log_success(tr!("API 密钥校验通过。")),
+                    Ok(false) => {
+                        self.log_error(tr!(
+                            "API 密钥被服务端拒绝（HTTP 401/403），请检查密钥后重试。"
+                        ));
+                        return Command::none();
+                    }
+                    // 网络暂时不通时无法判断密钥是否有效，不阻止启动，由扫描自行报告错误。
+                    Err(e) => self.log_info(tr!("无法校验 API 密钥：{}，仍继续执行。", e)),
+                }
+                match after {
+                    AfterKeyCheck::StartSchedule => {
+                        self.is_running = true;
+                        self.last_runs.clear();
+                        self.log_success(tr!("定时任务已启动。"));
+                    }
+                    AfterKeyCheck::RunNow(_) | AfterKeyCheck::RunFiles(_) if self.is_processing => {
+                        self.log_info(tr!("已有扫描任务在进行中，请稍候。"));
+                    }
+                    AfterKeyCheck::RunNow(dirs) => {
+                        return self.start_scan(
+                            ScanTarget::Directories(dirs),
+                            tr!("立即扫描开始……").to_string(),
+                            false,
+                            None,
+                        );
+                    }
+                    AfterKeyCheck::RunFiles(files) => {
+                        let reason = match files.as_slice() {
+                            [file] => tr!("开始转写单个文件 {}……", display_path(file)),
+                            _ => tr!("开始转写 {} 个文件……", files.len()),
+                        };
+                        return self.start_scan(ScanTarget::Files(files), reason, false, None);
+                    }
+                }
+            }
+            Message::CancelScan => {
+                if let Some(token) = &self.cancel_token {
+                    if !token.is_cancelled() {
+                        token.cancel();
+                        self.log_info(tr!("正在取消扫描，当前分段结束后停止……"));
+                    }
+                }
+            }
+            Message::PauseScan => {
+                if let Some(pause) = self.pause_token.as_ref().filter(|p| !p.is_paused()) {
+                    pause.pause();
+                    self.log_info(tr!(
+                        "扫描已暂停：进行中的转码与上传完成后不再开始新的工作。"
+                    ));
+                }
+            }
+            Message::ResumeScan => {
+                if let Some(pause) = self.pause_token.as_ref().filter(|p| p.is_paused()) {
+                    pause.resume();
+                    self.log_info(tr!("扫描已继续。"));
+                }
+            }
+            Message::ClearLogs => {
+                // 进行中的扫描随后送达的日志会照常追加，已清空的部分不会在扫描结束时重新出现。
+                self.logs.clear();
+                self.log_info(tr!("日志已清空。"));
+            }
             Message::SaveConfig => {
+                if let Some(proxy) = self.config.proxy_url() {
+                    if let Err(e) = config::validate_proxy_url(&proxy) {
+                        self.log_error(tr!("保存配置失败：{}", e));
+                        return Command::none();
+                    }
+                }
+                if let Err(e) = validate_export_bitrate(&self.config.export_bitrate) {
+                    self.log_error(tr!("保存配置失败：{}", e));
+                    return Command::none();
+                }
+                if let Err(e) = validate_track_selection(&self.config.track_selection) {
+                    self.log_error(tr!("保存配置失败：{}", e));
+                    return Command::none();
+                }
+                if let Err(e) = validate_include_patterns(&self.config.include_patterns) {
+                    self.log_error(tr!("保存配置失败：{}", e));
+                    return Command::none();
+                }
+                if let Err(e) = validate_text_replacements(&self.config.text_replacements) {
+                    self.log_error(tr!("保存配置失败：{}", e));
+                    return Command::none();
+                }
+                if let Err(e) =
+                    validate_vad_format(self.config.vad_sample_rate, self.config.vad_chunk_size)
+                {
+                    self.log_error(tr!("保存配置失败：{}", e));
+                    return Command::none();
+                }
                 let config = self.config.clone();
+                let profile = self.active_profile.clone();
                 return Command::perform(
-                    async move { config.save().map_err(|e| e.to_string()) },
+                    async move { config.save_profile(&profile).map_err(|e| e.to_string()) },
                     Message::ConfigSaved,
                 );
             }
             Message::ConfigSaved(res) => match res {
-                Ok(_) => self.log_success("配置已保存。"),
-                Err(e) => self.log_error(format!("保存配置失败：{}", e)),
+                Ok(note) => {
+                    self.log_success(tr!("配置已保存（配置档：{}）。", self.active_profile));
+                    if let Some(note) = note {
+                        self.log_error(note);
+                    }
+                }
+                Err(e) => self.log_error(tr!("保存配置失败：{}", e)),
+            },
+            Message::ProfileSelected(name) => match AppConfig::load_profile(&name) {
+                Ok((config, note)) => {
+                    self.config = config;
+                    self.active_profile = name;
+                    self.log_info(tr!("已切换到配置档：{}", self.active_profile));
+                    if let Some(note) = note {
+                        self.log_error(note);
+                    }
+                    self.apply_loaded_config();
+                }
+                Err(e) => self.log_error(tr!("加载配置档 {} 失败：{}", name, e)),
+            },
+            Message::ProfileNameChanged(name) => {
+                self.profile_name_input = name;
+            }
+            Message::SaveProfileAs => {
+                let name = self.profile_name_input.trim().to_string();
+                if let Err(e) = config::validate_profile_name(&name) {
+                    self.log_error(tr!("无法另存配置档：{}", e));
+                    return Command::none();
+                }
+                let config = self.config.clone();
+                return Command::perform(
+                    async move {
+                        config
+                            .save_profile(&name)
+                            .map(|note| (name, note))
+                            .map_err(|e| e.to_string())
+                    },
+                    Message::ProfileSaved,
+                );
+            }
+            Message::ProfileSaved(res) => match res {
+                Ok((name, note)) => {
+                    self.active_profile = name;
+                    self.profile_name_input.clear();
+                    self.refresh_profiles();
+                    self.log_success(tr!("已另存为配置档：{}", self.active_profile));
+                    if let Some(note) = note {
+                        self.log_error(note);
+                    }
+                }
+                Err(e) => self.log_error(tr!("另存配置档失败：{}", e)),
             },
+            Message::DeleteProfile => {
+                let name = self.active_profile.clone();
+                match AppConfig::delete_profile(&name) {
+                    Ok(delete_note) => {
+                        self.active_profile = DEFAULT_PROFILE.to_string();
+                        let (config, load_error) = AppConfig::load_or_default();
+                        self.config = config;
+                        self.refresh_profiles();
+                        self.log_success(tr!("已删除配置档 {}，已切换回默认配置。", name));
+                        if let Some(note) = delete_note {
+                            self.log_error(note);
+                        }
+                        if let Some(note) = load_error {
+                            self.log_error(note);
+                        }
+                        self.apply_loaded_config();
+                    }
+                    Err(e) => self.log_error(tr!("删除配置档 {} 失败：{}", name, e)),
+                }
+            }
+            Message::RenameProfile => {
+                let old = self.active_profile.clone();
+                let new = self.profile_name_input.trim().to_string();
+                if let Err(e) = config::validate_profile_name(&new) {
+                    self.log_error(tr!("无法重命名配置档：{}", e));
+                    return Command::none();
+                }
+                match AppConfig::rename_profile(&old, &new) {
+                    Ok(note) => {
+                        self.active_profile = new;
+                        self.profile_name_input.clear();
+                        self.refresh_profiles();
+                        self.log_success(tr!(
+                            "已将配置档 {} 重命名为 {}。",
+                            old,
+                            self.active_profile
+                        ));
+                        if let Some(note) = note {
+                            self.log_error(note);
+                        }
+                    }
+                    Err(e) => self.log_error(tr!("重命名配置档 {} 失败：{}", old, e)),
+                }
+            }
             Message::Tick(now) => {
+                let write_errors = std::mem::take(
+                    &mut *self
+                        .log_write_errors
+                        .lock()
+                        .unwrap_or_else(|poisoned| poisoned.into_inner()),
+                );
+                // 直接加入面板而不经 `push_log`，避免再次写入出错的日志文件。
+                for message in write_errors {
+                    self.logs.push(ScanLog::new(ScanLogLevel::Error, message));
+                }
                 if self.is_running && !self.is_processing {
-                    let target_time =
-                        match NaiveTime::parse_from_str(&self.config.schedule_time, "%H:%M") {
-                            Ok(t) => t,
-                            Err(_) => {
-                                self.log_error("时间格式无效，已停止定时任务。");
-                                self.is_running = false;
-                                return Command::none();
-                            }
-                        };
+                    let target_times = match self.config.parsed_schedule_times() {
+                        Ok(times) => times,
+                        Err(_) => {
+                            self.log_error(tr!("时间格式无效，已停止定时任务。"));
+                            self.is_running = false;
+                            return Command::none();
+                        }
+                    };
 
                     let now_time = now.time();
                     let current_date = now.format("%Y-%m-%d").to_string();
 
-                    if now_time.hour() == target_time.hour()
-                        && now_time.minute() == target_time.minute()
-                        && self.last_run_date.as_deref() != Some(&current_date)
-                    {
-                        if let Some(dir) = self.config.directory.clone() {
-                            self.last_run_date = Some(current_date);
-                            let dir_path = PathBuf::from(dir);
-                            return self
-                                .start_scan(dir_path, "到达定时时间，开始扫描……".to_string());
+                    let due = target_times.iter().find(|target| {
+                        self.config.runs_on(now.weekday())
+                            && now_time.hour() == target.hour()
+                            && now_time.minute() == target.minute()
+                            && self.last_runs.get(&Self::schedule_key(target))
+                                != Some(&current_date)
+                    });
+                    if let Some(target) = due {
+                        let dirs = self.config.directory_paths();
+                        if dirs.is_empty() {
+                            self.log_error(tr!("到达定时时间但尚未选择目录。"));
                         } else {
-                            self.log_error("到达定时时间但尚未选择目录。");
+                            self.last_runs
+                                .insert(Self::schedule_key(target), current_date);
+                            self.scheduled_scan = true;
+                            return self.start_scan(
+                                ScanTarget::Directories(dirs),
+                                tr!("到达定时时间，开始扫描……").to_string(),
+                                false,
+                                None,
+                            );
                         }
                     }
                 }
@@ -198,18 +1225,134 @@ impl Application for AutoAsrApp {
             Message::ScanFinished(res) => {
                 self.is_processing = false;
                 self.scan_progress_rx = None;
+                self.scan_progress = None;
+                self.scan_secs_per_target = None;
+                // 取消后仍在处理中的文件并未完成，退回等待状态。
+                for status in &mut self.file_statuses {
+                    if status.state == FileState::Processing {
+                        status.state = FileState::Pending;
+                        status.segments = None;
+                    }
+                }
+                self.cancel_token = None;
+                self.pause_token = None;
+                let notify =
+                    std::mem::take(&mut self.scheduled_scan) && self.config.notify_on_completion;
                 match res {
-                    Ok(new_logs) => {
-                        self.logs.extend(new_logs);
-                        self.log_success("扫描流程完成。");
+                    Ok(report) if report.dry_run => {
+                        self.cost_summary = report.cost_summary();
+                        self.append_unstreamed_logs(report.logs);
+                        self.log_success(tr!(
+                            "预览完成：待转写 {} 个音轨，跳过 {} 个。",
+                            report.pending,
+                            report.skipped
+                        ));
+                    }
+                    Ok(report) => {
+                        let headline = if report.cancelled {
+                            tr!("扫描已取消")
+                        } else {
+                            tr!("扫描流程完成")
+                        };
+                        self.cost_summary = report.cost_summary();
+                        self.append_unstreamed_logs(report.logs);
+                        if let Some(path) = report.outputs.last() {
+                            self.last_output = Some(path.clone());
+                        }
+                        self.log_success(tr!(
+                            "{}：成功 {}，失败 {}，跳过 {}，共 {} 个分段，用时 {} 秒。",
+                            headline,
+                            report.succeeded.len(),
+                            report.failed.len(),
+                            report.skipped,
+                            report.total_segments,
+                            report.elapsed.as_secs()
+                        ));
+                        if notify {
+                            let summary = if report.cancelled {
+                                tr!("AutoASR 定时扫描已取消")
+                            } else if report.failed.is_empty() {
+                                tr!("AutoASR 定时扫描完成")
+                            } else {
+                                tr!("AutoASR 定时扫描完成，但有文件失败")
+                            };
+                            let body = tr!(
+                                "成功 {} 个，失败 {} 个，跳过 {} 个。",
+                                report.succeeded.len(),
+                                report.failed.len(),
+                                report.skipped
+                            );
+                            return Command::batch(vec![
+                                Self::send_notification(summary, body),
+                                self.drain_watch_queue(),
+                            ]);
+                        }
                     }
                     Err(e) => {
-                        self.log_error(format!("扫描过程中出现错误：{}", e));
+                        self.log_error(tr!("扫描过程中出现错误：{}", e));
+                        if notify {
+                            return Command::batch(vec![
+                                Self::send_notification(tr!("AutoASR 定时扫描出错"), e),
+                                self.drain_watch_queue(),
+                            ]);
+                        }
                     }
                 }
+                return self.drain_watch_queue();
             }
-            Message::ScanProgress(Some(log)) => {
-                self.logs.push(log);
+            Message::ScanProgress(Some(event)) => {
+                match event {
+                    ScanEvent::Log(log) => {
+                        self.streamed_logs.insert(log.id);
+                        self.logs.push(log);
+                    }
+                    ScanEvent::Progress {
+                        completed,
+                        total,
+                        secs_per_target,
+                    } => {
+                        self.scan_progress = Some((completed, total));
+                        self.scan_secs_per_target = secs_per_target;
+                    }
+                    ScanEvent::File {
+                        path,
+                        state,
+                        tracks,
+                    } => match self.file_statuses.iter_mut().find(|s| s.path == path) {
+                        Some(status) => {
+                            status.state = state;
+                            status.tracks = tracks;
+                        }
+                        None => self.file_statuses.push(FileStatus {
+                            path,
+                            state,
+                            tracks,
+                            segments: None,
+                            outputs: Vec::new(),
+                            error: None,
+                        }),
+                    },
+                    ScanEvent::Segment { path, done, total } => {
+                        if let Some(status) = self.file_statuses.iter_mut().find(|s| s.path == path)
+                        {
+                            status.segments = Some((done, total));
+                        }
+                    }
+                    ScanEvent::TrackDone { path, output } => {
+                        if let Some(status) = self.file_statuses.iter_mut().find(|s| s.path == path)
+                        {
+                            status.segments = None;
+                            status.outputs.push(output);
+                        }
+                    }
+                    ScanEvent::TrackFailed { path, error } => {
+                        if let Some(status) = self.file_statuses.iter_mut().find(|s| s.path == path)
+                        {
+                            status.segments = None;
+                            status.error = Some(error);
+                        }
+                    }
+                }
                 if let Some(rx) = &self.scan_progress_rx {
                     return AutoAsrApp::listen_scan_progress(rx.clone());
                 }
@@ -224,75 +1367,814 @@ impl Application for AutoAsrApp {
     fn view(&self) -> Element<'_, Message> {
         let font = Self::preferred_font();
 
-        let title = text("AutoASR 语音转写助手").font(font).size(30);
+        let title = text(tr!("AutoASR 语音转写助手")).font(font).size(30);
 
-        let dir_display =
-            text(self.config.directory.as_deref().unwrap_or("尚未选择目录")).font(font);
-        let dir_btn = button(text("选择目录").font(font)).on_press(Message::SelectDirectory);
+        let dir_list = if self.config.directories.is_empty() {
+            Column::new().push(text(tr!("尚未选择目录")).font(font))
+        } else {
+            self.config.directories.iter().enumerate().fold(
+                Column::new().spacing(5),
+                |col, (idx, dir)| {
+                    col.push(
+                        Row::new()
+                            .spacing(10)
+                            .align_items(Alignment::Center)
+                            .push(
+                                button(text(tr!("移除")).font(font))
+                                    .on_press(Message::RemoveDirectory(idx))
+                                    .padding(5),
+                            )
+                            .push(text(dir).font(font)),
+                    )
+                },
+            )
+        };
+        let dir_btn = button(text(tr!("添加目录")).font(font)).on_press(Message::SelectDirectory);
+        let recent_picker = (!self.config.recent_directories.is_empty()).then(|| {
+            pick_list(
+                &self.config.recent_directories[..],
+                None::<String>,
+                Message::RecentDirectorySelected,
+            )
+            .placeholder(tr!("最近使用的目录"))
+            .font(font)
+        });
 
-        let api_key_input = text_input("请输入 API 密钥", &self.config.api_key)
+        let api_key_input = text_input(tr!("请输入 API 密钥"), &self.config.api_key)
             .on_input(Message::ApiKeyChanged)
             .padding(10)
             .font(font);
 
-        let api_url_input = text_input("API 地址", &self.config.api_url)
+        let api_url_input = text_input(tr!("API 地址"), &self.config.api_url)
             .on_input(Message::ApiUrlChanged)
             .padding(10)
             .font(font);
 
-        let model_name_input = text_input("模型名称", &self.config.model_name)
+        let proxy_url_input = text_input(
+            tr!("代理地址（可选，如 http://127.0.0.1:7890）"),
+            self.config.proxy_url.as_deref().unwrap_or(""),
+        )
+        .on_input(Message::ProxyUrlChanged)
+        .padding(10)
+        .font(font);
+
+        let model_name_input = text_input(tr!("模型名称"), &self.config.model_name)
             .on_input(Message::ModelNameChanged)
             .padding(10)
             .font(font);
+        let model_picker = pick_list(
+            SUGGESTED_MODELS,
+            SUGGESTED_MODELS
+                .iter()
+                .copied()
+                .find(|model| *model == self.config.model_name.trim()),
+            |model: &str| Message::ModelNameChanged(model.to_string()),
+        )
+        .placeholder(tr!("常用模型"))
+        .font(font);
+
+        let api_flavor_picker = pick_list(
+            &ApiFlavor::ALL[..],
+            Some(self.config.api_flavor),
+            Message::ApiFlavorSelected,
+        )
+        .font(font);
 
-        let schedule_input = text_input("执行时间（HH:MM）", &self.config.schedule_time)
-            .on_input(Message::ScheduleTimeChanged)
+        let can_remove_time = self.config.schedule_times.len() > 1;
+        let schedule_list = self.config.schedule_times.iter().enumerate().fold(
+            Column::new().spacing(5),
+            |col, (idx, time)| {
+                let mut remove_btn = button(text(tr!("删除")).font(font)).padding(10);
+                if can_remove_time {
+                    remove_btn = remove_btn.on_press(Message::RemoveScheduleTime(idx));
+                }
+                col.push(
+                    Row::new()
+                        .spacing(10)
+                        .align_items(Alignment::Center)
+                        .push(
+                            text_input(tr!("执行时间（HH:MM）"), time)
+                                .on_input(move |value| Message::ScheduleTimeChanged(idx, value))
+                                .padding(10)
+                                .font(font),
+                        )
+                        .push(remove_btn),
+                )
+            },
+        );
+        let add_time_btn = button(text(tr!("添加时间")).font(font))
+            .on_press(Message::AddScheduleTime)
+            .padding(10);
+
+        let exclude_list = self.config.exclude_patterns.iter().enumerate().fold(
+            Column::new().spacing(5),
+            |col, (idx, pattern)| {
+                col.push(
+                    Row::new()
+                        .spacing(10)
+                        .align_items(Alignment::Center)
+                        .push(
+                            text_input(tr!("例如 **/backup/** 或 *.sample.mp4"), pattern)
+                                .on_input(move |value| Message::ExcludePatternChanged(idx, value))
+                                .padding(10)
+                                .font(font),
+                        )
+                        .push(
+                            button(text(tr!("删除")).font(font))
+                                .on_press(Message::RemoveExcludePattern(idx))
+                                .padding(10),
+                        ),
+                )
+            },
+        );
+        let add_exclude_btn = button(text(tr!("添加排除规则")).font(font))
+            .on_press(Message::AddExcludePattern)
+            .padding(10);
+
+        let include_list = self.config.include_patterns.iter().enumerate().fold(
+            Column::new().spacing(5),
+            |col, (idx, pattern)| {
+                col.push(
+                    Row::new()
+                        .spacing(10)
+                        .align_items(Alignment::Center)
+                        .push(
+                            text_input(tr!("例如 *.interview.mp4"), pattern)
+                                .on_input(move |value| Message::IncludePatternChanged(idx, value))
+                                .padding(10)
+                                .font(font),
+                        )
+                        .push(
+                            button(text(tr!("删除")).font(font))
+                                .on_press(Message::RemoveIncludePattern(idx))
+                                .padding(10),
+                        ),
+                )
+            },
+        );
+        let add_include_btn = button(text(tr!("添加包含规则")).font(font))
+            .on_press(Message::AddIncludePattern)
+            .padding(10);
+
+        let subtitle_suffixes_value = self.config.subtitle_suffixes.join(",");
+        let subtitle_suffixes_input =
+            text_input(tr!("例如 zh,chi,chs，留空不检查"), &subtitle_suffixes_value)
+                .on_input(Message::SubtitleSuffixesChanged)
+                .padding(10)
+                .font(font);
+
+        let max_depth_value = self
+            .config
+            .max_depth
+            .map(|depth| depth.to_string())
+            .unwrap_or_default();
+        let max_depth_input = text_input(tr!("留空不限制，1 仅扫描所选目录"), &max_depth_value)
+            .on_input(Message::MaxDepthChanged)
             .padding(10)
             .font(font);
 
-        let vad_toggle = checkbox("启用 VAD 语音分段", self.config.vad_enabled)
-            .on_toggle(Message::VadToggled)
-            .spacing(10)
-            .text_size(16)
+        let max_file_size_value = self
+            .config
+            .max_file_size_mb
+            .map(|size| size.to_string())
+            .unwrap_or_default();
+        let max_file_size_input = text_input(tr!("留空不限制"), &max_file_size_value)
+            .on_input(Message::MaxFileSizeChanged)
+            .padding(10)
             .font(font);
 
+        let weekdays = [
+            (Weekday::Mon, tr!("周一")),
+            (Weekday::Tue, tr!("周二")),
+            (Weekday::Wed, tr!("周三")),
+            (Weekday::Thu, tr!("周四")),
+            (Weekday::Fri, tr!("周五")),
+            (Weekday::Sat, tr!("周六")),
+            (Weekday::Sun, tr!("周日")),
+        ];
+        let weekday_row = weekdays.iter().fold(
+            Row::new().spacing(15).align_items(Alignment::Center),
+            |row, (day, label)| {
+                let day = *day;
+                row.push(
+                    checkbox(*label, self.config.schedule_weekdays.contains(&day))
+                        .on_toggle(move |enabled| Message::WeekdayToggled(day, enabled))
+                        .spacing(5)
+                        .text_size(16)
+                        .font(font),
+                )
+            },
+        );
+
+        let request_timeout_input = text_input(
+            tr!("上传请求超时（秒，0 为不限制）"),
+            &self.config.request_timeout_secs.to_string(),
+        )
+        .on_input(Message::RequestTimeoutChanged)
+        .padding(10)
+        .font(font);
+
+        let tool_timeout_input = text_input(
+            tr!("FFmpeg/ffprobe 超时（秒，0 为不限制）"),
+            &self.config.tool_timeout_secs.to_string(),
+        )
+        .on_input(Message::ToolTimeoutChanged)
+        .padding(10)
+        .font(font);
+
+        let tool_path_row =
+            |label: &str, value: Option<&String>, tool, on_input: fn(String) -> Message| {
+                Column::new()
+                    .spacing(5)
+                    .push(text(label.to_string()).font(font))
+                    .push(
+                        Row::new()
+                            .spacing(10)
+                            .align_items(Alignment::Center)
+                            .push(
+                                text_input(
+                                    tr!("留空则自动查找"),
+                                    value.map(String::as_str).unwrap_or(""),
+                                )
+                                .on_input(on_input)
+                                .padding(10)
+                                .font(font),
+                            )
+                            .push(
+                                button(text(tr!("浏览")).font(font))
+                                    .on_press(Message::BrowseTool(tool))
+                                    .padding(10),
+                            ),
+                    )
+            };
+        let ffmpeg_path_row = tool_path_row(
+            tr!("FFmpeg 路径："),
+            self.config.ffmpeg_path.as_ref(),
+            ExternalTool::Ffmpeg,
+            Message::FfmpegPathChanged,
+        );
+        let ffprobe_path_row = tool_path_row(
+            tr!("ffprobe 路径："),
+            self.config.ffprobe_path.as_ref(),
+            ExternalTool::Ffprobe,
+            Message::FfprobePathChanged,
+        );
+
+        let output_dir_row = Column::new()
+            .spacing(5)
+            .push(text(tr!("结果输出目录：")).font(font))
+            .push(
+                Row::new()
+                    .spacing(10)
+                    .align_items(Alignment::Center)
+                    .push(
+                        text_input(
+                            tr!("留空则写在媒体文件旁边"),
+                            self.config.output_dir.as_deref().unwrap_or(""),
+                        )
+                        .on_input(Message::OutputDirChanged)
+                        .padding(10)
+                        .font(font),
+                    )
+                    .push(
+                        button(text(tr!("浏览")).font(font))
+                            .on_press(Message::SelectOutputDir)
+                            .padding(10),
+                    ),
+            );
+
+        let max_concurrency_input = text_input(
+            tr!("同时转写数量（1 为逐个处理）"),
+            &self.config.max_concurrency.to_string(),
+        )
+        .on_input(Message::MaxConcurrencyChanged)
+        .padding(10)
+        .font(font);
+
+        let ffmpeg_concurrency_input = text_input(
+            tr!("同时转码数量（0 表示随转写并发）"),
+            &self.config.ffmpeg_concurrency.to_string(),
+        )
+        .on_input(Message::FfmpegConcurrencyChanged)
+        .padding(10)
+        .font(font);
+
+        let track_selection_input = text_input(
+            tr!("all、first 或音轨编号（如 1,3）"),
+            &self.config.track_selection,
+        )
+        .on_input(Message::TrackSelectionChanged)
+        .padding(10)
+        .font(font);
+
+        let requests_per_minute_value = self
+            .config
+            .requests_per_minute
+            .map(|limit| limit.to_string())
+            .unwrap_or_default();
+        let requests_per_minute_input = text_input(tr!("留空不限制"), &requests_per_minute_value)
+            .on_input(Message::RequestsPerMinuteChanged)
+            .padding(10)
+            .font(font);
+
+        let abort_after_failures_value = match self.config.abort_after_consecutive_failures {
+            0 => String::new(),
+            limit => limit.to_string(),
+        };
+        let abort_after_failures_input =
+            text_input(tr!("留空或 0 不中止"), &abort_after_failures_value)
+                .on_input(Message::AbortAfterFailuresChanged)
+                .padding(10)
+                .font(font);
+
+        let cost_per_minute_input = text_input(tr!("留空不估算"), &self.cost_per_minute_input)
+            .on_input(Message::CostPerMinuteChanged)
+            .padding(10)
+            .font(font);
+
+        let vad_gap_policy_picker = pick_list(
+            &GapPolicy::ALL[..],
+            Some(self.config.vad_gap_policy),
+            Message::VadGapPolicySelected,
+        )
+        .font(font);
+
+        // 配置文件中的组合不受支持时不选中任何项，保存或扫描时会提示错误。
+        let vad_format_picker = pick_list(
+            &VadFormat::ALL[..],
+            VadFormat::find(self.config.vad_sample_rate, self.config.vad_chunk_size),
+            Message::VadFormatSelected,
+        )
+        .font(font);
+
+        let output_format_picker = pick_list(
+            &OutputFormat::ALL[..],
+            Some(self.config.output_format),
+            Message::OutputFormatSelected,
+        )
+        .font(font);
+        let ass_style_row = (self.config.output_format == OutputFormat::Ass).then(|| {
+            Row::new()
+                .spacing(10)
+                .align_items(Alignment::Center)
+                .push(text(tr!("ASS 字体：")).font(font))
+                .push(
+                    text_input("Microsoft YaHei", &self.config.ass_font_name)
+                        .on_input(Message::AssFontNameChanged)
+                        .padding(10)
+                        .width(Length::Fixed(220.0))
+                        .font(font),
+                )
+                .push(text(tr!("字号：")).font(font))
+                .push(
+                    text_input("54", &self.config.ass_font_size.to_string())
+                        .on_input(Message::AssFontSizeChanged)
+                        .padding(10)
+                        .width(Length::Fixed(80.0))
+                        .font(font),
+                )
+        });
+        let sanitize_filenames_toggle = checkbox(
+            tr!("清理结果文件名中的不安全字符"),
+            self.config.sanitize_filenames,
+        )
+        .on_toggle(Message::SanitizeFilenamesToggled)
+        .spacing(10)
+        .text_size(16)
+        .font(font);
+        let mut filename_substitute_input = text_input("_", &self.config.filename_substitute)
+            .padding(10)
+            .width(Length::Fixed(80.0))
+            .font(font);
+        if self.config.sanitize_filenames {
+            filename_substitute_input =
+                filename_substitute_input.on_input(Message::FilenameSubstituteChanged);
+        }
+
+        let export_codec_picker = pick_list(
+            &ExportCodec::ALL[..],
+            Some(self.config.export_codec),
+            Message::ExportCodecSelected,
+        )
+        .font(font);
+        let export_bitrate_input = text_input(tr!("默认码率"), &self.config.export_bitrate)
+            .on_input(Message::ExportBitrateChanged)
+            .padding(10)
+            .width(Length::Fixed(120.0))
+            .font(font);
+
+        let segment_interval = match self.config.segment_mode {
+            SegmentMode::FixedInterval(secs) => secs,
+            _ => DEFAULT_SEGMENT_INTERVAL_SECS,
+        };
+        let mut segment_mode_row = Row::new()
+            .spacing(10)
+            .align_items(Alignment::Center)
+            .push(text(tr!("分段方式")).font(font))
+            .push(
+                pick_list(
+                    SegmentMode::choices(segment_interval),
+                    Some(self.config.segment_mode),
+                    Message::SegmentModeSelected,
+                )
+                .font(font),
+            );
+        if let SegmentMode::FixedInterval(secs) = self.config.segment_mode {
+            segment_mode_row = segment_mode_row
+                .push(
+                    text_input(tr!("间隔秒数"), &secs.to_string())
+                        .on_input(Message::SegmentIntervalChanged)
+                        .padding(10)
+                        .width(Length::Fixed(100.0))
+                        .font(font),
+                )
+                .push(text(tr!("秒/段")).font(font));
+        }
+
         let vad_threshold_slider = slider(
-            0.3..=0.9,
+            VAD_THRESHOLD_RANGE,
             self.config.vad_threshold,
             Message::VadThresholdChanged,
         )
-        .step(0.01);
-        let vad_min_duration_slider = slider(
-            0.5..=6.0,
-            self.config.vad_min_segment_secs,
-            Message::VadMinDurationChanged,
+        .step(0.01);
+        let vad_min_duration_slider = slider(
+            VAD_MIN_SEGMENT_RANGE,
+            self.config.vad_min_segment_secs,
+            Message::VadMinDurationChanged,
+        )
+        .step(0.1);
+        let vad_merge_gap_slider = slider(
+            0.0..=2.0,
+            self.config.vad_merge_gap_secs,
+            Message::VadMergeGapChanged,
+        )
+        .step(0.05);
+        let vad_smoothing_slider = slider(
+            1..=VAD_SMOOTHING_WINDOW_MAX as u8,
+            self.config
+                .vad_smoothing_window
+                .clamp(1, VAD_SMOOTHING_WINDOW_MAX) as u8,
+            Message::VadSmoothingChanged,
+        );
+        let smoothing_label = if self.config.vad_smoothing_window > 1 {
+            tr!("{} 块", self.config.vad_smoothing_window)
+        } else {
+            tr!("关闭").to_string()
+        };
+        // 0 表示不切分超长语音段。
+        let vad_max_segment_slider = slider(
+            0.0..=120.0,
+            self.config.vad_max_segment_secs,
+            Message::VadMaxSegmentChanged,
+        )
+        .step(5.0);
+        let max_segment_label = if self.config.vad_max_segment_secs > 0.0 {
+            tr!("{:.0}秒", self.config.vad_max_segment_secs)
+        } else {
+            tr!("不限制").to_string()
+        };
+        let vad_lead_padding_slider = slider(
+            0.0..=1.0,
+            self.config.vad_lead_padding_secs,
+            Message::VadLeadPaddingChanged,
+        )
+        .step(0.05);
+        let vad_tail_padding_slider = slider(
+            0.0..=2.0,
+            self.config.vad_tail_padding_secs,
+            Message::VadTailPaddingChanged,
+        )
+        .step(0.05);
+
+        let speedup_slider = slider(
+            1.0..=2.0,
+            self.config.speedup_factor,
+            Message::SpeedupChanged,
+        )
+        .step(0.05);
+        let speedup_label = if self.config.speedup_factor > 1.0 {
+            format!("{:.2}x", self.config.speedup_factor)
+        } else {
+            tr!("关闭").to_string()
+        };
+
+        let audio_normalize_toggle = checkbox(
+            tr!("转写前进行响度归一化（改善小声录音）"),
+            self.config.audio_normalize,
+        )
+        .on_toggle(Message::AudioNormalizeToggled)
+        .spacing(10)
+        .text_size(16)
+        .font(font);
+
+        let downmix_filter_picker = pick_list(
+            &DownmixFilter::ALL[..],
+            Some(self.config.downmix_filter),
+            Message::DownmixFilterSelected,
+        )
+        .font(font);
+
+        let trim_segment_silence_toggle = checkbox(
+            tr!("裁掉 VAD 分段首尾的静音（减少幻听）"),
+            self.config.trim_segment_silence,
+        )
+        .on_toggle(Message::TrimSegmentSilenceToggled)
+        .spacing(10)
+        .text_size(16)
+        .font(font);
+
+        let discard_partial_toggle = checkbox(
+            tr!("取消扫描时丢弃未完成的字幕"),
+            self.config.discard_partial_on_cancel,
+        )
+        .on_toggle(Message::DiscardPartialToggled)
+        .spacing(10)
+        .text_size(16)
+        .font(font);
+
+        let mut vad_preview_btn = button(text(tr!("VAD 预览")).font(font))
+            .padding([4, 10])
+            .style(iced::theme::Button::Secondary);
+        let mut vad_preview_pick_btn = button(text(tr!("选择预览文件")).font(font))
+            .padding([4, 10])
+            .style(iced::theme::Button::Secondary);
+        // 扫描进行中不允许预览，避免与正在转写的文件争用 FFmpeg 与磁盘。
+        if !self.is_previewing_vad && !self.is_processing {
+            vad_preview_btn = vad_preview_btn.on_press(Message::PreviewVad);
+            vad_preview_pick_btn = vad_preview_pick_btn.on_press(Message::PickVadPreviewFile);
+        }
+        let vad_preview_file_label = match &self.vad_preview_file {
+            Some(path) => path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string(),
+            None => tr!("只分析开头 {:.0} 秒，不上传", VAD_PREVIEW_SECS),
+        };
+        let vad_preview_row = Row::new()
+            .spacing(10)
+            .align_items(Alignment::Center)
+            .push(vad_preview_btn)
+            .push(vad_preview_pick_btn)
+            .push(text(vad_preview_file_label).font(font).size(14));
+
+        let vad_adaptive_toggle = checkbox(
+            tr!("自适应阈值（按录音电平微调）"),
+            self.config.vad_adaptive_threshold,
+        )
+        .on_toggle(Message::VadAdaptiveToggled)
+        .spacing(10)
+        .text_size(16)
+        .font(font);
+
+        let notify_on_completion_toggle = checkbox(
+            tr!("定时扫描完成后发送桌面通知"),
+            self.config.notify_on_completion,
+        )
+        .on_toggle(Message::NotifyOnCompletionToggled)
+        .spacing(10)
+        .text_size(16)
+        .font(font);
+
+        let log_to_file_toggle = checkbox(tr!("同时将日志写入文件"), self.config.log_to_file)
+            .on_toggle(Message::LogToFileToggled)
+            .spacing(10)
+            .text_size(16)
+            .font(font);
+
+        let write_report_toggle = checkbox(tr!("每次扫描后写出汇总报告"), self.config.write_report)
+            .on_toggle(Message::WriteReportToggled)
+            .spacing(10)
+            .text_size(16)
+            .font(font);
+
+        let auto_start_toggle = checkbox(tr!("程序启动后自动开始定时任务"), self.config.auto_start)
+            .on_toggle(Message::AutoStartToggled)
+            .spacing(10)
+            .text_size(16)
+            .font(font);
+
+        // 托盘不可用（平台不支持或创建失败）时该选项不起作用，不再显示。
+        let minimize_to_tray_toggle = self.tray.is_some().then(|| {
+            checkbox(
+                tr!("关闭窗口时最小化到系统托盘"),
+                self.config.minimize_to_tray,
+            )
+            .on_toggle(Message::MinimizeToTrayToggled)
+            .spacing(10)
+            .text_size(16)
+            .font(font)
+        });
+
+        let split_whole_cues_toggle = checkbox(
+            tr!("未启用 VAD 时按句子拆分字幕"),
+            self.config.split_whole_cues,
+        )
+        .on_toggle(Message::SplitWholeCuesToggled)
+        .spacing(10)
+        .text_size(16)
+        .font(font);
+
+        let trim_punctuation_toggle = checkbox(
+            tr!("去掉字幕末尾的标点"),
+            self.config.text_trim_trailing_punctuation,
+        )
+        .on_toggle(Message::TrimTrailingPunctuationToggled)
+        .spacing(10)
+        .text_size(16)
+        .font(font);
+
+        let collapse_whitespace_toggle = checkbox(
+            tr!("合并字幕中的连续空白"),
+            self.config.text_collapse_whitespace,
+        )
+        .on_toggle(Message::CollapseWhitespaceToggled)
+        .spacing(10)
+        .text_size(16)
+        .font(font);
+
+        let diarize_toggle = checkbox(tr!("标注说话人（实验性）"), self.config.diarize)
+            .on_toggle(Message::DiarizeToggled)
+            .spacing(10)
+            .text_size(16)
+            .font(font);
+
+        let word_timestamps_toggle = checkbox(
+            tr!("按逐词时间戳生成更细的字幕（Whisper 兼容接口）"),
+            self.config.word_timestamps,
+        )
+        .on_toggle(Message::WordTimestampsToggled)
+        .spacing(10)
+        .text_size(16)
+        .font(font);
+
+        let keep_intermediates_toggle = checkbox(
+            tr!("保留中间音频文件（调试用）"),
+            self.config.keep_intermediates,
+        )
+        .on_toggle(Message::KeepIntermediatesToggled)
+        .spacing(10)
+        .text_size(16)
+        .font(font);
+
+        let embedded_subs_toggle = checkbox(
+            tr!("视频已有内嵌字幕时直接提取，不再转写"),
+            self.config.skip_if_embedded_subs,
+        )
+        .on_toggle(Message::SkipIfEmbeddedSubsToggled)
+        .spacing(10)
+        .text_size(16)
+        .font(font);
+
+        let name_by_language_toggle = checkbox(
+            tr!("多音轨结果按语言命名（如 video.jpn.srt）"),
+            self.config.name_tracks_by_language,
+        )
+        .on_toggle(Message::NameTracksByLanguageToggled)
+        .spacing(10)
+        .text_size(16)
+        .font(font);
+
+        let probe_extensionless_toggle = checkbox(
+            tr!("探测没有扩展名的文件（较慢）"),
+            self.config.probe_extensionless,
+        )
+        .on_toggle(Message::ProbeExtensionlessToggled)
+        .spacing(10)
+        .text_size(16)
+        .font(font);
+
+        let combine_outputs_toggle = checkbox(
+            tr!("按文件名顺序把每个目录的字幕合并为一个 SRT"),
+            self.config.combine_outputs,
+        )
+        .on_toggle(Message::CombineOutputsToggled)
+        .spacing(10)
+        .text_size(16)
+        .font(font);
+
+        let mux_subtitles_toggle = checkbox(
+            tr!("转写完成后把字幕封装回视频（MKV/MP4/MOV/WebM）"),
+            self.config.mux_subtitles,
+        )
+        .on_toggle(Message::MuxSubtitlesToggled)
+        .spacing(10)
+        .text_size(16)
+        .font(font);
+
+        // 仅在启用封装时可切换，避免误以为单独勾选就会改动原视频。
+        let mut mux_replace_toggle = checkbox(
+            tr!("直接替换原视频（否则另存为 .subtitled）"),
+            self.config.mux_replace_original,
+        )
+        .spacing(10)
+        .text_size(16)
+        .font(font);
+        if self.config.mux_subtitles {
+            mux_replace_toggle = mux_replace_toggle.on_toggle(Message::MuxReplaceOriginalToggled);
+        }
+
+        let force_reprocess_toggle = checkbox(
+            tr!("忽略已有结果，全部重新转写"),
+            self.config.force_reprocess,
+        )
+        .on_toggle(Message::ForceReprocessToggled)
+        .spacing(10)
+        .text_size(16)
+        .font(font);
+
+        let append_growing_toggle = checkbox(
+            tr!("对增长中的文件仅转写新增部分并追加"),
+            self.config.append_growing_files,
         )
-        .step(0.1);
+        .on_toggle(Message::AppendGrowingToggled)
+        .spacing(10)
+        .text_size(16)
+        .font(font);
 
         let vad_controls = Column::new()
             .spacing(10)
-            .push(vad_toggle)
+            .push(segment_mode_row)
             .push(
                 Row::new()
                     .spacing(10)
                     .align_items(Alignment::Center)
-                    .push(text("VAD 阈值").font(font))
+                    .push(text(tr!("VAD 阈值")).font(font))
                     .push(vad_threshold_slider)
                     .push(text(format!("{:.2}", self.config.vad_threshold)).font(font)),
             )
+            .push(vad_adaptive_toggle)
             .push(
                 Row::new()
                     .spacing(10)
                     .align_items(Alignment::Center)
-                    .push(text("最短片段（秒）").font(font))
+                    .push(text(tr!("最短片段（秒）")).font(font))
                     .push(vad_min_duration_slider)
-                    .push(text(format!("{:.1}秒", self.config.vad_min_segment_secs)).font(font)),
-            );
+                    .push(text(tr!("{:.1}秒", self.config.vad_min_segment_secs)).font(font)),
+            )
+            .push(
+                Row::new()
+                    .spacing(10)
+                    .align_items(Alignment::Center)
+                    .push(text(tr!("合并间隔（秒）")).font(font))
+                    .push(vad_merge_gap_slider)
+                    .push(text(tr!("{:.2}秒", self.config.vad_merge_gap_secs)).font(font)),
+            )
+            .push(
+                Row::new()
+                    .spacing(10)
+                    .align_items(Alignment::Center)
+                    .push(text(tr!("字幕提前（秒）")).font(font))
+                    .push(vad_lead_padding_slider)
+                    .push(text(tr!("{:.2}秒", self.config.vad_lead_padding_secs)).font(font))
+                    .push(text(tr!("字幕延后（秒）")).font(font))
+                    .push(vad_tail_padding_slider)
+                    .push(text(tr!("{:.2}秒", self.config.vad_tail_padding_secs)).font(font)),
+            )
+            .push(
+                Row::new()
+                    .spacing(10)
+                    .align_items(Alignment::Center)
+                    .push(text(tr!("概率平滑")).font(font))
+                    .push(vad_smoothing_slider)
+                    .push(text(smoothing_label).font(font)),
+            )
+            .push(
+                Row::new()
+                    .spacing(10)
+                    .align_items(Alignment::Center)
+                    .push(text(tr!("语音段间的静音")).font(font))
+                    .push(vad_gap_policy_picker),
+            )
+            .push(
+                Row::new()
+                    .spacing(10)
+                    .align_items(Alignment::Center)
+                    .push(text(tr!("采样率 / 分块大小")).font(font))
+                    .push(vad_format_picker),
+            )
+            .push(
+                Row::new()
+                    .spacing(10)
+                    .align_items(Alignment::Center)
+                    .push(text(tr!("最长片段（秒）")).font(font))
+                    .push(vad_max_segment_slider)
+                    .push(text(max_segment_label).font(font)),
+            )
+            .push(
+                Row::new()
+                    .spacing(10)
+                    .align_items(Alignment::Center)
+                    .push(text(tr!("音频加速")).font(font))
+                    .push(speedup_slider)
+                    .push(text(speedup_label).font(font)),
+            )
+            .push(vad_preview_row);
 
         let toggle_btn = button(if self.is_running {
-            text("停止定时").font(font)
+            text(tr!("停止定时")).font(font)
         } else {
-            text("启动定时").font(font)
+            text(tr!("启动定时")).font(font)
         })
         .on_press(Message::ToggleRunning)
         .padding(10)
@@ -302,59 +2184,309 @@ impl Application for AutoAsrApp {
             iced::theme::Button::Primary
         });
 
-        let mut run_now_btn = button(text("立即扫描").font(font))
+        let mut run_now_btn = button(text(tr!("立即扫描")).font(font))
+            .padding(10)
+            .style(iced::theme::Button::Secondary);
+        let mut single_file_btn = button(text(tr!("转写单个文件")).font(font))
             .padding(10)
             .style(iced::theme::Button::Secondary);
         if !self.is_processing {
             run_now_btn = run_now_btn.on_press(Message::RunOnce);
+            single_file_btn = single_file_btn.on_press(Message::SelectFile);
         }
 
-        let save_btn = button(text("保存设置").font(font))
+        let save_btn = button(text(tr!("保存设置")).font(font))
             .on_press(Message::SaveConfig)
             .padding(10);
 
+        let dry_run_toggle = checkbox(tr!("预览"), self.dry_run)
+            .on_toggle(Message::DryRunToggled)
+            .spacing(10)
+            .text_size(16)
+            .font(font);
+
+        let mut action_row = Row::new()
+            .spacing(20)
+            .align_items(Alignment::Center)
+            .push(toggle_btn)
+            .push(run_now_btn)
+            .push(single_file_btn)
+            .push(dry_run_toggle);
+        if self.is_processing {
+            let mut cancel_btn = button(text(tr!("取消扫描")).font(font))
+                .padding(10)
+                .style(iced::theme::Button::Destructive);
+            if self
+                .cancel_token
+                .as_ref()
+                .is_some_and(|token| !token.is_cancelled())
+            {
+                cancel_btn = cancel_btn.on_press(Message::CancelScan);
+            }
+            let pause_btn = if self.is_paused() {
+                button(text(tr!("继续扫描")).font(font)).on_press(Message::ResumeScan)
+            } else {
+                button(text(tr!("暂停扫描")).font(font)).on_press(Message::PauseScan)
+            }
+            .padding(10)
+            .style(iced::theme::Button::Secondary);
+            action_row = action_row.push(pause_btn).push(cancel_btn);
+        }
+        let mut open_output_btn = button(text(tr!("打开结果目录")).font(font)).padding(10);
+        if self.last_output.is_some() {
+            open_output_btn = open_output_btn.on_press(Message::OpenLastOutput);
+        }
+        let watch_btn = button(if self.is_watching {
+            text(tr!("停止监视")).font(font)
+        } else {
+            text(tr!("开始监视")).font(font)
+        })
+        .on_press(Message::ToggleWatch)
+        .padding(10)
+        .style(if self.is_watching {
+            iced::theme::Button::Destructive
+        } else {
+            iced::theme::Button::Secondary
+        });
+        let action_row = action_row
+            .push(watch_btn)
+            .push(open_output_btn)
+            .push(save_btn);
+        let dropped_row = (!self.dropped_files.is_empty()).then(|| {
+            let mut transcribe_btn =
+                button(text(tr!("转写拖入的文件（{} 个）", self.dropped_files.len())).font(font))
+                    .padding(10)
+                    .style(iced::theme::Button::Primary);
+            if !self.is_processing {
+                transcribe_btn = transcribe_btn.on_press(Message::TranscribeDroppedFiles);
+            }
+            Row::new()
+                .spacing(20)
+                .align_items(Alignment::Center)
+                .push(transcribe_btn)
+                .push(
+                    button(text(tr!("清空拖入列表")).font(font))
+                        .on_press(Message::ClearDroppedFiles)
+                        .padding(10)
+                        .style(iced::theme::Button::Secondary),
+                )
+        });
+        let shortcut_hint = text(tr!(
+            "快捷键：Ctrl+R 立即扫描　Ctrl+S 保存设置　空格 启动/停止定时"
+        ))
+        .font(font)
+        .size(14);
+
+        let theme_picker = pick_list(
+            &AppTheme::ALL[..],
+            Some(self.config.theme),
+            Message::ThemeChanged,
+        )
+        .font(font);
+        let language_picker = pick_list(
+            &Language::ALL[..],
+            Some(self.config.language),
+            Message::LanguageChanged,
+        )
+        .font(font);
+
+        let profile_picker = pick_list(
+            &self.profiles[..],
+            Some(&self.active_profile),
+            Message::ProfileSelected,
+        )
+        .font(font);
+        let profile_name_input = text_input(tr!("新配置档名称"), &self.profile_name_input)
+            .on_input(Message::ProfileNameChanged)
+            .on_submit(Message::SaveProfileAs)
+            .padding(10)
+            .font(font);
+        let save_as_btn = button(text(tr!("另存为")).font(font))
+            .on_press(Message::SaveProfileAs)
+            .padding(10);
+        let mut rename_profile_btn = button(text(tr!("重命名")).font(font))
+            .padding(10)
+            .style(iced::theme::Button::Secondary);
+        if self.active_profile != DEFAULT_PROFILE && !self.profile_name_input.trim().is_empty() {
+            rename_profile_btn = rename_profile_btn.on_press(Message::RenameProfile);
+        }
+        let mut delete_profile_btn = button(text(tr!("删除配置档")).font(font))
+            .padding(10)
+            .style(iced::theme::Button::Destructive);
+        if self.active_profile != DEFAULT_PROFILE {
+            delete_profile_btn = delete_profile_btn.on_press(Message::DeleteProfile);
+        }
+        let profile_row = Row::new()
+            .spacing(10)
+            .align_items(Alignment::Center)
+            .push(text(tr!("配置档：")).font(font))
+            .push(profile_picker)
+            .push(profile_name_input)
+            .push(save_as_btn)
+            .push(rename_profile_btn)
+            .push(delete_profile_btn)
+            .push(text(tr!("主题：")).font(font))
+            .push(theme_picker)
+            .push(text(tr!("语言：")).font(font))
+            .push(language_picker);
+
         let controls = Column::new()
             .spacing(20)
             .push(title)
+            .push(profile_row)
             .push(
                 Row::new()
                     .spacing(10)
                     .push(dir_btn)
-                    .push(dir_display)
+                    .push_maybe(recent_picker)
+                    .push(dir_list)
                     .align_items(Alignment::Center),
             )
             .push(
                 Column::new()
                     .spacing(5)
-                    .push(text("API 地址：").font(font))
-                    .push(api_url_input),
+                    .push(text(tr!("API 地址：")).font(font))
+                    .push(
+                        Row::new()
+                            .spacing(10)
+                            .align_items(Alignment::Center)
+                            .push(api_url_input)
+                            .push(api_flavor_picker),
+                    ),
             )
             .push(
                 Column::new()
                     .spacing(5)
-                    .push(text("模型名称：").font(font))
-                    .push(model_name_input),
+                    .push(text(tr!("代理：")).font(font))
+                    .push(proxy_url_input),
             )
             .push(
                 Column::new()
                     .spacing(5)
-                    .push(text("API 密钥：").font(font))
+                    .push(text(tr!("模型名称：")).font(font))
+                    .push(
+                        Row::new()
+                            .spacing(10)
+                            .align_items(Alignment::Center)
+                            .push(model_name_input)
+                            .push(model_picker),
+                    ),
+            )
+            .push(
+                Column::new()
+                    .spacing(5)
+                    .push(text(tr!("API 密钥：")).font(font))
                     .push(api_key_input),
             )
             .push(
                 Column::new()
                     .spacing(5)
-                    .push(text("执行时间：").font(font))
-                    .push(schedule_input),
+                    .push(text(tr!("执行时间：")).font(font))
+                    .push(schedule_list)
+                    .push(add_time_btn)
+                    .push(text(tr!("执行星期（全不选表示每天）：")).font(font))
+                    .push(weekday_row),
+            )
+            .push(
+                Column::new()
+                    .spacing(5)
+                    .push(text(tr!("FFmpeg/ffprobe 超时（秒，0 为不限制）：")).font(font))
+                    .push(tool_timeout_input),
+            )
+            .push(
+                Column::new()
+                    .spacing(5)
+                    .push(text(tr!("上传请求超时（秒，0 为不限制）：")).font(font))
+                    .push(request_timeout_input)
+                    .push(text(tr!("单个上传文件大小上限（MB）：")).font(font))
+                    .push(max_file_size_input),
+            )
+            .push(
+                Column::new()
+                    .spacing(5)
+                    .push(text(tr!("扫描深度：")).font(font))
+                    .push(max_depth_input)
+                    .push(text(tr!("包含规则（留空处理全部文件）：")).font(font))
+                    .push(include_list)
+                    .push(add_include_btn)
+                    .push(text(tr!("排除规则（相对媒体目录的 glob）：")).font(font))
+                    .push(exclude_list)
+                    .push(add_exclude_btn)
+                    .push(text(tr!("已有外挂字幕的语言后缀（逗号分隔）：")).font(font))
+                    .push(subtitle_suffixes_input),
+            )
+            .push(output_dir_row)
+            .push(ffmpeg_path_row)
+            .push(ffprobe_path_row)
+            .push(
+                Column::new()
+                    .spacing(5)
+                    .push(text(tr!("同时转写数量：")).font(font))
+                    .push(max_concurrency_input)
+                    .push(text(tr!("同时转码数量：")).font(font))
+                    .push(ffmpeg_concurrency_input)
+                    .push(text(tr!("每分钟请求上限：")).font(font))
+                    .push(requests_per_minute_input)
+                    .push(text(tr!("连续失败多少个音轨后中止扫描：")).font(font))
+                    .push(abort_after_failures_input)
+                    .push(text(tr!("每分钟音频费用（用于估算）：")).font(font))
+                    .push(cost_per_minute_input)
+                    .push(text(tr!("转写的视频音轨：")).font(font))
+                    .push(track_selection_input),
             )
-            .push(vad_controls)
             .push(
                 Row::new()
-                    .spacing(20)
-                    .push(toggle_btn)
-                    .push(run_now_btn)
-                    .push(save_btn),
-            );
+                    .spacing(10)
+                    .align_items(Alignment::Center)
+                    .push(text(tr!("输出格式：")).font(font))
+                    .push(output_format_picker)
+                    .push(sanitize_filenames_toggle)
+                    .push(text(tr!("替换为：")).font(font))
+                    .push(filename_substitute_input),
+            )
+            .push_maybe(ass_style_row)
+            .push(
+                Row::new()
+                    .spacing(10)
+                    .align_items(Alignment::Center)
+                    .push(text(tr!("上传音频编码：")).font(font))
+                    .push(export_codec_picker)
+                    .push(text(tr!("码率：")).font(font))
+                    .push(export_bitrate_input),
+            )
+            .push(audio_normalize_toggle)
+            .push(
+                Row::new()
+                    .spacing(10)
+                    .align_items(Alignment::Center)
+                    .push(text(tr!("多声道混音：")).font(font))
+                    .push(downmix_filter_picker),
+            )
+            .push(trim_segment_silence_toggle)
+            .push(discard_partial_toggle)
+            .push(append_growing_toggle)
+            .push(force_reprocess_toggle)
+            .push(split_whole_cues_toggle)
+            .push(trim_punctuation_toggle)
+            .push(collapse_whitespace_toggle)
+            .push(diarize_toggle)
+            .push(word_timestamps_toggle)
+            .push(keep_intermediates_toggle)
+            .push(embedded_subs_toggle)
+            .push(name_by_language_toggle)
+            .push(probe_extensionless_toggle)
+            .push(combine_outputs_toggle)
+            .push(mux_subtitles_toggle)
+            .push(mux_replace_toggle)
+            .push(auto_start_toggle)
+            .push_maybe(minimize_to_tray_toggle)
+            .push(notify_on_completion_toggle)
+            .push(log_to_file_toggle)
+            .push(write_report_toggle)
+            .push(vad_controls)
+            .push(action_row)
+            .push_maybe(dropped_row)
+            .push(shortcut_hint);
 
         const MAX_LOGS: usize = 500;
         let logs_content =
@@ -363,7 +2495,7 @@ impl Application for AutoAsrApp {
                 .rev()
                 .take(MAX_LOGS)
                 .fold(Column::new().spacing(5), |col, log| {
-                    let (label, color) = Self::log_visuals(log.level);
+                    let (label, color) = Self::log_visuals(log.level, self.config.theme);
                     let display = format!("[{}] {}", label, log.message);
                     col.push(
                         text(display)
@@ -376,11 +2508,103 @@ impl Application for AutoAsrApp {
             .height(Length::Fill)
             .width(Length::Fill);
 
-        let content = Column::new()
+        let (completed, total) = self.scan_progress.unwrap_or((0, 0));
+        let progress_label = if self.is_paused() {
+            tr!("进度：{} / {} 个音轨，已暂停", completed, total)
+        } else if total > 0 && completed < total {
+            let eta = match self.scan_secs_per_target {
+                Some(avg) => Self::format_eta((total - completed) as f64 * avg),
+                None => tr!("计算中…").to_string(),
+            };
+            tr!("进度：{} / {} 个音轨，预计剩余 {}", completed, total, eta)
+        } else if total > 0 {
+            tr!("进度：{} / {} 个音轨", completed, total)
+        } else {
+            tr!("进度：空闲").to_string()
+        };
+        let progress_row = Row::new()
+            .spacing(10)
+            .align_items(Alignment::Center)
+            .push(progress_bar(0.0..=total.max(1) as f32, completed as f32).height(12))
+            .push(text(progress_label).font(font));
+
+        let mut content = Column::new()
             .spacing(20)
             .padding(20)
             .push(controls)
-            .push(text("日志").font(font).size(20))
+            .push(progress_row);
+        if let Some(summary) = &self.cost_summary {
+            let (_, color) = Self::log_visuals(ScanLogLevel::Success, self.config.theme);
+            content = content.push(
+                text(tr!("上次扫描：{}", summary))
+                    .font(font)
+                    .size(20)
+                    .style(iced::theme::Text::Color(color)),
+            );
+        }
+        if !self.file_statuses.is_empty() {
+            let done = self
+                .file_statuses
+                .iter()
+                .filter(|s| s.state == FileState::Done)
+                .count();
+            let status_list =
+                self.file_statuses
+                    .iter()
+                    .fold(Column::new().spacing(5), |col, status| {
+                        let badge = Container::new(text(status.state).font(font).size(14))
+                            .padding([2, 8])
+                            .style(iced::theme::Container::Custom(Box::new(StatusBadge(
+                                Self::file_state_color(status.state),
+                            ))));
+                        let mut row = Row::new()
+                            .spacing(10)
+                            .align_items(Alignment::Center)
+                            .push(badge)
+                            .push(text(status.path.display()).font(font))
+                            .push(text(tr!("{} 条音轨", status.tracks)).font(font));
+                        if let Some(detail) = Self::file_status_detail(status) {
+                            let detail = text(detail).font(font).size(14);
+                            row = row.push(if status.state == FileState::Failed {
+                                detail.style(iced::theme::Text::Color(Self::file_state_color(
+                                    FileState::Failed,
+                                )))
+                            } else {
+                                detail
+                            });
+                        }
+                        col.push(row)
+                    });
+            content = content
+                .push(
+                    text(tr!(
+                        "文件状态（完成 {} / {}）",
+                        done,
+                        self.file_statuses.len()
+                    ))
+                    .font(font)
+                    .size(20),
+                )
+                .push(
+                    Container::new(scrollable(status_list).width(Length::Fill))
+                        .max_height(200)
+                        .style(iced::theme::Container::Box)
+                        .padding(10),
+                );
+        }
+        let content = content
+            .push(
+                Row::new()
+                    .spacing(10)
+                    .align_items(Alignment::Center)
+                    .push(text(tr!("日志")).font(font).size(20))
+                    .push(
+                        button(text(tr!("清空日志")).font(font).size(14))
+                            .on_press(Message::ClearLogs)
+                            .padding([4, 10])
+                            .style(iced::theme::Button::Secondary),
+                    ),
+            )
             .push(
                 Container::new(logs_scroll)
                     .style(iced::theme::Container::Box)
@@ -394,8 +2618,69 @@ impl Application for AutoAsrApp {
             .into()
     }
 
+    fn theme(&self) -> Theme {
+        match self.config.theme {
+            AppTheme::Light => Theme::Light,
+            AppTheme::Dark => Theme::Dark,
+        }
+    }
+
     fn subscription(&self) -> Subscription<Message> {
-        time::every(std::time::Duration::from_secs(1)).map(|_| Message::Tick(Local::now()))
+        let tick =
+            time::every(std::time::Duration::from_secs(1)).map(|_| Message::Tick(Local::now()));
+        let window_events = event::listen_with(|event, _status| match event {
+            Event::Window(_, window::Event::Resized { width, height }) => {
+                Some(Message::WindowResized(width, height))
+            }
+            Event::Window(_, window::Event::Moved { x, y }) => Some(Message::WindowMoved(x, y)),
+            Event::Window(_, window::Event::FileDropped(path)) => Some(Message::PathDropped(path)),
+            Event::Window(id, window::Event::CloseRequested) => {
+                Some(Message::WindowCloseRequested(id))
+            }
+            _ => None,
+        });
+        let shortcuts = event::listen_with(|event, status| match event {
+            Event::Keyboard(keyboard::Event::KeyPressed { key, modifiers, .. }) => {
+                shortcut_message(&key, modifiers, status == event::Status::Captured)
+            }
+            _ => None,
+        });
+        let mut subscriptions = vec![tick, window_events, shortcuts];
+        if self.tray.is_some() {
+            subscriptions.push(
+                time::every(std::time::Duration::from_millis(200)).map(|_| Message::TrayPolled),
+            );
+        }
+        if self.is_watching {
+            subscriptions.extend(
+                self.config
+                    .directory_paths()
+                    .into_iter()
+                    .map(|dir| watch::watch_directory(dir).map(Message::Watch)),
+            );
+        }
+        Subscription::batch(subscriptions)
+    }
+}
+
+/// 键盘快捷键：Ctrl+R（macOS 为 Cmd+R）立即扫描、Ctrl+S 保存设置、空格启动/停止定时。
+/// 输入框获得焦点时空格用于输入文字，`captured` 为真时不触发。
+fn shortcut_message(
+    key: &keyboard::Key,
+    modifiers: keyboard::Modifiers,
+    captured: bool,
+) -> Option<Message> {
+    match key.as_ref() {
+        keyboard::Key::Character(c) if modifiers.command() && c.eq_ignore_ascii_case("r") => {
+            Some(Message::RunOnce)
+        }
+        keyboard::Key::Character(c) if modifiers.command() && c.eq_ignore_ascii_case("s") => {
+            Some(Message::SaveConfig)
+        }
+        keyboard::Key::Named(keyboard::key::Named::Space) if modifiers.is_empty() && !captured => {
+            Some(Message::ToggleRunning)
+        }
+        _ => None,
     }
 }
 
@@ -417,8 +2702,90 @@ impl AutoAsrApp {
         }
     }
 
+    /// 根据保存的几何信息构造窗口设置，首次运行时回退到默认尺寸与位置。
+    fn window_settings(config: &AppConfig) -> window::Settings {
+        let mut settings = window::Settings {
+            exit_on_close_request: false,
+            ..window::Settings::default()
+        };
+        if let (Some(width), Some(height)) = (config.window_width, config.window_height) {
+            settings.size = Size::new(
+                width.max(MIN_WINDOW_SIZE.width),
+                height.max(MIN_WINDOW_SIZE.height),
+            );
+        }
+        if let (Some(x), Some(y)) = (config.window_x, config.window_y) {
+            settings.position = window::Position::Specific(Point::new(x as f32, y as f32));
+        }
+        settings
+    }
+
+    /// 执行时间在 `last_runs` 中的键，统一为 `HH:MM`。
+    fn schedule_key(time: &NaiveTime) -> String {
+        time.format("%H:%M").to_string()
+    }
+
+    /// 按当前（未保存的）VAD 参数预览 `path` 开头的分段；未勾选 VAD 时同样按这些参数检测。
+    fn vad_preview_command(&mut self, path: PathBuf) -> Command<Message> {
+        if self.is_previewing_vad || self.is_processing {
+            return Command::none();
+        }
+        self.is_previewing_vad = true;
+        self.log_info(tr!(
+            "正在预览 {} 前 {:.0} 秒的 VAD 分段……",
+            display_path(&path),
+            VAD_PREVIEW_SECS
+        ));
+        let options = self.config.scanner_options(CancellationToken::new());
+        let mut config = self.config.clone();
+        config.segment_mode = SegmentMode::Vad;
+        let vad_cfg = config.vad_config().unwrap_or_default();
+        Command::perform(
+            async move {
+                preview_vad(path, options, vad_cfg)
+                    .await
+                    .map_err(|e| e.to_string())
+            },
+            Message::VadPreviewFinished,
+        )
+    }
+
+    fn check_ffmpeg_command(custom: Option<PathBuf>) -> Command<Message> {
+        Command::perform(check_ffmpeg(custom), |res| {
+            Message::FfmpegChecked(res.map_err(|e| e.to_string()))
+        })
+    }
+
+    /// 在阻塞线程池中发送桌面通知，避免 D-Bus/系统调用阻塞界面。
+    fn send_notification(summary: &str, body: String) -> Command<Message> {
+        let summary = summary.to_string();
+        Command::perform(
+            async move {
+                tokio::task::spawn_blocking(move || {
+                    notify_rust::Notification::new()
+                        .appname("AutoASR")
+                        .summary(&summary)
+                        .body(&body)
+                        .show()
+                        .map(|_| ())
+                        .map_err(|e| e.to_string())
+                })
+                .await
+                .map_err(|e| e.to_string())?
+            },
+            Message::NotificationSent,
+        )
+    }
+
+    /// 补上扫描结束时仍未经进度通道送达的日志，避免与实时日志重复。
+    fn append_unstreamed_logs(&mut self, logs: Vec<ScanLog>) {
+        let streamed = std::mem::take(&mut self.streamed_logs);
+        self.logs
+            .extend(logs.into_iter().filter(|log| !streamed.contains(&log.id)));
+    }
+
     fn listen_scan_progress(
-        receiver: Arc<Mutex<mpsc::UnboundedReceiver<ScanLog>>>,
+        receiver: Arc<Mutex<mpsc::UnboundedReceiver<ScanEvent>>>,
     ) -> Command<Message> {
         Command::perform(
             async move {
@@ -429,8 +2796,59 @@ impl AutoAsrApp {
         )
     }
 
+    fn refresh_profiles(&mut self) {
+        match AppConfig::list_profiles() {
+            Ok(profiles) => self.profiles = profiles,
+            Err(e) => self.log_error(tr!("读取配置档列表失败：{}", e)),
+        }
+    }
+
     fn push_log(&mut self, level: ScanLogLevel, message: impl Into<String>) {
-        self.logs.push(ScanLog::new(level, message));
+        let log = ScanLog::new(level, message);
+        match self.log_dir() {
+            Some(dir) => {
+                if !matches!(&self.log_writer, Some((current, _)) if *current == dir) {
+                    let errors = self.log_write_errors.clone();
+                    let writer = LogWriter::spawn(dir.clone(), move |message| {
+                        errors
+                            .lock()
+                            .unwrap_or_else(|poisoned| poisoned.into_inner())
+                            .push(message);
+                    });
+                    self.log_writer = Some((dir, writer));
+                }
+                if let Some((_, writer)) = &self.log_writer {
+                    writer.write(&log);
+                }
+            }
+            None => self.log_writer = None,
+        }
+        self.logs.push(log);
+    }
+
+    /// 启用日志文件时返回日志目录。
+    fn log_dir(&self) -> Option<PathBuf> {
+        if !self.config.log_to_file {
+            return None;
+        }
+        AppConfig::log_dir().ok()
+    }
+
+    /// 切换配置后截断超出滑块范围的 VAD 参数，并记录调整说明。
+    /// 切换配置后规范化 VAD 参数，并同步保存了原始文本的输入框。
+    fn apply_loaded_config(&mut self) {
+        i18n::set_language(self.config.language);
+        for note in self.config.clamp_vad_settings() {
+            self.log_info(note);
+        }
+        self.cost_per_minute_input = Self::cost_per_minute_text(&self.config);
+    }
+
+    fn cost_per_minute_text(config: &AppConfig) -> String {
+        config
+            .cost_per_minute
+            .map(|rate| rate.to_string())
+            .unwrap_or_default()
     }
 
     fn log_info(&mut self, message: impl Into<String>) {
@@ -445,85 +2863,263 @@ impl AutoAsrApp {
         self.push_log(ScanLogLevel::Error, message);
     }
 
-    fn log_visuals(level: ScanLogLevel) -> (&'static str, Color) {
-        match level {
-            ScanLogLevel::Info => ("信息", Color::from_rgb(0.75, 0.75, 0.78)),
-            ScanLogLevel::Success => ("成功", Color::from_rgb(0.3, 0.75, 0.4)),
-            ScanLogLevel::Error => ("错误", Color::from_rgb(0.92, 0.32, 0.32)),
+    /// 日志级别对应的标签与颜色；浅色主题下使用更深的色值以保证对比度。
+    fn log_visuals(level: ScanLogLevel, theme: AppTheme) -> (&'static str, Color) {
+        match (level, theme) {
+            (ScanLogLevel::Info, AppTheme::Dark) => {
+                (tr!("信息"), Color::from_rgb(0.75, 0.75, 0.78))
+            }
+            (ScanLogLevel::Info, AppTheme::Light) => (tr!("信息"), Color::from_rgb(0.3, 0.3, 0.35)),
+            (ScanLogLevel::Success, AppTheme::Dark) => {
+                (tr!("成功"), Color::from_rgb(0.3, 0.75, 0.4))
+            }
+            (ScanLogLevel::Success, AppTheme::Light) => {
+                (tr!("成功"), Color::from_rgb(0.1, 0.5, 0.2))
+            }
+            (ScanLogLevel::Error, AppTheme::Dark) => {
+                (tr!("错误"), Color::from_rgb(0.92, 0.32, 0.32))
+            }
+            (ScanLogLevel::Error, AppTheme::Light) => {
+                (tr!("错误"), Color::from_rgb(0.75, 0.12, 0.12))
+            }
+        }
+    }
+
+    /// 当前扫描是否处于暂停状态。
+    fn is_paused(&self) -> bool {
+        self.pause_token.as_ref().is_some_and(PauseToken::is_paused)
+    }
+
+    /// 把剩余秒数格式化为“约 X 小时 Y 分”这类粗略描述。
+    fn format_eta(secs: f64) -> String {
+        let secs = secs.max(0.0).round() as u64;
+        if secs >= 3600 {
+            tr!("约 {} 小时 {} 分", secs / 3600, secs % 3600 / 60)
+        } else if secs >= 60 {
+            tr!("约 {} 分 {} 秒", secs / 60, secs % 60)
+        } else {
+            tr!("约 {} 秒", secs)
         }
     }
 
-    /// 校验调度启动前的必要条件，避免无效配置触发任务。
-    fn validate_ready_state(&self) -> Result<(), String> {
-        let dir = self
-            .config
-            .directory
-            .as_ref()
-            .ok_or_else(|| "请先选择媒体目录。".to_string())?;
+    /// 文件状态行末尾的补充说明：处理中显示分段进度，完成后显示结果文件，失败时显示原因的首行。
+    fn file_status_detail(status: &FileStatus) -> Option<String> {
+        match status.state {
+            FileState::Processing => status
+                .segments
+                .map(|(done, total)| tr!("分段 {} / {}", done, total)),
+            FileState::Done => match status.outputs.as_slice() {
+                [] => None,
+                [output] => Some(format!(
+                    "→ {}",
+                    output.file_name().unwrap_or_default().to_string_lossy()
+                )),
+                outputs => Some(tr!("→ {} 个结果文件", outputs.len())),
+            },
+            FileState::Failed => status
+                .error
+                .as_deref()
+                .and_then(|error| error.lines().next())
+                .map(str::to_string),
+            FileState::Pending => None,
+        }
+    }
 
-        if !Path::new(dir).exists() {
-            return Err("选择的目录不存在。".to_string());
+    /// 文件状态徽章的颜色，深浅主题下都配白色文字。
+    fn file_state_color(state: FileState) -> Color {
+        match state {
+            FileState::Pending => Color::from_rgb(0.5, 0.5, 0.55),
+            FileState::Processing => Color::from_rgb(0.18, 0.49, 0.82),
+            FileState::Done => Color::from_rgb(0.15, 0.6, 0.3),
+            FileState::Failed => Color::from_rgb(0.8, 0.2, 0.2),
         }
+    }
+
+    /// 校验调度启动前的必要条件，避免无效配置触发任务。
+    fn validate_ready_state(&self) -> Result<(), String> {
+        self.ready_directories()?;
 
         if self.config.api_key.trim().is_empty() {
-            return Err("需要填写 API 密钥。".to_string());
+            return Err(tr!("需要填写 API 密钥。").to_string());
         }
 
-        if NaiveTime::parse_from_str(&self.config.schedule_time, "%H:%M").is_err() {
-            return Err("执行时间必须符合 HH:MM 格式。".to_string());
+        config::validate_api_url(&self.config.api_url).map_err(|e| e.to_string())?;
+        if let Some(proxy) = self.config.proxy_url() {
+            config::validate_proxy_url(&proxy).map_err(|e| e.to_string())?;
         }
+        validate_export_bitrate(&self.config.export_bitrate).map_err(|e| e.to_string())?;
+        validate_track_selection(&self.config.track_selection).map_err(|e| e.to_string())?;
+        validate_include_patterns(&self.config.include_patterns).map_err(|e| e.to_string())?;
+        validate_text_replacements(&self.config.text_replacements).map_err(|e| e.to_string())?;
+        validate_vad_format(self.config.vad_sample_rate, self.config.vad_chunk_size)
+            .map_err(|e| e.to_string())?;
+
+        self.config
+            .parsed_schedule_times()
+            .map_err(|e| e.to_string())?;
 
         Ok(())
     }
 
-    fn manual_ready_state(&self) -> Result<PathBuf, String> {
-        let dir = self
-            .config
-            .directory
-            .as_ref()
-            .ok_or_else(|| "请先选择媒体目录。".to_string())?;
+    /// 把目录加入扫描列表并记入最近使用的目录，已在列表中时只更新最近使用顺序。
+    fn add_directory(&mut self, dir: String) {
+        self.config.remember_directory(&dir);
+        if self.config.directories.contains(&dir) {
+            self.log_info(tr!("目录已在列表中：{}", display_path(&dir)));
+        } else {
+            self.log_info(tr!("已添加目录：{}", display_path(&dir)));
+            self.config.directories.push(dir);
+        }
+    }
+
+    fn manual_ready_state(&self) -> Result<Vec<PathBuf>, String> {
+        let dirs = self.ready_directories()?;
+        self.validate_run_settings()?;
+        Ok(dirs)
+    }
+
+    /// 转写所选文件前的检查：文件必须存在，其余设置与目录扫描相同，不要求配置媒体目录。
+    fn file_ready_state(&self, files: &[PathBuf]) -> Result<(), String> {
+        if let Some(missing) = files.iter().find(|file| !file.is_file()) {
+            return Err(tr!("所选文件不存在：{}", display_path(missing)));
+        }
+        self.validate_run_settings()
+    }
+
+    /// 转写单独选择或拖入的文件；预览模式直接开始，否则先校验 API 密钥。
+    fn run_files(&mut self, files: Vec<PathBuf>) -> Command<Message> {
+        if self.is_processing {
+            self.log_info(tr!("已有扫描任务在进行中，请稍候。"));
+        } else if self.checking_api_key {
+            self.log_info(tr!("正在校验 API 密钥，请稍候。"));
+        } else {
+            match self.file_ready_state(&files) {
+                Ok(()) if self.dry_run => {
+                    return self.start_scan(
+                        ScanTarget::Files(files),
+                        tr!("预览开始，仅检查所选文件……").to_string(),
+                        true,
+                        None,
+                    );
+                }
+                Ok(()) => return self.check_api_key(AfterKeyCheck::RunFiles(files)),
+                Err(err) => self.log_error(err),
+            }
+        }
+        Command::none()
+    }
 
-        if !Path::new(dir).exists() {
-            return Err("选择的目录不存在。".to_string());
+    /// 开始转写前校验 API 密钥与各项设置；预览模式不调用 API，无需检查。
+    fn validate_run_settings(&self) -> Result<(), String> {
+        if self.dry_run {
+            return Ok(());
         }
 
         if self.config.api_key.trim().is_empty() {
-            return Err("需要填写 API 密钥。".to_string());
+            return Err(tr!("需要填写 API 密钥。").to_string());
         }
 
-        Ok(PathBuf::from(dir))
+        config::validate_api_url(&self.config.api_url).map_err(|e| e.to_string())?;
+        if let Some(proxy) = self.config.proxy_url() {
+            config::validate_proxy_url(&proxy).map_err(|e| e.to_string())?;
+        }
+        validate_export_bitrate(&self.config.export_bitrate).map_err(|e| e.to_string())?;
+        validate_track_selection(&self.config.track_selection).map_err(|e| e.to_string())?;
+        validate_include_patterns(&self.config.include_patterns).map_err(|e| e.to_string())?;
+        validate_text_replacements(&self.config.text_replacements).map_err(|e| e.to_string())?;
+        validate_vad_format(self.config.vad_sample_rate, self.config.vad_chunk_size)
+            .map_err(|e| e.to_string())?;
+
+        Ok(())
     }
 
-    fn start_scan(&mut self, dir_path: PathBuf, reason: String) -> Command<Message> {
-        self.is_processing = true;
-        self.log_info(reason);
+    /// 返回全部已配置的目录，至少需要其中一个存在；不存在的目录在扫描时记录后跳过。
+    fn ready_directories(&self) -> Result<Vec<PathBuf>, String> {
+        let dirs = self.config.directory_paths();
+        if dirs.is_empty() {
+            return Err(tr!("请先添加至少一个媒体目录。").to_string());
+        }
+        if !dirs.iter().any(|dir| dir.exists()) {
+            return Err(tr!("所选目录均不存在。").to_string());
+        }
+        Ok(dirs)
+    }
 
+    /// 在后台校验 API 密钥，完成后以 [`Message::ApiKeyChecked`] 继续 `after` 对应的操作。
+    fn check_api_key(&mut self, after: AfterKeyCheck) -> Command<Message> {
+        self.checking_api_key = true;
+        self.log_info(tr!("正在校验 API 密钥……"));
         let api_key = self.config.api_key.clone();
-        let api_url = self.config.api_url.clone();
-        let model_name = self.config.model_name.clone();
-        let vad = if self.config.vad_enabled {
-            Some(VadConfig::from_user_settings(
-                self.config.vad_threshold,
-                self.config.vad_min_segment_secs,
-            ))
-        } else {
-            None
+        let api_url = self.config.api_url.trim().to_string();
+        let proxy_url = self.config.proxy_url();
+        Command::perform(
+            async move {
+                let client = build_client(proxy_url.as_deref())?;
+                validate_api_key(&client, &api_key, &api_url).await
+            },
+            move |res| Message::ApiKeyChecked(after, res.map_err(|e| e.to_string())),
+        )
+    }
+
+    /// 空闲时转写监视队列中的文件；正在扫描时留待本次扫描结束后再处理。
+    fn drain_watch_queue(&mut self) -> Command<Message> {
+        if !self.is_watching || self.is_processing || self.watch_queue.is_empty() {
+            return Command::none();
+        }
+        let dirs = match self.manual_ready_state() {
+            Ok(dirs) => dirs,
+            Err(err) => {
+                self.log_error(tr!("无法转写新文件：{}", err));
+                return Command::none();
+            }
         };
+        let files = std::mem::take(&mut self.watch_queue);
+        self.start_scan(
+            ScanTarget::Directories(dirs),
+            tr!("检测到 {} 个新文件，开始转写……", files.len()),
+            false,
+            Some(files),
+        )
+    }
 
+    fn start_scan(
+        &mut self,
+        target: ScanTarget,
+        reason: String,
+        dry_run: bool,
+        only_files: Option<Vec<PathBuf>>,
+    ) -> Command<Message> {
+        self.is_processing = true;
+        self.log_info(reason);
+
+        let cancel = CancellationToken::new();
+        self.cancel_token = Some(cancel.clone());
+        let pause = PauseToken::default();
+        self.pause_token = Some(pause.clone());
+
+        self.file_statuses.clear();
+        self.streamed_logs.clear();
         let (progress_tx, progress_rx) = mpsc::unbounded_channel();
         let progress_handle = Arc::new(Mutex::new(progress_rx));
         self.scan_progress_rx = Some(progress_handle.clone());
 
         let options = ScannerOptions {
-            api_key,
-            api_url,
-            model_name,
-            vad,
+            dry_run,
+            only_files,
+            pause,
+            ..self.config.scanner_options(cancel)
+        };
+        let finished =
+            |res: anyhow::Result<RunReport>| Message::ScanFinished(res.map_err(|e| e.to_string()));
+        let scan_cmd = match target {
+            ScanTarget::Directories(dirs) => Command::perform(
+                process_directories(dirs, options, Some(progress_tx)),
+                finished,
+            ),
+            ScanTarget::Files(files) => {
+                Command::perform(process_files(files, options, Some(progress_tx)), finished)
+            }
         };
-        let scan_cmd = Command::perform(
-            process_directory(dir_path, options, Some(progress_tx)),
-            |res| Message::ScanFinished(res.map_err(|e| e.to_string())),
-        );
         let progress_cmd = AutoAsrApp::listen_scan_progress(progress_handle);
 
         Command::batch(vec![scan_cmd, progress_cmd])
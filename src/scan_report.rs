@@ -0,0 +1,170 @@
+//! 每次扫描目录后写出的汇总报告，作为 GUI 日志之外的持久审计记录。
+
+use crate::scanner::{write_atomic, RunReport, ScanLogLevel};
+use crate::tr;
+use anyhow::Result;
+use chrono::{DateTime, Local};
+use std::{
+    fmt::Write as _,
+    path::{Path, PathBuf},
+};
+use tokio::fs;
+
+/// 报告文件名前缀，完整文件名形如 `scan-report-20240501-020304.txt`。
+const REPORT_FILE_PREFIX: &str = "scan-report-";
+
+/// 指定时间对应的报告路径；同一秒内已有报告时追加 `-2`、`-3` 等序号，避免覆盖。
+pub async fn report_file_path(dir: &Path, time: DateTime<Local>) -> PathBuf {
+    let stem = format!("{}{}", REPORT_FILE_PREFIX, time.format("%Y%m%d-%H%M%S"));
+    let mut path = dir.join(format!("{}.txt", stem));
+    let mut attempt = 2;
+    while fs::try_exists(&path).await.unwrap_or(false) {
+        path = dir.join(format!("{}-{}.txt", stem, attempt));
+        attempt += 1;
+    }
+    path
+}
+
+/// 把一次目录扫描的计数、失败明细与错误日志整理为纯文本报告。
+pub fn format_report(scan_dir: &Path, time: DateTime<Local>, report: &RunReport) -> String {
+    let mut text = String::new();
+    let status = if report.cancelled {
+        tr!("已取消")
+    } else {
+        tr!("完成")
+    };
+    let _ = writeln!(text, "{}", tr!("AutoASR 扫描报告"));
+    let _ = writeln!(
+        text,
+        "{}",
+        tr!("时间：{}", time.format("%Y-%m-%d %H:%M:%S"))
+    );
+    let _ = writeln!(text, "{}", tr!("目录：{}", scan_dir.display()));
+    let _ = writeln!(text, "{}", tr!("状态：{}", status));
+    let _ = writeln!(
+        text,
+        "{}",
+        tr!("耗时：{:.1} 秒", report.elapsed.as_secs_f64())
+    );
+    let _ = writeln!(text);
+    let _ = writeln!(text, "{}", tr!("发现媒体文件：{}", report.found));
+    let _ = writeln!(text, "{}", tr!("转写成功：{}", report.succeeded.len()));
+    let _ = writeln!(text, "{}", tr!("跳过：{}", report.skipped));
+    let _ = writeln!(text, "{}", tr!("失败：{}", report.failed.len()));
+    let _ = writeln!(
+        text,
+        "{}",
+        tr!(
+            "处理音频时长：{:.1} 秒（约 {:.1} 分钟）",
+            report.audio_secs,
+            report.audio_secs / 60.0
+        )
+    );
+    if report.api_duration_secs > 0.0 {
+        let _ = writeln!(
+            text,
+            "{}",
+            tr!("API 报告的转写时长：{:.1} 秒", report.api_duration_secs)
+        );
+    }
+    if let Some(summary) = report.cost_summary() {
+        let _ = writeln!(text, "{}", summary);
+    }
+
+    if !report.failed.is_empty() {
+        let _ = writeln!(text);
+        let _ = writeln!(text, "{}", tr!("失败明细："));
+        for (path, reason) in &report.failed {
+            let _ = writeln!(text, "- {}：{}", path.display(), reason);
+        }
+    }
+
+    // 失败原因已在上方列出，这里只保留其余错误（如排除规则无效、合并失败）。
+    let errors: Vec<&str> = report
+        .logs
+        .iter()
+        .filter(|log| matches!(log.level, ScanLogLevel::Error))
+        .map(|log| log.message.as_str())
+        .filter(|message| !report.failed.iter().any(|(_, reason)| reason == message))
+        .collect();
+    if !errors.is_empty() {
+        let _ = writeln!(text);
+        let _ = writeln!(text, "{}", tr!("其他错误："));
+        for message in errors {
+            let _ = writeln!(text, "- {}", message.replace('\n', "\n  "));
+        }
+    }
+    text
+}
+
+/// 把报告写入 `dir`，目录不存在时自动创建，返回报告路径。
+pub async fn write(dir: &Path, scan_dir: &Path, report: &RunReport) -> Result<PathBuf> {
+    fs::create_dir_all(dir).await?;
+    let now = Local::now();
+    let path = report_file_path(dir, now).await;
+    write_atomic(&path, format_report(scan_dir, now, report)).await?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::ScanLog;
+    use chrono::TimeZone;
+    use std::time::Duration;
+
+    #[test]
+    fn report_lists_counters_failures_and_other_errors() {
+        let time = Local.with_ymd_and_hms(2024, 5, 1, 2, 3, 4).unwrap();
+        let report = RunReport {
+            logs: vec![
+                ScanLog::new(ScanLogLevel::Info, "开始扫描"),
+                ScanLog::new(ScanLogLevel::Error, "调用 API 失败"),
+                ScanLog::new(ScanLogLevel::Error, "排除规则无效"),
+            ],
+            succeeded: vec![PathBuf::from("/media/a.mp3")],
+            failed: vec![(PathBuf::from("/media/b.mp3"), "调用 API 失败".to_string())],
+            skipped: 3,
+            found: 5,
+            audio_secs: 90.0,
+            estimated_cost: Some(0.5),
+            actual_cost: Some(0.375),
+            elapsed: Duration::from_secs(12),
+            ..RunReport::default()
+        };
+
+        let text = format_report(Path::new("/media"), time, &report);
+        assert!(text.contains("时间：2024-05-01 02:03:04"));
+        assert!(text.contains("状态：完成"));
+        assert!(text.contains("发现媒体文件：5\n转写成功：1\n跳过：3\n失败：1\n"));
+        assert!(text.contains("处理音频时长：90.0 秒（约 1.5 分钟）"));
+        assert!(text.contains("- /media/b.mp3：调用 API 失败"));
+        assert!(text.contains("其他错误：\n- 排除规则无效\n"));
+        assert!(!text.contains("API 报告的转写时长"));
+        assert!(text.contains("预估费用 0.50，实际费用 0.38"));
+    }
+
+    #[tokio::test]
+    async fn report_path_does_not_overwrite_existing_reports() {
+        let dir = std::env::temp_dir().join(format!("auto_asr_report_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let time = Local.with_ymd_and_hms(2024, 5, 1, 2, 3, 4).unwrap();
+        let report = RunReport::default();
+
+        // 目录不存在时自动创建并写出报告。
+        let written = write(&dir, Path::new("/media"), &report).await.unwrap();
+        assert_eq!(written.parent(), Some(dir.as_path()));
+        assert!(std::fs::read_to_string(&written)
+            .unwrap()
+            .contains("/media"));
+
+        let fixed = report_file_path(&dir, time).await;
+        assert_eq!(fixed, dir.join("scan-report-20240501-020304.txt"));
+        std::fs::write(&fixed, "x").unwrap();
+        assert_eq!(
+            report_file_path(&dir, time).await,
+            dir.join("scan-report-20240501-020304-2.txt")
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
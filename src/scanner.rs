@@ -1,12 +1,37 @@
 //! 目录扫描与媒体处理逻辑，包含递归遍历、FFmpeg 转码与结果落盘。
 
-use crate::api::transcribe_file;
+use crate::api::{build_client, transcribe_file, ApiError, ApiFlavor, TimedWord, Transcription};
+use crate::log_file::LogWriter;
+use crate::scan_report;
+use crate::tr;
 use anyhow::{anyhow, Context, Result};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use regex::Regex;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
 use std::env;
-use std::ffi::OsString;
+use std::ffi::{OsStr, OsString};
+use std::future::Future;
+use std::ops::RangeInclusive;
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
-use tokio::{fs, process::Command, sync::mpsc::UnboundedSender, task};
+use std::sync::{
+    atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+    Arc, Mutex,
+};
+use std::time::{Duration, Instant};
+use tokio::{
+    fs,
+    io::AsyncWriteExt,
+    process::Command,
+    sync::{
+        mpsc::{self, UnboundedSender},
+        Notify, OwnedSemaphorePermit, Semaphore,
+    },
+    task::{self, JoinSet},
+};
+use tokio_util::sync::CancellationToken;
 use voice_activity_detector::VoiceActivityDetector;
 use walkdir::WalkDir;
 
@@ -19,27 +44,213 @@ pub enum ScanLogLevel {
 
 #[derive(Debug, Clone)]
 pub struct ScanLog {
+    /// 进程内唯一的序号，用于区分经进度通道实时送达与仅出现在扫描结果中的日志。
+    pub id: u64,
     pub level: ScanLogLevel,
     pub message: String,
 }
 
+/// 扫描过程中通过进度通道发送给 GUI 的事件。
+#[derive(Debug, Clone)]
+pub enum ScanEvent {
+    /// 一条实时日志。
+    Log(ScanLog),
+    /// 已处理完的音轨数与本次待处理音轨总数。
+    ///
+    /// `secs_per_target` 为按已用时间估算的每个音轨平均耗时（秒），完成的音轨少于
+    /// [`ETA_MIN_COMPLETED`] 个时为 `None`，避免估算偏差过大。
+    Progress {
+        completed: usize,
+        total: usize,
+        secs_per_target: Option<f64>,
+    },
+    /// 单个源文件的处理状态变化，`tracks` 为该文件本次待转写的音轨数。
+    File {
+        path: PathBuf,
+        state: FileState,
+        tracks: usize,
+    },
+    /// VAD 模式下某个源文件已处理的分段数与分段总数；多音轨并发时各音轨交替上报。
+    Segment {
+        path: PathBuf,
+        done: usize,
+        total: usize,
+    },
+    /// 源文件的一条音轨转写成功，`output` 为写出的结果文件。
+    TrackDone { path: PathBuf, output: PathBuf },
+    /// 源文件的一条音轨转写失败，`error` 为失败原因。
+    TrackFailed { path: PathBuf, error: String },
+}
+
+/// 至少完成这么多音轨后才给出剩余时间估算。
+pub const ETA_MIN_COMPLETED: usize = 2;
+
+/// 从开始转写到现在的耗时平均到每个已完成音轨上；并发处理时即为实际吞吐。
+fn average_secs_per_target(elapsed: Duration, completed: usize) -> Option<f64> {
+    (completed >= ETA_MIN_COMPLETED).then(|| elapsed.as_secs_f64() / completed as f64)
+}
+
+/// 源文件在一次扫描中的处理状态。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileState {
+    /// 已列入本次扫描，尚未开始。
+    Pending,
+    /// 至少一条音轨正在转码或转写。
+    Processing,
+    /// 全部音轨转写成功。
+    Done,
+    /// 至少一条音轨转写失败。
+    Failed,
+}
+
+impl std::fmt::Display for FileState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FileState::Pending => f.write_str(tr!("等待")),
+            FileState::Processing => f.write_str(tr!("处理中")),
+            FileState::Done => f.write_str(tr!("完成")),
+            FileState::Failed => f.write_str(tr!("失败")),
+        }
+    }
+}
+
 impl ScanLog {
     pub fn new(level: ScanLogLevel, message: impl Into<String>) -> Self {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(0);
         Self {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
             level,
             message: message.into(),
         }
     }
 }
 
-const VAD_SAMPLE_RATE: u32 = 16_000;
-const VAD_CHUNK_SIZE: usize = 512;
+/// 单次扫描的结构化结果，供 GUI 汇总与后续功能直接读取计数。
+#[derive(Debug, Clone, Default)]
+pub struct RunReport {
+    /// 本次扫描产生的全部日志。
+    pub logs: Vec<ScanLog>,
+    /// 成功生成字幕的源文件，多音轨视频只出现一次。
+    pub succeeded: Vec<PathBuf>,
+    /// 成功写出的转写结果文件，按完成顺序排列。
+    pub outputs: Vec<PathBuf>,
+    /// 转写失败的源文件及原因。
+    pub failed: Vec<(PathBuf, String)>,
+    /// 因已有结果或无音轨而跳过的目标数。
+    pub skipped: usize,
+    /// 成功转写的分段总数。
+    pub total_segments: usize,
+    /// 扫描总耗时。
+    pub elapsed: Duration,
+    /// 扫描是否被用户取消。
+    pub cancelled: bool,
+    /// 是否为预览运行：只列出待处理目标，不转码也不调用 API。
+    pub dry_run: bool,
+    /// 本次扫描判定为待处理的音轨数。
+    pub pending: usize,
+    /// API 报告的已转写音频总时长（秒），用于估算费用；服务端未提供时为 0。
+    pub api_duration_secs: f64,
+    /// 扫描时发现的媒体文件数（不含被排除规则过滤的文件）。
+    pub found: usize,
+    /// 成功转写的音频总时长（秒），按原始媒体时间轴计算。
+    pub audio_secs: f64,
+    /// 开始转写前按待处理音轨时长估算的费用；未设置每分钟单价时为 `None`。
+    pub estimated_cost: Option<f64>,
+    /// 按 API 报告（或实测）时长计算的实际费用；未设置单价或只是预览时为 `None`。
+    pub actual_cost: Option<f64>,
+}
+
+impl RunReport {
+    /// 合并另一次扫描的结果，耗时由调用方统一计算。
+    fn absorb(&mut self, other: RunReport) {
+        self.logs.extend(other.logs);
+        self.succeeded.extend(other.succeeded);
+        self.outputs.extend(other.outputs);
+        self.failed.extend(other.failed);
+        self.skipped += other.skipped;
+        self.total_segments += other.total_segments;
+        self.cancelled |= other.cancelled;
+        self.pending += other.pending;
+        self.api_duration_secs += other.api_duration_secs;
+        self.found += other.found;
+        self.audio_secs += other.audio_secs;
+        self.estimated_cost = sum_costs(self.estimated_cost, other.estimated_cost);
+        self.actual_cost = sum_costs(self.actual_cost, other.actual_cost);
+    }
+
+    /// 预估与实际费用的一行摘要，两者都没有时返回 `None`。
+    pub fn cost_summary(&self) -> Option<String> {
+        match (self.estimated_cost, self.actual_cost) {
+            (Some(estimated), Some(actual)) => {
+                Some(tr!("预估费用 {:.2}，实际费用 {:.2}", estimated, actual))
+            }
+            (Some(estimated), None) => Some(tr!("预估费用 {:.2}", estimated)),
+            (None, Some(actual)) => Some(tr!("实际费用 {:.2}", actual)),
+            (None, None) => None,
+        }
+    }
+}
+
+fn sum_costs(a: Option<f64>, b: Option<f64>) -> Option<f64> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a + b),
+        (a, b) => a.or(b),
+    }
+}
+
+/// 默认 VAD 采样率（Hz）。
+pub const VAD_DEFAULT_SAMPLE_RATE: u32 = 16_000;
+/// 默认每个 VAD 分块的采样点数。
+pub const VAD_DEFAULT_CHUNK_SIZE: usize = 512;
 const VAD_MIN_SPEECH_CHUNKS: usize = 10;
 const VAD_PADDING_CHUNKS: usize = 3;
 const VAD_DEFAULT_THRESHOLD: f32 = 0.6;
 const VAD_DEFAULT_MIN_SEGMENT_SECS: f32 = 2.0;
+const VAD_DEFAULT_MERGE_GAP_SECS: f32 = 0.3;
+const VAD_DEFAULT_MAX_SEGMENT_SECS: f32 = 30.0;
+/// 默认在语音段开头前额外留出的秒数；为 0 时只保留 [`VAD_PADDING_CHUNKS`] 的分块留白，
+/// 已有配置升级后字幕时间不变。
+pub const VAD_DEFAULT_LEAD_PADDING_SECS: f32 = 0.0;
+/// 默认在语音段结尾后额外延长的秒数，同样默认为 0。
+pub const VAD_DEFAULT_TAIL_PADDING_SECS: f32 = 0.0;
+/// 用户可设置的 VAD 语音概率阈值范围，超出时截断；GUI 滑块使用同一范围。
+pub const VAD_THRESHOLD_RANGE: RangeInclusive<f32> = 0.1..=0.99;
+/// 用户可设置的最短语音段秒数范围，超出时截断；GUI 滑块使用同一范围。
+pub const VAD_MIN_SEGMENT_RANGE: RangeInclusive<f32> = 0.5..=10.0;
+/// 语音概率平滑窗口（分块数）的上限，`1` 表示不平滑。
+pub const VAD_SMOOTHING_WINDOW_MAX: usize = 15;
+/// VAD 预览只分析媒体开头的这么多秒。
+pub const VAD_PREVIEW_SECS: f64 = 60.0;
 const MIN_EXPORT_DURATION_SEC: f64 = 0.25;
 const MIN_SEGMENT_EPS: f64 = 1e-3;
+const ADAPTIVE_REFERENCE_DB: f32 = -20.0;
+/// 语音电平高出噪声底不足这么多分贝时视为嘈杂录音，按差距提高阈值。
+const ADAPTIVE_REFERENCE_MARGIN_DB: f32 = 30.0;
+const ADAPTIVE_DB_PER_STEP: f32 = 20.0;
+const ADAPTIVE_STEP: f32 = 0.1;
+const ADAPTIVE_MAX_OFFSET: f32 = 0.15;
+const SILENCE_FLOOR_DB: f32 = -96.0;
+/// 启发式说话人区分：停顿超过该秒数视为换人。
+const DIARIZE_TURN_GAP_SECS: f64 = 1.5;
+/// 启发式说话人区分：两簇电平至少相差该分贝数才按电平区分说话人。
+const DIARIZE_MIN_SPREAD_DB: f32 = 4.0;
+const SPEEDUP_MAX: f32 = 4.0;
+const APPEND_MIN_GROWTH_SECS: f64 = 1.0;
+const ATEMPO_MAX_STEP: f64 = 2.0;
+/// 单遍 EBU R128 响度归一化参数，目标 -16 LUFS，与常见语音平台一致。
+const LOUDNORM_FILTER: &str = "loudnorm=I=-16:TP=-1.5:LRA=11";
+/// 去掉片段首尾静音：先裁开头，倒放后再裁一次（即原来的结尾），最后恢复顺序。
+/// 低于 -50 dB 视为静音，首尾各保留至多 0.1 秒，避免切掉轻声的起音与尾音；
+/// 接在响度归一化之后，阈值相对归一化后的电平，轻声录音不会被整段裁空。
+const TRIM_SILENCE_FILTER: &str =
+    "silenceremove=start_periods=1:start_threshold=-50dB:start_silence=0.1,\
+areverse,silenceremove=start_periods=1:start_threshold=-50dB:start_silence=0.1,areverse";
+const FFMPEG_CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+const MAX_CUE_CHARS: usize = 40;
+/// 词间停顿超过该值（秒）时开始新的一条逐词字幕。
+const WORD_CUE_MAX_GAP_SECS: f64 = 0.8;
+/// 单次上传请求的默认超时（秒）。
+pub const DEFAULT_REQUEST_TIMEOUT_SECS: u64 = 3600;
 
 fn resolve_tool_path(tool: &str) -> OsString {
     fn candidate_name(tool: &str) -> String {
@@ -62,962 +273,7003 @@ fn resolve_tool_path(tool: &str) -> OsString {
     OsString::from(tool)
 }
 
-fn ffmpeg_program() -> OsString {
-    resolve_tool_path("ffmpeg")
+/// 用户指定了 FFmpeg 路径时直接使用，否则按可执行文件目录与 PATH 查找。
+fn ffmpeg_program(custom: Option<&Path>) -> OsString {
+    match custom {
+        Some(path) => path.as_os_str().to_os_string(),
+        None => resolve_tool_path("ffmpeg"),
+    }
 }
 
-fn ffprobe_program() -> OsString {
-    resolve_tool_path("ffprobe")
+/// 用户指定了 ffprobe 路径时直接使用，否则按可执行文件目录与 PATH 查找。
+fn ffprobe_program(custom: Option<&Path>) -> OsString {
+    match custom {
+        Some(path) => path.as_os_str().to_os_string(),
+        None => resolve_tool_path("ffprobe"),
+    }
 }
 
-#[derive(Clone)]
-pub struct ScannerOptions {
-    pub api_key: String,
-    pub api_url: String,
-    pub model_name: String,
-    pub vad: Option<VadConfig>,
+/// 运行 `ffmpeg -version` 确认 FFmpeg 可用，返回版本信息的首行。
+pub async fn check_ffmpeg(custom: Option<PathBuf>) -> Result<String> {
+    let program = ffmpeg_program(custom.as_deref());
+    let mut cmd = Command::new(&program);
+    cmd.arg("-version");
+    let output = run_tool(cmd, Some(FFMPEG_CHECK_TIMEOUT), Path::new(&program))
+        .await
+        .map_err(|e| anyhow!(tr!("无法运行 {}：{}", display_path(&program), e)))?;
+    if !output.status.success() {
+        return Err(anyhow!(tr!(
+            "{} -version 执行失败，退出状态：{}",
+            display_path(&program),
+            output.status
+        )));
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Ok(stdout.lines().next().unwrap_or_default().trim().to_string())
 }
 
-#[derive(Clone)]
-pub struct VadConfig {
-    pub threshold: f32,
-    pub min_speech_chunks: usize,
-    pub padding_chunks: usize,
+/// 上传前转码（整段音轨与 VAD 分段）使用的音频编码。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ExportCodec {
+    /// MP3（`libmp3lame`），兼容性最好。
+    #[default]
+    #[serde(rename = "libmp3lame")]
+    Mp3,
+    /// Opus（`libopus`，Ogg 容器），同等音质下体积更小。
+    #[serde(rename = "libopus")]
+    Opus,
 }
 
-impl Default for VadConfig {
-    fn default() -> Self {
-        Self {
-            threshold: VAD_DEFAULT_THRESHOLD,
-            min_speech_chunks: secs_to_chunks(VAD_DEFAULT_MIN_SEGMENT_SECS),
-            padding_chunks: VAD_PADDING_CHUNKS,
+impl ExportCodec {
+    pub const ALL: [ExportCodec; 2] = [ExportCodec::Mp3, ExportCodec::Opus];
+
+    /// FFmpeg 编码器名称。
+    fn encoder(self) -> &'static str {
+        match self {
+            ExportCodec::Mp3 => "libmp3lame",
+            ExportCodec::Opus => "libopus",
+        }
+    }
+
+    /// 导出文件的扩展名，上传时据此推断 MIME 类型。
+    fn extension(self) -> &'static str {
+        match self {
+            ExportCodec::Mp3 => "mp3",
+            ExportCodec::Opus => "ogg",
         }
     }
 }
 
-impl VadConfig {
-    pub fn from_user_settings(threshold: f32, min_segment_secs: f32) -> Self {
-        let threshold = threshold.clamp(0.1, 0.99);
-        let min_secs = min_segment_secs.clamp(0.5, 10.0);
-        Self {
-            threshold,
-            min_speech_chunks: secs_to_chunks(min_secs),
-            padding_chunks: VAD_PADDING_CHUNKS,
+impl std::fmt::Display for ExportCodec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExportCodec::Mp3 => write!(f, "MP3 (libmp3lame)"),
+            ExportCodec::Opus => write!(f, "Opus (libopus)"),
         }
     }
 }
 
-struct ScanLogger {
-    logs: Vec<ScanLog>,
-    progress: Option<UnboundedSender<ScanLog>>,
+/// 多声道音轨混成单声道的方式；除默认外均在所有转码中加入 FFmpeg `pan` 滤镜。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DownmixFilter {
+    /// 保持原有行为：VAD 用 `-ac 1` 平均混合，上传音频保留原声道。
+    #[default]
+    Average,
+    /// 以中置声道为主、少量混入前置左右声道，适合 5.1/7.1 电影对白。
+    CenterDialogue,
+    /// 只保留前置左声道，如左右声道为不同语言的双语节目。
+    FrontLeft,
+    /// 只保留前置右声道。
+    FrontRight,
 }
 
-impl ScanLogger {
-    fn new(progress: Option<UnboundedSender<ScanLog>>) -> Self {
-        Self {
-            logs: Vec::new(),
-            progress,
+impl DownmixFilter {
+    pub const ALL: [DownmixFilter; 4] = [
+        DownmixFilter::Average,
+        DownmixFilter::CenterDialogue,
+        DownmixFilter::FrontLeft,
+        DownmixFilter::FrontRight,
+    ];
+
+    /// 预设需要的最少声道数：中置对白需要多声道音轨，左右声道预设需要立体声。
+    fn min_channels(self) -> u32 {
+        match self {
+            DownmixFilter::Average => 1,
+            DownmixFilter::CenterDialogue => 3,
+            DownmixFilter::FrontLeft | DownmixFilter::FrontRight => 2,
         }
     }
 
-    fn emit(&mut self, log: ScanLog) {
-        if let Some(tx) = &self.progress {
-            let _ = tx.send(log.clone());
+    /// 对应的 `pan` 滤镜；默认的平均混合，或音轨声道数未知、不足以应用预设时返回 `None`，
+    /// 避免单声道音轨取不到 FL/FR 而变成静音。
+    fn filter(self, channels: Option<u32>) -> Option<&'static str> {
+        if channels? < self.min_channels() {
+            return None;
+        }
+        match self {
+            DownmixFilter::Average => None,
+            DownmixFilter::CenterDialogue => Some("pan=mono|c0=0.6*FC+0.2*FL+0.2*FR"),
+            DownmixFilter::FrontLeft => Some("pan=mono|c0=FL"),
+            DownmixFilter::FrontRight => Some("pan=mono|c0=FR"),
         }
-        self.logs.push(log);
-    }
-
-    fn info(&mut self, message: impl Into<String>) {
-        self.emit(ScanLog::new(ScanLogLevel::Info, message));
     }
+}
 
-    fn success(&mut self, message: impl Into<String>) {
-        self.emit(ScanLog::new(ScanLogLevel::Success, message));
+impl std::fmt::Display for DownmixFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DownmixFilter::Average => f.write_str(tr!("平均混合（默认）")),
+            DownmixFilter::CenterDialogue => f.write_str(tr!("突出中置对白（5.1/7.1）")),
+            DownmixFilter::FrontLeft => f.write_str(tr!("仅左声道")),
+            DownmixFilter::FrontRight => f.write_str(tr!("仅右声道")),
+        }
     }
+}
 
-    fn error(&mut self, message: impl Into<String>) {
-        self.emit(ScanLog::new(ScanLogLevel::Error, message));
-    }
+/// VAD 分析使用的采样率与分块采样点数。
+///
+/// 内置的 Silero VAD V5 模型只接受固定 32 毫秒的窗口，即 16000 Hz / 512 与 8000 Hz / 256；
+/// 旧版模型支持的 1024、1536 等更大窗口会被模型拒绝，因此不提供。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VadFormat {
+    pub sample_rate: u32,
+    pub chunk_size: usize,
+}
 
-    fn finish(self) -> Vec<ScanLog> {
-        self.logs
+impl VadFormat {
+    pub const ALL: [VadFormat; 2] = [
+        VadFormat {
+            sample_rate: 16_000,
+            chunk_size: 512,
+        },
+        VadFormat {
+            sample_rate: 8_000,
+            chunk_size: 256,
+        },
+    ];
+
+    /// 与给定采样率和分块大小一致的受支持格式。
+    pub fn find(sample_rate: u32, chunk_size: usize) -> Option<VadFormat> {
+        Self::ALL
+            .into_iter()
+            .find(|format| format.sample_rate == sample_rate && format.chunk_size == chunk_size)
     }
 }
 
-enum PendingJob {
-    Audio(PathBuf),
-    Video { path: PathBuf, tracks: Vec<u32> },
+impl std::fmt::Display for VadFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} Hz / {}", self.sample_rate, self.chunk_size)
+    }
 }
 
-struct MaterializedAudio {
-    path: PathBuf,
-    cleanup: bool,
+/// 校验 VAD 采样率与分块大小是否为检测器支持的组合。
+pub fn validate_vad_format(sample_rate: u32, chunk_size: usize) -> Result<()> {
+    if VadFormat::find(sample_rate, chunk_size).is_some() {
+        return Ok(());
+    }
+    let supported: Vec<String> = VadFormat::ALL.iter().map(VadFormat::to_string).collect();
+    Err(anyhow!(tr!(
+        "VAD 采样率 {} Hz 与分块大小 {} 不匹配，仅支持：{}。",
+        sample_rate,
+        chunk_size,
+        supported.join("、")
+    )))
 }
 
-#[derive(Clone)]
-struct AudioSource {
-    original_path: PathBuf,
-    track_index: Option<u32>,
-    kind: AudioSourceKind,
+/// 校验导出码率，接受 `64k`、`128K`、`96000` 这类 FFmpeg `-b:a` 写法，空白视为默认码率。
+pub fn validate_export_bitrate(bitrate: &str) -> Result<()> {
+    let trimmed = bitrate.trim();
+    if trimmed.is_empty() {
+        return Ok(());
+    }
+    let digits = trimmed.strip_suffix(['k', 'K']).unwrap_or(trimmed);
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return Err(anyhow!(tr!(
+            "导出码率 {} 无效，应形如 64k 或 128k。",
+            format!("{:?}", bitrate)
+        )));
+    }
+    Ok(())
 }
 
-#[derive(Clone)]
-enum AudioSourceKind {
-    DirectAudio {
-        audio_path: PathBuf,
-    },
-    VideoTrack {
-        video_path: PathBuf,
-        track_index: u32,
-    },
+/// 视频中需要转写的音轨。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum TrackSelection {
+    /// 转写所有音轨。
+    #[default]
+    All,
+    /// 只转写流编号最小的音轨。
+    First,
+    /// 只转写列出的流编号（与输出文件名中的 `.轨道N` 一致）。
+    Indices(Vec<u32>),
 }
 
-impl AudioSource {
-    fn from_audio_file(path: PathBuf) -> Self {
-        Self {
-            original_path: path.clone(),
-            track_index: None,
-            kind: AudioSourceKind::DirectAudio { audio_path: path },
+impl TrackSelection {
+    /// 解析配置中的音轨选择：`all`（或留空）、`first`，或逗号分隔的流编号如 `1,3`。
+    pub fn parse(value: &str) -> Result<Self> {
+        let trimmed = value.trim();
+        if trimmed.is_empty() || trimmed.eq_ignore_ascii_case("all") {
+            return Ok(TrackSelection::All);
+        }
+        if trimmed.eq_ignore_ascii_case("first") {
+            return Ok(TrackSelection::First);
+        }
+        let mut indices = Vec::new();
+        for part in trimmed.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let index = part.parse::<u32>().map_err(|_| {
+                anyhow!(tr!(
+                    "音轨选择 {} 无效，应为 all、first 或逗号分隔的音轨编号（如 1,3）。",
+                    format!("{:?}", value)
+                ))
+            })?;
+            if !indices.contains(&index) {
+                indices.push(index);
+            }
         }
+        if indices.is_empty() {
+            return Err(anyhow!(tr!(
+                "音轨选择 {} 未列出任何音轨编号。",
+                format!("{:?}", value)
+            )));
+        }
+        Ok(TrackSelection::Indices(indices))
     }
 
-    fn from_video_track(path: PathBuf, track_index: u32) -> Self {
-        Self {
-            original_path: path.clone(),
-            track_index: Some(track_index),
-            kind: AudioSourceKind::VideoTrack {
-                video_path: path,
-                track_index,
-            },
+    /// 从 ffprobe 列出的音轨中筛选出需要转写的部分，保持原有顺序。
+    fn apply(&self, streams: Vec<AudioStream>) -> Vec<AudioStream> {
+        match self {
+            TrackSelection::All => streams,
+            TrackSelection::First => streams
+                .into_iter()
+                .min_by_key(|stream| stream.index)
+                .into_iter()
+                .collect(),
+            TrackSelection::Indices(wanted) => streams
+                .into_iter()
+                .filter(|stream| wanted.contains(&stream.index))
+                .collect(),
         }
     }
+}
 
-    fn original_path(&self) -> &Path {
-        &self.original_path
-    }
+/// 校验音轨选择的写法，留空视为转写所有音轨。
+pub fn validate_track_selection(value: &str) -> Result<()> {
+    TrackSelection::parse(value).map(|_| ())
+}
 
-    fn track_index(&self) -> Option<u32> {
-        self.track_index
+/// 填写了包含规则却没有一条能解析时报错：否则包含过滤整体失效，整棵目录树都会被转写。
+pub fn validate_include_patterns(patterns: &[String]) -> Result<()> {
+    let mut given = patterns.iter().map(|p| p.trim()).filter(|p| !p.is_empty());
+    let Some(first) = given.clone().next() else {
+        return Ok(());
+    };
+    if given.any(|pattern| Glob::new(pattern).is_ok()) {
+        return Ok(());
     }
+    Err(anyhow!(tr!(
+        "包含规则均无效（如 {}），请修正后再扫描。",
+        format!("{:?}", first)
+    )))
+}
 
-    fn display_name(&self) -> String {
-        format!(
-            "{:?}{}",
-            self.original_path,
-            track_suffix(self.track_index, None)
-        )
-    }
+/// 清理字幕文本时视为句末标点、可从末尾去掉的字符。
+const TRAILING_PUNCTUATION: &[char] = &[
+    '。', '，', '、', '；', '：', '！', '？', '…', '.', ',', ';', ':', '!', '?',
+];
+
+/// 编译一条替换规则，正则无效时返回带规则原文的错误。
+fn compile_replacement(rule: &TextReplacement) -> Result<(Regex, String)> {
+    Regex::new(&rule.pattern)
+        .map(|regex| (regex, rule.replacement.clone()))
+        .map_err(|e| {
+            anyhow!(tr!(
+                "文本替换规则 {} 无效：{}",
+                format!("{:?}", rule.pattern),
+                e
+            ))
+        })
+}
 
-    fn input_path(&self) -> &Path {
-        match &self.kind {
-            AudioSourceKind::DirectAudio { audio_path } => audio_path,
-            AudioSourceKind::VideoTrack { video_path, .. } => video_path,
-        }
-    }
+/// 一条文本替换规则：`pattern` 为正则表达式，`replacement` 可用 `$1` 引用捕获组。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TextReplacement {
+    pub pattern: String,
+    #[serde(default)]
+    pub replacement: String,
+}
 
-    fn map_arg(&self) -> Option<String> {
-        match (&self.kind, self.track_index) {
-            (AudioSourceKind::VideoTrack { .. }, Some(track)) => Some(format!("0:{}", track)),
-            _ => None,
-        }
+/// 写入结果前对每条字幕文本做的后处理，默认不做任何改动。
+#[derive(Debug, Clone, Default)]
+pub struct TextCleanup {
+    /// 去掉每条字幕末尾的句末标点，如 `。`、`，`、`?`。
+    pub trim_trailing_punctuation: bool,
+    /// 把连续空白（含换行）合并为一个空格。
+    pub collapse_whitespace: bool,
+    /// 按配置顺序依次应用的正则替换。
+    replacements: Vec<(Regex, String)>,
+    /// [`TextCleanup::lenient`] 因正则无效而跳过的规则说明，扫描开始时写入日志。
+    skipped: Vec<String>,
+}
+
+impl TextCleanup {
+    /// 编译替换规则；任一正则无效时返回错误。
+    pub fn new(
+        trim_trailing_punctuation: bool,
+        collapse_whitespace: bool,
+        replacements: &[TextReplacement],
+    ) -> Result<Self> {
+        let replacements = replacements
+            .iter()
+            .map(compile_replacement)
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self {
+            trim_trailing_punctuation,
+            collapse_whitespace,
+            replacements,
+            skipped: Vec::new(),
+        })
     }
 
-    async fn materialize_full_audio(&self) -> Result<MaterializedAudio> {
-        match &self.kind {
-            AudioSourceKind::DirectAudio { audio_path } => Ok(MaterializedAudio {
-                path: audio_path.clone(),
-                cleanup: false,
-            }),
-            AudioSourceKind::VideoTrack {
-                video_path,
-                track_index,
-            } => {
-                let output = audio_track_path(video_path, *track_index);
-                if output.exists() {
-                    let _ = fs::remove_file(&output).await;
-                }
-                convert_track_to_mp3(video_path, *track_index, &output).await?;
-                Ok(MaterializedAudio {
-                    path: output,
-                    cleanup: true,
-                })
+    /// 与 [`TextCleanup::new`] 相同，但只跳过无效的规则，其余规则照常生效；
+    /// 跳过的规则可由 [`TextCleanup::skipped_rules`] 取得。
+    pub fn lenient(
+        trim_trailing_punctuation: bool,
+        collapse_whitespace: bool,
+        replacements: &[TextReplacement],
+    ) -> Self {
+        let mut cleanup = Self {
+            trim_trailing_punctuation,
+            collapse_whitespace,
+            ..Self::default()
+        };
+        for rule in replacements {
+            match compile_replacement(rule) {
+                Ok(compiled) => cleanup.replacements.push(compiled),
+                Err(e) => cleanup
+                    .skipped
+                    .push(tr!("{}，已跳过该规则，其余规则照常生效。", e)),
             }
         }
+        cleanup
     }
 
-    async fn convert_to_pcm16(&self) -> Result<PathBuf> {
-        let output = vad_audio_path(&self.original_path, self.track_index);
-        if output.exists() {
-            let _ = fs::remove_file(&output).await;
-        }
+    /// 因正则无效而被跳过的规则说明。
+    pub fn skipped_rules(&self) -> &[String] {
+        &self.skipped
+    }
 
-        let mut cmd = Command::new(ffmpeg_program());
-        cmd.arg("-i").arg(self.input_path());
-        if let Some(map) = self.map_arg() {
-            cmd.arg("-map").arg(map);
+    /// 依次应用正则替换、空白合并与末尾标点清理，返回去掉首尾空白的文本。
+    pub fn apply(&self, text: &str) -> String {
+        let mut text = sanitize_srt_text(text);
+        for (regex, replacement) in &self.replacements {
+            text = regex.replace_all(&text, replacement.as_str()).into_owned();
         }
-        cmd.arg("-ac")
-            .arg("1")
-            .arg("-ar")
-            .arg(VAD_SAMPLE_RATE.to_string())
-            .arg("-sample_fmt")
-            .arg("s16")
-            .arg("-y")
-            .arg(&output);
-
-        let status = cmd.status().await?;
-        if status.success() {
-            Ok(output)
-        } else {
-            Err(anyhow!(
-                "FFmpeg 转换音频用于 VAD 时失败，退出状态：{}",
-                status
-            ))
+        if self.collapse_whitespace {
+            text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        }
+        if self.trim_trailing_punctuation {
+            text = text
+                .trim_end()
+                .trim_end_matches(TRAILING_PUNCTUATION)
+                .to_string();
         }
+        text.trim().to_string()
     }
+}
 
-    async fn export_segment_audio(
-        &self,
-        segment_idx: usize,
-        segment: &SpeechSegment,
-    ) -> Result<PathBuf> {
-        let output = segment_audio_path(&self.original_path, self.track_index, segment_idx);
-        if output.exists() {
-            let _ = fs::remove_file(&output).await;
-        }
+/// 校验文本替换规则中的正则表达式。
+pub fn validate_text_replacements(replacements: &[TextReplacement]) -> Result<()> {
+    TextCleanup::new(false, false, replacements).map(|_| ())
+}
 
-        let duration = (segment.end_sec - segment.start_sec).max(MIN_EXPORT_DURATION_SEC);
-        let mut cmd = Command::new(ffmpeg_program());
-        cmd.arg("-ss")
-            .arg(format!("{:.3}", segment.start_sec))
-            .arg("-i")
-            .arg(self.input_path());
-        if let Some(map) = self.map_arg() {
-            cmd.arg("-map").arg(map);
+/// 转写结果的输出格式。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    /// 带时间轴的 `.srt` 字幕。
+    #[default]
+    Srt,
+    /// 不含时间戳的纯文本 `.txt`，每个分段一行。
+    Txt,
+    /// `.srt` 字幕，并在旁边额外输出含分段时间戳的 `.json`。
+    Json,
+    /// 带样式的 `.ass` 字幕，字体与字号取自 [`AssStyle`]。
+    Ass,
+}
+
+impl OutputFormat {
+    pub const ALL: [OutputFormat; 4] = [
+        OutputFormat::Srt,
+        OutputFormat::Txt,
+        OutputFormat::Json,
+        OutputFormat::Ass,
+    ];
+
+    /// 结果文件的扩展名。
+    pub fn extension(self) -> &'static str {
+        match self {
+            OutputFormat::Srt | OutputFormat::Json => "srt",
+            OutputFormat::Txt => "txt",
+            OutputFormat::Ass => "ass",
         }
-        cmd.arg("-t")
-            .arg(format!("{:.3}", duration))
-            .arg("-acodec")
-            .arg("libmp3lame")
-            .arg("-y")
-            .arg(&output);
+    }
 
-        let status = cmd.status().await?;
-        if status.success() {
-            Ok(output)
-        } else {
-            Err(anyhow!("FFmpeg 裁剪语音片段失败，退出状态：{}", status))
+    /// 按格式渲染单条结果；纯文本模式直接丢弃时间戳，ASS 不使用序号。
+    fn render_entry(self, index: usize, start: f64, end: f64, text: &str) -> String {
+        match self {
+            OutputFormat::Srt | OutputFormat::Json => build_srt_entry(index, start, end, text),
+            OutputFormat::Txt => format!("{}\n", sanitize_srt_text(text)),
+            OutputFormat::Ass => build_ass_dialogue(start, end, text),
         }
     }
 }
 
-async fn cleanup_materialized(audio: MaterializedAudio) -> Result<()> {
-    if audio.cleanup {
-        fs::remove_file(&audio.path).await?;
+impl std::fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OutputFormat::Srt => f.write_str(tr!("SRT 字幕")),
+            OutputFormat::Txt => f.write_str(tr!("纯文本 TXT")),
+            OutputFormat::Json => write!(f, "SRT + JSON"),
+            OutputFormat::Ass => f.write_str(tr!("ASS 字幕")),
+        }
     }
-    Ok(())
 }
 
-/// 扫描指定目录并对尚未转写的媒体文件执行 ASR，返回日志列表。
-pub async fn process_directory(
-    dir: PathBuf,
-    options: ScannerOptions,
-    progress: Option<UnboundedSender<ScanLog>>,
-) -> Result<Vec<ScanLog>> {
-    let mut logger = ScanLogger::new(progress);
-    let mut jobs = Vec::new();
-    let api_key = options.api_key.clone();
+/// ASS 默认字体。
+pub const DEFAULT_ASS_FONT: &str = "Microsoft YaHei";
+/// ASS 默认字号，按 1080p 画布（`PlayResY: 1080`）计算。
+pub const DEFAULT_ASS_FONT_SIZE: u32 = 54;
 
-    if api_key.trim().is_empty() {
-        return Err(anyhow!("API Key 为空，请在设置中填写后再运行。"));
-    }
+/// 写入 ASS `[V4+ Styles]` 中 `Default` 样式的字体设置。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssStyle {
+    pub font_name: String,
+    pub font_size: u32,
+}
 
-    if !dir.exists() {
-        return Err(anyhow!("目录不存在：{:?}", dir));
+impl Default for AssStyle {
+    fn default() -> Self {
+        Self {
+            font_name: DEFAULT_ASS_FONT.to_string(),
+            font_size: DEFAULT_ASS_FONT_SIZE,
+        }
     }
+}
 
-    for entry in WalkDir::new(&dir).into_iter().filter_map(|e| e.ok()) {
-        let path = entry.path();
-        if !path.is_file() {
-            continue;
-        }
+#[derive(Clone, Default)]
+pub struct ScannerOptions {
+    pub api_key: String,
+    pub api_url: String,
+    pub model_name: String,
+    /// 转写接口的协议类型；OpenAI Whisper 兼容接口直接使用服务端分段时间戳。
+    pub api_flavor: ApiFlavor,
+    /// 按扩展名覆盖上传时的 MIME 类型，键为小写、不带点的扩展名；未覆盖的使用内置默认值。
+    pub mime_overrides: HashMap<String, String>,
+    pub vad: Option<VadConfig>,
+    /// 长音频的切分方式；仅 [`SegmentMode::Vad`] 时使用 `vad` 中的参数。
+    pub segment_mode: SegmentMode,
+    /// 上传前的音频加速倍率，`None` 表示不加速。
+    pub speedup_factor: Option<f32>,
+    /// 转码时先做响度归一化，VAD 分析与上传的音频使用同一滤镜。
+    pub audio_normalize: bool,
+    /// 多声道混成单声道的方式，VAD 分析与上传的音频使用同一滤镜。
+    pub downmix_filter: DownmixFilter,
+    /// 导出 VAD 语音分段时裁掉首尾静音；字幕时间仍按分段在原始时间轴上的位置计算。
+    pub trim_segment_silence: bool,
+    /// 单次 ffprobe/ffmpeg 调用的超时秒数，`0` 表示不限制。
+    pub tool_timeout_secs: u64,
+    /// 取消令牌，触发后立即中止进行中的 FFmpeg 与上传请求并停止扫描。
+    pub cancel: CancellationToken,
+    /// 暂停令牌，暂停期间不再开始新的音轨或分段，进行中的 FFmpeg 与上传照常完成。
+    pub pause: PauseToken,
+    /// 取消时是否丢弃未完成的 `.partial` 字幕；否则原样保留供查看。两种情况下分段缓存都会保留，
+    /// 下次扫描只上传缺失的分段。
+    pub discard_partial_on_cancel: bool,
+    /// 对时长增长的已转写文件仅转写新增尾部并追加到原字幕。
+    pub append_growing_files: bool,
+    /// 忽略已有结果，所有媒体都重新转写。
+    pub force_reprocess: bool,
+    /// 在字幕前加上 `[说话人 N]` 标签；优先使用 API 返回的标签，否则按电平与停顿推断。
+    pub diarize: bool,
+    /// 向 Whisper 兼容接口请求逐词时间戳，并用 [`build_word_cues`] 生成更细的字幕。
+    pub word_timestamps: bool,
+    /// 同时处理的音轨数量上限，`0` 与 `1` 均表示逐个处理。
+    pub max_concurrency: usize,
+    /// 同时运行的 FFmpeg 转码数量上限，`0` 表示不单独限制，转码随音轨一起受 `max_concurrency` 约束。
+    ///
+    /// 设置后 `max_concurrency` 只限制同时上传的数量，另有至多这么多个音轨可以提前转码。
+    pub ffmpeg_concurrency: usize,
+    /// 按 `ffmpeg_concurrency` 创建的共享信号量，与 `rate_limiter` 一同设置。
+    pub ffmpeg_limiter: Option<Arc<Semaphore>>,
+    /// 单独限制 FFmpeg 并发时，按 `max_concurrency` 限制同时上传数量的共享信号量。
+    pub upload_limiter: Option<Arc<Semaphore>>,
+    /// 每分钟音频的 API 单价，设置后在扫描前后记录预估与实际费用。
+    pub cost_per_minute: Option<f64>,
+    /// 连续这么多个音轨转写失败时中止本次扫描，`0` 表示不限制；鉴权失败总是立即中止。
+    pub abort_after_consecutive_failures: usize,
+    /// 每分钟最多发起的转写请求数，`None` 不限制；整段与 VAD 分段上传都计入。
+    pub requests_per_minute: Option<u32>,
+    /// 按 `requests_per_minute` 创建的共享限速器，由 [`process_directories`] 或
+    /// [`process_directory`] 设置，同一次运行的所有目录与并发任务共用。
+    pub rate_limiter: Option<Arc<RateLimiter>>,
+    /// 结果文件格式，同时决定跳过已转写文件时检查的扩展名。
+    pub output_format: OutputFormat,
+    /// ASS 输出使用的字体与字号。
+    pub ass_style: AssStyle,
+    /// 生成结果文件名时用 [`sanitize_output_filename`] 替换源文件名中的不安全字符。
+    pub sanitize_filenames: bool,
+    /// 清理文件名时替换不安全字符使用的字符串。
+    pub filename_substitute: String,
+    /// 自定义 FFmpeg 可执行文件路径，`None` 时自动查找。
+    pub ffmpeg_path: Option<PathBuf>,
+    /// 自定义 ffprobe 可执行文件路径，`None` 时自动查找。
+    pub ffprobe_path: Option<PathBuf>,
+    /// 排除规则（glob），匹配相对于扫描根目录的路径，如 `**/backup/**`、`*.sample.mp4`。
+    pub exclude_patterns: Vec<String>,
+    /// 包含规则（glob），非空时只处理至少匹配一条的文件；先按包含规则筛选，再应用排除规则。
+    pub include_patterns: Vec<String>,
+    /// 最大递归深度，`Some(1)` 只扫描所选目录本身，`None` 不限制。
+    pub max_depth: Option<usize>,
+    /// 单个上传文件的大小上限（MB），超出时跳过而不上传，`None` 不限制。
+    ///
+    /// 音频文件按原始大小在扫描时判断；视频按导出后的音轨在上传前判断。
+    pub max_file_size_mb: Option<u64>,
+    /// 用 ffprobe 探测没有扩展名的文件，含音轨的按音频或视频处理；每个文件多一次探测，较慢。
+    pub probe_extensionless: bool,
+    /// 扫描结束后按路径顺序把目录内各文件的 SRT 合并为一个字幕，时间轴依次顺延。
+    pub combine_outputs: bool,
+    /// 视频的全部音轨转写成功后，把本次生成的 SRT 作为软字幕封装回视频容器。
+    pub mux_subtitles: bool,
+    /// 封装字幕时替换原视频；否则写出 `<文件名>.subtitled.<扩展名>`。
+    pub mux_replace_original: bool,
+    /// 转写结果与中间文件的输出目录，按源文件相对扫描目录的路径存放；`None` 时写在源文件旁。
+    pub output_dir: Option<PathBuf>,
+    /// 当前扫描的根目录，由 [`process_directory`] 设置，用于计算输出目录下的相对路径。
+    pub scan_root: Option<PathBuf>,
+    /// 多个扫描根共用输出目录时，当前根在输出目录下的子文件夹，由 [`process_directories`] 设置；
+    /// 避免不同根中相对路径相同的文件写到同一结果。
+    pub output_subdir: Option<String>,
+    /// 预览模式：只列出待处理的文件与音轨，跳过 FFmpeg 转码与 API 调用。
+    pub dry_run: bool,
+    /// 未启用 VAD 时按句子拆分整段结果，并按字数比例分配时间戳。
+    pub split_whole_cues: bool,
+    /// HTTP/HTTPS 代理地址，`None` 时直连（仍会读取系统代理环境变量）。
+    pub proxy_url: Option<String>,
+    /// 单次上传请求的超时秒数，`0` 表示不限制。
+    pub request_timeout_secs: u64,
+    /// 日志文件目录，`None` 时不写入磁盘。
+    pub log_dir: Option<PathBuf>,
+    /// 每个目录扫描结束后写出汇总报告的目录，`None` 时不写报告；预览模式不写。
+    pub report_dir: Option<PathBuf>,
+    /// 上传前转码使用的音频编码。
+    pub export_codec: ExportCodec,
+    /// 导出码率（FFmpeg `-b:a`，如 `64k`），`None` 时使用编码器默认值。
+    pub export_bitrate: Option<String>,
+    /// 保留转码、VAD 与分段过程中生成的中间音频，便于排查识别质量。
+    pub keep_intermediates: bool,
+    /// 视频已带文本字幕流时直接提取为 `.srt`，不再转写音轨。
+    pub skip_if_embedded_subs: bool,
+    /// 视频中需要转写的音轨，默认转写全部。
+    pub track_selection: TrackSelection,
+    /// 写入结果前对字幕文本的后处理，默认不做改动。
+    pub text_cleanup: TextCleanup,
+    /// 多音轨结果按音轨语言标签命名（如 `video.jpn.srt`），无标签或语言重复时仍用音轨编号。
+    pub name_tracks_by_language: bool,
+    /// 视为已有字幕的外挂字幕语言后缀，如 `zh` 对应 `movie.zh.srt`。
+    pub subtitle_suffixes: Vec<String>,
+    /// 只处理列出的文件（监视模式使用），`None` 时处理目录中的全部媒体文件。
+    pub only_files: Option<Vec<PathBuf>>,
+}
 
-        let Some(ext) = path.extension() else {
-            continue;
+impl ScannerOptions {
+    /// 把源文件旁的路径映射到输出目录下相同的相对位置；未设置输出目录时原样返回。
+    fn relocate(&self, path: PathBuf) -> PathBuf {
+        let (Some(output_dir), Some(root)) = (&self.output_dir, &self.scan_root) else {
+            return path;
         };
-
-        let ext_str = ext.to_string_lossy().to_lowercase();
-        if !is_media_extension(&ext_str) {
-            continue;
+        let Ok(relative) = path.strip_prefix(root) else {
+            return path;
+        };
+        match &self.output_subdir {
+            Some(subdir) => output_dir.join(subdir).join(relative),
+            None => output_dir.join(relative),
         }
+    }
 
-        if is_video(path) {
-            match audio_stream_indices(path).await {
-                Ok(indices) => {
-                    if indices.is_empty() {
-                        logger.info(format!("跳过 {:?}：视频中未检测到音轨。", path));
-                        continue;
-                    }
-
-                    let mut pending_tracks = Vec::new();
-                    for idx in indices {
-                        let transcript_path = transcript_result_path(path, Some(idx));
-                        if !transcript_path.exists() {
-                            pending_tracks.push(idx);
-                        }
-                    }
-
-                    if pending_tracks.is_empty() {
-                        logger.info(format!("跳过 {:?}：所有音轨均已转写。", path));
-                        continue;
-                    }
+    /// 源文件（或其某条音轨）转写结果的最终位置，`track_label` 见 [`track_labels`]。
+    fn transcript_path(&self, original: &Path, track_label: Option<&str>) -> PathBuf {
+        match self.sanitized_stem(original) {
+            Some(stem) => self.relocate(
+                result_path_with_stem(original, Some(&stem), track_label)
+                    .with_extension(self.output_format.extension()),
+            ),
+            None => self.relocate(transcript_output_path(
+                original,
+                track_label,
+                self.output_format,
+            )),
+        }
+    }
 
-                    jobs.push(PendingJob::Video {
-                        path: path.to_path_buf(),
-                        tracks: pending_tracks,
-                    });
-                }
-                Err(e) => {
-                    logger.error(format!("读取 {:?} 音轨失败：{}", path, e));
-                }
-            }
-        } else {
-            let transcript_path = transcript_result_path(path, None);
-            if transcript_path.exists() {
-                continue;
-            }
-            jobs.push(PendingJob::Audio(path.to_path_buf()));
+    /// 转写结果 `.srt` 的最终位置；启用文件名清理时使用清理后的主干，跳过检查与写入保持一致。
+    fn result_srt_path(&self, original: &Path, track_label: Option<&str>) -> PathBuf {
+        match self.sanitized_stem(original) {
+            Some(stem) => self.relocate(result_path_with_stem(original, Some(&stem), track_label)),
+            None => self.relocate(transcript_result_path(original, track_label)),
         }
     }
 
-    if jobs.is_empty() {
-        logger.info("没有检测到新的待转写文件。");
-        return Ok(logger.finish());
+    /// 派生结果文件名使用的主干，启用 `sanitize_filenames` 时替换其中的不安全字符。
+    fn result_stem(&self, original: &Path) -> Option<OsString> {
+        self.sanitized_stem(original)
+            .or_else(|| original.file_stem().map(OsStr::to_os_string))
     }
 
-    let total_targets: usize = jobs
-        .iter()
-        .map(|job| match job {
-            PendingJob::Audio(_) => 1,
-            PendingJob::Video { tracks, .. } => tracks.len(),
-        })
-        .sum();
-
-    logger.info(format!("待处理音轨总数：{}。", total_targets));
-
-    let options = Arc::new(options);
-
-    for job in jobs {
-        match job {
-            PendingJob::Audio(path) => {
-                let source = AudioSource::from_audio_file(path);
-                process_audio_source(options.clone(), source, &mut logger).await;
-            }
-            PendingJob::Video { path, tracks } => {
-                for track in tracks {
-                    let source = AudioSource::from_video_track(path.clone(), track);
-                    process_audio_source(options.clone(), source, &mut logger).await;
-                }
-            }
+    /// 启用 `sanitize_filenames` 且主干含不安全字符时返回清理后的主干，否则返回 `None`。
+    /// 同目录另一个媒体文件的主干清理后与之相同时，附加源主干的短哈希，避免两者写到同一结果、
+    /// 后者被当作已转写跳过。
+    fn sanitized_stem(&self, original: &Path) -> Option<OsString> {
+        if !self.sanitize_filenames {
+            return None;
         }
+        let stem = original.file_stem()?.to_string_lossy().into_owned();
+        let sanitized = sanitize_output_filename(&stem, &self.filename_substitute);
+        if sanitized == stem {
+            return None;
+        }
+        let collides = original
+            .parent()
+            .and_then(|dir| std::fs::read_dir(dir).ok())
+            .is_some_and(|entries| {
+                entries.filter_map(|entry| entry.ok()).any(|entry| {
+                    let other = entry.path();
+                    let Some(other_stem) = other.file_stem().map(|s| s.to_string_lossy()) else {
+                        return false;
+                    };
+                    other_stem != stem
+                        && has_media_extension(&other)
+                        && sanitize_output_filename(&other_stem, &self.filename_substitute)
+                            == sanitized
+                })
+            });
+        if !collides {
+            return Some(OsString::from(sanitized));
+        }
+        let digest = Sha256::digest(stem.as_bytes());
+        let tag: String = digest[..4]
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect();
+        Some(OsString::from(format!("{}~{}", sanitized, tag)))
     }
 
-    Ok(logger.finish())
-}
-fn is_media_extension(ext: &str) -> bool {
-    matches!(
-        ext,
-        "mkv" | "mp4" | "avi" | "mov" | "flv" | "wmv" | "wav" | "ogg" | "opus" | "mp3" | "m4a"
-    )
-}
-
-/// 判断给定路径是否属于需要先转码的视频文件。
-fn is_video(path: &Path) -> bool {
-    if let Some(ext) = path.extension() {
-        let ext = ext.to_string_lossy().to_lowercase();
-        matches!(ext.as_str(), "mkv" | "mp4" | "avi" | "mov" | "flv" | "wmv")
-    } else {
-        false
+    /// 新建结果文件时需要先写入的文件头，目前只有 ASS 需要。
+    fn result_header(&self) -> Option<String> {
+        (self.output_format == OutputFormat::Ass).then(|| ass_header(&self.ass_style))
     }
-}
 
-/// 通过 FFmpeg 将特定音轨转为 MP3 音频，供 ASR 上传使用。
-async fn convert_track_to_mp3(input: &Path, stream_index: u32, output: &Path) -> Result<()> {
-    let status = Command::new(ffmpeg_program())
-        .arg("-i")
-        .arg(input)
-        .arg("-map")
-        .arg(format!("0:{}", stream_index))
-        .arg("-c:a")
-        .arg("libmp3lame")
-        .arg("-y")
-        .arg(output)
-        .status()
-        .await?;
-
-    if status.success() {
+    /// 使用输出目录时创建 `path` 所在的文件夹。
+    async fn ensure_output_parent(&self, path: &Path) -> Result<()> {
+        if self.output_dir.is_some() {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)
+                    .await
+                    .with_context(|| tr!("创建输出目录 {} 失败", display_path(&parent)))?;
+            }
+        }
         Ok(())
-    } else {
-        Err(anyhow!("FFmpeg 转码音轨失败，退出状态：{}", status))
     }
-}
 
-/// 基于原始文件名生成转写结果 `.srt` 路径，可附带音轨编号。
-fn transcript_result_path(original: &Path, track_index: Option<u32>) -> PathBuf {
-    let base_name = original
-        .file_stem()
-        .map(|name| name.to_string_lossy().to_string())
-        .unwrap_or_else(|| "result".to_string());
+    /// 返回规范化后的加速倍率，限制在 `1.0..=4.0`，接近 1 时视为不加速。
+    fn speedup(&self) -> Option<f64> {
+        let factor = self.speedup_factor?.clamp(1.0, SPEEDUP_MAX) as f64;
+        if factor - 1.0 < 1e-3 {
+            None
+        } else {
+            Some(factor)
+        }
+    }
 
-    let target_name = match track_index {
-        Some(idx) => format!("{}.轨道{}.srt", base_name, idx),
-        None => format!("{}.srt", base_name),
-    };
+    /// 组合转码时使用的 `-filter:a` 滤镜：先混音、再响度归一化，`with_speedup` 时再串联加速。
+    /// `channels` 为源音轨的声道数，决定是否应用混音预设。
+    fn audio_filter(&self, with_speedup: bool, channels: Option<u32>) -> Option<String> {
+        self.filter_chain(with_speedup, false, channels)
+    }
 
-    original.with_file_name(target_name)
-}
+    /// 导出 VAD 分段使用的滤镜：语音分段按配置在混音与归一化之后裁掉首尾静音，再加速。
+    /// 静音补间本身就是静音，不做裁剪，否则会得到空音频。
+    fn segment_audio_filter(&self, kind: SegmentKind, channels: Option<u32>) -> Option<String> {
+        self.filter_chain(true, self.trims_segment(kind), channels)
+    }
 
-/// 基于原始视频生成指定音轨的 mp3 文件名。
-fn audio_track_path(original: &Path, track_index: u32) -> PathBuf {
-    let file_name = original
-        .file_name()
-        .map(|name| name.to_string_lossy().to_string())
-        .unwrap_or_else(|| "audio".to_string());
-    original.with_file_name(format!("{}-track{}.mp3", file_name, track_index))
-}
+    /// 导出该类分段时是否裁剪首尾静音。逐词时间戳按分段起点换算，
+    /// 裁掉开头会使每个词都提前，因此开启 `word_timestamps` 时不裁剪。
+    fn trims_segment(&self, kind: SegmentKind) -> bool {
+        self.trim_segment_silence && !self.word_timestamps && kind == SegmentKind::Speech
+    }
 
-fn segment_audio_path(original: &Path, track_index: Option<u32>, segment_idx: usize) -> PathBuf {
-    let file_name = original
-        .file_name()
-        .map(|name| name.to_string_lossy().to_string())
-        .unwrap_or_else(|| "segment".to_string());
-    let track_suffix = track_file_suffix(track_index);
-    original.with_file_name(format!(
-        "{}{}-seg{}.mp3",
-        file_name, track_suffix, segment_idx
-    ))
-}
+    fn filter_chain(
+        &self,
+        with_speedup: bool,
+        trim_silence: bool,
+        channels: Option<u32>,
+    ) -> Option<String> {
+        let mut filters = Vec::new();
+        if let Some(pan) = self.downmix_filter.filter(channels) {
+            filters.push(pan.to_string());
+        }
+        if self.audio_normalize {
+            filters.push(LOUDNORM_FILTER.to_string());
+        }
+        if trim_silence {
+            filters.push(TRIM_SILENCE_FILTER.to_string());
+        }
+        if let Some(factor) = self.speedup().filter(|_| with_speedup) {
+            filters.push(atempo_filter_chain(factor));
+        }
+        (!filters.is_empty()).then(|| filters.join(","))
+    }
 
-fn vad_audio_path(original: &Path, track_index: Option<u32>) -> PathBuf {
-    let file_name = original
-        .file_name()
-        .map(|name| name.to_string_lossy().to_string())
-        .unwrap_or_else(|| "segment".to_string());
-    let track_suffix = track_file_suffix(track_index);
-    original.with_file_name(format!("{}{}-vad.wav", file_name, track_suffix))
-}
+    /// 导出上传用音频的 FFmpeg 编码参数：编码器与可选码率。
+    fn export_codec_args(&self) -> Vec<String> {
+        let mut args = vec!["-c:a".to_string(), self.export_codec.encoder().to_string()];
+        if let Some(bitrate) = self
+            .export_bitrate
+            .as_deref()
+            .map(str::trim)
+            .filter(|b| !b.is_empty())
+        {
+            args.push("-b:a".to_string());
+            args.push(bitrate.to_string());
+        }
+        args
+    }
 
-fn track_file_suffix(track_index: Option<u32>) -> String {
-    track_index
-        .map(|idx| format!("-track{}", idx))
-        .unwrap_or_default()
-}
+    fn ffmpeg(&self) -> OsString {
+        ffmpeg_program(self.ffmpeg_path.as_deref())
+    }
 
-async fn process_audio_source(
-    options: Arc<ScannerOptions>,
-    source: AudioSource,
-    logger: &mut ScanLogger,
-) {
-    let mut handled = false;
+    fn ffprobe(&self) -> OsString {
+        ffprobe_program(self.ffprobe_path.as_deref())
+    }
 
-    if let Some(vad_cfg) = options.vad.clone() {
-        match process_with_vad(
-            &options.api_key,
-            &options.api_url,
-            &options.model_name,
-            &source,
-            &vad_cfg,
-            logger,
-        )
-        .await
-        {
-            Ok(_) => handled = true,
-            Err(err) => {
-                logger.info(format!(
-                    "VAD 分段失败（{}），回退整段上传：{}",
-                    err,
-                    source.display_name()
-                ));
-            }
-        }
+    fn concurrency(&self) -> usize {
+        self.max_concurrency.max(1)
     }
 
-    if !handled {
-        process_without_vad(
-            &options.api_key,
-            &options.api_url,
-            &options.model_name,
-            &source,
-            logger,
-        )
-        .await;
+    /// 同时进行中的音轨数量：单独限制 FFmpeg 并发时，在上传名额之外再放行
+    /// `ffmpeg_concurrency` 个音轨，使转码可以赶在上传之前进行。
+    fn track_concurrency(&self) -> usize {
+        self.concurrency() + self.ffmpeg_concurrency
     }
-}
 
-async fn process_without_vad(
-    api_key: &str,
-    api_url: &str,
-    model_name: &str,
-    source: &AudioSource,
-    logger: &mut ScanLogger,
-) {
-    let target_name = source.display_name();
-    let materialized = match source.materialize_full_audio().await {
-        Ok(audio) => audio,
-        Err(err) => {
-            logger.error(format!("准备 {} 音频失败：{}", target_name, err));
-            return;
+    /// 按配置创建尚未创建的限速器与并发信号量，已有的保持共享。
+    fn ensure_limiters(&mut self) {
+        if self.rate_limiter.is_none() {
+            self.rate_limiter = self
+                .requests_per_minute
+                .filter(|limit| *limit > 0)
+                .map(|limit| Arc::new(RateLimiter::new(limit)));
         }
-    };
+        if self.ffmpeg_concurrency > 0 && self.ffmpeg_limiter.is_none() {
+            self.ffmpeg_limiter = Some(Arc::new(Semaphore::new(self.ffmpeg_concurrency)));
+            self.upload_limiter = Some(Arc::new(Semaphore::new(self.concurrency())));
+        }
+    }
 
-    logger.info(format!(
-        "开始转写 {}，音频源 {:?}",
-        target_name, materialized.path
-    ));
+    /// 上传请求的总时长上限，`0` 与 FFmpeg 超时一样表示不限制。
+    fn request_timeout(&self) -> Option<Duration> {
+        (self.request_timeout_secs > 0).then(|| Duration::from_secs(self.request_timeout_secs))
+    }
 
-    match transcribe_file(api_key, api_url, model_name, &materialized.path).await {
-        Ok(text) => {
-            let trimmed = text.trim();
-            if trimmed.is_empty() {
-                logger.error(format!("{} 的识别结果为空，跳过写入。", target_name));
-                let _ = cleanup_materialized(materialized).await;
-                return;
-            }
+    fn tool_timeout(&self) -> Option<Duration> {
+        (self.tool_timeout_secs > 0).then(|| Duration::from_secs(self.tool_timeout_secs))
+    }
+}
 
-            let duration = match media_duration(&materialized.path).await {
-                Ok(value) => value.max(0.5),
-                Err(e) => {
-                    logger.info(format!(
-                        "无法获取 {:?} 的时长（{}），使用估算值。",
-                        materialized.path, e
-                    ));
-                    estimate_duration_from_text(trimmed)
-                }
-            };
+/// 转写请求的全局限速器：相邻两次请求至少间隔 `60 / 每分钟上限` 秒，
+/// 保证任意一分钟内的请求数不超过上限。
+#[derive(Debug)]
+pub struct RateLimiter {
+    requests_per_minute: u32,
+    interval: Duration,
+    next_slot: Mutex<Option<Instant>>,
+}
 
-            let srt_content = build_srt_entry(1, 0.0, duration, trimmed);
-            let srt_path = transcript_result_path(source.original_path(), source.track_index());
-            match fs::write(&srt_path, srt_content).await {
-                Ok(_) => logger.success(format!("完成 {}，结果输出 {:?}", target_name, srt_path)),
-                Err(e) => logger.error(format!("写入 {} 失败：{}", target_name, e)),
-            }
+impl RateLimiter {
+    pub fn new(requests_per_minute: u32) -> Self {
+        let requests_per_minute = requests_per_minute.max(1);
+        Self {
+            requests_per_minute,
+            interval: Duration::from_secs(60) / requests_per_minute,
+            next_slot: Mutex::new(None),
         }
-        Err(e) => logger.error(format!("调用 API 转写 {} 失败：{}", target_name, e)),
     }
 
-    if let Err(err) = cleanup_materialized(materialized).await {
-        logger.info(format!("清理临时音轨失败：{}", err));
+    /// 预约下一个可用的发送时刻，返回距 `now` 还需等待的时长。
+    fn reserve(&self, now: Instant) -> Duration {
+        let mut next_slot = self.next_slot.lock().unwrap_or_else(|e| e.into_inner());
+        let slot = next_slot.map_or(now, |next| next.max(now));
+        *next_slot = Some(slot + self.interval);
+        slot - now
     }
 }
 
-async fn process_with_vad(
-    api_key: &str,
-    api_url: &str,
-    model_name: &str,
-    source: &AudioSource,
-    vad_cfg: &VadConfig,
+/// 发送转写请求前等待限速器放行，需要等待时记录一条日志；等待期间可被取消。
+async fn throttle_request(
+    options: &ScannerOptions,
+    subject: &str,
     logger: &mut ScanLogger,
 ) -> Result<()> {
-    let display_name = source.display_name();
-    logger.info(format!("{} 启用 VAD，准备语音分段。", display_name));
+    let Some(limiter) = &options.rate_limiter else {
+        return Ok(());
+    };
+    let wait = limiter.reserve(Instant::now());
+    if wait.is_zero() {
+        return Ok(());
+    }
+    logger.info(tr!(
+        "已达到每分钟 {} 次请求的上限，{} 将在 {:.1} 秒后上传。",
+        limiter.requests_per_minute,
+        subject,
+        wait.as_secs_f64()
+    ));
+    cancellable(&options.cancel, async {
+        tokio::time::sleep(wait).await;
+        Ok(())
+    })
+    .await
+}
 
-    let pcm_path = source.convert_to_pcm16().await?;
-    let samples = read_wav_samples(&pcm_path).await?;
-    let _ = fs::remove_file(&pcm_path).await;
-    let total_duration = samples.len() as f64 / VAD_SAMPLE_RATE as f64;
+/// 从可选的共享信号量取得许可，未设置时直接放行。
+async fn acquire_slot(limiter: &Option<Arc<Semaphore>>) -> Option<OwnedSemaphorePermit> {
+    limiter.clone()?.acquire_owned().await.ok()
+}
 
-    let speech_segments = detect_speech_segments(&samples, vad_cfg)?;
-    if speech_segments.is_empty() {
-        return Err(anyhow!("未检测到有效语音"));
+/// 扫描的暂停开关，克隆后共享同一状态。
+#[derive(Clone, Default)]
+pub struct PauseToken {
+    paused: Arc<AtomicBool>,
+    resumed: Arc<Notify>,
+}
+
+impl PauseToken {
+    pub fn pause(&self) {
+        self.paused.store(true, Ordering::SeqCst);
     }
 
-    let segments = expand_segments_with_gaps(&speech_segments, total_duration);
-    let extra_gaps = segments
-        .iter()
-        .filter(|seg| seg.kind == SegmentKind::Gap)
-        .count();
-    if extra_gaps > 0 {
-        logger.info(format!(
-            "检测到 {} 段语音，额外包含 {} 个静音覆盖区。",
-            speech_segments.len(),
-            extra_gaps
-        ));
-    } else {
-        logger.info(format!(
-            "检测到 {} 段语音，逐段上传。",
-            speech_segments.len()
-        ));
+    pub fn resume(&self) {
+        self.paused.store(false, Ordering::SeqCst);
+        self.resumed.notify_waiters();
     }
 
-    let mut entries: Vec<String> = Vec::new();
-    for (idx, segment) in segments.iter().enumerate() {
-        let segment_audio = source.export_segment_audio(idx + 1, segment).await?;
-        match transcribe_file(api_key, api_url, model_name, &segment_audio).await {
-            Ok(text) => {
-                let trimmed = text.trim();
-                if trimmed.is_empty() {
-                    logger.info(format!("分段 {} 结果为空，已跳过。", idx + 1));
-                    let _ = fs::remove_file(&segment_audio).await;
-                    continue;
-                }
-                let label = match segment.kind {
-                    SegmentKind::Speech => "语音",
-                    SegmentKind::Gap => "补间",
-                };
-                logger.success(format!(
-                    "分段 {} [{}] 完成（{} - {}）。",
-                    idx + 1,
-                    label,
-                    format_timestamp(segment.start_sec),
-                    format_timestamp(segment.end_sec)
-                ));
-                entries.push(build_srt_entry(
-                    entries.len() + 1,
-                    segment.start_sec,
-                    segment.end_sec,
-                    trimmed,
-                ));
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::SeqCst)
+    }
+
+    /// 暂停时等待恢复；等待期间取消扫描会返回取消错误。
+    async fn wait_if_paused(&self, cancel: &CancellationToken) -> Result<()> {
+        loop {
+            // 先登记等待再检查状态，避免检查与等待之间的恢复通知丢失。
+            let resumed = self.resumed.notified();
+            if !self.is_paused() {
+                return Ok(());
             }
-            Err(e) => {
-                logger.error(format!("分段 {} 调用 API 失败：{}", idx + 1, e));
+            tokio::select! {
+                _ = cancel.cancelled() => return Err(ScanCancelled.into()),
+                _ = resumed => {}
             }
         }
-        let _ = fs::remove_file(&segment_audio).await;
     }
+}
 
-    if entries.is_empty() {
-        return Err(anyhow!("所有分段均转写失败"));
+/// 外部工具执行超时，子进程已被终止。
+#[derive(Debug)]
+struct ToolTimeout(PathBuf);
+
+impl std::fmt::Display for ToolTimeout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            tr!("ffprobe/ffmpeg 超时，已跳过 {}", display_path(&self.0))
+        )
     }
+}
 
-    let srt_path = transcript_result_path(source.original_path(), source.track_index());
-    let srt_content: String = entries.concat();
-    fs::write(&srt_path, srt_content).await?;
-    logger.success(format!(
-        "{} VAD 分段完成，结果输出 {:?}",
-        display_name, srt_path
-    ));
-    Ok(())
+impl std::error::Error for ToolTimeout {}
+
+fn is_tool_timeout(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<ToolTimeout>().is_some()
 }
 
-async fn read_wav_samples(path: &Path) -> Result<Vec<i16>> {
-    let path = path.to_path_buf();
-    task::spawn_blocking(move || {
-        let mut reader = hound::WavReader::open(&path)?;
-        let spec = reader.spec();
-        if spec.sample_rate != VAD_SAMPLE_RATE || spec.channels != 1 || spec.bits_per_sample != 16 {
-            return Err(anyhow!("生成的 WAV 格式不符合 VAD 要求"));
-        }
+/// 扫描被用户取消。
+#[derive(Debug)]
+struct ScanCancelled;
 
-        let mut samples = Vec::new();
-        for sample in reader.samples::<i16>() {
-            samples.push(sample?);
-        }
-        Ok::<_, anyhow::Error>(samples)
-    })
-    .await?
+impl std::fmt::Display for ScanCancelled {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(tr!("扫描已取消"))
+    }
 }
 
-#[derive(Clone, Debug)]
-struct SegmentState {
-    start_chunk: usize,
-    last_active_chunk: usize,
+impl std::error::Error for ScanCancelled {}
+
+fn is_cancelled(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<ScanCancelled>().is_some()
 }
 
-impl SegmentState {
-    fn new(start_chunk: usize) -> Self {
-        Self {
-            start_chunk,
-            last_active_chunk: start_chunk,
-        }
-    }
+/// API 拒绝了密钥（HTTP 401/403）；此时继续处理其余文件只会得到同样的错误。
+fn is_auth_failure(err: &anyhow::Error) -> bool {
+    matches!(
+        err.downcast_ref::<ApiError>(),
+        Some(ApiError::Unauthorized { .. })
+    )
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
-enum SegmentKind {
-    Speech,
-    Gap,
+/// 错误链中是否含有转写接口错误；媒体损坏、ffprobe 失败等本地错误返回 `false`。
+fn is_api_failure(err: &anyhow::Error) -> bool {
+    err.chain()
+        .any(|cause| cause.downcast_ref::<ApiError>().is_some())
 }
 
-#[derive(Clone, Debug)]
-struct SpeechSegment {
-    start_sec: f64,
-    end_sec: f64,
-    kind: SegmentKind,
+/// 统计连续调用转写接口失败的音轨数，判断是否应中止本次扫描；本地错误不计入也不打断连续计数。
+struct FailureStreak {
+    consecutive: usize,
+    limit: usize,
 }
 
-impl SpeechSegment {
-    fn new(start_sec: f64, end_sec: f64, kind: SegmentKind) -> Self {
+impl FailureStreak {
+    fn new(limit: usize) -> Self {
         Self {
-            start_sec,
-            end_sec,
-            kind,
+            consecutive: 0,
+            limit,
         }
     }
 
-    fn from_chunks(start_chunk: usize, end_chunk: usize) -> Self {
-        Self::new(
-            chunk_to_time(start_chunk),
-            chunk_to_time(end_chunk),
-            SegmentKind::Speech,
-        )
-    }
-
-    fn try_new(start_sec: f64, end_sec: f64, kind: SegmentKind) -> Option<Self> {
-        if end_sec - start_sec <= MIN_SEGMENT_EPS {
-            None
+    /// 记录一个音轨的结果；需要中止时返回写入日志的原因。
+    fn record(&mut self, failure: Option<&anyhow::Error>) -> Option<String> {
+        let Some(err) = failure else {
+            self.consecutive = 0;
+            return None;
+        };
+        if !is_api_failure(err) {
+            return None;
+        }
+        self.consecutive += 1;
+        if is_auth_failure(err) {
+            Some(tr!("认证失败，已中止本次扫描，请检查 API 密钥后重试。").to_string())
+        } else if self.limit > 0 && self.consecutive >= self.limit {
+            Some(tr!(
+                "连续 {} 个音轨转写失败，已中止本次扫描，请检查网络或 API 服务。",
+                self.consecutive
+            ))
         } else {
-            Some(Self::new(start_sec, end_sec, kind))
+            None
         }
     }
 }
 
-fn chunk_to_time(chunk: usize) -> f64 {
-    (chunk as f64 * VAD_CHUNK_SIZE as f64) / VAD_SAMPLE_RATE as f64
+/// 包装转写接口错误，保留 [`ApiError`] 以便调用方按类别处理，日志文本仍为 `message`。
+fn api_failure(err: ApiError, message: String) -> anyhow::Error {
+    anyhow::Error::new(err).context(message)
 }
 
-fn secs_to_chunks(secs: f32) -> usize {
-    let raw = ((secs * VAD_SAMPLE_RATE as f32) / VAD_CHUNK_SIZE as f32).ceil() as usize;
-    raw.max(VAD_MIN_SPEECH_CHUNKS)
+/// 执行外部工具并收集输出；超时后丢弃子进程句柄，借助 `kill_on_drop` 终止并回收进程。
+async fn run_tool(
+    mut cmd: Command,
+    timeout: Option<Duration>,
+    subject: &Path,
+) -> Result<std::process::Output> {
+    cmd.kill_on_drop(true);
+    let output = match timeout {
+        Some(limit) => tokio::time::timeout(limit, cmd.output())
+            .await
+            .map_err(|_| ToolTimeout(subject.to_path_buf()))??,
+        None => cmd.output().await?,
+    };
+    Ok(output)
 }
 
-fn detect_speech_segments(samples: &[i16], cfg: &VadConfig) -> Result<Vec<SpeechSegment>> {
-    let mut vad = VoiceActivityDetector::builder()
-        .sample_rate(VAD_SAMPLE_RATE)
-        .chunk_size(VAD_CHUNK_SIZE)
-        .build()
-        .context("语音活动检测器初始化失败")?;
-
-    let mut segments = Vec::new();
-    let mut current: Option<SegmentState> = None;
-    let mut trailing_silence = 0usize;
-
-    let mut chunk_index = 0usize;
-    let mut sample_index = 0usize;
-    while sample_index < samples.len() {
-        let end = usize::min(sample_index + VAD_CHUNK_SIZE, samples.len());
-        let mut chunk = vec![0i16; VAD_CHUNK_SIZE];
-        chunk[..(end - sample_index)].copy_from_slice(&samples[sample_index..end]);
+/// 在取消令牌触发时立即放弃 `fut`；被丢弃的子进程（`kill_on_drop`）与 HTTP 请求随之中止。
+async fn cancellable<T>(
+    cancel: &CancellationToken,
+    fut: impl Future<Output = Result<T>>,
+) -> Result<T> {
+    tokio::select! {
+        biased;
+        _ = cancel.cancelled() => Err(ScanCancelled.into()),
+        result = fut => result,
+    }
+}
 
-        let probability = vad.predict(chunk);
-        if probability >= cfg.threshold {
-            match &mut current {
-                Some(state) => state.last_active_chunk = chunk_index,
-                None => current = Some(SegmentState::new(chunk_index)),
-            }
-            trailing_silence = 0;
-        } else if let Some(state) = &mut current {
-            trailing_silence += 1;
-            if trailing_silence > cfg.padding_chunks {
-                finalize_segment(state, cfg, &mut segments);
-                current = None;
-                trailing_silence = 0;
-            }
+/// 运行输出到 `output` 的 FFmpeg 命令，支持超时与取消；失败或取消时删除写了一半的输出文件。
+///
+/// 退出状态非零时返回 [`tool_failure`] 构造的错误，附带 FFmpeg 的错误输出。
+async fn run_tool_to_output(
+    cmd: Command,
+    options: &ScannerOptions,
+    subject: &Path,
+    output: &Path,
+    failure: &str,
+) -> Result<()> {
+    // 等待转码名额的时间不计入超时。
+    let result = cancellable(&options.cancel, async {
+        let _slot = acquire_slot(&options.ffmpeg_limiter).await;
+        run_tool(cmd, options.tool_timeout(), subject).await
+    })
+    .await;
+    match result {
+        Ok(out) if out.status.success() => Ok(()),
+        Ok(out) => {
+            let _ = fs::remove_file(output).await;
+            Err(tool_failure(failure, &out))
+        }
+        Err(err) => {
+            let _ = fs::remove_file(output).await;
+            Err(err)
         }
-
-        sample_index = end;
-        chunk_index += 1;
     }
+}
 
-    if let Some(state) = current {
-        finalize_segment(&state, cfg, &mut segments);
+/// 错误信息中附带的 FFmpeg 错误输出行数。
+const TOOL_STDERR_TAIL_LINES: usize = 8;
+
+/// 构造外部工具失败的错误：`message`、退出状态，以及 stderr 的最后几行。
+fn tool_failure(message: &str, output: &std::process::Output) -> anyhow::Error {
+    match stderr_tail(&output.stderr, TOOL_STDERR_TAIL_LINES) {
+        Some(tail) => anyhow!(tr!("{}，退出状态：{}\n{}", message, output.status, tail)),
+        None => anyhow!(tr!("{}，退出状态：{}", message, output.status)),
     }
+}
 
-    Ok(segments)
+/// 取 stderr 中最后 `max_lines` 个非空行，没有输出时返回 `None`。
+fn stderr_tail(stderr: &[u8], max_lines: usize) -> Option<String> {
+    let stderr = String::from_utf8_lossy(stderr);
+    let lines: Vec<&str> = stderr
+        .lines()
+        .map(str::trim_end)
+        .filter(|line| !line.trim().is_empty())
+        .collect();
+    let tail = &lines[lines.len().saturating_sub(max_lines)..];
+    (!tail.is_empty()).then(|| tail.join("\n"))
 }
 
-fn finalize_segment(state: &SegmentState, cfg: &VadConfig, segments: &mut Vec<SpeechSegment>) {
-    let duration_chunks = state.last_active_chunk.saturating_sub(state.start_chunk) + 1;
-    if duration_chunks >= cfg.min_speech_chunks {
-        segments.push(SpeechSegment::from_chunks(
-            state.start_chunk,
-            state.last_active_chunk + 1,
-        ));
+/// VAD 模式下语音段之间静音区（补间）的处理方式。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GapPolicy {
+    /// 不上传静音区，字幕在语音段之间留空。
+    #[default]
+    Skip,
+    /// 与语音段一样上传转写（旧版行为），可能补回 VAD 漏检的轻声内容。
+    Transcribe,
+    /// 不上传，为每个静音区写一条覆盖该时段的空字幕。
+    MarkEmpty,
+}
+
+impl GapPolicy {
+    pub const ALL: [GapPolicy; 3] = [GapPolicy::Skip, GapPolicy::Transcribe, GapPolicy::MarkEmpty];
+}
+
+impl std::fmt::Display for GapPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GapPolicy::Skip => f.write_str(tr!("跳过")),
+            GapPolicy::Transcribe => f.write_str(tr!("照常转写")),
+            GapPolicy::MarkEmpty => f.write_str(tr!("写入空字幕")),
+        }
     }
 }
 
-fn expand_segments_with_gaps(
-    speech_segments: &[SpeechSegment],
-    total_duration: f64,
-) -> Vec<SpeechSegment> {
-    if speech_segments.is_empty() {
-        return Vec::new();
+/// 默认的固定分段间隔（秒）。
+pub const DEFAULT_SEGMENT_INTERVAL_SECS: f64 = 30.0;
+/// 固定分段间隔的允许范围（秒）。
+pub const SEGMENT_INTERVAL_RANGE: RangeInclusive<f64> = 5.0..=600.0;
+/// 固定分段末尾短于该秒数的余量并入前一段，避免上传几乎无内容的片段。
+const MIN_TRAILING_SEGMENT_SECS: f64 = 1.0;
+
+/// 长音频的切分方式。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SegmentMode {
+    /// 整段上传，只生成一条字幕或按文本估算切分。
+    Whole,
+    /// 按固定秒数切分后逐段上传，不依赖 VAD 模型。
+    FixedInterval(f64),
+    /// 用 Silero VAD 检测语音段后逐段上传。
+    #[default]
+    Vad,
+}
+
+impl SegmentMode {
+    /// 供界面选择的全部模式，固定分段使用 `interval_secs` 作为间隔。
+    pub fn choices(interval_secs: f64) -> [SegmentMode; 3] {
+        [
+            SegmentMode::Whole,
+            SegmentMode::FixedInterval(interval_secs),
+            SegmentMode::Vad,
+        ]
     }
+}
 
-    let mut sorted = speech_segments.to_vec();
-    sorted.sort_by(|a, b| {
-        a.start_sec
-            .partial_cmp(&b.start_sec)
-            .unwrap_or(std::cmp::Ordering::Less)
-    });
+impl std::fmt::Display for SegmentMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SegmentMode::Whole => f.write_str(tr!("整段上传")),
+            SegmentMode::FixedInterval(_) => f.write_str(tr!("固定间隔分段")),
+            SegmentMode::Vad => f.write_str(tr!("VAD 语音分段")),
+        }
+    }
+}
 
-    let mut expanded = Vec::new();
-    let mut cursor = 0.0f64;
+#[derive(Clone)]
+pub struct VadConfig {
+    pub threshold: f32,
+    /// 短于该秒数的语音段会被丢弃。
+    pub min_speech_secs: f32,
+    pub padding_chunks: usize,
+    /// VAD 分析使用的采样率（Hz），须与 `chunk_size` 组成检测器支持的组合。
+    pub sample_rate: u32,
+    /// 每次送入检测器的采样点数，决定语音段边界的时间精度。
+    pub chunk_size: usize,
+    /// 是否按每个文件测得的电平在 `threshold` 附近自动微调阈值。
+    pub adaptive_threshold: bool,
+    /// 相邻语音段间隔小于该秒数时合并为一段上传，`0` 表示不合并。
+    pub merge_gap_secs: f32,
+    /// 单个语音段的最长秒数，超出时在段内语音概率最低处切开，`0` 表示不限制。
+    pub max_segment_secs: f32,
+    /// 语音段开头向前延伸的秒数，只影响分段起止与字幕时间，不影响检测，因此不计入 VAD 缓存键。
+    pub lead_padding_secs: f32,
+    /// 语音段结尾向后延伸的秒数，同样不计入 VAD 缓存键。
+    pub tail_padding_secs: f32,
+    /// 与阈值比较前对语音概率做滑动平均的窗口（分块数），`1` 表示不平滑。
+    pub smoothing_window: usize,
+    /// 语音段之间静音区的处理方式，不影响检测结果，因此不计入 VAD 缓存键。
+    pub gap_policy: GapPolicy,
+}
 
-    for segment in sorted {
-        if let Some(gap) = SpeechSegment::try_new(cursor, segment.start_sec, SegmentKind::Gap) {
-            expanded.push(gap);
+impl Default for VadConfig {
+    fn default() -> Self {
+        Self {
+            threshold: VAD_DEFAULT_THRESHOLD,
+            min_speech_secs: VAD_DEFAULT_MIN_SEGMENT_SECS,
+            padding_chunks: VAD_PADDING_CHUNKS,
+            sample_rate: VAD_DEFAULT_SAMPLE_RATE,
+            chunk_size: VAD_DEFAULT_CHUNK_SIZE,
+            adaptive_threshold: false,
+            merge_gap_secs: VAD_DEFAULT_MERGE_GAP_SECS,
+            max_segment_secs: VAD_DEFAULT_MAX_SEGMENT_SECS,
+            lead_padding_secs: VAD_DEFAULT_LEAD_PADDING_SECS,
+            tail_padding_secs: VAD_DEFAULT_TAIL_PADDING_SECS,
+            smoothing_window: 1,
+            gap_policy: GapPolicy::Skip,
         }
-        let end = segment.end_sec;
-        expanded.push(segment);
-        cursor = end;
     }
+}
 
-    if let Some(tail) = SpeechSegment::try_new(cursor, total_duration, SegmentKind::Gap) {
-        expanded.push(tail);
+impl VadConfig {
+    pub fn from_user_settings(threshold: f32, min_segment_secs: f32) -> Self {
+        let threshold = threshold.clamp(*VAD_THRESHOLD_RANGE.start(), *VAD_THRESHOLD_RANGE.end());
+        let min_secs =
+            min_segment_secs.clamp(*VAD_MIN_SEGMENT_RANGE.start(), *VAD_MIN_SEGMENT_RANGE.end());
+        Self {
+            threshold,
+            min_speech_secs: min_secs,
+            ..Self::default()
+        }
     }
 
-    expanded
+    fn chunk_to_time(&self, chunk: usize) -> f64 {
+        (chunk as f64 * self.chunk_size as f64) / self.sample_rate as f64
+    }
+
+    fn secs_to_chunks(&self, secs: f32) -> usize {
+        let raw = ((secs * self.sample_rate as f32) / self.chunk_size as f32).ceil() as usize;
+        raw.max(VAD_MIN_SPEECH_CHUNKS)
+    }
+
+    /// 秒数换算为分块数（不设下限），`0` 或负数返回 0。
+    fn secs_to_chunks_exact(&self, secs: f32) -> usize {
+        ((secs.max(0.0) * self.sample_rate as f32) / self.chunk_size as f32).floor() as usize
+    }
+
+    fn time_to_chunk(&self, seconds: f64) -> usize {
+        (seconds * self.sample_rate as f64 / self.chunk_size as f64).round() as usize
+    }
 }
 
-fn format_timestamp(seconds: f64) -> String {
-    let total_ms = (seconds * 1000.0).round().max(0.0) as u64;
-    let hours = total_ms / 3_600_000;
-    let minutes = (total_ms % 3_600_000) / 60_000;
-    let secs = (total_ms % 60_000) / 1000;
-    let millis = total_ms % 1000;
-    if hours > 0 {
-        format!("{:02}:{:02}:{:02}.{:03}", hours, minutes, secs, millis)
-    } else {
-        format!("{:02}:{:02}.{:03}", minutes, secs, millis)
+/// 扫描日志收集器；克隆后共享同一份日志，供并发任务同时写入。
+#[derive(Clone)]
+struct ScanLogger {
+    logs: Arc<Mutex<Vec<ScanLog>>>,
+    progress: Option<UnboundedSender<ScanEvent>>,
+    log_file: Option<LogWriter>,
+}
+
+impl ScanLogger {
+    fn new(progress: Option<UnboundedSender<ScanEvent>>) -> Self {
+        Self {
+            logs: Arc::new(Mutex::new(Vec::new())),
+            progress,
+            log_file: None,
+        }
+    }
+
+    /// 同时把日志追加到 `dir` 下按日期命名的日志文件；写入失败作为错误日志报告，但不再写入文件。
+    fn with_log_dir(mut self, dir: Option<PathBuf>) -> Self {
+        self.log_file = dir.map(|dir| {
+            let mut reporter = ScanLogger {
+                log_file: None,
+                ..self.clone()
+            };
+            LogWriter::spawn(dir, move |message| reporter.error(message))
+        });
+        self
+    }
+
+    fn emit(&mut self, log: ScanLog) {
+        self.send(ScanEvent::Log(log.clone()));
+        if let Some(writer) = &self.log_file {
+            writer.write(&log);
+        }
+        self.logs
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .push(log);
+    }
+
+    fn info(&mut self, message: impl Into<String>) {
+        self.emit(ScanLog::new(ScanLogLevel::Info, message));
+    }
+
+    fn success(&mut self, message: impl Into<String>) {
+        self.emit(ScanLog::new(ScanLogLevel::Success, message));
+    }
+
+    fn error(&mut self, message: impl Into<String>) {
+        self.emit(ScanLog::new(ScanLogLevel::Error, message));
+    }
+
+    /// 上报整体进度，仅发送到 GUI，不记入日志。
+    fn progress(&self, completed: usize, total: usize, secs_per_target: Option<f64>) {
+        self.send(ScanEvent::Progress {
+            completed,
+            total,
+            secs_per_target,
+        });
+    }
+
+    /// 上报单个源文件的状态变化，仅发送到 GUI，不记入日志。
+    fn file_state(&self, path: &Path, state: FileState, tracks: usize) {
+        self.send(ScanEvent::File {
+            path: path.to_path_buf(),
+            state,
+            tracks,
+        });
+    }
+
+    /// 上报 VAD 分段进度，仅发送到 GUI，不记入日志。
+    fn segment_progress(&self, path: &Path, done: usize, total: usize) {
+        self.send(ScanEvent::Segment {
+            path: path.to_path_buf(),
+            done,
+            total,
+        });
+    }
+
+    /// 上报一条音轨的转写结果，仅发送到 GUI；失败原因另由调用方记入日志。
+    fn track_finished(&self, path: &Path, result: Result<&Path, &str>) {
+        let path = path.to_path_buf();
+        self.send(match result {
+            Ok(output) => ScanEvent::TrackDone {
+                path,
+                output: output.to_path_buf(),
+            },
+            Err(error) => ScanEvent::TrackFailed {
+                path,
+                error: error.to_string(),
+            },
+        });
+    }
+
+    fn send(&self, event: ScanEvent) {
+        if let Some(tx) = &self.progress {
+            let _ = tx.send(event);
+        }
+    }
+
+    /// 复制目前收集到的日志，不影响后续写入。
+    fn snapshot(&self) -> Vec<ScanLog> {
+        self.logs
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    /// 取走收集到的日志；写日志文件时先等待已发出的日志落盘，避免命令行模式退出时丢失末尾几行。
+    async fn finish(self) -> Vec<ScanLog> {
+        if let Some(writer) = &self.log_file {
+            writer.flush().await;
+        }
+        std::mem::take(
+            &mut *self
+                .logs
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner()),
+        )
     }
 }
 
-fn format_srt_timestamp(seconds: f64) -> String {
-    let total_ms = (seconds * 1000.0).round().max(0.0) as u64;
-    let hours = total_ms / 3_600_000;
-    let minutes = (total_ms % 3_600_000) / 60_000;
-    let secs = (total_ms % 60_000) / 1000;
-    let millis = total_ms % 1000;
-    format!("{:02}:{:02}:{:02},{:03}", hours, minutes, secs, millis)
+enum PendingJob {
+    Audio {
+        path: PathBuf,
+        append: Option<AppendState>,
+    },
+    Video {
+        path: PathBuf,
+        tracks: Vec<PendingTrack>,
+    },
 }
 
-fn sanitize_srt_text(input: &str) -> String {
-    input.replace("\r\n", "\n").trim().to_string()
+impl PendingJob {
+    fn path(&self) -> &Path {
+        match self {
+            PendingJob::Audio { path, .. } | PendingJob::Video { path, .. } => path,
+        }
+    }
+
+    /// 该任务包含的待转写音轨数。
+    fn target_count(&self) -> usize {
+        match self {
+            PendingJob::Audio { .. } => 1,
+            PendingJob::Video { tracks, .. } => tracks.len(),
+        }
+    }
+
+    /// 展开为逐个音轨的音频源。
+    fn into_sources(self) -> Vec<AudioSource> {
+        match self {
+            PendingJob::Audio { path, append } => {
+                vec![AudioSource::from_audio_file(path).with_append(append)]
+            }
+            PendingJob::Video { path, tracks } => tracks
+                .into_iter()
+                .map(|track| {
+                    AudioSource::from_video_track(path.clone(), track.stream.index)
+                        .with_track_label(track.label)
+                        .with_append(track.append)
+                })
+                .collect(),
+        }
+    }
 }
 
-fn build_srt_entry(index: usize, start: f64, end: f64, text: &str) -> String {
-    let safe_end = if end <= start { start + 0.5 } else { end };
-    format!(
-        "{idx}\n{start} --> {end}\n{body}\n\n",
-        idx = index,
-        start = format_srt_timestamp(start),
-        end = format_srt_timestamp(safe_end),
-        body = sanitize_srt_text(text)
-    )
+/// 视频中一条待转写的音轨。
+struct PendingTrack {
+    stream: AudioStream,
+    /// 结果文件名中的音轨部分，如 `轨道2` 或 `jpn`。
+    label: String,
+    append: Option<AppendState>,
 }
 
-fn estimate_duration_from_text(text: &str) -> f64 {
-    let chars = text.chars().count() as f64;
-    (chars / 15.0).max(5.0)
+/// 按源文件汇总各音轨的完成情况，视频的全部音轨结束后才给出最终状态。
+#[derive(Default)]
+struct FileTracker {
+    /// 源文件 -> (音轨总数, 尚未结束的音轨数, 是否有音轨失败)。
+    files: HashMap<PathBuf, (usize, usize, bool)>,
+    /// 已计入运行报告成功列表的源文件。
+    succeeded: HashSet<PathBuf>,
 }
 
-async fn audio_stream_indices(path: &Path) -> Result<Vec<u32>> {
-    let output = Command::new(ffprobe_program())
-        .arg("-v")
-        .arg("error")
-        .arg("-select_streams")
-        .arg("a")
-        .arg("-show_entries")
-        .arg("stream=index")
-        .arg("-of")
-        .arg("csv=p=0")
-        .arg(path)
-        .output()
-        .await?;
+impl FileTracker {
+    fn add(&mut self, path: PathBuf, tracks: usize) {
+        self.files.insert(path, (tracks, tracks, false));
+    }
 
-    if !output.status.success() {
-        return Err(anyhow!("ffprobe 解析音轨失败，退出状态：{}", output.status));
+    fn tracks(&self, path: &Path) -> usize {
+        self.files
+            .get(path)
+            .map(|(tracks, ..)| *tracks)
+            .unwrap_or(1)
     }
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let indices = stdout
-        .lines()
-        .filter_map(|line| line.trim().parse::<u32>().ok())
-        .collect();
+    /// 记录一条音轨转写成功；仅在该文件首次有音轨成功时返回 `true`，
+    /// 多音轨视频在运行报告中只计为一个成功文件。
+    fn first_success(&mut self, path: &Path) -> bool {
+        self.succeeded.insert(path.to_path_buf())
+    }
 
-    Ok(indices)
+    /// 记录一条音轨结束；该文件全部音轨结束时返回最终状态。
+    fn finish_track(&mut self, path: &Path, succeeded: bool) -> Option<FileState> {
+        let (_, remaining, failed) = self.files.get_mut(path)?;
+        *remaining = remaining.saturating_sub(1);
+        *failed |= !succeeded;
+        if *remaining > 0 {
+            return None;
+        }
+        Some(if *failed {
+            FileState::Failed
+        } else {
+            FileState::Done
+        })
+    }
 }
 
-async fn media_duration(path: &Path) -> Result<f64> {
-    let output = Command::new(ffprobe_program())
-        .arg("-v")
-        .arg("error")
-        .arg("-show_entries")
-        .arg("format=duration")
-        .arg("-of")
-        .arg("default=noprint_wrappers=1:nokey=1")
-        .arg(path)
-        .output()
-        .await?;
+/// 增长中文件的增量转写状态：只处理 `offset` 之后新增的音频，并续接已有字幕编号。
+#[derive(Clone, Copy, Debug)]
+struct AppendState {
+    offset: f64,
+    existing_cues: usize,
+}
 
-    if !output.status.success() {
-        return Err(anyhow!(
-            "ffprobe 读取 {:?} 时长失败，退出状态：{}",
-            path,
-            output.status
-        ));
-    }
+/// 字幕旁的状态文件内容，记录上次转写覆盖到的媒体时长。
+#[derive(Serialize, Deserialize, Debug)]
+struct TranscriptState {
+    duration_secs: f64,
+}
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    stdout
-        .lines()
-        .find_map(|line| line.trim().parse::<f64>().ok())
-        .ok_or_else(|| anyhow!("无法解析 {:?} 的时长", path))
+struct MaterializedAudio {
+    path: PathBuf,
+    cleanup: bool,
 }
 
-fn track_suffix(track_index: Option<u32>, segment_index: Option<usize>) -> String {
-    match (track_index, segment_index) {
-        (Some(track), Some(segment)) => format!("（音轨 {} · 片段 {}）", track, segment),
-        (Some(track), None) => format!("（音轨 {}）", track),
-        (None, Some(segment)) => format!("（片段 {}）", segment),
-        (None, None) => String::new(),
-    }
+#[derive(Clone)]
+struct AudioSource {
+    original_path: PathBuf,
+    track_index: Option<u32>,
+    /// 结果文件名中的音轨部分，直接音频为 `None`。
+    track_label: Option<String>,
+    kind: AudioSourceKind,
+    append: Option<AppendState>,
+    /// 源音轨的声道数，由 [`AudioSource::probe_channels`] 填充，未知时为 `None`。
+    channels: Option<u32>,
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+#[derive(Clone)]
+enum AudioSourceKind {
+    DirectAudio {
+        audio_path: PathBuf,
+    },
+    VideoTrack {
+        video_path: PathBuf,
+        track_index: u32,
+    },
+}
 
-    #[test]
-    fn media_extension_detection() {
-        for ext in ["mp3", "wav", "ogg", "mp4", "mkv"] {
-            assert!(is_media_extension(ext));
+impl AudioSource {
+    fn from_audio_file(path: PathBuf) -> Self {
+        Self {
+            original_path: path.clone(),
+            track_index: None,
+            track_label: None,
+            kind: AudioSourceKind::DirectAudio { audio_path: path },
+            append: None,
+            channels: None,
         }
+    }
 
-        for ext in ["txt", "rs", "json", "zip"] {
-            assert!(!is_media_extension(ext));
+    fn from_video_track(path: PathBuf, track_index: u32) -> Self {
+        Self {
+            original_path: path.clone(),
+            track_index: Some(track_index),
+            track_label: Some(numbered_track_label(track_index)),
+            kind: AudioSourceKind::VideoTrack {
+                video_path: path,
+                track_index,
+            },
+            append: None,
+            channels: None,
         }
     }
 
-    #[test]
-    fn video_detection() {
-        assert!(is_video(Path::new("C:/data/sample.MP4")));
-        assert!(!is_video(Path::new("C:/data/audio.mp3")));
-        assert!(!is_video(Path::new("C:/data/no_ext")));
+    fn with_append(mut self, append: Option<AppendState>) -> Self {
+        self.append = append;
+        self
     }
 
-    #[test]
-    fn transcript_path_preserves_original_name() {
-        let path = Path::new("C:/tmp/input/video.mp4");
-        let txt = transcript_result_path(path, None);
-        assert_eq!(txt, PathBuf::from("C:/tmp/input/video.srt"));
+    fn with_track_label(mut self, label: String) -> Self {
+        self.track_label = Some(label);
+        self
+    }
 
-        let track_txt = transcript_result_path(path, Some(2));
-        assert_eq!(track_txt, PathBuf::from("C:/tmp/input/video.轨道2.srt"));
+    /// 本次转写在原始媒体中的起点（秒），增量追加时为上次覆盖到的位置。
+    fn start_offset(&self) -> f64 {
+        self.append.map(|state| state.offset).unwrap_or(0.0)
+    }
 
-        let no_ext = Path::new("/tmp/audio");
-        let txt2 = transcript_result_path(no_ext, None);
-        assert_eq!(txt2, PathBuf::from("/tmp/audio.srt"));
+    /// 续写字幕时已有的条目数，新条目从其后编号。
+    fn existing_cues(&self) -> usize {
+        self.append.map(|state| state.existing_cues).unwrap_or(0)
     }
 
-    #[test]
-    fn audio_track_path_includes_track_id() {
-        let path = Path::new("/media/sample.mkv");
-        let mp3 = audio_track_path(path, 1);
-        assert_eq!(mp3, PathBuf::from("/media/sample.mkv-track1.mp3"));
+    fn original_path(&self) -> &Path {
+        &self.original_path
     }
 
-    #[test]
+    fn track_index(&self) -> Option<u32> {
+        self.track_index
+    }
+
+    fn track_label(&self) -> Option<&str> {
+        self.track_label.as_deref()
+    }
+
+    fn display_name(&self) -> String {
+        format!(
+            "{}{}",
+            display_path(&self.original_path),
+            track_suffix(self.track_index, None)
+        )
+    }
+
+    fn input_path(&self) -> &Path {
+        match &self.kind {
+            AudioSourceKind::DirectAudio { audio_path } => audio_path,
+            AudioSourceKind::VideoTrack { video_path, .. } => video_path,
+        }
+    }
+
+    /// 设置了混音预设时用 ffprobe 读取源音轨的声道数；声道不足或读取失败时记录日志，
+    /// 转码不应用预设、保持原声道。
+    async fn probe_channels(&mut self, options: &ScannerOptions, logger: &mut ScanLogger) {
+        if options.downmix_filter == DownmixFilter::Average {
+            return;
+        }
+        let stream = match self.track_index {
+            Some(track) if matches!(self.kind, AudioSourceKind::VideoTrack { .. }) => {
+                track.to_string()
+            }
+            _ => "a:0".to_string(),
+        };
+        let probed = probe_entries(
+            self.input_path(),
+            options,
+            &["-select_streams", &stream],
+            "stream=channels",
+        )
+        .await
+        .ok()
+        .and_then(|output| output.lines().next()?.trim().parse::<u32>().ok());
+        match probed {
+            Some(count) if count >= options.downmix_filter.min_channels() => {}
+            Some(count) => logger.info(tr!(
+                "{} 只有 {} 个声道，不应用混音预设“{}”。",
+                self.display_name(),
+                count,
+                options.downmix_filter
+            )),
+            None => logger.info(tr!(
+                "无法读取 {} 的声道数，不应用混音预设“{}”。",
+                self.display_name(),
+                options.downmix_filter
+            )),
+        }
+        self.channels = probed;
+    }
+
+    fn map_arg(&self) -> Option<String> {
+        match (&self.kind, self.track_index) {
+            (AudioSourceKind::VideoTrack { .. }, Some(track)) => Some(format!("0:{}", track)),
+            _ => None,
+        }
+    }
+
+    async fn materialize_full_audio(&self, options: &ScannerOptions) -> Result<MaterializedAudio> {
+        match &self.kind {
+            AudioSourceKind::DirectAudio { audio_path } => {
+                // 没有扩展名的文件无法确定上传格式，总是先转码。
+                if options.audio_filter(true, self.channels).is_none()
+                    && self.append.is_none()
+                    && audio_path.extension().is_some()
+                {
+                    return Ok(MaterializedAudio {
+                        path: audio_path.clone(),
+                        cleanup: false,
+                    });
+                }
+                let output = options.relocate(prepared_audio_path(
+                    audio_path,
+                    None,
+                    options.export_codec.extension(),
+                ));
+                if output.exists() {
+                    let _ = fs::remove_file(&output).await;
+                }
+                convert_for_upload(
+                    audio_path,
+                    None,
+                    self.start_offset(),
+                    self.channels,
+                    &output,
+                    options,
+                )
+                .await?;
+                Ok(MaterializedAudio {
+                    path: output,
+                    cleanup: true,
+                })
+            }
+            AudioSourceKind::VideoTrack {
+                video_path,
+                track_index,
+            } => {
+                let output = options.relocate(audio_track_path(
+                    video_path,
+                    *track_index,
+                    options.export_codec.extension(),
+                ));
+                if output.exists() {
+                    let _ = fs::remove_file(&output).await;
+                }
+                convert_for_upload(
+                    video_path,
+                    Some(*track_index),
+                    self.start_offset(),
+                    self.channels,
+                    &output,
+                    options,
+                )
+                .await?;
+                Ok(MaterializedAudio {
+                    path: output,
+                    cleanup: true,
+                })
+            }
+        }
+    }
+
+    /// 转换为 VAD 使用的单声道 16 位 PCM；`max_secs` 限制只转换开头的若干秒（用于预览）。
+    async fn convert_to_pcm16(
+        &self,
+        options: &ScannerOptions,
+        sample_rate: u32,
+        max_secs: Option<f64>,
+        output: PathBuf,
+    ) -> Result<PathBuf> {
+        if output.exists() {
+            let _ = fs::remove_file(&output).await;
+        }
+
+        let mut cmd = Command::new(options.ffmpeg());
+        if self.start_offset() > 0.0 {
+            cmd.arg("-ss").arg(format!("{:.3}", self.start_offset()));
+        }
+        cmd.arg("-i").arg(self.input_path());
+        if let Some(map) = self.map_arg() {
+            cmd.arg("-map").arg(map);
+        }
+        // VAD 在原始时间轴上分析，只应用混音与归一化，不加速。
+        if let Some(filter) = options.audio_filter(false, self.channels) {
+            cmd.arg("-filter:a").arg(filter);
+        }
+        if let Some(secs) = max_secs {
+            cmd.arg("-t").arg(format!("{:.3}", secs));
+        }
+        cmd.arg("-ac")
+            .arg("1")
+            .arg("-ar")
+            .arg(sample_rate.to_string())
+            .arg("-sample_fmt")
+            .arg("s16")
+            .arg("-y")
+            .arg(&output);
+
+        run_tool_to_output(
+            cmd,
+            options,
+            self.input_path(),
+            &output,
+            tr!("FFmpeg 转换音频用于 VAD 时失败"),
+        )
+        .await?;
+        Ok(output)
+    }
+
+    async fn export_segment_audio(
+        &self,
+        segment_idx: usize,
+        segment: &SpeechSegment,
+        options: &ScannerOptions,
+    ) -> Result<PathBuf> {
+        let output = options.relocate(segment_audio_path(
+            &self.original_path,
+            self.track_index,
+            segment_idx,
+            options.export_codec.extension(),
+        ));
+        if output.exists() {
+            let _ = fs::remove_file(&output).await;
+        }
+
+        // 字幕时间取自分段在原始时间轴上的起止，裁掉首尾静音只影响上传的音频。
+        self.run_segment_export(
+            &output,
+            segment,
+            options,
+            options.segment_audio_filter(segment.kind, self.channels),
+        )
+        .await?;
+        if options.trims_segment(segment.kind)
+            && !matches!(
+                media_duration(&output, options).await,
+                Ok(secs) if secs >= MIN_EXPORT_DURATION_SEC
+            )
+        {
+            // 整段都低于静音阈值时裁剪结果为空或过短，改为上传未裁剪的音频。
+            let filter = options.audio_filter(true, self.channels);
+            self.run_segment_export(&output, segment, options, filter)
+                .await?;
+        }
+        Ok(output)
+    }
+
+    async fn run_segment_export(
+        &self,
+        output: &Path,
+        segment: &SpeechSegment,
+        options: &ScannerOptions,
+        filter: Option<String>,
+    ) -> Result<()> {
+        let duration = (segment.end_sec - segment.start_sec).max(MIN_EXPORT_DURATION_SEC);
+        let mut cmd = Command::new(options.ffmpeg());
+        // `-t` 作为输入参数，保证加速后截取的仍是原始时间轴上的片段。
+        cmd.arg("-ss")
+            .arg(format!("{:.3}", self.start_offset() + segment.start_sec))
+            .arg("-t")
+            .arg(format!("{:.3}", duration))
+            .arg("-i")
+            .arg(self.input_path());
+        if let Some(map) = self.map_arg() {
+            cmd.arg("-map").arg(map);
+        }
+        if let Some(filter) = filter {
+            cmd.arg("-filter:a").arg(filter);
+        }
+        cmd.args(options.export_codec_args()).arg("-y").arg(output);
+
+        run_tool_to_output(
+            cmd,
+            options,
+            self.input_path(),
+            output,
+            tr!("FFmpeg 裁剪语音片段失败"),
+        )
+        .await
+    }
+}
+
+/// 删除为上传生成的临时音轨；保留中间文件时不做任何事。
+async fn cleanup_materialized(audio: MaterializedAudio, options: &ScannerOptions) -> Result<()> {
+    if audio.cleanup && !options.keep_intermediates {
+        fs::remove_file(&audio.path).await?;
+    }
+    Ok(())
+}
+
+/// 依次扫描多个根目录并合并结果；不存在或扫描失败的目录记录错误后跳过，不影响其余目录。
+pub async fn process_directories(
+    dirs: Vec<PathBuf>,
+    mut options: ScannerOptions,
+    progress: Option<UnboundedSender<ScanEvent>>,
+) -> Result<RunReport> {
+    // 限速器与并发信号量在此创建，各目录共用同一份请求配额与转码、上传名额。
+    options.ensure_limiters();
+    // 中止扫描时取消子令牌，跳过剩余目录，又不影响调用方持有的令牌（如 CLI 定时任务）。
+    options.cancel = options.cancel.child_token();
+    let started = Instant::now();
+    let mut report = RunReport {
+        dry_run: options.dry_run,
+        ..RunReport::default()
+    };
+    if options.api_key.trim().is_empty() && !options.dry_run {
+        return Err(anyhow!(tr!("API Key 为空，请在设置中填写后再运行。")));
+    }
+    validate_include_patterns(&options.include_patterns)?;
+    let subdirs = match &options.output_dir {
+        Some(_) if dirs.len() > 1 => output_subdirs(&dirs).into_iter().map(Some).collect(),
+        _ => vec![None; dirs.len()],
+    };
+    for (dir, subdir) in dirs.into_iter().zip(subdirs) {
+        if options.cancel.is_cancelled() {
+            report.cancelled = true;
+            break;
+        }
+        options.output_subdir = subdir;
+        if !dir.exists() {
+            let message = tr!("目录不存在，已跳过：{}", display_path(&dir));
+            log_skipped_directory(&mut report, &options, &progress, message).await;
+            continue;
+        }
+        match process_directory(dir.clone(), options.clone(), progress.clone()).await {
+            Ok(part) => report.absorb(part),
+            Err(e) if is_cancelled(&e) => {
+                report.cancelled = true;
+                break;
+            }
+            Err(e) => {
+                let message = tr!("扫描 {} 失败，已跳过：{}", display_path(&dir), e);
+                log_skipped_directory(&mut report, &options, &progress, message).await;
+            }
+        }
+    }
+    report.elapsed = started.elapsed();
+    Ok(report)
+}
+
+/// 记录跳过某个扫描根的原因，并计入合并后的报告。
+async fn log_skipped_directory(
+    report: &mut RunReport,
+    options: &ScannerOptions,
+    progress: &Option<UnboundedSender<ScanEvent>>,
+    message: String,
+) {
+    let mut logger = ScanLogger::new(progress.clone()).with_log_dir(options.log_dir.clone());
+    logger.error(message);
+    report.logs.extend(logger.finish().await);
+}
+
+/// 各扫描根在共用输出目录下的子文件夹名：取目录名，重名的依次加上 `-2`、`-3` 等后缀。
+fn output_subdirs(dirs: &[PathBuf]) -> Vec<String> {
+    let mut used: Vec<String> = Vec::with_capacity(dirs.len());
+    for dir in dirs {
+        let base = dir
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| "root".to_string());
+        let mut name = base.clone();
+        let mut n = 2;
+        while used.contains(&name) {
+            name = format!("{}-{}", base, n);
+            n += 1;
+        }
+        used.push(name);
+    }
+    used
+}
+
+/// 只转写指定的媒体文件：按所在目录分组，以该目录为扫描根、只处理列出的文件，跳过已转写结果、
+/// 输出位置与扫描报告都与目录扫描一致。用户明确选择的文件不受包含与排除规则影响。
+pub async fn process_files(
+    files: Vec<PathBuf>,
+    mut options: ScannerOptions,
+    progress: Option<UnboundedSender<ScanEvent>>,
+) -> Result<RunReport> {
+    let mut by_dir: Vec<(PathBuf, Vec<PathBuf>)> = Vec::new();
+    for file in files {
+        if !file.is_file() {
+            return Err(anyhow!(tr!("文件不存在：{}", display_path(&file))));
+        }
+        let supported = match file.extension() {
+            Some(_) => has_media_extension(&file),
+            None => options.probe_extensionless,
+        };
+        if !supported {
+            return Err(anyhow!(tr!("不是支持的媒体文件：{}", display_path(&file))));
+        }
+        // 目录遍历得到的路径以扫描根为前缀，统一为绝对路径才能与 `only_files` 逐一比较。
+        let file = if file.is_absolute() {
+            file
+        } else {
+            env::current_dir()?.join(&file)
+        };
+        let dir = file
+            .parent()
+            .ok_or_else(|| anyhow!(tr!("无法确定 {} 所在的目录", display_path(&file))))?
+            .to_path_buf();
+        match by_dir.iter_mut().find(|(known, _)| *known == dir) {
+            Some((_, listed)) if listed.contains(&file) => {}
+            Some((_, listed)) => listed.push(file),
+            None => by_dir.push((dir, vec![file])),
+        }
+    }
+
+    options.ensure_limiters();
+    options.cancel = options.cancel.child_token();
+    options.max_depth = Some(1);
+    options.include_patterns.clear();
+    options.exclude_patterns.clear();
+    let started = Instant::now();
+    let mut report = RunReport {
+        dry_run: options.dry_run,
+        ..RunReport::default()
+    };
+    for (dir, listed) in by_dir {
+        if options.cancel.is_cancelled() {
+            report.cancelled = true;
+            break;
+        }
+        let dir_options = ScannerOptions {
+            only_files: Some(listed),
+            ..options.clone()
+        };
+        report.absorb(process_directory(dir, dir_options, progress.clone()).await?);
+    }
+    report.elapsed = started.elapsed();
+    Ok(report)
+}
+
+/// 扫描指定目录并对尚未转写的媒体文件执行 ASR，返回结构化的运行报告。
+pub async fn process_directory(
+    dir: PathBuf,
+    mut options: ScannerOptions,
+    progress: Option<UnboundedSender<ScanEvent>>,
+) -> Result<RunReport> {
+    options.scan_root = Some(dir.clone());
+    options.ensure_limiters();
+    let started = Instant::now();
+    let mut logger = ScanLogger::new(progress).with_log_dir(options.log_dir.clone());
+    let mut report = RunReport {
+        dry_run: options.dry_run,
+        ..RunReport::default()
+    };
+    let api_key = options.api_key.clone();
+
+    if api_key.trim().is_empty() && !options.dry_run {
+        return Err(anyhow!(tr!("API Key 为空，请在设置中填写后再运行。")));
+    }
+
+    if !dir.exists() {
+        return Err(anyhow!(tr!("目录不存在：{}", display_path(&dir))));
+    }
+
+    // 监视模式只处理个别新文件，不重新生成合并字幕。
+    let combine = options.combine_outputs && options.only_files.is_none();
+    if options.force_reprocess {
+        logger.info(tr!("已开启强制重新转写，忽略所有已有结果。"));
+    }
+    for note in options.text_cleanup.skipped_rules() {
+        logger.error(note.clone());
+    }
+    if let Some(output_dir) = &options.output_dir {
+        logger.info(tr!("转写结果输出到 {}。", display_path(&output_dir)));
+    }
+
+    // 整个扫描共用一个客户端，复用连接池与 TLS 会话；`Client` 内部引用计数，克隆开销很小。
+    // 预览模式不上传，没有客户端。
+    let client = if options.dry_run {
+        None
+    } else {
+        Some(build_client(options.proxy_url.as_deref())?)
+    };
+    let options = Arc::new(options);
+    let concurrency = options.track_concurrency();
+
+    // 遍历与转写同时进行：发现一个待转写任务就立即开始，不等整棵目录树遍历完毕；
+    // 进行中的音轨达到上限时暂停接收，通道写满后遍历随之等待，内存占用不随目录规模增长。
+    let (job_tx, mut job_rx) = mpsc::channel(concurrency);
+    let discovery = discover_jobs(&dir, &options, logger.clone(), job_tx);
+
+    let mut tracker = FileTracker::default();
+    // 需要封装字幕的视频及其本次生成的 SRT，全部音轨完成后统一封装。
+    let mut mux_targets: HashMap<PathBuf, Vec<PathBuf>> = HashMap::new();
+    let mut targets = 0usize;
+    let mut not_started = 0usize;
+    let mut aborted = false;
+    let transcription = async {
+        // 每个音轨作为独立任务启动，由信号量限制同时进行的音轨数量；取消后尚未拿到许可的任务直接返回。
+        let semaphore = Arc::new(Semaphore::new(concurrency));
+        let max_in_flight = concurrency * 2;
+        let mut tasks = JoinSet::new();
+        let mut discovering = true;
+        let transcribe_started = Instant::now();
+        let mut completed = 0usize;
+        let mut streak = FailureStreak::new(options.abort_after_consecutive_failures);
+        loop {
+            let next = tokio::select! {
+                job = job_rx.recv(), if discovering && tasks.len() < max_in_flight => Ok(job),
+                Some(joined) = tasks.join_next() => Err(joined),
+                else => break,
+            };
+            let joined = match next {
+                Ok(Some(job)) => {
+                    let tracks = job.target_count();
+                    targets += tracks;
+                    // 预览模式只列出待转写的任务。
+                    let Some(client) = &client else {
+                        logger.info(describe_pending_job(&job));
+                        continue;
+                    };
+                    if targets == tracks && options.ffmpeg_concurrency > 0 {
+                        logger.info(tr!(
+                            "最多同时运行 {} 个 FFmpeg 转码、{} 个上传。",
+                            options.ffmpeg_concurrency,
+                            options.concurrency()
+                        ));
+                    } else if targets == tracks && concurrency > 1 {
+                        logger.info(tr!("最多同时处理 {} 个音轨。", concurrency));
+                    }
+                    let path = job.path().to_path_buf();
+                    logger.file_state(&path, FileState::Pending, tracks);
+                    if options.mux_subtitles && matches!(job, PendingJob::Video { .. }) {
+                        mux_targets.insert(path.clone(), Vec::new());
+                    }
+                    tracker.add(path, tracks);
+                    for source in job.into_sources() {
+                        let options = options.clone();
+                        let client = client.clone();
+                        let semaphore = semaphore.clone();
+                        let mut task_logger = logger.clone();
+                        tasks.spawn(async move {
+                            let original = source.original_path().to_path_buf();
+                            let _permit = semaphore.acquire_owned().await.ok()?;
+                            // 暂停期间持有许可等待，恢复后从这里继续；等待中取消则视为未开始。
+                            if options.pause.wait_if_paused(&options.cancel).await.is_err()
+                                || options.cancel.is_cancelled()
+                            {
+                                return None;
+                            }
+                            task_logger.file_state(&original, FileState::Processing, tracks);
+                            let result =
+                                process_audio_source(options, &client, source, &mut task_logger)
+                                    .await;
+                            Some((original, result))
+                        });
+                    }
+                    // 总数随遍历增长，剩余时间在遍历结束前只按已发现的音轨估算。
+                    logger.progress(
+                        completed,
+                        targets,
+                        average_secs_per_target(transcribe_started.elapsed(), completed),
+                    );
+                    continue;
+                }
+                Ok(None) => {
+                    discovering = false;
+                    continue;
+                }
+                Err(joined) => joined,
+            };
+
+            let Some((original, result)) =
+                joined.map_err(|e| anyhow!(tr!("转写任务异常退出：{}", e)))?
+            else {
+                not_started += 1;
+                continue;
+            };
+            completed += 1;
+            logger.progress(
+                completed,
+                targets,
+                average_secs_per_target(transcribe_started.elapsed(), completed),
+            );
+            let mut finished = None;
+            if !matches!(&result, Err(err) if is_cancelled(err)) {
+                finished = tracker.finish_track(&original, result.is_ok());
+                if let Some(state) = finished {
+                    logger.file_state(&original, state, tracker.tracks(&original));
+                }
+            }
+            if !matches!(&result, Err(err) if is_cancelled(err)) {
+                if let Some(reason) = streak.record(result.as_ref().err()) {
+                    if !options.cancel.is_cancelled() {
+                        // 遍历随之停止，尚未开始的任务拿到许可后直接返回，进行中的任务在下一个取消检查点结束。
+                        logger.error(reason);
+                        aborted = true;
+                        options.cancel.cancel();
+                    }
+                }
+            }
+            match result {
+                Ok(output) => {
+                    if let Some(srts) = mux_targets.get_mut(&original) {
+                        if output.path.extension().is_some_and(|ext| ext == "srt") {
+                            srts.push(output.path.clone());
+                        }
+                    }
+                    // 只有全部音轨都成功时才封装，避免写出缺少部分字幕的视频。
+                    if finished == Some(FileState::Done) {
+                        if let Some(srts) = mux_targets.remove(&original) {
+                            mux_video(&original, &srts, &options, &mut logger).await;
+                        }
+                    }
+                    if options.append_growing_files {
+                        if let Some(duration) = output.covered_until {
+                            if let Err(e) = write_transcript_state(&output.path, duration).await {
+                                logger.info(tr!(
+                                    "写入 {} 的转写状态失败：{}",
+                                    display_path(&output.path),
+                                    e
+                                ));
+                            }
+                        }
+                    }
+                    logger.track_finished(&original, Ok(&output.path));
+                    if tracker.first_success(&original) {
+                        report.succeeded.push(original);
+                    }
+                    report.outputs.push(output.path);
+                    report.total_segments += output.segments;
+                    report.api_duration_secs += output.api_duration;
+                    report.audio_secs += output.audio_secs;
+                }
+                Err(err) if is_cancelled(&err) => {}
+                Err(err) => {
+                    let reason = err.to_string();
+                    logger.error(reason.clone());
+                    logger.track_finished(&original, Err(&reason));
+                    report.failed.push((original, reason));
+                }
+            }
+        }
+        Ok::<_, anyhow::Error>(())
+    };
+    let (discovery, transcribed) = tokio::join!(discovery, transcription);
+    transcribed?;
+    let estimated_cost = discovery.estimate(&options).map(|(_, estimated)| estimated);
+    report.absorb(discovery.report);
+
+    if targets == 0 {
+        logger.info(tr!("没有检测到新的待转写文件。"));
+        if combine && !options.dry_run && !options.cancel.is_cancelled() {
+            combine_directory(
+                &dir,
+                discovery.combine_media,
+                &options,
+                &mut logger,
+                &mut report,
+            )
+            .await;
+        }
+        report.elapsed = started.elapsed();
+        write_scan_report(&dir, &options, &mut report, &mut logger).await;
+        report.logs = logger.finish().await;
+        return Ok(report);
+    }
+
+    report.pending += targets;
+    report.estimated_cost = estimated_cost;
+
+    if options.dry_run {
+        logger.info(tr!("预览模式：未执行转码与转写。"));
+        report.logs = logger.finish().await;
+        report.elapsed = started.elapsed();
+        return Ok(report);
+    }
+
+    if aborted {
+        report.cancelled = true;
+        logger.info(tr!("扫描已中止，剩余 {} 个音轨未处理。", not_started));
+    } else if options.cancel.is_cancelled() {
+        report.cancelled = true;
+        logger.info(tr!("扫描已取消，剩余 {} 个音轨未处理。", not_started));
+    } else if combine {
+        combine_directory(
+            &dir,
+            discovery.combine_media,
+            &options,
+            &mut logger,
+            &mut report,
+        )
+        .await;
+    }
+
+    if report.api_duration_secs > 0.0 {
+        logger.info(tr!(
+            "API 报告的转写时长合计 {:.1} 秒（约 {:.1} 分钟），可据此估算费用。",
+            report.api_duration_secs,
+            report.api_duration_secs / 60.0
+        ));
+    }
+    if let Some(rate) = options.cost_per_minute {
+        // 服务端未报告时长时，按实测时长折算加速后实际上传的部分。
+        let billed_secs = if report.api_duration_secs > 0.0 {
+            report.api_duration_secs
+        } else {
+            report.audio_secs / options.speedup().unwrap_or(1.0)
+        };
+        let actual = billed_secs / 60.0 * rate;
+        logger.info(tr!(
+            "按每分钟 {} 计算，本次实际费用约 {:.2}。",
+            rate,
+            actual
+        ));
+        report.actual_cost = Some(actual);
+    }
+
+    report.elapsed = started.elapsed();
+    write_scan_report(&dir, &options, &mut report, &mut logger).await;
+    report.logs = logger.finish().await;
+    Ok(report)
+}
+
+/// 遍历目录得到的结果：跳过与直接提取内嵌字幕等不需要转写的文件计入 `report`。
+#[derive(Default)]
+struct Discovery {
+    report: RunReport,
+    included: usize,
+    not_included: usize,
+    excluded: usize,
+    /// 合并字幕时按顺序串接的全部媒体文件，含已转写而跳过的文件。
+    combine_media: Vec<PathBuf>,
+    /// 已发出任务的待转写音频时长合计（秒），仅在设置了每分钟单价时统计。
+    estimated_secs: f64,
+    /// 无法读取时长、未计入预估的文件数。
+    unknown_durations: usize,
+    /// 已发现的待转写音轨数。
+    tracks: usize,
+    /// 设置了每分钟单价时暂存的任务，遍历结束、给出费用预估后才交给转写端。
+    held: Vec<PendingJob>,
+    /// 检查外挂字幕时按目录缓存的字幕文件列表。
+    subtitles: SubtitleListing,
+}
+
+impl Discovery {
+    /// 统计任务时长并发给转写端；接收端已关闭时返回 `false`，遍历随之停止。
+    async fn send(
+        &mut self,
+        job: PendingJob,
+        options: &ScannerOptions,
+        jobs: &mpsc::Sender<PendingJob>,
+    ) -> bool {
+        self.tracks += job.target_count();
+        if options.cost_per_minute.is_none() {
+            return jobs.send(job).await.is_ok();
+        }
+        match pending_job_secs(&job, options).await {
+            Some(secs) => self.estimated_secs += secs,
+            None => self.unknown_durations += 1,
+        }
+        self.held.push(job);
+        true
+    }
+
+    /// 按每分钟单价估算的 `(计费分钟数, 费用)`，未设置单价时为 `None`。
+    fn estimate(&self, options: &ScannerOptions) -> Option<(f64, f64)> {
+        let rate = options.cost_per_minute?;
+        let billed_minutes = self.estimated_secs / options.speedup().unwrap_or(1.0) / 60.0;
+        Some((billed_minutes, billed_minutes * rate))
+    }
+}
+
+/// 遍历 `dir` 并逐个检查媒体文件，每发现一个待转写任务就通过 `jobs` 发出；
+/// 遍历结束（或扫描被取消）时丢弃发送端，转写端据此得知没有更多任务。
+async fn discover_jobs(
+    dir: &Path,
+    options: &ScannerOptions,
+    mut logger: ScanLogger,
+    jobs: mpsc::Sender<PendingJob>,
+) -> Discovery {
+    let mut discovery = Discovery::default();
+    let includes = build_glob_set(&options.include_patterns, tr!("包含"), &mut logger);
+    let excludes = build_glob_set(&options.exclude_patterns, tr!("排除"), &mut logger);
+    let combine = options.combine_outputs && options.only_files.is_none();
+
+    // 只处理指定文件时直接以列出的文件为候选，不再遍历整个目录树。
+    let candidates: Box<dyn Iterator<Item = PathBuf> + Send> = match &options.only_files {
+        Some(files) => Box::new(listed_files_under(dir, files, options.max_depth).into_iter()),
+        None => Box::new(
+            scan_walker(dir, options.max_depth)
+                .into_iter()
+                .filter_map(|e| e.ok())
+                .map(walkdir::DirEntry::into_path),
+        ),
+    };
+    for path in candidates {
+        if options.cancel.is_cancelled() {
+            break;
+        }
+        let path = path.as_path();
+        if !path.is_file() {
+            continue;
+        }
+
+        let has_extension = match path.extension() {
+            Some(ext) => {
+                let ext_str = ext.to_string_lossy().to_lowercase();
+                if !is_media_extension(&ext_str)
+                    || is_intermediate_audio(path)
+                    || is_muxed_video(path)
+                {
+                    continue;
+                }
+                true
+            }
+            None if options.probe_extensionless => false,
+            None => continue,
+        };
+
+        if !includes.is_empty() {
+            if !matches_relative(&includes, dir, path) {
+                discovery.not_included += 1;
+                continue;
+            }
+            discovery.included += 1;
+        }
+
+        if matches_relative(&excludes, dir, path) {
+            discovery.excluded += 1;
+            continue;
+        }
+
+        let video = if has_extension {
+            is_video(path)
+        } else {
+            match cancellable(&options.cancel, probe_media_kind(path, options)).await {
+                Ok(Some(video)) => {
+                    let kind = if video { tr!("视频") } else { tr!("音频") };
+                    logger.info(tr!(
+                        "{} 没有扩展名，探测为{}文件。",
+                        display_path(&path),
+                        kind
+                    ));
+                    video
+                }
+                Ok(None) => continue,
+                Err(e) if is_cancelled(&e) => break,
+                // 普通的无扩展名文件（如 README）ffprobe 无法识别，直接忽略。
+                Err(_) => continue,
+            }
+        };
+
+        discovery.report.found += 1;
+        if combine {
+            discovery.combine_media.push(path.to_path_buf());
+        }
+
+        // 视频的外挂字幕检查放在读取音轨之后，以便排除本程序按语言标签写出的结果。
+        if !video && !options.force_reprocess {
+            if let Some(existing) = has_existing_subtitle(
+                path,
+                &options.subtitle_suffixes,
+                &[],
+                &mut discovery.subtitles,
+            ) {
+                logger.info(tr!(
+                    "跳过 {}：已有外挂字幕 {}。",
+                    display_path(&path),
+                    display_path(&existing)
+                ));
+                discovery.report.skipped += 1;
+                continue;
+            }
+        }
+
+        if video {
+            let streams = match cancellable(&options.cancel, audio_streams(path, options)).await {
+                Ok(streams) => streams,
+                Err(e) if is_cancelled(&e) => break,
+                Err(e) if is_tool_timeout(&e) => {
+                    logger.error(e.to_string());
+                    continue;
+                }
+                Err(e) => {
+                    logger.error(tr!("读取 {} 音轨失败：{}", display_path(&path), e));
+                    continue;
+                }
+            };
+            let labels = track_labels(&streams, options.name_tracks_by_language);
+            if !options.force_reprocess {
+                // 按语言命名的结果（如 `movie.chi.srt`）与外挂字幕同名，不能据此跳过整个视频。
+                let own: Vec<PathBuf> = labels
+                    .values()
+                    .map(|label| options.transcript_path(path, Some(label)))
+                    .collect();
+                if let Some(existing) = has_existing_subtitle(
+                    path,
+                    &options.subtitle_suffixes,
+                    &own,
+                    &mut discovery.subtitles,
+                ) {
+                    logger.info(tr!(
+                        "跳过 {}：已有外挂字幕 {}。",
+                        display_path(&path),
+                        display_path(&existing)
+                    ));
+                    discovery.report.skipped += 1;
+                    continue;
+                }
+            }
+            if options.skip_if_embedded_subs {
+                match use_embedded_subtitle(path, options, &mut logger).await {
+                    Ok(EmbeddedSubtitle::Extracted(output)) => {
+                        discovery.report.succeeded.push(path.to_path_buf());
+                        discovery.report.outputs.push(output);
+                        continue;
+                    }
+                    Ok(EmbeddedSubtitle::AlreadyExtracted) => {
+                        discovery.report.skipped += 1;
+                        continue;
+                    }
+                    Ok(EmbeddedSubtitle::Preview) => {
+                        discovery.report.pending += 1;
+                        continue;
+                    }
+                    Ok(EmbeddedSubtitle::Unavailable) => {}
+                    // 只有取消扫描时才会返回错误。
+                    Err(_) => break,
+                }
+            }
+            if streams.is_empty() {
+                logger.info(tr!("跳过 {}：视频中未检测到音轨。", display_path(&path)));
+                discovery.report.skipped += 1;
+                continue;
+            }
+            let found = streams.len();
+            let streams = options.track_selection.apply(streams);
+            if streams.is_empty() {
+                logger.info(tr!(
+                    "跳过 {}：{} 条音轨均不在音轨选择范围内。",
+                    display_path(&path),
+                    found
+                ));
+                discovery.report.skipped += 1;
+                continue;
+            }
+
+            let mut pending_tracks = Vec::new();
+            for stream in streams {
+                let label = labels
+                    .get(&stream.index)
+                    .cloned()
+                    .unwrap_or_else(|| numbered_track_label(stream.index));
+                let transcript_path = options.transcript_path(path, Some(&label));
+                let append =
+                    match check_transcript(path, &transcript_path, options, &mut logger).await {
+                        TranscriptCheck::Done => {
+                            discovery.report.skipped += 1;
+                            continue;
+                        }
+                        TranscriptCheck::Transcribe => None,
+                        TranscriptCheck::Append(state) => Some(state),
+                    };
+                pending_tracks.push(PendingTrack {
+                    stream,
+                    label,
+                    append,
+                });
+            }
+
+            if pending_tracks.is_empty() {
+                logger.info(tr!("跳过 {}：所有音轨均已转写。", display_path(&path)));
+                continue;
+            }
+
+            let job = PendingJob::Video {
+                path: path.to_path_buf(),
+                tracks: pending_tracks,
+            };
+            if !discovery.send(job, options, &jobs).await {
+                break;
+            }
+        } else {
+            if let Some(size_mb) = oversized_mb(path, options) {
+                logger.error(tr!(
+                    "跳过 {}：文件大小 {} MB 超过上限 {} MB。",
+                    display_path(&path),
+                    size_mb,
+                    options.max_file_size_mb.unwrap_or_default()
+                ));
+                discovery.report.skipped += 1;
+                continue;
+            }
+            let transcript_path = options.transcript_path(path, None);
+            let append = match check_transcript(path, &transcript_path, options, &mut logger).await
+            {
+                TranscriptCheck::Done => {
+                    discovery.report.skipped += 1;
+                    continue;
+                }
+                TranscriptCheck::Transcribe => None,
+                TranscriptCheck::Append(state) => Some(state),
+            };
+            let job = PendingJob::Audio {
+                path: path.to_path_buf(),
+                append,
+            };
+            if !discovery.send(job, options, &jobs).await {
+                break;
+            }
+        }
+    }
+
+    if !includes.is_empty() {
+        logger.info(tr!(
+            "包含规则匹配了 {} 个媒体文件，另有 {} 个未匹配，已跳过。",
+            discovery.included,
+            discovery.not_included
+        ));
+    }
+    if discovery.excluded > 0 {
+        logger.info(tr!(
+            "根据排除规则跳过了 {} 个媒体文件。",
+            discovery.excluded
+        ));
+    }
+    if discovery.tracks == 0 {
+        return discovery;
+    }
+    logger.info(tr!("待处理音轨总数：{}。", discovery.tracks));
+
+    // 设置了单价时任务暂存到遍历结束，保证费用预估出现在任何上传之前。
+    if let Some((billed_minutes, estimated)) = discovery.estimate(options) {
+        logger.info(tr!(
+            "预计上传音频约 {:.1} 分钟，按每分钟 {} 估算费用约 {:.2}。",
+            billed_minutes,
+            options.cost_per_minute.unwrap_or_default(),
+            estimated
+        ));
+        if discovery.unknown_durations > 0 {
+            logger.info(tr!(
+                "{} 个文件无法读取时长，未计入预估。",
+                discovery.unknown_durations
+            ));
+        }
+    }
+    for job in std::mem::take(&mut discovery.held) {
+        if options.cancel.is_cancelled() || jobs.send(job).await.is_err() {
+            break;
+        }
+    }
+    discovery
+}
+
+/// 按配置写出本次目录扫描的汇总报告；写入失败只记录日志，不影响扫描结果。
+async fn write_scan_report(
+    dir: &Path,
+    options: &ScannerOptions,
+    report: &mut RunReport,
+    logger: &mut ScanLogger,
+) {
+    let Some(report_dir) = options.report_dir.as_deref().filter(|_| !options.dry_run) else {
+        return;
+    };
+    // 报告需要完整日志中的错误，此时日志尚未交给 `report`，先取一份快照。
+    report.logs = logger.snapshot();
+    match scan_report::write(report_dir, dir, report).await {
+        Ok(path) => logger.info(tr!("扫描报告已写入 {}", display_path(&path))),
+        Err(e) => logger.error(tr!("写入扫描报告失败：{}", e)),
+    }
+}
+
+/// 封装字幕后另存的视频文件名标记，如 `movie.subtitled.mkv`。
+const MUXED_VIDEO_TAG: &str = "subtitled";
+/// 替换原视频时先写入的临时文件标记，如 `movie.muxing.mkv`，成功后再改名覆盖原文件。
+const MUXING_TEMP_TAG: &str = "muxing";
+
+/// 容器可存放的软字幕编码：MKV 直接保存 SRT，MP4/MOV 只支持 `mov_text`，WebM 只支持 WebVTT；
+/// 其他容器（如 AVI、FLV、WMV）返回 `None`。
+fn subtitle_codec_for_container(video: &Path) -> Option<&'static str> {
+    let ext = video.extension()?.to_string_lossy().to_lowercase();
+    match ext.as_str() {
+        "mkv" | "mka" => Some("srt"),
+        "mp4" | "mov" => Some("mov_text"),
+        "webm" => Some("webvtt"),
+        _ => None,
+    }
+}
+
+/// 在视频扩展名前插入标记：`movie.mkv` → `movie.<tag>.mkv`。
+fn tagged_video_path(video: &Path, tag: &str) -> PathBuf {
+    let mut name = video.file_stem().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(tag);
+    if let Some(ext) = video.extension() {
+        name.push(".");
+        name.push(ext);
+    }
+    video.with_file_name(name)
+}
+
+/// 识别封装字幕时生成的视频（`movie.subtitled.mkv`、`movie.muxing.mkv`），避免被当作新媒体再次转写。
+fn is_muxed_video(path: &Path) -> bool {
+    let Some(stem) = path.file_stem().map(|stem| stem.to_string_lossy()) else {
+        return false;
+    };
+    is_video(path)
+        && [MUXED_VIDEO_TAG, MUXING_TEMP_TAG]
+            .iter()
+            .any(|tag| stem.ends_with(&format!(".{}", tag)))
+}
+
+/// 把本次生成的字幕封装回视频并记录结果；失败只记录日志，字幕文件本身不受影响。
+async fn mux_video(
+    video: &Path,
+    srts: &[PathBuf],
+    options: &ScannerOptions,
+    logger: &mut ScanLogger,
+) {
+    if srts.is_empty() {
+        return;
+    }
+    let Some(codec) = subtitle_codec_for_container(video) else {
+        logger.info(tr!(
+            "跳过封装 {}：该容器不支持软字幕，仅支持 MKV、MP4、MOV 与 WebM。",
+            display_path(&video)
+        ));
+        return;
+    };
+    match mux_subtitle_streams(video, srts, codec, options).await {
+        Ok(path) => logger.success(tr!(
+            "已把 {} 条字幕封装到 {}",
+            srts.len(),
+            display_path(&path)
+        )),
+        Err(e) if is_cancelled(&e) => {}
+        Err(e) => logger.error(tr!("封装 {} 的字幕失败：{}", display_path(&video), e)),
+    }
+}
+
+/// 用 FFmpeg 复制原视频的全部流并追加字幕流，返回封装后的视频路径。
+///
+/// 原有字幕流保持原编码，只有新追加的字幕流按容器转为 `codec`。
+async fn mux_subtitle_streams(
+    video: &Path,
+    srts: &[PathBuf],
+    codec: &str,
+    options: &ScannerOptions,
+) -> Result<PathBuf> {
+    let existing = cancellable(&options.cancel, subtitle_streams(video, options))
+        .await?
+        .len();
+    let (output, target) = if options.mux_replace_original {
+        (
+            tagged_video_path(video, MUXING_TEMP_TAG),
+            video.to_path_buf(),
+        )
+    } else {
+        let target = options.relocate(tagged_video_path(video, MUXED_VIDEO_TAG));
+        options.ensure_output_parent(&target).await?;
+        (target.clone(), target)
+    };
+
+    let mut cmd = Command::new(options.ffmpeg());
+    cmd.arg("-y").arg("-v").arg("error").arg("-i").arg(video);
+    for srt in srts {
+        cmd.arg("-i").arg(srt);
+    }
+    cmd.arg("-map").arg("0");
+    for input in 1..=srts.len() {
+        cmd.arg("-map").arg(input.to_string());
+    }
+    cmd.arg("-c").arg("copy");
+    for offset in 0..srts.len() {
+        cmd.arg(format!("-c:s:{}", existing + offset)).arg(codec);
+    }
+    cmd.arg(&output);
+    run_tool_to_output(cmd, options, video, &output, tr!("FFmpeg 封装字幕失败")).await?;
+
+    if output != target {
+        if let Err(e) = replace_with_muxed(&output, &target).await {
+            let _ = fs::remove_file(&output).await;
+            return Err(anyhow!(tr!(
+                "用封装后的视频替换 {} 失败：{}",
+                display_path(&target),
+                e
+            )));
+        }
+    }
+    Ok(target)
+}
+
+/// 以封装结果替换原视频，并沿用原视频的修改时间，
+/// 避免下次扫描因源文件比字幕新而重新转写、再封装一条字幕流。
+async fn replace_with_muxed(muxed: &Path, original: &Path) -> std::io::Result<()> {
+    let modified = fs::metadata(original).await?.modified()?;
+    let file = fs::OpenOptions::new().write(true).open(muxed).await?;
+    file.into_std().await.set_modified(modified)?;
+    fs::rename(muxed, original).await
+}
+
+/// 合并目录内的字幕并记录结果，失败只记录日志，不影响本次扫描的其他结果。
+async fn combine_directory(
+    dir: &Path,
+    media: Vec<PathBuf>,
+    options: &ScannerOptions,
+    logger: &mut ScanLogger,
+    report: &mut RunReport,
+) {
+    match write_combined_srt(dir, media, options, logger).await {
+        Ok(Some(path)) => report.outputs.push(path),
+        Ok(None) => {}
+        Err(e) if is_cancelled(&e) => report.cancelled = true,
+        Err(e) => logger.error(tr!("合并 {} 的字幕失败：{}", display_path(&dir), e)),
+    }
+}
+
+/// 按路径字母顺序把各媒体的 SRT 串接为 `<目录名>.合并.srt`，
+/// 每个文件的时间轴加上之前所有文件的时长，序号重新从 1 编排。
+///
+/// 缺少字幕的文件只计入时长，使后续字幕仍与连续播放的时间轴对齐。
+async fn write_combined_srt(
+    dir: &Path,
+    mut media: Vec<PathBuf>,
+    options: &ScannerOptions,
+    logger: &mut ScanLogger,
+) -> Result<Option<PathBuf>> {
+    if media.len() < 2 {
+        return Ok(None);
+    }
+    media.sort();
+
+    let mut content = String::new();
+    let mut offset = 0.0;
+    let mut index = 1;
+    let mut combined = 0;
+    // 合并发生在转写之后，此时列出的目录已包含本次写出的结果。
+    let mut listing = SubtitleListing::default();
+    for path in &media {
+        match find_combinable_srt(path, options, &mut listing) {
+            Some(srt) => {
+                let text = fs::read_to_string(&srt)
+                    .await
+                    .with_context(|| tr!("读取 {} 失败", display_path(&srt)))?;
+                for (start, end, body) in parse_srt_cues(&text) {
+                    content.push_str(&build_srt_entry(index, start + offset, end + offset, &body));
+                    index += 1;
+                }
+                combined += 1;
+            }
+            None => logger.info(tr!(
+                "合并字幕时 {} 没有可用的 SRT，仅计入时长。",
+                display_path(&path)
+            )),
+        }
+        offset += cancellable(&options.cancel, media_duration(path, options)).await?;
+    }
+    if combined == 0 {
+        return Ok(None);
+    }
+
+    let name = dir
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| "combined".to_string());
+    let output = options.relocate(dir.join(format!("{}.合并.srt", name)));
+    options.ensure_output_parent(&output).await?;
+    write_atomic(&output, content).await?;
+    logger.success(tr!(
+        "已按文件名顺序将 {} 个字幕合并为 {}（共 {} 条）。",
+        combined,
+        display_path(&output),
+        index - 1
+    ));
+    Ok(Some(output))
+}
+
+/// 合并时使用的字幕：同名结果或内嵌字幕提取结果、序号最小的音轨结果，最后是外挂字幕。
+fn find_combinable_srt(
+    media: &Path,
+    options: &ScannerOptions,
+    listing: &mut SubtitleListing,
+) -> Option<PathBuf> {
+    let own = options.result_srt_path(media, None);
+    if own.is_file() {
+        return Some(own);
+    }
+    let stem = options.result_stem(media)?.to_string_lossy().to_string();
+    let track_prefix = format!("{}.轨道", stem);
+    let first_track = listing
+        .srt_files(own.parent()?)
+        .iter()
+        .filter_map(|path| {
+            let name = path.file_name()?.to_string_lossy();
+            let track: u32 = name
+                .strip_prefix(&track_prefix)?
+                .strip_suffix(".srt")?
+                .parse()
+                .ok()?;
+            Some((track, path.clone()))
+        })
+        .min_by_key(|(track, _)| *track)
+        .map(|(_, path)| path);
+    first_track.or_else(|| has_existing_subtitle(media, &options.subtitle_suffixes, &[], listing))
+}
+
+/// 解析 SRT 内容为 `(开始, 结束, 文本)`，忽略无法识别的块。
+fn parse_srt_cues(content: &str) -> Vec<(f64, f64, String)> {
+    content
+        .replace("\r\n", "\n")
+        .split("\n\n")
+        .filter_map(|block| {
+            let mut lines = block.lines().skip_while(|line| !line.contains("-->"));
+            let (start, end) = lines.next()?.split_once("-->")?;
+            let start = parse_srt_timestamp(start.trim())?;
+            let end = parse_srt_timestamp(end.trim())?;
+            let body = lines.collect::<Vec<_>>().join("\n");
+            Some((start, end, body))
+        })
+        .collect()
+}
+
+/// 解析 `HH:MM:SS,mmm`（也接受 `.` 作为毫秒分隔符）为秒数。
+fn parse_srt_timestamp(value: &str) -> Option<f64> {
+    let (hms, millis) = value.split_once([',', '.'])?;
+    let mut parts = hms.split(':').map(|part| part.parse::<u64>().ok());
+    let (hours, minutes, secs) = (parts.next()??, parts.next()??, parts.next()??);
+    let millis: u64 = millis.parse().ok()?;
+    Some((hours * 3600 + minutes * 60 + secs) as f64 + millis as f64 / 1000.0)
+}
+
+/// 预览模式下描述一个待处理目标，视频会列出待转写的音轨编号。
+fn describe_pending_job(job: &PendingJob) -> String {
+    let append_note = |append: &Option<AppendState>| {
+        append
+            .map(|state| tr!("（从 {} 起追加）", format_timestamp(state.offset)))
+            .unwrap_or_default()
+    };
+    match job {
+        PendingJob::Audio { path, append } => {
+            tr!("待转写音频：{}{}", display_path(&path), append_note(append))
+        }
+        PendingJob::Video { path, tracks } => {
+            let tracks: Vec<String> = tracks
+                .iter()
+                .map(|track| {
+                    format!(
+                        "{}{}{}",
+                        track.stream.index,
+                        track.stream.describe_tags(),
+                        append_note(&track.append)
+                    )
+                })
+                .collect();
+            tr!(
+                "待转写视频：{}，音轨 {}",
+                display_path(&path),
+                tracks.join("、")
+            )
+        }
+    }
+}
+
+/// 把 `only_files` 中位于扫描根 `dir` 下的文件映射为以 `dir` 为前缀的路径，超出 `max_depth`
+/// 或不在扫描根下的文件被忽略。写法不同（相对路径、符号链接、`/private` 前缀等）时按规范化后的
+/// 路径比较，结果仍以 `dir` 为前缀，以便包含规则与输出目录按相对路径计算。
+fn listed_files_under(dir: &Path, files: &[PathBuf], max_depth: Option<usize>) -> Vec<PathBuf> {
+    let canonical_dir = std::fs::canonicalize(dir).ok();
+    let mut listed: Vec<PathBuf> = Vec::new();
+    for file in files {
+        let relative = match file.strip_prefix(dir) {
+            Ok(relative) => Some(relative.to_path_buf()),
+            Err(_) => canonical_dir
+                .as_deref()
+                .zip(std::fs::canonicalize(file).ok())
+                .and_then(|(root, file)| Some(file.strip_prefix(root).ok()?.to_path_buf())),
+        };
+        let Some(relative) = relative else {
+            continue;
+        };
+        if max_depth.is_some_and(|depth| relative.components().count() > depth) {
+            continue;
+        }
+        let path = dir.join(relative);
+        if !listed.contains(&path) {
+            listed.push(path);
+        }
+    }
+    listed
+}
+
+/// 构造目录遍历器，按需限制递归深度。
+fn scan_walker(dir: &Path, max_depth: Option<usize>) -> WalkDir {
+    let walker = WalkDir::new(dir);
+    match max_depth {
+        Some(depth) => walker.max_depth(depth),
+        None => walker,
+    }
+}
+
+/// 编译包含或排除规则，`kind` 用于错误日志；无效的规则记录错误后忽略，不影响其余规则。
+fn build_glob_set(patterns: &[String], kind: &str, logger: &mut ScanLogger) -> GlobSet {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns.iter().map(|p| p.trim()).filter(|p| !p.is_empty()) {
+        match Glob::new(pattern) {
+            Ok(glob) => {
+                builder.add(glob);
+            }
+            Err(e) => logger.error(tr!(
+                "{}规则 {} 无效，已忽略：{}",
+                kind,
+                format!("{:?}", pattern),
+                e
+            )),
+        }
+    }
+    builder.build().unwrap_or_else(|_| GlobSet::empty())
+}
+
+/// 使用相对扫描根目录的路径（统一为 `/` 分隔）匹配规则，规则为空时不匹配任何文件。
+fn matches_relative(patterns: &GlobSet, root: &Path, path: &Path) -> bool {
+    if patterns.is_empty() {
+        return false;
+    }
+    let relative = path.strip_prefix(root).unwrap_or(path);
+    let normalized = relative.to_string_lossy().replace('\\', "/");
+    patterns.is_match(normalized.as_str())
+}
+
+fn is_media_extension(ext: &str) -> bool {
+    is_video_extension(ext)
+        || matches!(ext, "wav" | "ogg" | "opus" | "mp3" | "m4a" | "flac" | "aac")
+}
+
+/// 按扩展名判断路径是否为支持的媒体文件，不探测无扩展名的文件。
+pub fn has_media_extension(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| is_media_extension(&ext.to_string_lossy().to_lowercase()))
+}
+
+/// 按容器处理的扩展名：先用 ffprobe 列出音轨再逐轨导出。
+///
+/// `webm` 可能带视频，`mka` 虽只含音频但常有多条音轨，二者都不适合直接上传。
+fn is_video_extension(ext: &str) -> bool {
+    matches!(
+        ext,
+        "mkv" | "mp4" | "avi" | "mov" | "flv" | "wmv" | "webm" | "mka"
+    )
+}
+
+/// 判断文件是否是值得监视的媒体文件：扩展名受支持，且不是扫描过程中生成的临时音频或封装字幕后的视频。
+pub fn is_watchable_media(path: &Path) -> bool {
+    path.extension()
+        .map(|ext| is_media_extension(&ext.to_string_lossy().to_lowercase()))
+        .unwrap_or(false)
+        && !is_intermediate_audio(path)
+        && !is_muxed_video(path)
+}
+
+/// 识别扫描过程中生成的临时音频，如 `a.mp4-track1.mp3`、`a.mp3-prepared.mp3`、
+/// `a.wav-track2-seg3.mp3`、`a.mp4-vad.wav`，避免被当作新媒体再次处理。
+fn is_intermediate_audio(path: &Path) -> bool {
+    let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+        return false;
+    };
+    let Some(stem) = name
+        .strip_suffix(".mp3")
+        .or_else(|| name.strip_suffix(".ogg"))
+        .or_else(|| name.strip_suffix(".wav"))
+    else {
+        return false;
+    };
+    let Some(base) = stem
+        .strip_suffix("-prepared")
+        .or_else(|| stem.strip_suffix("-vad"))
+        .or_else(|| strip_numbered_tag(stem, "-seg"))
+        .or_else(|| strip_numbered_tag(stem, "-track"))
+    else {
+        return false;
+    };
+    let base = strip_numbered_tag(base, "-track").unwrap_or(base);
+    Path::new(base)
+        .extension()
+        .map(|ext| is_media_extension(&ext.to_string_lossy().to_lowercase()))
+        .unwrap_or(false)
+}
+
+/// 去掉形如 `{tag}{数字}` 的后缀，如 `-track1`、`-seg12`。
+fn strip_numbered_tag<'a>(name: &'a str, tag: &str) -> Option<&'a str> {
+    let digits = name.trim_end_matches(|c: char| c.is_ascii_digit());
+    if digits.len() == name.len() {
+        return None;
+    }
+    digits.strip_suffix(tag)
+}
+
+/// 判断给定路径是否属于需要先转码的视频文件。
+fn is_video(path: &Path) -> bool {
+    if let Some(ext) = path.extension() {
+        is_video_extension(&ext.to_string_lossy().to_lowercase())
+    } else {
+        false
+    }
+}
+
+/// 通过 FFmpeg 将音频（或视频的特定音轨）按所选编码转码，供 ASR 上传使用，可选起点与加速。
+async fn convert_for_upload(
+    input: &Path,
+    stream_index: Option<u32>,
+    seek: f64,
+    channels: Option<u32>,
+    output: &Path,
+    options: &ScannerOptions,
+) -> Result<()> {
+    let mut cmd = Command::new(options.ffmpeg());
+    if seek > 0.0 {
+        cmd.arg("-ss").arg(format!("{:.3}", seek));
+    }
+    cmd.arg("-i").arg(input);
+    if let Some(index) = stream_index {
+        cmd.arg("-map").arg(format!("0:{}", index));
+    }
+    if let Some(filter) = options.audio_filter(true, channels) {
+        cmd.arg("-filter:a").arg(filter);
+    }
+    cmd.args(options.export_codec_args()).arg("-y").arg(output);
+    run_tool_to_output(cmd, options, input, output, tr!("FFmpeg 转码音轨失败")).await
+}
+
+/// 生成 FFmpeg `atempo` 滤镜链；单个 `atempo` 最多 2 倍，超出部分拆成多级串联。
+fn atempo_filter_chain(factor: f64) -> String {
+    let mut remaining = factor.clamp(1.0, SPEEDUP_MAX as f64);
+    let mut filters = Vec::new();
+    while remaining > ATEMPO_MAX_STEP {
+        filters.push(format!("atempo={:.1}", ATEMPO_MAX_STEP));
+        remaining /= ATEMPO_MAX_STEP;
+    }
+    filters.push(format!("atempo={:.4}", remaining));
+    filters.join(",")
+}
+
+/// 将加速后音频上的时间换算回原始媒体时间轴。
+fn rescale_to_original(seconds: f64, speedup: Option<f64>) -> f64 {
+    match speedup {
+        Some(factor) => seconds * factor,
+        None => seconds,
+    }
+}
+
+/// 基于原始文件名生成转写结果 `.srt` 路径，可附带音轨标签（如 `轨道2`、`jpn`）。
+fn transcript_result_path(original: &Path, track_label: Option<&str>) -> PathBuf {
+    result_path_with_stem(original, original.file_stem(), track_label)
+}
+
+/// 按输出格式替换结果文件扩展名，例如纯文本模式下生成 `.txt`。
+fn transcript_output_path(
+    original: &Path,
+    track_label: Option<&str>,
+    format: OutputFormat,
+) -> PathBuf {
+    transcript_result_path(original, track_label).with_extension(format.extension())
+}
+
+/// 以文件名主干 `stem` 生成与原始文件同目录的转写结果 `.srt` 路径，可附带音轨标签
+/// （如 `轨道2`、`jpn`）。
+fn result_path_with_stem(
+    original: &Path,
+    stem: Option<&OsStr>,
+    track_label: Option<&str>,
+) -> PathBuf {
+    let suffix = match track_label {
+        Some(label) => format!(".{}.srt", label),
+        None => ".srt".to_string(),
+    };
+    sibling_path(original, stem, "result", &suffix)
+}
+
+/// Windows 等文件系统不允许出现在文件名中的字符。
+const UNSAFE_FILENAME_CHARS: &[char] = &['<', '>', ':', '"', '/', '\\', '|', '?', '*'];
+
+/// 把文件名主干中的不安全字符（`<>:"/\|?*` 与控制字符）替换为 `substitute`，并去掉
+/// Windows 不允许的结尾空格和句点。`substitute` 本身含不安全字符时改用 `_`；清理后为空时返回 `_`。
+pub fn sanitize_output_filename(stem: &str, substitute: &str) -> String {
+    let is_unsafe = |c: char| UNSAFE_FILENAME_CHARS.contains(&c) || c.is_control();
+    let substitute = if substitute.chars().any(is_unsafe) {
+        "_"
+    } else {
+        substitute
+    };
+    let mut sanitized = String::with_capacity(stem.len());
+    for c in stem.chars() {
+        if is_unsafe(c) {
+            sanitized.push_str(substitute);
+        } else {
+            sanitized.push(c);
+        }
+    }
+    let trimmed = sanitized.trim_end_matches(['.', ' ']);
+    if trimmed.is_empty() {
+        "_".to_string()
+    } else {
+        trimmed.to_string()
+    }
+}
+
+/// 在源文件名（或其主干 `base`）后拼接 `suffix`，得到同目录下的派生文件路径；取不到文件名时
+/// 使用 `fallback`。按 `OsStr` 拼接而非先转成 UTF-8，非 UTF-8 文件名也能与源文件一一对应。
+fn sibling_path(original: &Path, base: Option<&OsStr>, fallback: &str, suffix: &str) -> PathBuf {
+    let mut name = base
+        .map(OsStr::to_os_string)
+        .unwrap_or_else(|| OsString::from(fallback));
+    name.push(suffix);
+    original.with_file_name(name)
+}
+
+/// 按音轨编号命名的结果标签，如 `轨道2`。
+fn numbered_track_label(track_index: u32) -> String {
+    format!("轨道{}", track_index)
+}
+
+/// 计算各音轨在结果文件名中的标签。
+///
+/// `by_language` 时使用语言标签（如 `jpn`）；没有标签、标签为 `und` 或与其他音轨重复时
+/// 回退为音轨编号，保证同一视频的结果不会互相覆盖。标签按视频的全部音轨计算，
+/// 不受音轨选择影响，结果文件名在调整选择后保持不变。
+fn track_labels(streams: &[AudioStream], by_language: bool) -> HashMap<u32, String> {
+    let languages: Vec<Option<String>> = streams
+        .iter()
+        .map(|stream| stream.filename_language().filter(|_| by_language))
+        .collect();
+    streams
+        .iter()
+        .zip(&languages)
+        .map(|(stream, language)| {
+            let unique = language.as_ref().filter(|language| {
+                languages
+                    .iter()
+                    .filter(|other| other.as_ref() == Some(*language))
+                    .count()
+                    == 1
+            });
+            let label = unique
+                .cloned()
+                .unwrap_or_else(|| numbered_track_label(stream.index));
+            (stream.index, label)
+        })
+        .collect()
+}
+
+/// 查找其他工具生成的外挂字幕，如 `movie.zh.srt`、`movie.chi.srt`。
+///
+/// 只匹配 `suffixes` 中列出的语言后缀（不区分大小写），本程序自己的 `movie.srt`
+/// 与多音轨结果由 [`check_transcript`] 处理。
+fn has_existing_subtitle(
+    original: &Path,
+    suffixes: &[String],
+    own: &[PathBuf],
+    listing: &mut SubtitleListing,
+) -> Option<PathBuf> {
+    if suffixes.is_empty() {
+        return None;
+    }
+    let stem = original.file_stem()?.to_string_lossy().to_lowercase();
+    let parent = original.parent()?;
+    listing
+        .srt_files(parent)
+        .iter()
+        .find(|candidate| {
+            let Some(name) = candidate.file_name() else {
+                return false;
+            };
+            let name = name.to_string_lossy().to_lowercase();
+            let Some(middle) = name
+                .strip_prefix(&stem)
+                .and_then(|rest| rest.strip_prefix('.'))
+                .and_then(|rest| rest.strip_suffix(".srt"))
+            else {
+                return false;
+            };
+            suffixes
+                .iter()
+                .any(|suffix| suffix.eq_ignore_ascii_case(middle))
+                && !own.contains(candidate)
+        })
+        .cloned()
+}
+
+/// 按目录缓存的 `.srt` 文件列表，同一目录在一次遍历中只读取一次，
+/// 避免大目录中每个媒体文件都重新列出整个目录。
+#[derive(Debug, Default)]
+struct SubtitleListing {
+    dirs: HashMap<PathBuf, Vec<PathBuf>>,
+}
+
+impl SubtitleListing {
+    /// `dir` 下的 `.srt` 文件（扩展名不区分大小写），首次访问时读取目录；无法读取时视为空。
+    fn srt_files(&mut self, dir: &Path) -> &[PathBuf] {
+        self.dirs.entry(dir.to_path_buf()).or_insert_with(|| {
+            std::fs::read_dir(dir)
+                .map(|entries| {
+                    entries
+                        .filter_map(|entry| entry.ok())
+                        .map(|entry| entry.path())
+                        .filter(|path| {
+                            path.extension()
+                                .is_some_and(|ext| ext.eq_ignore_ascii_case("srt"))
+                                && path.is_file()
+                        })
+                        .collect()
+                })
+                .unwrap_or_default()
+        })
+    }
+}
+
+/// 与字幕同名的 `.json` 分段明细文件。
+fn transcript_json_path(srt_path: &Path) -> PathBuf {
+    srt_path.with_extension("json")
+}
+
+/// 分段转写进行中的字幕文件，完成后重命名为正式 `.srt`。
+fn partial_transcript_path(srt_path: &Path) -> PathBuf {
+    let mut name = srt_path
+        .file_name()
+        .map(|name| name.to_os_string())
+        .unwrap_or_default();
+    name.push(".partial");
+    srt_path.with_file_name(name)
+}
+
+/// 已转写分段的缓存文件，与 `.partial` 字幕并列，中断后重跑时只上传缺失的分段。
+/// 首行为缓存键，其后每行一个分段，逐段追加而非整体重写。
+fn segment_cache_path(srt_path: &Path) -> PathBuf {
+    let mut name = srt_path
+        .file_name()
+        .map(|name| name.to_os_string())
+        .unwrap_or_default();
+    name.push(".partial.jsonl");
+    srt_path.with_file_name(name)
+}
+
+/// 单个已转写分段，按分段序号与时间范围匹配；空结果同样记录，避免重复上传。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedSegment {
+    index: usize,
+    start_sec: f64,
+    end_sec: f64,
+    text: String,
+}
+
+/// 分段缓存文件的首行，记录写入时的缓存键。
+#[derive(Debug, Serialize, Deserialize)]
+struct SegmentCacheHeader {
+    key: String,
+}
+
+/// 分段转写缓存，`key` 由 [`segment_cache_key`] 计算，源文件、分段参数或接口与模型变化时整体失效。
+#[derive(Debug, Default)]
+struct SegmentCache {
+    key: String,
+    segments: Vec<CachedSegment>,
+}
+
+impl SegmentCache {
+    /// 查找与当前分段序号和时间范围一致的缓存结果。
+    fn lookup(&self, index: usize, segment: &SpeechSegment) -> Option<&str> {
+        self.segments
+            .iter()
+            .find(|cached| {
+                cached.index == index
+                    && (cached.start_sec - segment.start_sec).abs() < 1e-6
+                    && (cached.end_sec - segment.end_sec).abs() < 1e-6
+            })
+            .map(|cached| cached.text.as_str())
+    }
+}
+
+/// 读取与 `key` 匹配的分段缓存；文件缺失或键不一致时返回空缓存，
+/// 中断时写了一半的末行直接忽略。
+async fn load_segment_cache(path: &Path, key: &str) -> SegmentCache {
+    let content = fs::read_to_string(path).await.unwrap_or_default();
+    let mut lines = content.lines();
+    let matches = lines
+        .next()
+        .and_then(|line| serde_json::from_str::<SegmentCacheHeader>(line).ok())
+        .is_some_and(|header| header.key == key);
+    let segments = if matches {
+        lines
+            .filter_map(|line| serde_json::from_str::<CachedSegment>(line).ok())
+            .collect()
+    } else {
+        Vec::new()
+    };
+    SegmentCache {
+        key: key.to_string(),
+        segments,
+    }
+}
+
+/// 以当前键和已命中的分段重建缓存文件，之后的分段通过 [`append_segment_cache`] 逐行追加。
+async fn save_segment_cache(path: &Path, cache: &SegmentCache) -> Result<()> {
+    let mut content = serde_json::to_string(&SegmentCacheHeader {
+        key: cache.key.clone(),
+    })?;
+    content.push('\n');
+    for segment in &cache.segments {
+        content.push_str(&serde_json::to_string(segment)?);
+        content.push('\n');
+    }
+    write_atomic(path, content).await
+}
+
+/// 向分段缓存追加一个新完成的分段，只写入一行，不随分段数增长而重写整个文件。
+async fn append_segment_cache(path: &Path, segment: &CachedSegment) -> Result<()> {
+    let mut line = serde_json::to_string(segment)?;
+    line.push('\n');
+    append_srt_entry(path, &line).await
+}
+
+/// 基于原始视频生成指定音轨的导出文件名，`ext` 为导出编码对应的扩展名。
+fn audio_track_path(original: &Path, track_index: u32, ext: &str) -> PathBuf {
+    let suffix = format!("-track{}.{}", track_index, ext);
+    sibling_path(original, original.file_name(), "audio", &suffix)
+}
+
+/// 纯音频需要加速或截取尾部时使用的临时文件名。
+fn prepared_audio_path(original: &Path, track_index: Option<u32>, ext: &str) -> PathBuf {
+    let suffix = format!("{}-prepared.{}", track_file_suffix(track_index), ext);
+    sibling_path(original, original.file_name(), "audio", &suffix)
+}
+
+/// 记录上次转写时长的状态文件，位于字幕旁。
+fn transcript_state_path(srt_path: &Path) -> PathBuf {
+    let mut name = srt_path
+        .file_name()
+        .map(|name| name.to_os_string())
+        .unwrap_or_default();
+    name.push(".state.json");
+    srt_path.with_file_name(name)
+}
+
+async fn read_transcript_state(srt_path: &Path) -> Option<TranscriptState> {
+    let content = fs::read_to_string(transcript_state_path(srt_path))
+        .await
+        .ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+async fn write_transcript_state(srt_path: &Path, duration_secs: f64) -> Result<()> {
+    let content = serde_json::to_string(&TranscriptState { duration_secs })?;
+    write_atomic(&transcript_state_path(srt_path), content).await
+}
+
+fn count_srt_cues(content: &str) -> usize {
+    content.lines().filter(|line| line.contains("-->")).count()
+}
+
+/// 对已有结果文件的判定。
+enum TranscriptCheck {
+    /// 结果有效，跳过。
+    Done,
+    /// 没有结果或结果无效，从头转写。
+    Transcribe,
+    /// 文件增长，只转写新增部分并追加。
+    Append(AppendState),
+}
+
+/// 判断某个目标是否需要转写：强制重转、结果缺失或失效时从头转写，增长的文件改为追加。
+async fn check_transcript(
+    media: &Path,
+    transcript_path: &Path,
+    options: &ScannerOptions,
+    logger: &mut ScanLogger,
+) -> TranscriptCheck {
+    if options.force_reprocess || !transcript_path.exists() {
+        return TranscriptCheck::Transcribe;
+    }
+    // 增量追加模式下源文件本就会比结果新，由时长记录判断是否变长，只检查空结果。
+    if let Some(reason) =
+        stale_transcript_reason(media, transcript_path, !options.append_growing_files)
+    {
+        logger.info(tr!("重新转写 {}：{}。", display_path(&media), reason));
+        return TranscriptCheck::Transcribe;
+    }
+    if options.append_growing_files {
+        if let Some(state) = detect_append(media, transcript_path, options, logger).await {
+            return TranscriptCheck::Append(state);
+        }
+    }
+    TranscriptCheck::Done
+}
+
+/// 已有结果为空，或（`check_mtime` 时）早于源文件的修改时间，则返回需要重新转写的原因。
+fn stale_transcript_reason(
+    media: &Path,
+    transcript_path: &Path,
+    check_mtime: bool,
+) -> Option<&'static str> {
+    let transcript = std::fs::metadata(transcript_path).ok()?;
+    if transcript.len() == 0 {
+        return Some(tr!("已有结果为空"));
+    }
+    if check_mtime {
+        let media_modified = std::fs::metadata(media).and_then(|m| m.modified()).ok()?;
+        if transcript.modified().ok()? < media_modified {
+            return Some(tr!("源文件在上次转写后被修改"));
+        }
+    }
+    None
+}
+
+/// 检查已转写文件是否在上次转写后变长，若是则返回续写所需的状态。
+async fn detect_append(
+    media: &Path,
+    transcript_path: &Path,
+    options: &ScannerOptions,
+    logger: &mut ScanLogger,
+) -> Option<AppendState> {
+    let state = read_transcript_state(transcript_path).await?;
+    let current = media_duration(media, options).await.ok()?;
+    let growth = current - state.duration_secs;
+    if growth < APPEND_MIN_GROWTH_SECS {
+        return None;
+    }
+    let existing = fs::read_to_string(transcript_path).await.ok()?;
+    logger.info(tr!(
+        "{} 自上次转写后增长了 {:.1} 秒，将仅转写新增部分并追加到 {}。",
+        display_path(&media),
+        growth,
+        display_path(&transcript_path)
+    ));
+    Some(AppendState {
+        offset: state.duration_secs,
+        existing_cues: count_srt_cues(&existing),
+    })
+}
+
+fn segment_audio_path(
+    original: &Path,
+    track_index: Option<u32>,
+    segment_idx: usize,
+    ext: &str,
+) -> PathBuf {
+    let suffix = format!(
+        "{}-seg{}.{}",
+        track_file_suffix(track_index),
+        segment_idx,
+        ext
+    );
+    sibling_path(original, original.file_name(), "segment", &suffix)
+}
+
+fn vad_cache_path(original: &Path, track_index: Option<u32>) -> PathBuf {
+    let suffix = format!("{}.vadcache.json", track_file_suffix(track_index));
+    sibling_path(original, original.file_name(), "segment", &suffix)
+}
+
+/// 保存在媒体旁的 VAD 检测结果，扫描中断后重跑时可跳过 PCM 转换与检测。
+#[derive(Debug, Serialize, Deserialize)]
+struct VadCache {
+    /// 源文件路径、修改时间、VAD 参数等的哈希，任一变化即失效。
+    key: String,
+    /// 参与检测的音频时长（秒）。
+    total_duration: f64,
+    /// 检测出的语音段（尚未补齐静音间隔）。
+    segments: Vec<SpeechSegment>,
+}
+
+/// 源文件身份、修改时间、起始偏移与预处理选项组成的缓存键前缀。
+async fn source_cache_identity(
+    source: &AudioSource,
+    options: &ScannerOptions,
+) -> Result<serde_json::Value> {
+    let modified = fs::metadata(source.original_path())
+        .await?
+        .modified()?
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default();
+    Ok(serde_json::json!({
+        "path": source.original_path().to_string_lossy(),
+        "track": source.track_index(),
+        "modified": [modified.as_secs(), modified.subsec_nanos()],
+        "start_offset": source.start_offset().to_bits(),
+        "normalize": options.audio_normalize,
+        "downmix": options.downmix_filter,
+    }))
+}
+
+/// 对序列化后的键字段取 SHA-256；结果不随工具链版本变化，可安全写入磁盘缓存。
+fn cache_digest(fields: &serde_json::Value) -> Result<String> {
+    let digest = Sha256::digest(serde_json::to_vec(fields)?);
+    Ok(digest.iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+
+/// 分段缓存键：在分段方式的缓存键之上再覆盖接口地址、模型等影响转写文本的参数，
+/// 切换服务或模型后不会复用旧模型的结果。
+fn segment_cache_key(segmentation_key: &str, options: &ScannerOptions) -> Result<String> {
+    cache_digest(&serde_json::json!({
+        "segmentation": segmentation_key,
+        "api_url": options.api_url,
+        "model": options.model_name,
+        "flavor": options.api_flavor,
+        "word_timestamps": options.word_timestamps,
+        "speedup": options.speedup_factor.map(f32::to_bits),
+    }))
+}
+
+/// 计算 VAD 缓存键：覆盖源文件身份、修改时间以及所有影响检测结果的参数。
+async fn vad_cache_key(
+    source: &AudioSource,
+    options: &ScannerOptions,
+    vad_cfg: &VadConfig,
+) -> Result<String> {
+    cache_digest(&serde_json::json!({
+        "source": source_cache_identity(source, options).await?,
+        "vad": {
+            "threshold": vad_cfg.threshold.to_bits(),
+            "min_speech_secs": vad_cfg.min_speech_secs.to_bits(),
+            "padding_chunks": vad_cfg.padding_chunks,
+            "sample_rate": vad_cfg.sample_rate,
+            "chunk_size": vad_cfg.chunk_size,
+            "adaptive_threshold": vad_cfg.adaptive_threshold,
+            "max_segment_secs": vad_cfg.max_segment_secs.to_bits(),
+            "smoothing_window": vad_cfg.smoothing_window,
+        },
+    }))
+}
+
+/// 固定间隔分段的缓存键，间隔变化后已缓存的分段文本随之失效。
+async fn fixed_interval_cache_key(
+    source: &AudioSource,
+    options: &ScannerOptions,
+    interval_secs: f64,
+) -> Result<String> {
+    cache_digest(&serde_json::json!({
+        "source": source_cache_identity(source, options).await?,
+        "fixed_interval": interval_secs.to_bits(),
+    }))
+}
+
+/// 读取与 `key` 匹配的 VAD 缓存；文件缺失、损坏或键不一致时返回 `None`。
+async fn load_vad_cache(path: &Path, key: &str) -> Option<VadCache> {
+    let content = fs::read_to_string(path).await.ok()?;
+    let cache: VadCache = serde_json::from_str(&content).ok()?;
+    (cache.key == key).then_some(cache)
+}
+
+async fn save_vad_cache(path: &Path, cache: &VadCache) -> Result<()> {
+    write_atomic(path, serde_json::to_string(cache)?).await
+}
+
+fn vad_audio_path(original: &Path, track_index: Option<u32>) -> PathBuf {
+    let suffix = format!("{}-vad.wav", track_file_suffix(track_index));
+    sibling_path(original, original.file_name(), "segment", &suffix)
+}
+
+fn track_file_suffix(track_index: Option<u32>) -> String {
+    track_index
+        .map(|idx| format!("-track{}", idx))
+        .unwrap_or_default()
+}
+
+/// 单个音频源成功转写后的输出信息。
+struct TranscriptOutput {
+    path: PathBuf,
+    segments: usize,
+    /// 字幕覆盖到的原始媒体时长（秒），时长为估算值时为 `None`。
+    covered_until: Option<f64>,
+    /// API 报告的上传音频时长之和（秒），未报告的请求不计入。
+    api_duration: f64,
+    /// 本次转写的音频时长（秒），增量追加时只计新增部分。
+    audio_secs: f64,
+}
+
+async fn process_audio_source(
+    options: Arc<ScannerOptions>,
+    client: &Client,
+    mut source: AudioSource,
+    logger: &mut ScanLogger,
+) -> Result<TranscriptOutput> {
+    // 中间文件与结果都放在源文件映射到输出目录后的同一文件夹中。
+    options
+        .ensure_output_parent(&options.relocate(source.original_path().to_path_buf()))
+        .await?;
+    source.probe_channels(&options, logger).await;
+    if options.vad.is_some() && options.api_flavor == ApiFlavor::OpenAiWhisper {
+        logger.info(tr!(
+            "{} 使用 Whisper 接口返回的分段时间戳，跳过本地 VAD。",
+            source.display_name()
+        ));
+    } else if let SegmentMode::FixedInterval(interval_secs) = options.segment_mode {
+        return process_with_fixed_intervals(&options, client, &source, interval_secs, logger)
+            .await;
+    } else if let Some(vad_cfg) = options.vad.clone() {
+        match process_with_vad(&options, client, &source, &vad_cfg, logger).await {
+            Ok(output) => return Ok(output),
+            Err(err) if is_tool_timeout(&err) || is_cancelled(&err) || is_auth_failure(&err) => {
+                return Err(err)
+            }
+            Err(err) => {
+                logger.info(tr!(
+                    "VAD 分段失败（{}），回退整段上传：{}",
+                    err,
+                    source.display_name()
+                ));
+            }
+        }
+    }
+
+    process_without_vad(&options, client, &source, logger).await
+}
+
+async fn process_without_vad(
+    options: &ScannerOptions,
+    client: &Client,
+    source: &AudioSource,
+    logger: &mut ScanLogger,
+) -> Result<TranscriptOutput> {
+    let target_name = source.display_name();
+    let speedup = options.speedup();
+    let materialized = source
+        .materialize_full_audio(options)
+        .await
+        .map_err(|err| {
+            if is_cancelled(&err) {
+                err
+            } else {
+                anyhow!(tr!("准备 {} 音频失败：{}", target_name, err))
+            }
+        })?;
+
+    logger.info(tr!(
+        "开始转写 {}，音频源 {}",
+        target_name,
+        display_path(&materialized.path)
+    ));
+
+    let result =
+        transcribe_whole(options, client, source, &materialized.path, speedup, logger).await;
+
+    if options.keep_intermediates && materialized.cleanup {
+        logger.info(tr!("已保留临时音轨：{}", display_path(&materialized.path)));
+    }
+    if let Err(err) = cleanup_materialized(materialized, options).await {
+        logger.info(tr!("清理临时音轨失败：{}", err));
+    }
+
+    result
+}
+
+/// 文件超过 [`ScannerOptions::max_file_size_mb`] 时返回其大小（MB，向上取整）。
+fn oversized_mb(path: &Path, options: &ScannerOptions) -> Option<u64> {
+    let limit = options.max_file_size_mb?;
+    let size = std::fs::metadata(path).ok()?.len();
+    let size_mb = size.div_ceil(1024 * 1024);
+    (size > limit.saturating_mul(1024 * 1024)).then_some(size_mb)
+}
+
+/// 整段上传已准备好的音频并写入单条字幕。
+async fn transcribe_whole(
+    options: &ScannerOptions,
+    client: &Client,
+    source: &AudioSource,
+    audio_path: &Path,
+    speedup: Option<f64>,
+    logger: &mut ScanLogger,
+) -> Result<TranscriptOutput> {
+    let target_name = source.display_name();
+    if options.cancel.is_cancelled() {
+        return Err(ScanCancelled.into());
+    }
+    if let Some(size_mb) = oversized_mb(audio_path, options) {
+        return Err(anyhow!(tr!(
+            "{} 待上传音频 {} MB 超过上限 {} MB，已跳过。",
+            target_name,
+            size_mb,
+            options.max_file_size_mb.unwrap_or_default()
+        )));
+    }
+    throttle_request(options, &target_name, logger).await?;
+    let transcription = cancellable(&options.cancel, async {
+        let _slot = acquire_slot(&options.upload_limiter).await;
+        transcribe_file(
+            client,
+            &options.api_key,
+            &options.api_url,
+            &options.model_name,
+            options.api_flavor,
+            options.request_timeout(),
+            &options.mime_overrides,
+            options.word_timestamps,
+            audio_path,
+        )
+        .await
+        .map_err(|e| {
+            let message = tr!("调用 API 转写 {} 失败：{}", target_name, e);
+            api_failure(e, message)
+        })
+    })
+    .await?;
+
+    let trimmed = transcription.text.trim();
+    if trimmed.is_empty() && transcription.segments.is_empty() {
+        return Err(anyhow!(tr!("{} 的识别结果为空，跳过写入。", target_name)));
+    }
+
+    let (duration, measured) = match media_duration(audio_path, options).await {
+        Ok(value) => (rescale_to_original(value, speedup).max(0.5), true),
+        Err(e) => {
+            logger.info(tr!(
+                "无法获取 {} 的时长（{}），使用估算值。",
+                display_path(&audio_path),
+                e
+            ));
+            let estimated = transcription
+                .segments
+                .last()
+                .map(|seg| rescale_to_original(seg.end, speedup))
+                .unwrap_or_else(|| estimate_duration_from_text(trimmed));
+            (estimated, false)
+        }
+    };
+
+    let offset = source.start_offset();
+    if options.word_timestamps && transcription.words.is_empty() {
+        logger.info(tr!(
+            "{} 的接口未返回逐词时间戳，按分段时间生成字幕。",
+            target_name
+        ));
+    }
+    let word_cues = word_level_cues(options, &transcription, speedup);
+    let by_word = word_cues.is_some();
+    let cues = if let Some(cues) = word_cues {
+        cues
+    } else if !transcription.segments.is_empty() {
+        // 服务端已给出分段时间戳，直接换算回原始时间轴使用。
+        transcription
+            .segments
+            .iter()
+            .map(|seg| {
+                (
+                    rescale_to_original(seg.start, speedup),
+                    rescale_to_original(seg.end, speedup),
+                    seg.text.clone(),
+                )
+            })
+            .collect()
+    } else if options.split_whole_cues {
+        split_text_into_cues(trimmed, duration)
+    } else {
+        vec![(0.0, duration, trimmed.to_string())]
+    };
+    // 整段上传时只能使用服务端返回的说话人标签，没有分段时无法区分。
+    let speakers = if options.diarize && !transcription.segments.is_empty() {
+        let labels: Vec<Option<String>> = transcription
+            .segments
+            .iter()
+            .map(|seg| seg.speaker.clone())
+            .collect();
+        let by_segment = speakers_from_labels(&labels);
+        if by_word {
+            // 逐词字幕按中点落在哪个服务端分段内取其说话人。
+            cues.iter()
+                .map(|(start, end, _)| {
+                    let mid = (start + end) / 2.0;
+                    transcription
+                        .segments
+                        .iter()
+                        .position(|seg| {
+                            rescale_to_original(seg.start, speedup) <= mid
+                                && mid <= rescale_to_original(seg.end, speedup)
+                        })
+                        .and_then(|idx| by_segment[idx])
+                })
+                .collect()
+        } else {
+            by_segment
+        }
+    } else {
+        vec![None; cues.len()]
+    };
+    // 后处理放在说话人推断之后，被清理规则清空的字幕连同其说话人一起丢弃。
+    let (cues, speakers): (Vec<_>, Vec<_>) = cues
+        .into_iter()
+        .zip(speakers)
+        .filter_map(|((start, end, text), speaker)| {
+            let cleaned = options.text_cleanup.apply(&text);
+            (!cleaned.is_empty() || text.trim().is_empty())
+                .then_some(((start, end, cleaned), speaker))
+        })
+        .unzip();
+    let mut entries = Vec::with_capacity(cues.len());
+    let mut json_segments = Vec::with_capacity(cues.len());
+    for (idx, ((start, end, chunk), speaker)) in cues.iter().zip(&speakers).enumerate() {
+        let index = source.existing_cues() + idx + 1;
+        entries.push((offset + start, offset + end, label_speaker(chunk, *speaker)));
+        json_segments.push(TranscriptSegment {
+            index,
+            start_sec: offset + start,
+            end_sec: offset + end,
+            text: chunk.clone(),
+            kind: SegmentKind::Speech,
+            speaker: speaker.map(|idx| idx + 1),
+        });
+    }
+    let srt_path = options.transcript_path(source.original_path(), source.track_label());
+    let content: String = match options.output_format {
+        OutputFormat::Ass if source.append.is_none() => {
+            build_ass_file(&options.ass_style, &entries)
+        }
+        format => entries
+            .iter()
+            .enumerate()
+            .map(|(idx, (start, end, text))| {
+                format.render_entry(source.existing_cues() + idx + 1, *start, *end, text)
+            })
+            .collect(),
+    };
+    let written = if source.append.is_some() {
+        append_atomic(&srt_path, &content).await
+    } else {
+        write_atomic(&srt_path, content).await
+    };
+    written.map_err(|e| anyhow!(tr!("写入 {} 失败：{}", target_name, e)))?;
+    if options.output_format == OutputFormat::Json {
+        write_json_transcript(&srt_path, json_segments, source.append.is_some())
+            .await
+            .map_err(|e| anyhow!(tr!("写入 {} 的 JSON 失败：{}", target_name, e)))?;
+    }
+    logger.success(tr!(
+        "完成 {}，结果输出 {}",
+        target_name,
+        display_path(&srt_path)
+    ));
+    Ok(TranscriptOutput {
+        path: srt_path,
+        segments: cues.len(),
+        covered_until: measured.then_some(offset + duration),
+        api_duration: transcription.duration.unwrap_or(0.0),
+        audio_secs: duration,
+    })
+}
+
+/// 转换 PCM 并运行 VAD，返回检测出的语音段与音频时长（秒）。
+async fn detect_with_vad(
+    options: &ScannerOptions,
+    source: &AudioSource,
+    vad_cfg: &VadConfig,
+    logger: &mut ScanLogger,
+) -> Result<(Vec<SpeechSegment>, f64)> {
+    validate_vad_format(vad_cfg.sample_rate, vad_cfg.chunk_size)?;
+    let pcm_path = source
+        .convert_to_pcm16(
+            options,
+            vad_cfg.sample_rate,
+            None,
+            options.relocate(vad_audio_path(source.original_path(), source.track_index())),
+        )
+        .await?;
+    let samples = read_wav_samples(&pcm_path, vad_cfg.sample_rate).await?;
+    if options.keep_intermediates {
+        logger.info(tr!("已保留 VAD 用 PCM 音频：{}", display_path(&pcm_path)));
+    } else {
+        let _ = fs::remove_file(&pcm_path).await;
+    }
+    let total_duration = samples.len() as f64 / vad_cfg.sample_rate as f64;
+
+    let mut vad_cfg = vad_cfg.clone();
+    if vad_cfg.adaptive_threshold {
+        if let Some(levels) = measure_levels(&samples, vad_cfg.chunk_size) {
+            let adapted = adapt_threshold(vad_cfg.threshold, &levels);
+            logger.info(tr!(
+                "{} 自适应阈值：语音电平 {:.1} dBFS，噪声底 {:.1} dBFS，阈值 {:.2} → {:.2}。",
+                source.display_name(),
+                levels.speech_level_db,
+                levels.noise_floor_db,
+                vad_cfg.threshold,
+                adapted
+            ));
+            vad_cfg.threshold = adapted;
+        }
+    }
+
+    let (segments, probabilities) = detect_speech_segments(&samples, &vad_cfg)?;
+    // 切分按分块边界重新换算时间，结尾一段可能再次越过音频末尾。
+    let mut segments = clamp_segments_to_duration(
+        split_long_segments(
+            segments,
+            &probabilities,
+            vad_cfg.secs_to_chunks_exact(vad_cfg.max_segment_secs),
+            &vad_cfg,
+        ),
+        total_duration,
+    );
+    let rate = vad_cfg.sample_rate as f64;
+    for segment in &mut segments {
+        let start = ((segment.start_sec * rate) as usize).min(samples.len());
+        let end = ((segment.end_sec * rate) as usize).clamp(start, samples.len());
+        segment.level_db = Some(rms_db(&samples[start..end]));
+    }
+    Ok((segments, total_duration))
+}
+
+/// VAD 预览结果：分析的音频时长、实际使用的阈值与检测出的语音段。
+#[derive(Debug, Clone)]
+pub struct VadPreview {
+    pub path: PathBuf,
+    pub analyzed_secs: f64,
+    /// 启用自适应阈值时为按电平微调后的阈值。
+    pub threshold: f32,
+    /// 语音段的起止时间（秒），已按合并间隔合并。
+    pub segments: Vec<(f64, f64)>,
+}
+
+impl VadPreview {
+    /// 供日志显示的摘要：首行为统计，其后每行一个语音段。
+    pub fn describe(&self) -> Vec<String> {
+        let speech: f64 = self.segments.iter().map(|(start, end)| end - start).sum();
+        let mut lines = vec![tr!(
+            "VAD 预览 {}（前 {:.0} 秒，阈值 {:.2}）：检测到 {} 段语音，共 {:.1} 秒。",
+            display_path(&self.path),
+            self.analyzed_secs,
+            self.threshold,
+            self.segments.len(),
+            speech
+        )];
+        lines.extend(self.segments.iter().enumerate().map(|(idx, (start, end))| {
+            tr!(
+                "  {}. {} - {}（{:.1} 秒）",
+                idx + 1,
+                format_timestamp(*start),
+                format_timestamp(*end),
+                end - start
+            )
+        }));
+        lines
+    }
+}
+
+/// 只分析媒体开头 [`VAD_PREVIEW_SECS`] 秒并按 `vad_cfg` 检测语音段，不上传、不读写 VAD 缓存，
+/// 便于调整阈值与最短片段后立即查看分段效果。视频使用第一条音轨。
+pub async fn preview_vad(
+    path: PathBuf,
+    options: ScannerOptions,
+    vad_cfg: VadConfig,
+) -> Result<VadPreview> {
+    validate_vad_format(vad_cfg.sample_rate, vad_cfg.chunk_size)?;
+    let mut source = if is_video(&path) {
+        let stream = audio_streams(&path, &options)
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow!(tr!("{} 中没有音轨", display_path(&path))))?;
+        AudioSource::from_video_track(path.clone(), stream.index)
+    } else {
+        AudioSource::from_audio_file(path.clone())
+    };
+    // 预览没有日志面板可写，声道不足的说明留给正式扫描。
+    source
+        .probe_channels(&options, &mut ScanLogger::new(None))
+        .await;
+    let pcm_path = source
+        .convert_to_pcm16(
+            &options,
+            vad_cfg.sample_rate,
+            Some(VAD_PREVIEW_SECS),
+            vad_preview_audio_path(),
+        )
+        .await?;
+    let samples = read_wav_samples(&pcm_path, vad_cfg.sample_rate).await;
+    let _ = fs::remove_file(&pcm_path).await;
+    let samples = samples?;
+
+    let mut vad_cfg = vad_cfg;
+    if vad_cfg.adaptive_threshold {
+        if let Some(levels) = measure_levels(&samples, vad_cfg.chunk_size) {
+            vad_cfg.threshold = adapt_threshold(vad_cfg.threshold, &levels);
+        }
+    }
+    let max_samples = (VAD_PREVIEW_SECS * vad_cfg.sample_rate as f64) as usize;
+    let (segments, analyzed_secs) = task::spawn_blocking({
+        let vad_cfg = vad_cfg.clone();
+        move || detect_speech_segments_preview(&samples, &vad_cfg, max_samples)
+    })
+    .await??;
+    Ok(VadPreview {
+        path,
+        analyzed_secs,
+        threshold: vad_cfg.threshold,
+        segments: segments
+            .iter()
+            .map(|seg| (seg.start_sec, seg.end_sec))
+            .collect(),
+    })
+}
+
+/// 预览用的临时 PCM 音频放在系统临时目录下且每次唯一，
+/// 不会与同一文件正在进行的扫描共用（并删除）其 VAD 音频。
+fn vad_preview_audio_path() -> PathBuf {
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+    env::temp_dir().join(format!(
+        "auto_asr_vad_preview_{}_{}.wav",
+        std::process::id(),
+        NEXT.fetch_add(1, Ordering::Relaxed)
+    ))
+}
+
+/// 只检测前 `max_samples` 个采样，并与正式转写一样切分过长语音段、合并短间隔并加上首尾留白，
+/// 返回语音段与实际分析的时长（秒）。
+fn detect_speech_segments_preview(
+    samples: &[i16],
+    cfg: &VadConfig,
+    max_samples: usize,
+) -> Result<(Vec<SpeechSegment>, f64)> {
+    let samples = &samples[..samples.len().min(max_samples)];
+    let analyzed = samples.len() as f64 / cfg.sample_rate as f64;
+    let (segments, probabilities) = detect_speech_segments(samples, cfg)?;
+    let segments = clamp_segments_to_duration(
+        split_long_segments(
+            segments,
+            &probabilities,
+            cfg.secs_to_chunks_exact(cfg.max_segment_secs),
+            cfg,
+        ),
+        analyzed,
+    );
+    let segments = merge_close_segments(
+        segments,
+        cfg.merge_gap_secs as f64,
+        cfg.max_segment_secs as f64,
+    );
+    Ok((pad_segments(segments, cfg, analyzed), analyzed))
+}
+
+async fn process_with_vad(
+    options: &ScannerOptions,
+    client: &Client,
+    source: &AudioSource,
+    vad_cfg: &VadConfig,
+    logger: &mut ScanLogger,
+) -> Result<TranscriptOutput> {
+    let display_name = source.display_name();
+    logger.info(tr!("{} 启用 VAD，准备语音分段。", display_name));
+
+    let cache_path = options.relocate(vad_cache_path(source.original_path(), source.track_index()));
+    let cache_key = vad_cache_key(source, options, vad_cfg).await.ok();
+    let cached = match &cache_key {
+        Some(key) => load_vad_cache(&cache_path, key).await,
+        None => None,
+    };
+    let (speech_segments, total_duration) = match cached {
+        Some(cache) => {
+            logger.info(tr!("{} 复用上次的 VAD 检测结果。", display_name));
+            (cache.segments, cache.total_duration)
+        }
+        None => {
+            let detected = detect_with_vad(options, source, vad_cfg, logger).await?;
+            if let Some(key) = &cache_key {
+                let cache = VadCache {
+                    key: key.clone(),
+                    total_duration: detected.1,
+                    segments: detected.0.clone(),
+                };
+                if let Err(e) = save_vad_cache(&cache_path, &cache).await {
+                    logger.info(tr!("写入 VAD 缓存失败：{}", e));
+                }
+            }
+            detected
+        }
+    };
+    if speech_segments.is_empty() {
+        return Err(anyhow!(tr!("未检测到有效语音")));
+    }
+
+    // 缓存保存的是原始检测结果，合并在读取后进行，调整合并间隔无需重新检测。
+    let detected_count = speech_segments.len();
+    let speech_segments = merge_close_segments(
+        speech_segments,
+        vad_cfg.merge_gap_secs as f64,
+        vad_cfg.max_segment_secs as f64,
+    );
+    if speech_segments.len() < detected_count {
+        logger.info(tr!(
+            "合并间隔小于 {:.2} 秒的相邻语音段：{} 段 → {} 段。",
+            vad_cfg.merge_gap_secs,
+            detected_count,
+            speech_segments.len()
+        ));
+    }
+    // 留白按合并后的语音段计算，与合并一样不写入缓存，调整后无需重新检测。
+    let speech_segments = pad_segments(speech_segments, vad_cfg, total_duration);
+
+    let segments = apply_gap_policy(
+        expand_segments_with_gaps(&speech_segments, total_duration),
+        vad_cfg.gap_policy,
+    );
+    let speakers = if options.diarize {
+        assign_speakers(&segments)
+    } else {
+        vec![None; segments.len()]
+    };
+    let extra_gaps = segments
+        .iter()
+        .filter(|seg| seg.kind == SegmentKind::Gap)
+        .count();
+    if extra_gaps > 0 && vad_cfg.gap_policy == GapPolicy::MarkEmpty {
+        logger.info(tr!(
+            "检测到 {} 段语音，{} 个静音区写为空字幕。",
+            speech_segments.len(),
+            extra_gaps
+        ));
+    } else if extra_gaps > 0 {
+        logger.info(tr!(
+            "检测到 {} 段语音，额外包含 {} 个静音覆盖区。",
+            speech_segments.len(),
+            extra_gaps
+        ));
+    } else {
+        logger.info(tr!("检测到 {} 段语音，逐段上传。", speech_segments.len()));
+    }
+
+    let output = transcribe_segments(
+        options,
+        client,
+        source,
+        &segments,
+        &speakers,
+        total_duration,
+        cache_key.as_deref(),
+        vad_cfg.gap_policy,
+        logger,
+    )
+    .await?;
+    // 结果已写出，检测缓存不再需要；失败或取消时保留以便下次复用。
+    let _ = fs::remove_file(&cache_path).await;
+    Ok(output)
+}
+
+/// 按固定间隔切分音频逐段转写，时间戳取自各段在原始时间轴上的起点。
+async fn process_with_fixed_intervals(
+    options: &ScannerOptions,
+    client: &Client,
+    source: &AudioSource,
+    interval_secs: f64,
+    logger: &mut ScanLogger,
+) -> Result<TranscriptOutput> {
+    let display_name = source.display_name();
+    let interval_secs = interval_secs.clamp(
+        *SEGMENT_INTERVAL_RANGE.start(),
+        *SEGMENT_INTERVAL_RANGE.end(),
+    );
+    let duration = media_duration(source.input_path(), options)
+        .await
+        .map_err(|e| {
+            anyhow!(tr!(
+                "获取 {} 的时长失败，无法按固定间隔分段：{}",
+                display_name,
+                e
+            ))
+        })?;
+    let total_duration = duration - source.start_offset();
+    let segments = fixed_interval_segments(total_duration, interval_secs);
+    if segments.is_empty() {
+        return Err(anyhow!(tr!("{} 没有可转写的音频", display_name)));
+    }
+    logger.info(tr!(
+        "{} 按每 {:.0} 秒固定分段，共 {} 段，逐段上传。",
+        display_name,
+        interval_secs,
+        segments.len()
+    ));
+    let speakers = if options.diarize {
+        assign_speakers(&segments)
+    } else {
+        vec![None; segments.len()]
+    };
+    let cache_key = fixed_interval_cache_key(source, options, interval_secs)
+        .await
+        .ok();
+    transcribe_segments(
+        options,
+        client,
+        source,
+        &segments,
+        &speakers,
+        total_duration,
+        cache_key.as_deref(),
+        GapPolicy::Skip,
+        logger,
+    )
+    .await
+}
+
+/// 把 `total_duration` 秒切成长度为 `interval_secs` 的连续分段，过短的末尾并入前一段。
+fn fixed_interval_segments(total_duration: f64, interval_secs: f64) -> Vec<SpeechSegment> {
+    let mut segments: Vec<SpeechSegment> = Vec::new();
+    if !(total_duration > 0.0 && interval_secs > 0.0) {
+        return segments;
+    }
+    let mut start = 0.0;
+    while start < total_duration {
+        let end = (start + interval_secs).min(total_duration);
+        match segments.last_mut() {
+            Some(last) if end - start < MIN_TRAILING_SEGMENT_SECS => last.end_sec = end,
+            _ => segments.push(SpeechSegment::new(start, end, SegmentKind::Speech)),
+        }
+        start = end;
+    }
+    segments
+}
+
+/// 逐段导出、上传并写入字幕，VAD 与固定间隔分段共用；`cache_key` 用于分段缓存的断点续传。
+#[allow(clippy::too_many_arguments)]
+async fn transcribe_segments(
+    options: &ScannerOptions,
+    client: &Client,
+    source: &AudioSource,
+    segments: &[SpeechSegment],
+    speakers: &[Option<usize>],
+    total_duration: f64,
+    cache_key: Option<&str>,
+    gap_policy: GapPolicy,
+    logger: &mut ScanLogger,
+) -> Result<TranscriptOutput> {
+    let display_name = source.display_name();
+    // 分段结果逐条追加到 `.partial`，全部完成后再提升为正式结果，避免被当作已完成。
+    let srt_path = options.transcript_path(source.original_path(), source.track_label());
+    let partial_path = partial_transcript_path(&srt_path);
+    if partial_path.exists() {
+        let _ = fs::remove_file(&partial_path).await;
+    }
+    // 已完成分段的文本单独缓存，`.partial` 每次从头重建，缓存命中的分段无需再次上传。
+    let segment_cache_path = segment_cache_path(&srt_path);
+    let mut segment_cache = match cache_key.and_then(|key| segment_cache_key(key, options).ok()) {
+        Some(key) => Some(load_segment_cache(&segment_cache_path, &key).await),
+        None => None,
+    };
+    if let Some(cache) = &segment_cache {
+        // 键不一致或文件损坏时在此处重写，之后只追加新完成的分段。
+        if let Err(e) = save_segment_cache(&segment_cache_path, cache).await {
+            logger.info(tr!("写入分段缓存失败：{}", e));
+            segment_cache = None;
+        }
+    }
+    let resumable = segment_cache.as_ref().map_or(0, |cache| {
+        segments
+            .iter()
+            .enumerate()
+            .filter(|(idx, segment)| cache.lookup(*idx, segment).is_some())
+            .count()
+    });
+    if resumable > 0 {
+        logger.info(tr!(
+            "{} 从分段缓存恢复 {} 段，仅上传其余 {} 段。",
+            display_name,
+            resumable,
+            segments.len() - resumable
+        ));
+    }
+
+    // 分段时间戳来自未加速的 PCM，加速只影响上传的音频，字幕仍对齐原始时间轴；
+    // 增量追加时再整体平移到上次覆盖的位置之后。
+    let offset = source.start_offset();
+    let mut entry_count = 0usize;
+    // 静音区写出的空字幕只占编号，不算转写成功。
+    let mut empty_cues = 0usize;
+    let mut last_error = None;
+    let mut api_duration = 0.0;
+    let mut json_segments = Vec::new();
+    let mut retained = Vec::new();
+    let mut words_unavailable_logged = false;
+    for (idx, segment) in segments.iter().enumerate() {
+        logger.segment_progress(source.original_path(), idx, segments.len());
+        if options.cancel.is_cancelled()
+            || options.pause.wait_if_paused(&options.cancel).await.is_err()
+        {
+            return Err(cancel_current_file(options, source, &partial_path, &[], logger).await);
+        }
+        if segment.kind == SegmentKind::Gap && gap_policy == GapPolicy::MarkEmpty {
+            let index = source.existing_cues() + entry_count + 1;
+            let (start_sec, end_sec) = (offset + segment.start_sec, offset + segment.end_sec);
+            let entry = options
+                .output_format
+                .render_entry(index, start_sec, end_sec, "");
+            append_partial_entry(options, &partial_path, &entry, source.append.is_some()).await?;
+            if options.output_format == OutputFormat::Json {
+                json_segments.push(TranscriptSegment {
+                    index,
+                    start_sec,
+                    end_sec,
+                    text: String::new(),
+                    kind: segment.kind,
+                    speaker: None,
+                });
+            }
+            entry_count += 1;
+            empty_cues += 1;
+            continue;
+        }
+        let cached_text = segment_cache
+            .as_ref()
+            .and_then(|cache| cache.lookup(idx, segment))
+            .map(|text| options.text_cleanup.apply(text));
+        if let Some(text) = cached_text {
+            if !text.is_empty() {
+                let index = source.existing_cues() + entry_count + 1;
+                let (start_sec, end_sec) = (offset + segment.start_sec, offset + segment.end_sec);
+                let entry = options.output_format.render_entry(
+                    index,
+                    start_sec,
+                    end_sec,
+                    &label_speaker(&text, speakers[idx]),
+                );
+                append_partial_entry(options, &partial_path, &entry, source.append.is_some())
+                    .await?;
+                if options.output_format == OutputFormat::Json {
+                    json_segments.push(TranscriptSegment {
+                        index,
+                        start_sec,
+                        end_sec,
+                        text,
+                        kind: segment.kind,
+                        speaker: speakers[idx].map(|idx| idx + 1),
+                    });
+                }
+                entry_count += 1;
+            }
+            continue;
+        }
+        // 导出中途取消时，写了一半的分段音频已由 `run_tool_to_output` 删除。
+        let segment_audio = match source.export_segment_audio(idx + 1, segment, options).await {
+            Ok(path) => path,
+            Err(err) if is_cancelled(&err) => {
+                return Err(cancel_current_file(options, source, &partial_path, &[], logger).await);
+            }
+            // FFmpeg 超时说明工具本身卡住，继续处理只会得到缺段却报告成功的结果。
+            Err(err) if is_tool_timeout(&err) => return Err(err),
+            // 单个分段导出失败（如文件局部损坏）不影响其余分段，与转写失败的处理一致。
+            Err(err) => {
+                logger.error(tr!("分段 {} 导出音频失败：{}", idx + 1, err));
+                continue;
+            }
+        };
+        if options.cancel.is_cancelled() {
+            return Err(cancel_current_file(
+                options,
+                source,
+                &partial_path,
+                &[segment_audio],
+                logger,
+            )
+            .await);
+        }
+        let throttled = throttle_request(options, &tr!("分段 {}", idx + 1), logger).await;
+        if throttled.is_err() {
+            return Err(cancel_current_file(
+                options,
+                source,
+                &partial_path,
+                &[segment_audio],
+                logger,
+            )
+            .await);
+        }
+        let transcribed = cancellable(&options.cancel, async {
+            let _slot = acquire_slot(&options.upload_limiter).await;
+            transcribe_file(
+                client,
+                &options.api_key,
+                &options.api_url,
+                &options.model_name,
+                options.api_flavor,
+                options.request_timeout(),
+                &options.mime_overrides,
+                options.word_timestamps,
+                &segment_audio,
+            )
+            .await
+            .map_err(|e| {
+                let message = tr!("分段 {} 调用 API 失败：{}", idx + 1, e);
+                api_failure(e, message)
+            })
+        })
+        .await;
+        match transcribed {
+            Ok(transcription) => {
+                // 空结果同样计费，先累加再判断。
+                api_duration += transcription.duration.unwrap_or(0.0);
+                let trimmed = transcription.text.trim();
+                if segment_cache.is_some() {
+                    let cached = CachedSegment {
+                        index: idx,
+                        start_sec: segment.start_sec,
+                        end_sec: segment.end_sec,
+                        text: trimmed.to_string(),
+                    };
+                    if let Err(e) = append_segment_cache(&segment_cache_path, &cached).await {
+                        logger.info(tr!("写入分段缓存失败：{}", e));
+                    }
+                }
+                if trimmed.is_empty() {
+                    logger.info(tr!("分段 {} 结果为空，已跳过。", idx + 1));
+                    release_intermediate(options, segment_audio, &mut retained).await;
+                    continue;
+                }
+                let label = match segment.kind {
+                    SegmentKind::Speech => tr!("语音"),
+                    SegmentKind::Gap => tr!("补间"),
+                };
+                logger.success(tr!(
+                    "分段 {} [{}] 完成（{} - {}）。",
+                    idx + 1,
+                    label,
+                    format_timestamp(segment.start_sec),
+                    format_timestamp(segment.end_sec)
+                ));
+                if options.word_timestamps
+                    && transcription.words.is_empty()
+                    && !words_unavailable_logged
+                {
+                    logger.info(tr!(
+                        "{} 的接口未返回逐词时间戳，按分段时间生成字幕。",
+                        display_name
+                    ));
+                    words_unavailable_logged = true;
+                }
+                // 逐词字幕的时间相对于分段音频，平移到分段起点并限制在分段范围内。
+                let cues = match word_level_cues(options, &transcription, options.speedup()) {
+                    Some(cues) => cues
+                        .into_iter()
+                        .map(|(start, end, text)| {
+                            (
+                                segment.start_sec + start,
+                                (segment.start_sec + end).min(segment.end_sec),
+                                text,
+                            )
+                        })
+                        .collect(),
+                    None => vec![(segment.start_sec, segment.end_sec, trimmed.to_string())],
+                };
+                for (start, end, text) in cues {
+                    let text = options.text_cleanup.apply(&text);
+                    if text.is_empty() {
+                        continue;
+                    }
+                    let index = source.existing_cues() + entry_count + 1;
+                    let (start_sec, end_sec) = (offset + start, offset + end);
+                    let entry = options.output_format.render_entry(
+                        index,
+                        start_sec,
+                        end_sec,
+                        &label_speaker(&text, speakers[idx]),
+                    );
+                    append_partial_entry(options, &partial_path, &entry, source.append.is_some())
+                        .await?;
+                    if options.output_format == OutputFormat::Json {
+                        json_segments.push(TranscriptSegment {
+                            index,
+                            start_sec,
+                            end_sec,
+                            text,
+                            kind: segment.kind,
+                            speaker: speakers[idx].map(|idx| idx + 1),
+                        });
+                    }
+                    entry_count += 1;
+                }
+            }
+            Err(e) if is_cancelled(&e) => {
+                return Err(cancel_current_file(
+                    options,
+                    source,
+                    &partial_path,
+                    &[segment_audio],
+                    logger,
+                )
+                .await);
+            }
+            // 密钥被拒绝时其余分段同样会失败，保留分段缓存直接结束该文件。
+            Err(e) if is_auth_failure(&e) => {
+                release_intermediate(options, segment_audio, &mut retained).await;
+                let _ = fs::remove_file(&partial_path).await;
+                return Err(e);
+            }
+            Err(e) => {
+                logger.error(e.to_string());
+                last_error = Some(e);
+            }
+        }
+        release_intermediate(options, segment_audio, &mut retained).await;
+    }
+    logger.segment_progress(source.original_path(), segments.len(), segments.len());
+    if !retained.is_empty() {
+        let list: Vec<String> = retained
+            .iter()
+            .map(|p| display_path(p).into_owned())
+            .collect();
+        logger.info(tr!(
+            "{} 已保留 {} 个分段音频：{}",
+            display_name,
+            retained.len(),
+            list.join("，")
+        ));
+    }
+
+    if entry_count == empty_cues {
+        let _ = fs::remove_file(&partial_path).await;
+        // 保留最后一个分段的错误，以便按错误类别统计连续失败。
+        return Err(match last_error {
+            Some(e) => e.context(tr!("所有分段均转写失败")),
+            None => anyhow!(tr!("所有分段均转写失败")),
+        });
+    }
+
+    promote_partial(&partial_path, &srt_path, source.append.is_some()).await?;
+    if options.output_format == OutputFormat::Json {
+        write_json_transcript(&srt_path, json_segments, source.append.is_some()).await?;
+    }
+    // 结果已写出，分段缓存不再需要；失败或取消时保留以便下次复用。
+    let _ = fs::remove_file(&segment_cache_path).await;
+    logger.success(tr!(
+        "{} 分段转写完成，结果输出 {}",
+        display_name,
+        display_path(&srt_path)
+    ));
+    Ok(TranscriptOutput {
+        path: srt_path,
+        segments: entry_count,
+        covered_until: Some(offset + total_duration),
+        api_duration,
+        audio_secs: total_duration,
+    })
+}
+
+/// JSON 输出中的单个分段，时间戳与字幕一致。
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct TranscriptSegment {
+    index: usize,
+    start_sec: f64,
+    end_sec: f64,
+    text: String,
+    kind: SegmentKind,
+    /// 说话人编号（从 1 开始），未启用说话人区分时省略。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    speaker: Option<usize>,
+}
+
+/// 将分段列表序列化为便于下游脚本读取的 JSON 数组。
+fn build_json_transcript(segments: &[TranscriptSegment]) -> Result<String> {
+    Ok(serde_json::to_string_pretty(segments)?)
+}
+
+/// 写出字幕旁的 `.json`；增量追加时与已有分段合并。
+async fn write_json_transcript(
+    srt_path: &Path,
+    segments: Vec<TranscriptSegment>,
+    append: bool,
+) -> Result<()> {
+    let json_path = transcript_json_path(srt_path);
+    let mut all = Vec::new();
+    if append {
+        if let Ok(existing) = fs::read_to_string(&json_path).await {
+            all = serde_json::from_str(&existing).unwrap_or_default();
+        }
+    }
+    all.extend(segments);
+    write_atomic(&json_path, build_json_transcript(&all)?).await
+}
+
+/// 原子写入使用的临时文件：与目标同目录，重命名时不会跨文件系统。
+fn atomic_temp_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".tmp");
+    path.with_file_name(name)
+}
+
+/// 先完整写入同目录下的临时文件再重命名到位，进程中途被终止时结果要么完整存在、要么不存在，
+/// 不会留下被跳过逻辑误当作已完成的半截文件。
+pub(crate) async fn write_atomic(path: &Path, content: impl AsRef<[u8]>) -> Result<()> {
+    let temp = atomic_temp_path(path);
+    let written = match fs::write(&temp, content).await {
+        Ok(()) => fs::rename(&temp, path).await,
+        Err(e) => Err(e),
+    };
+    if written.is_err() {
+        let _ = fs::remove_file(&temp).await;
+    }
+    Ok(written?)
+}
+
+/// [`write_atomic`] 的同步版本，供配置文件等非异步代码使用。
+pub(crate) fn write_atomic_blocking(path: &Path, content: impl AsRef<[u8]>) -> Result<()> {
+    let temp = atomic_temp_path(path);
+    let written = std::fs::write(&temp, content).and_then(|()| std::fs::rename(&temp, path));
+    if written.is_err() {
+        let _ = std::fs::remove_file(&temp);
+    }
+    Ok(written?)
+}
+
+/// 把内容接到已有结果末尾后整体原子写回，文件不存在时直接创建。
+async fn append_atomic(path: &Path, content: &str) -> Result<()> {
+    let mut existing = match fs::read_to_string(path).await {
+        Ok(existing) => existing,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e.into()),
+    };
+    existing.push_str(content);
+    write_atomic(path, existing).await
+}
+
+/// 将字幕条目追加到文件末尾，文件不存在时自动创建。
+async fn append_srt_entry(path: &Path, entry: &str) -> Result<()> {
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .await?;
+    file.write_all(entry.as_bytes()).await?;
+    file.flush().await?;
+    Ok(())
+}
+
+/// 向 `.partial` 追加一条结果；新建文件时先写入输出格式要求的文件头（如 ASS 的样式段），
+/// 增量追加到已有结果时不再重复写入。
+async fn append_partial_entry(
+    options: &ScannerOptions,
+    partial_path: &Path,
+    entry: &str,
+    append: bool,
+) -> Result<()> {
+    if !append && !partial_path.exists() {
+        if let Some(header) = options.result_header() {
+            append_srt_entry(partial_path, &header).await?;
+        }
+    }
+    append_srt_entry(partial_path, entry).await
+}
+
+/// 将 `.srt.partial` 提升为正式字幕；增量模式下追加到已有字幕末尾。
+async fn promote_partial(partial_path: &Path, srt_path: &Path, append: bool) -> Result<()> {
+    if append {
+        let content = fs::read_to_string(partial_path).await?;
+        append_atomic(srt_path, &content).await?;
+        fs::remove_file(partial_path).await?;
+    } else {
+        fs::rename(partial_path, srt_path).await?;
+    }
+    Ok(())
+}
+
+/// 处理当前文件的取消：回滚中间产物并记录清理结果，返回用于向上传递的取消错误。
+async fn cancel_current_file(
+    options: &ScannerOptions,
+    source: &AudioSource,
+    partial_path: &Path,
+    temp_files: &[PathBuf],
+    logger: &mut ScanLogger,
+) -> anyhow::Error {
+    // 分段缓存始终保留，下次扫描只上传缺失的分段。
+    let removed =
+        rollback_cancelled_outputs(partial_path, temp_files, options.discard_partial_on_cancel)
+            .await;
+    let name = source.display_name();
+    if removed.is_empty() {
+        logger.info(tr!("{} 已取消，无需清理中间文件。", name));
+    } else {
+        let list: Vec<String> = removed
+            .iter()
+            .map(|p| display_path(p).into_owned())
+            .collect();
+        logger.info(tr!("{} 已取消，已清理：{}", name, list.join("，")));
+    }
+    if partial_path.exists() {
+        logger.info(tr!(
+            "{} 已完成的分段保留在 {}，下次扫描将续传其余分段。",
+            name,
+            display_path(&partial_path)
+        ));
+    }
+    ScanCancelled.into()
+}
+
+/// 处理完一个分段音频：保留中间文件时记入 `retained`，否则直接删除。
+async fn release_intermediate(
+    options: &ScannerOptions,
+    path: PathBuf,
+    retained: &mut Vec<PathBuf>,
+) {
+    if options.keep_intermediates {
+        retained.push(path);
+    } else {
+        let _ = fs::remove_file(&path).await;
+    }
+}
+
+/// 取消时删除临时音频，并按配置丢弃 `.srt.partial`，返回已删除的文件。
+///
+/// 未丢弃的 `.srt.partial` 原样保留而不提升为正式结果：不完整的字幕一旦写成 `.srt`，
+/// 下次扫描会把它当作已完成而不再续传。
+async fn rollback_cancelled_outputs(
+    partial_path: &Path,
+    temp_files: &[PathBuf],
+    discard_partial: bool,
+) -> Vec<PathBuf> {
+    let mut removed = Vec::new();
+    for path in temp_files {
+        if fs::remove_file(path).await.is_ok() {
+            removed.push(path.clone());
+        }
+    }
+
+    if discard_partial && fs::remove_file(partial_path).await.is_ok() {
+        removed.push(partial_path.to_path_buf());
+    }
+
+    removed
+}
+
+async fn read_wav_samples(path: &Path, sample_rate: u32) -> Result<Vec<i16>> {
+    let path = path.to_path_buf();
+    task::spawn_blocking(move || {
+        let mut reader = hound::WavReader::open(&path)?;
+        let spec = reader.spec();
+        if spec.sample_rate != sample_rate || spec.channels != 1 || spec.bits_per_sample != 16 {
+            return Err(anyhow!(tr!("生成的 WAV 格式不符合 VAD 要求")));
+        }
+
+        let mut samples = Vec::new();
+        for sample in reader.samples::<i16>() {
+            samples.push(sample?);
+        }
+        Ok::<_, anyhow::Error>(samples)
+    })
+    .await?
+}
+
+/// 基于分块 RMS 估算的录音电平。
+#[derive(Clone, Copy, Debug)]
+struct AudioLevels {
+    /// 低分位（10%）分块电平，近似噪声底。
+    noise_floor_db: f32,
+    /// 高分位（90%）分块电平，近似语音电平。
+    speech_level_db: f32,
+}
+
+/// 计算一段采样的 RMS 电平（dBFS），静音或空输入返回 `SILENCE_FLOOR_DB`。
+fn rms_db(samples: &[i16]) -> f32 {
+    if samples.is_empty() {
+        return SILENCE_FLOOR_DB;
+    }
+    let energy: f64 = samples
+        .iter()
+        .map(|&s| {
+            let v = s as f64 / i16::MAX as f64;
+            v * v
+        })
+        .sum();
+    let rms = (energy / samples.len() as f64).sqrt();
+    if rms > 0.0 {
+        (20.0 * rms.log10()) as f32
+    } else {
+        SILENCE_FLOOR_DB
+    }
+}
+
+/// 按 VAD 分块统计 RMS 电平（dBFS），取分位数估计噪声底与语音电平。
+fn measure_levels(samples: &[i16], chunk_size: usize) -> Option<AudioLevels> {
+    let mut levels: Vec<f32> = samples.chunks(chunk_size).map(rms_db).collect();
+    if levels.is_empty() {
+        return None;
+    }
+
+    levels.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    let percentile = |p: f32| levels[((levels.len() - 1) as f32 * p).round() as usize];
+    Some(AudioLevels {
+        noise_floor_db: percentile(0.1),
+        speech_level_db: percentile(0.9),
+    })
+}
+
+/// 简易两人说话人区分：语音段电平明显分成两簇时按电平归类（两人与麦克风距离不同），
+/// 否则在停顿超过 `DIARIZE_TURN_GAP_SECS` 时轮换说话人。
+/// 返回与 `segments` 对齐的说话人编号（从 0 开始，按首次出现排序），静音补间为 `None`。
+fn assign_speakers(segments: &[SpeechSegment]) -> Vec<Option<usize>> {
+    let mut speakers = vec![None; segments.len()];
+    let speech: Vec<usize> = segments
+        .iter()
+        .enumerate()
+        .filter(|(_, seg)| seg.kind == SegmentKind::Speech)
+        .map(|(idx, _)| idx)
+        .collect();
+    let levels: Option<Vec<f32>> = speech.iter().map(|&idx| segments[idx].level_db).collect();
+
+    match levels.and_then(|levels| split_levels(&levels)) {
+        Some(groups) => {
+            let first = groups[0];
+            for (&idx, &group) in speech.iter().zip(&groups) {
+                speakers[idx] = Some(usize::from(group != first));
+            }
+        }
+        None => {
+            let mut current = 0;
+            let mut prev_end: Option<f64> = None;
+            for &idx in &speech {
+                let segment = &segments[idx];
+                if prev_end.is_some_and(|end| segment.start_sec - end >= DIARIZE_TURN_GAP_SECS) {
+                    current = 1 - current;
+                }
+                speakers[idx] = Some(current);
+                prev_end = Some(segment.end_sec);
+            }
+        }
+    }
+    speakers
+}
+
+/// 对电平做一维二均值聚类，返回每个元素是否属于高电平簇；两簇相差不足 `DIARIZE_MIN_SPREAD_DB` 时返回 `None`。
+fn split_levels(levels: &[f32]) -> Option<Vec<bool>> {
+    let mut low = levels.iter().copied().fold(f32::INFINITY, f32::min);
+    let mut high = levels.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    if high - low < DIARIZE_MIN_SPREAD_DB {
+        return None;
+    }
+    let mut groups = vec![false; levels.len()];
+    for _ in 0..16 {
+        for (group, &level) in groups.iter_mut().zip(levels) {
+            *group = (level - low).abs() > (level - high).abs();
+        }
+        let mean = |wanted: bool| {
+            let picked: Vec<f32> = levels
+                .iter()
+                .zip(&groups)
+                .filter(|(_, &group)| group == wanted)
+                .map(|(&level, _)| level)
+                .collect();
+            (!picked.is_empty()).then(|| picked.iter().sum::<f32>() / picked.len() as f32)
+        };
+        (low, high) = (mean(false)?, mean(true)?);
+    }
+    (high - low >= DIARIZE_MIN_SPREAD_DB).then_some(groups)
+}
+
+/// 把 API 返回的说话人标签按首次出现顺序映射为从 0 开始的编号。
+fn speakers_from_labels(labels: &[Option<String>]) -> Vec<Option<usize>> {
+    let mut seen: Vec<&str> = Vec::new();
+    labels
+        .iter()
+        .map(|label| {
+            let label = label.as_deref()?;
+            Some(match seen.iter().position(|known| *known == label) {
+                Some(idx) => idx,
+                None => {
+                    seen.push(label);
+                    seen.len() - 1
+                }
+            })
+        })
+        .collect()
+}
+
+/// 为字幕文本加上 `[说话人 N]` 前缀，`speaker` 为 `None` 时原样返回。
+fn label_speaker(text: &str, speaker: Option<usize>) -> String {
+    match speaker {
+        Some(idx) => format!("[说话人 {}] {}", idx + 1, text),
+        None => text.to_string(),
+    }
+}
+
+/// 以手动阈值为中心微调，偏移量有界：语音电平高于参考电平时提高、低于时降低；
+/// 噪声底接近语音电平（信噪比低于 [`ADAPTIVE_REFERENCE_MARGIN_DB`]）时再按差距提高，
+/// 避免持续的背景噪声被当作语音。
+fn adapt_threshold(base: f32, levels: &AudioLevels) -> f32 {
+    let level_offset =
+        (levels.speech_level_db - ADAPTIVE_REFERENCE_DB) / ADAPTIVE_DB_PER_STEP * ADAPTIVE_STEP;
+    let margin = levels.speech_level_db - levels.noise_floor_db;
+    let noise_offset =
+        (ADAPTIVE_REFERENCE_MARGIN_DB - margin).max(0.0) / ADAPTIVE_DB_PER_STEP * ADAPTIVE_STEP;
+    let offset = (level_offset + noise_offset).clamp(-ADAPTIVE_MAX_OFFSET, ADAPTIVE_MAX_OFFSET);
+    (base + offset).clamp(0.1, 0.99)
+}
+
+#[derive(Clone, Debug)]
+struct SegmentState {
+    start_chunk: usize,
+    last_active_chunk: usize,
+}
+
+impl SegmentState {
+    fn new(start_chunk: usize) -> Self {
+        Self {
+            start_chunk,
+            last_active_chunk: start_chunk,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum SegmentKind {
+    Speech,
+    Gap,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct SpeechSegment {
+    start_sec: f64,
+    end_sec: f64,
+    kind: SegmentKind,
+    /// 语音段的 RMS 电平（dBFS），供说话人区分使用；未测量时为 `None`。
+    #[serde(default)]
+    level_db: Option<f32>,
+}
+
+impl SpeechSegment {
+    fn new(start_sec: f64, end_sec: f64, kind: SegmentKind) -> Self {
+        Self {
+            start_sec,
+            end_sec,
+            kind,
+            level_db: None,
+        }
+    }
+
+    fn from_chunks(start_chunk: usize, end_chunk: usize, cfg: &VadConfig) -> Self {
+        Self::new(
+            cfg.chunk_to_time(start_chunk),
+            cfg.chunk_to_time(end_chunk),
+            SegmentKind::Speech,
+        )
+    }
+
+    fn try_new(start_sec: f64, end_sec: f64, kind: SegmentKind) -> Option<Self> {
+        if end_sec - start_sec <= MIN_SEGMENT_EPS {
+            None
+        } else {
+            Some(Self::new(start_sec, end_sec, kind))
+        }
+    }
+}
+
+/// 运行 VAD，返回检测出的语音段以及每个分块的语音概率（供切分过长语音段使用）。
+fn detect_speech_segments(
+    samples: &[i16],
+    cfg: &VadConfig,
+) -> Result<(Vec<SpeechSegment>, Vec<f32>)> {
+    let mut vad = VoiceActivityDetector::builder()
+        .sample_rate(cfg.sample_rate)
+        .chunk_size(cfg.chunk_size)
+        .build()
+        .context(tr!("语音活动检测器初始化失败"))?;
+
+    let mut probabilities = Vec::with_capacity(samples.len() / cfg.chunk_size + 1);
+    for range in chunk_ranges(samples.len(), cfg.chunk_size) {
+        let mut chunk = vec![0i16; cfg.chunk_size];
+        chunk[..range.len()].copy_from_slice(&samples[range]);
+        probabilities.push(vad.predict(chunk));
+    }
+
+    let smoothed = smooth_probabilities(&probabilities, cfg.smoothing_window);
+    let total_duration = samples.len() as f64 / cfg.sample_rate as f64;
+    let segments = segments_from_probabilities(&smoothed, cfg);
+    Ok((
+        clamp_segments_to_duration(segments, total_duration),
+        probabilities,
+    ))
+}
+
+/// 每个 VAD 分块对应的采样区间。
+///
+/// 长度不能整除时，最后一块改为取音频末尾完整的 `chunk_size` 个采样（与前一块部分重叠），
+/// 避免补零稀释语音概率而漏掉结尾的语音；只有整段音频不足一块时才补零。
+fn chunk_ranges(len: usize, chunk_size: usize) -> Vec<std::ops::Range<usize>> {
+    if chunk_size == 0 {
+        return Vec::new();
+    }
+    (0..len.div_ceil(chunk_size))
+        .map(|idx| {
+            let start = idx * chunk_size;
+            if start + chunk_size > len && len >= chunk_size {
+                len - chunk_size..len
+            } else {
+                start..usize::min(start + chunk_size, len)
+            }
+        })
+        .collect()
+}
+
+/// 把语音段限制在音频实际时长内：最后一个分块按完整时长换算，结束时间可能超出音频末尾。
+fn clamp_segments_to_duration(
+    segments: Vec<SpeechSegment>,
+    total_duration: f64,
+) -> Vec<SpeechSegment> {
+    segments
+        .into_iter()
+        .filter_map(|mut segment| {
+            segment.end_sec = segment.end_sec.min(total_duration);
+            (segment.end_sec - segment.start_sec > MIN_SEGMENT_EPS).then_some(segment)
+        })
+        .collect()
+}
+
+/// 以每个分块为中心做滑动平均，窗口在首尾处截短；`window` 不大于 1 时原样返回。
+fn smooth_probabilities(probabilities: &[f32], window: usize) -> Vec<f32> {
+    if window <= 1 {
+        return probabilities.to_vec();
+    }
+    let before = (window - 1) / 2;
+    let after = window / 2;
+    (0..probabilities.len())
+        .map(|idx| {
+            let start = idx.saturating_sub(before);
+            let end = usize::min(idx + after + 1, probabilities.len());
+            let slice = &probabilities[start..end];
+            slice.iter().sum::<f32>() / slice.len() as f32
+        })
+        .collect()
+}
+
+/// 按阈值把逐块语音概率转换为语音段：低于阈值的分块超过 `padding_chunks` 个时结束当前段。
+fn segments_from_probabilities(probabilities: &[f32], cfg: &VadConfig) -> Vec<SpeechSegment> {
+    let mut segments = Vec::new();
+    let mut current: Option<SegmentState> = None;
+    let mut trailing_silence = 0usize;
+
+    for (chunk_index, &probability) in probabilities.iter().enumerate() {
+        if probability >= cfg.threshold {
+            match &mut current {
+                Some(state) => state.last_active_chunk = chunk_index,
+                None => current = Some(SegmentState::new(chunk_index)),
+            }
+            trailing_silence = 0;
+        } else if let Some(state) = &mut current {
+            trailing_silence += 1;
+            if trailing_silence > cfg.padding_chunks {
+                finalize_segment(state, cfg, &mut segments);
+                current = None;
+                trailing_silence = 0;
+            }
+        }
+    }
+
+    if let Some(state) = current {
+        finalize_segment(&state, cfg, &mut segments);
+    }
+
+    segments
+}
+
+/// 将超过 `max_chunks` 个分块的语音段在段内语音概率最低的分块处切开，直到每段都不超长。
+/// 切点离两端至少保留 `max_chunks / 4` 个分块，避免切出过短的碎片；`max_chunks` 为 0 时不切分。
+fn split_long_segments(
+    segments: Vec<SpeechSegment>,
+    probabilities: &[f32],
+    max_chunks: usize,
+    cfg: &VadConfig,
+) -> Vec<SpeechSegment> {
+    if max_chunks == 0 {
+        return segments;
+    }
+    let probability = |chunk: usize| probabilities.get(chunk).copied().unwrap_or(1.0);
+    let mut result = Vec::with_capacity(segments.len());
+    for segment in segments {
+        let mut pending = vec![(
+            cfg.time_to_chunk(segment.start_sec),
+            cfg.time_to_chunk(segment.end_sec),
+        )];
+        while let Some((start, end)) = pending.pop() {
+            if end - start <= max_chunks {
+                result.push(SpeechSegment::from_chunks(start, end, cfg));
+                continue;
+            }
+            let margin = (max_chunks / 4).max(1);
+            let cut = (start + margin..end - margin)
+                .min_by(|&a, &b| {
+                    probability(a)
+                        .partial_cmp(&probability(b))
+                        .unwrap_or(std::cmp::Ordering::Equal)
+                })
+                .unwrap_or((start + end) / 2);
+            // 后压入的先处理，保证输出按时间顺序排列。
+            pending.push((cut, end));
+            pending.push((start, cut));
+        }
+    }
+    result
+}
+
+fn finalize_segment(state: &SegmentState, cfg: &VadConfig, segments: &mut Vec<SpeechSegment>) {
+    let duration_chunks = state.last_active_chunk.saturating_sub(state.start_chunk) + 1;
+    if duration_chunks >= cfg.secs_to_chunks(cfg.min_speech_secs) {
+        segments.push(SpeechSegment::from_chunks(
+            state.start_chunk,
+            state.last_active_chunk + 1,
+            cfg,
+        ));
+    }
+}
+
+/// 合并间隔小于 `max_gap` 秒的相邻语音段，减少 API 调用次数与零碎字幕；
+/// 合并后超过 `max_len` 秒（大于 0 时）则不合并，以免抵消过长语音段的切分。
+/// 合并后的电平按时长加权平均，任一段未测量电平时记为未测量。
+fn merge_close_segments(
+    segments: Vec<SpeechSegment>,
+    max_gap: f64,
+    max_len: f64,
+) -> Vec<SpeechSegment> {
+    if max_gap <= 0.0 {
+        return segments;
+    }
+    let mut merged: Vec<SpeechSegment> = Vec::with_capacity(segments.len());
+    for segment in segments {
+        match merged.last_mut() {
+            Some(prev)
+                if prev.kind == SegmentKind::Speech
+                    && segment.kind == SegmentKind::Speech
+                    && segment.start_sec - prev.end_sec < max_gap
+                    && (max_len <= 0.0 || segment.end_sec - prev.start_sec <= max_len) =>
+            {
+                let prev_len = prev.end_sec - prev.start_sec;
+                let next_len = segment.end_sec - segment.start_sec;
+                prev.level_db = match (prev.level_db, segment.level_db) {
+                    (Some(a), Some(b)) if prev_len + next_len > 0.0 => Some(
+                        ((a as f64 * prev_len + b as f64 * next_len) / (prev_len + next_len))
+                            as f32,
+                    ),
+                    _ => None,
+                };
+                prev.end_sec = prev.end_sec.max(segment.end_sec);
+            }
+            _ => merged.push(segment),
+        }
+    }
+    merged
+}
+
+/// 按 `lead_padding_secs`/`tail_padding_secs` 延伸每个语音段的起止，限制在 `[0, total_duration]` 内。
+/// 相邻两段的间隔不够同时容纳前一段的尾部与后一段的首部留白时，按两者比例分配间隔，保证不重叠。
+fn pad_segments(
+    segments: Vec<SpeechSegment>,
+    cfg: &VadConfig,
+    total_duration: f64,
+) -> Vec<SpeechSegment> {
+    let lead = cfg.lead_padding_secs.max(0.0) as f64;
+    let tail = cfg.tail_padding_secs.max(0.0) as f64;
+    if lead + tail <= 0.0 {
+        return segments;
+    }
+    // 相邻两段之间分给前一段尾部的留白与分给后一段首部的留白。
+    let share = |gap: f64| -> (f64, f64) {
+        if gap <= 0.0 {
+            (0.0, 0.0)
+        } else if lead + tail <= gap {
+            (tail, lead)
+        } else {
+            (gap * tail / (lead + tail), gap * lead / (lead + tail))
+        }
+    };
+    let bounds: Vec<(f64, f64)> = segments.iter().map(|s| (s.start_sec, s.end_sec)).collect();
+    segments
+        .into_iter()
+        .enumerate()
+        .map(|(idx, mut segment)| {
+            let before = match idx.checked_sub(1) {
+                Some(prev) => share(bounds[idx].0 - bounds[prev].1).1,
+                None => lead,
+            };
+            let after = match bounds.get(idx + 1) {
+                Some(next) => share(next.0 - bounds[idx].1).0,
+                None => tail,
+            };
+            segment.start_sec = (segment.start_sec - before).max(0.0);
+            segment.end_sec = (segment.end_sec + after).min(total_duration.max(segment.end_sec));
+            segment
+        })
+        .collect()
+}
+
+fn expand_segments_with_gaps(
+    speech_segments: &[SpeechSegment],
+    total_duration: f64,
+) -> Vec<SpeechSegment> {
+    if speech_segments.is_empty() {
+        return Vec::new();
+    }
+
+    let mut sorted = speech_segments.to_vec();
+    sorted.sort_by(|a, b| {
+        a.start_sec
+            .partial_cmp(&b.start_sec)
+            .unwrap_or(std::cmp::Ordering::Less)
+    });
+
+    let mut expanded = Vec::new();
+    let mut cursor = 0.0f64;
+
+    for segment in sorted {
+        if let Some(gap) = SpeechSegment::try_new(cursor, segment.start_sec, SegmentKind::Gap) {
+            expanded.push(gap);
+        }
+        let end = segment.end_sec;
+        expanded.push(segment);
+        cursor = end;
+    }
+
+    if let Some(tail) = SpeechSegment::try_new(cursor, total_duration, SegmentKind::Gap) {
+        expanded.push(tail);
+    }
+
+    expanded
+}
+
+/// 按策略处理补间：`Skip` 时去掉全部静音区，其余策略保留，由调用方决定上传或写空字幕。
+fn apply_gap_policy(segments: Vec<SpeechSegment>, policy: GapPolicy) -> Vec<SpeechSegment> {
+    match policy {
+        GapPolicy::Skip => segments
+            .into_iter()
+            .filter(|seg| seg.kind == SegmentKind::Speech)
+            .collect(),
+        GapPolicy::Transcribe | GapPolicy::MarkEmpty => segments,
+    }
+}
+
+/// 日志与错误信息中显示的路径：按 `to_string_lossy` 原样输出，中文等非 ASCII 字符不转义，
+/// 无法按 UTF-8 解码的部分替换为 `�`，不会像 `{:?}` 那样带引号和反斜杠转义。
+pub fn display_path<P: AsRef<Path> + ?Sized>(path: &P) -> std::borrow::Cow<'_, str> {
+    path.as_ref().to_string_lossy()
+}
+
+fn format_timestamp(seconds: f64) -> String {
+    let total_ms = (seconds * 1000.0).round().max(0.0) as u64;
+    let hours = total_ms / 3_600_000;
+    let minutes = (total_ms % 3_600_000) / 60_000;
+    let secs = (total_ms % 60_000) / 1000;
+    let millis = total_ms % 1000;
+    if hours > 0 {
+        format!("{:02}:{:02}:{:02}.{:03}", hours, minutes, secs, millis)
+    } else {
+        format!("{:02}:{:02}.{:03}", minutes, secs, millis)
+    }
+}
+
+fn format_srt_timestamp(seconds: f64) -> String {
+    let total_ms = (seconds * 1000.0).round().max(0.0) as u64;
+    let hours = total_ms / 3_600_000;
+    let minutes = (total_ms % 3_600_000) / 60_000;
+    let secs = (total_ms % 60_000) / 1000;
+    let millis = total_ms % 1000;
+    format!("{:02}:{:02}:{:02},{:03}", hours, minutes, secs, millis)
+}
+
+fn sanitize_srt_text(input: &str) -> String {
+    input.replace("\r\n", "\n").trim().to_string()
+}
+
+fn build_srt_entry(index: usize, start: f64, end: f64, text: &str) -> String {
+    let safe_end = if end <= start { start + 0.5 } else { end };
+    format!(
+        "{idx}\n{start} --> {end}\n{body}\n\n",
+        idx = index,
+        start = format_srt_timestamp(start),
+        end = format_srt_timestamp(safe_end),
+        body = sanitize_srt_text(text)
+    )
+}
+
+/// ASS 时间戳 `H:MM:SS.cc`（厘秒）。
+fn format_ass_timestamp(seconds: f64) -> String {
+    let total_cs = (seconds * 100.0).round().max(0.0) as u64;
+    format!(
+        "{}:{:02}:{:02}.{:02}",
+        total_cs / 360_000,
+        (total_cs % 360_000) / 6000,
+        (total_cs % 6000) / 100,
+        total_cs % 100
+    )
+}
+
+/// ASS 文件头：`[Script Info]`、只含 `Default` 样式的 `[V4+ Styles]` 与 `[Events]` 的格式行，
+/// 之后逐行追加 `Dialogue` 即可。
+fn ass_header(style: &AssStyle) -> String {
+    format!(
+        "[Script Info]\n\
+; Generated by AutoASR\n\
+ScriptType: v4.00+\n\
+WrapStyle: 0\n\
+ScaledBorderAndShadow: yes\n\
+PlayResX: 1920\n\
+PlayResY: 1080\n\
+\n\
+[V4+ Styles]\n\
+Format: Name, Fontname, Fontsize, PrimaryColour, SecondaryColour, OutlineColour, BackColour, \
+Bold, Italic, Underline, StrikeOut, ScaleX, ScaleY, Spacing, Angle, BorderStyle, Outline, Shadow, \
+Alignment, MarginL, MarginR, MarginV, Encoding\n\
+Style: Default,{font},{size},&H00FFFFFF,&H000000FF,&H00000000,&H80000000,\
+0,0,0,0,100,100,0,0,1,2,1,2,20,20,40,1\n\
+\n\
+[Events]\n\
+Format: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text\n",
+        font = style.font_name.replace(',', " "),
+        size = style.font_size,
+    )
+}
+
+/// 单条 `Dialogue` 行：换行改为 `\N`，花括号转义，避免被当作样式覆盖标签。
+fn build_ass_dialogue(start: f64, end: f64, text: &str) -> String {
+    let safe_end = if end <= start { start + 0.5 } else { end };
+    let body = sanitize_srt_text(text)
+        .replace('{', "\\{")
+        .replace('}', "\\}")
+        .replace('\n', "\\N");
+    format!(
+        "Dialogue: 0,{},{},Default,,0,0,0,,{}\n",
+        format_ass_timestamp(start),
+        format_ass_timestamp(safe_end),
+        body
+    )
+}
+
+/// 生成完整的 ASS 字幕：文件头加上每条 `(开始, 结束, 文本)` 对应的 `Dialogue` 行。
+pub fn build_ass_file(style: &AssStyle, entries: &[(f64, f64, String)]) -> String {
+    let mut content = ass_header(style);
+    for (start, end, text) in entries {
+        content.push_str(&build_ass_dialogue(*start, *end, text));
+    }
+    content
+}
+
+/// 将整段识别结果按句末标点拆成多条字幕，过长的句子再按 `MAX_CUE_CHARS` 截断，
+/// 并按字数比例把 `total_duration` 分配给各条，返回 `(开始, 结束, 文本)`。
+fn split_text_into_cues(text: &str, total_duration: f64) -> Vec<(f64, f64, String)> {
+    let mut sentences = Vec::new();
+    let mut current = String::new();
+    let mut chars = text.chars().peekable();
+    while let Some(ch) = chars.next() {
+        if ch == '\n' || ch == '\r' {
+            if !current.trim().is_empty() {
+                sentences.push(current.trim().to_string());
+            }
+            current.clear();
+            continue;
+        }
+        current.push(ch);
+        // 英文句点后须跟空白或结尾，避免把 `3.5` 之类的小数拆开。
+        let ends_sentence = match ch {
+            '。' | '！' | '？' | '；' | '!' | '?' | ';' => true,
+            '.' => !matches!(chars.peek(), Some(next) if !next.is_whitespace()),
+            _ => false,
+        };
+        if ends_sentence {
+            if !current.trim().is_empty() {
+                sentences.push(current.trim().to_string());
+            }
+            current.clear();
+        }
+    }
+    if !current.trim().is_empty() {
+        sentences.push(current.trim().to_string());
+    }
+
+    let chunks: Vec<String> = sentences
+        .into_iter()
+        .flat_map(|sentence| {
+            let chars: Vec<char> = sentence.chars().collect();
+            chars
+                .chunks(MAX_CUE_CHARS)
+                .map(|piece| piece.iter().collect::<String>())
+                .collect::<Vec<_>>()
+        })
+        .collect();
+
+    let total_chars: usize = chunks.iter().map(|chunk| chunk.chars().count()).sum();
+    if total_chars == 0 {
+        return Vec::new();
+    }
+
+    let mut cues = Vec::with_capacity(chunks.len());
+    let mut consumed = 0usize;
+    for chunk in chunks {
+        let start = total_duration * consumed as f64 / total_chars as f64;
+        consumed += chunk.chars().count();
+        let end = total_duration * consumed as f64 / total_chars as f64;
+        cues.push((start, end, chunk));
+    }
+    cues
+}
+
+/// 由逐词时间戳生成字幕：遇到句末标点、词间停顿超过 [`WORD_CUE_MAX_GAP_SECS`] 或累计超过
+/// `MAX_CUE_CHARS` 字时断开，每条的起止取首尾词的时间，返回 `(开始, 结束, 文本)`。
+pub fn build_word_cues(words: &[TimedWord]) -> Vec<(f64, f64, String)> {
+    let mut cues = Vec::new();
+    let mut current: Option<(f64, f64, String)> = None;
+    for word in words {
+        let token = word.word.trim();
+        if token.is_empty() {
+            continue;
+        }
+        if let Some((_, end, text)) = &current {
+            let too_long = text.chars().count() + token.chars().count() > MAX_CUE_CHARS;
+            if word.start - end > WORD_CUE_MAX_GAP_SECS || too_long {
+                cues.extend(current.take());
+            }
+        }
+        match current.as_mut() {
+            Some((_, end, text)) => {
+                if needs_word_space(text, token) {
+                    text.push(' ');
+                }
+                text.push_str(token);
+                *end = end.max(word.end);
+            }
+            None => current = Some((word.start, word.end, token.to_string())),
+        }
+        if token.ends_with(['。', '！', '？', '；', '!', '?', ';', '.']) {
+            cues.extend(current.take());
+        }
+    }
+    cues.extend(current);
+    cues
+}
+
+/// 拼接相邻词时是否插入空格：前一个词不以中日韩文字结尾，且后一个词以非中日韩的字母或数字开头。
+fn needs_word_space(prev: &str, next: &str) -> bool {
+    let is_cjk =
+        |c: char| ('\u{2E80}'..='\u{9FFF}').contains(&c) || ('\u{AC00}'..='\u{D7AF}').contains(&c);
+    let prev_ok = prev
+        .chars()
+        .last()
+        .is_some_and(|c| !c.is_whitespace() && !is_cjk(c));
+    let next_ok = next
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_alphanumeric() && !is_cjk(c));
+    prev_ok && next_ok
+}
+
+/// 开启 `word_timestamps` 且服务端返回了词级时间时，生成换算回原始时间轴的逐词字幕；
+/// 否则返回 `None`，由调用方按分段生成字幕。
+fn word_level_cues(
+    options: &ScannerOptions,
+    transcription: &Transcription,
+    speedup: Option<f64>,
+) -> Option<Vec<(f64, f64, String)>> {
+    if !options.word_timestamps || transcription.words.is_empty() {
+        return None;
+    }
+    Some(
+        build_word_cues(&transcription.words)
+            .into_iter()
+            .map(|(start, end, text)| {
+                (
+                    rescale_to_original(start, speedup),
+                    rescale_to_original(end, speedup),
+                    text,
+                )
+            })
+            .collect(),
+    )
+}
+
+fn estimate_duration_from_text(text: &str) -> f64 {
+    let chars = text.chars().count() as f64;
+    (chars / 15.0).max(5.0)
+}
+
+/// ffprobe 报告的一条音轨及其元数据标签。
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct AudioStream {
+    index: u32,
+    /// `language` 标签，如 `jpn`、`eng`。
+    language: Option<String>,
+    /// `title` 标签，如 `Commentary`。
+    title: Option<String>,
+}
+
+impl AudioStream {
+    /// 可用于文件名的语言标签：去掉 `und` 与文件名中不安全的字符，为空时返回 `None`。
+    fn filename_language(&self) -> Option<String> {
+        let language = self.language.as_deref()?.trim();
+        if language.eq_ignore_ascii_case("und") {
+            return None;
+        }
+        let cleaned: String = language
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric() || *c == '-')
+            .collect::<String>()
+            .to_lowercase();
+        (!cleaned.is_empty()).then_some(cleaned)
+    }
+
+    /// 预览与日志中附在音轨编号后的说明，如 `（jpn · Commentary）`，没有标签时为空。
+    fn describe_tags(&self) -> String {
+        let parts: Vec<&str> = [self.language.as_deref(), self.title.as_deref()]
+            .into_iter()
+            .flatten()
+            .map(str::trim)
+            .filter(|part| !part.is_empty())
+            .collect();
+        if parts.is_empty() {
+            String::new()
+        } else {
+            format!("（{}）", parts.join(" · "))
+        }
+    }
+}
+
+/// 列出视频中的音轨，同时读取语言与标题标签。
+async fn audio_streams(path: &Path, options: &ScannerOptions) -> Result<Vec<AudioStream>> {
+    let mut cmd = Command::new(options.ffprobe());
+    cmd.arg("-v")
+        .arg("error")
+        .arg("-select_streams")
+        .arg("a")
+        .arg("-show_entries")
+        .arg("stream=index:stream_tags=language,title")
+        .arg("-of")
+        .arg("json")
+        .arg(path);
+    let output = run_tool(cmd, options.tool_timeout(), path).await?;
+
+    if !output.status.success() {
+        return Err(anyhow!(tr!(
+            "ffprobe 解析音轨失败，退出状态：{}",
+            output.status
+        )));
+    }
+
+    parse_audio_streams(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// 解析 ffprobe `-of json` 的音轨列表；标签名不区分大小写（部分容器写作 `LANGUAGE`）。
+fn parse_audio_streams(json: &str) -> Result<Vec<AudioStream>> {
+    #[derive(Deserialize)]
+    struct Probe {
+        #[serde(default)]
+        streams: Vec<ProbeStream>,
+    }
+    #[derive(Deserialize)]
+    struct ProbeStream {
+        index: u32,
+        #[serde(default)]
+        tags: HashMap<String, String>,
+    }
+
+    let probe: Probe =
+        serde_json::from_str(json).map_err(|e| anyhow!(tr!("无法解析 ffprobe 音轨信息：{}", e)))?;
+    Ok(probe
+        .streams
+        .into_iter()
+        .map(|stream| {
+            let tag = |name: &str| {
+                stream
+                    .tags
+                    .iter()
+                    .find(|(key, _)| key.eq_ignore_ascii_case(name))
+                    .map(|(_, value)| value.trim().to_string())
+                    .filter(|value| !value.is_empty())
+            };
+            AudioStream {
+                index: stream.index,
+                language: tag("language"),
+                title: tag("title"),
+            }
+        })
+        .collect())
+}
+
+/// 探测没有扩展名的文件：不含音轨时返回 `None`，否则返回是否含有视频画面。
+///
+/// 音频文件内嵌的封面（`attached_pic`）不算视频。
+async fn probe_media_kind(path: &Path, options: &ScannerOptions) -> Result<Option<bool>> {
+    let mut cmd = Command::new(options.ffprobe());
+    cmd.arg("-v")
+        .arg("error")
+        .arg("-show_entries")
+        .arg("stream=codec_type:stream_disposition=attached_pic")
+        .arg("-of")
+        .arg("csv=p=0")
+        .arg(path);
+    let output = run_tool(cmd, options.tool_timeout(), path).await?;
+
+    if !output.status.success() {
+        return Err(anyhow!(tr!(
+            "ffprobe 无法识别 {}，退出状态：{}",
+            display_path(&path),
+            output.status
+        )));
+    }
+
+    Ok(parse_media_kind(&String::from_utf8_lossy(&output.stdout)))
+}
+
+fn parse_media_kind(stdout: &str) -> Option<bool> {
+    let mut has_audio = false;
+    let mut has_video = false;
+    for line in stdout.lines() {
+        let mut fields = line.trim().split(',');
+        match (fields.next(), fields.next()) {
+            (Some("audio"), _) => has_audio = true,
+            (Some("video"), attached) if attached != Some("1") => has_video = true,
+            _ => {}
+        }
+    }
+    has_audio.then_some(has_video)
+}
+
+/// ffprobe 可用的字幕流：`(流序号, 编码名)`。
+async fn subtitle_streams(path: &Path, options: &ScannerOptions) -> Result<Vec<(u32, String)>> {
+    let mut cmd = Command::new(options.ffprobe());
+    cmd.arg("-v")
+        .arg("error")
+        .arg("-select_streams")
+        .arg("s")
+        .arg("-show_entries")
+        .arg("stream=index,codec_name")
+        .arg("-of")
+        .arg("csv=p=0")
+        .arg(path);
+    let output = run_tool(cmd, options.tool_timeout(), path).await?;
+
+    if !output.status.success() {
+        return Err(anyhow!(tr!(
+            "ffprobe 解析字幕流失败，退出状态：{}",
+            output.status
+        )));
+    }
+
+    Ok(parse_subtitle_streams(&String::from_utf8_lossy(
+        &output.stdout,
+    )))
+}
+
+fn parse_subtitle_streams(stdout: &str) -> Vec<(u32, String)> {
+    stdout
+        .lines()
+        .filter_map(|line| {
+            let (index, codec) = line.trim().split_once(',')?;
+            Some((index.parse().ok()?, codec.trim().to_string()))
+        })
+        .collect()
+}
+
+/// 能转换为 SRT 的文本字幕编码；PGS、DVD 等图形字幕需要 OCR，不在此列。
+fn is_text_subtitle_codec(codec: &str) -> bool {
+    matches!(
+        codec,
+        "subrip" | "srt" | "ass" | "ssa" | "mov_text" | "webvtt" | "text"
+    )
+}
+
+/// [`use_embedded_subtitle`] 的结果。
+enum EmbeddedSubtitle {
+    /// 没有可用的文本字幕流或提取失败，继续转写音轨。
+    Unavailable,
+    /// 已提取到给定路径。
+    Extracted(PathBuf),
+    /// 之前已提取过，本次跳过。
+    AlreadyExtracted,
+    /// 预览模式：只记录将要提取。
+    Preview,
+}
+
+/// 视频带有文本字幕流时将第一条提取为与源文件同名的 `.srt`，只在取消时返回错误。
+async fn use_embedded_subtitle(
+    path: &Path,
+    options: &ScannerOptions,
+    logger: &mut ScanLogger,
+) -> Result<EmbeddedSubtitle> {
+    let streams = match cancellable(&options.cancel, subtitle_streams(path, options)).await {
+        Ok(streams) => streams,
+        Err(e) if is_cancelled(&e) => return Err(e),
+        Err(e) => {
+            logger.error(tr!(
+                "读取 {} 字幕流失败，改为转写音轨：{}",
+                display_path(&path),
+                e
+            ));
+            return Ok(EmbeddedSubtitle::Unavailable);
+        }
+    };
+    let Some((index, codec)) = streams
+        .into_iter()
+        .find(|(_, codec)| is_text_subtitle_codec(codec))
+    else {
+        return Ok(EmbeddedSubtitle::Unavailable);
+    };
+
+    let output = options.result_srt_path(path, None);
+    if output.exists() && !options.force_reprocess {
+        logger.info(tr!(
+            "跳过 {}：内嵌字幕已提取到 {}。",
+            display_path(&path),
+            display_path(&output)
+        ));
+        return Ok(EmbeddedSubtitle::AlreadyExtracted);
+    }
+    if options.dry_run {
+        logger.info(tr!(
+            "待提取内嵌字幕：{}（字幕流 {}，{}）",
+            display_path(&path),
+            index,
+            codec
+        ));
+        return Ok(EmbeddedSubtitle::Preview);
+    }
+
+    if let Err(e) = options.ensure_output_parent(&output).await {
+        logger.error(tr!("{}，改为转写音轨。", e));
+        return Ok(EmbeddedSubtitle::Unavailable);
+    }
+    let temp = atomic_temp_path(&output);
+    let mut cmd = Command::new(options.ffmpeg());
+    cmd.arg("-y")
+        .arg("-v")
+        .arg("error")
+        .arg("-i")
+        .arg(path)
+        .arg("-map")
+        .arg(format!("0:{}", index))
+        .arg("-c:s")
+        .arg("srt")
+        .arg("-f")
+        .arg("srt")
+        .arg(&temp);
+    let extracted = match run_tool_to_output(cmd, options, path, &temp, tr!("FFmpeg 提取字幕失败"))
+        .await
+    {
+        // 先提取到临时文件再重命名，中途被终止时不会留下被当作已提取的半截字幕。
+        Ok(()) => fs::rename(&temp, &output).await.map_err(|e| {
+            let _ = std::fs::remove_file(&temp);
+            anyhow::Error::from(e)
+        }),
+        Err(e) => Err(e),
+    };
+    match extracted {
+        Ok(()) => {
+            logger.success(tr!(
+                "{} 已包含字幕流 {}（{}），已提取到 {}，未调用 ASR 转写。",
+                display_path(&path),
+                index,
+                codec,
+                display_path(&output)
+            ));
+            Ok(EmbeddedSubtitle::Extracted(output))
+        }
+        Err(e) if is_cancelled(&e) => Err(e),
+        Err(e) => {
+            logger.error(tr!(
+                "提取 {} 的内嵌字幕失败，改为转写音轨：{}",
+                display_path(&path),
+                e
+            ));
+            Ok(EmbeddedSubtitle::Unavailable)
+        }
+    }
+}
+
+/// 任务待处理音轨的音频时长合计（秒），续写的文件只计新增部分；无法读取时长时返回 `None`。
+async fn pending_job_secs(job: &PendingJob, options: &ScannerOptions) -> Option<f64> {
+    let new_secs = |duration: f64, append: &Option<AppendState>| {
+        (duration - append.map(|state| state.offset).unwrap_or(0.0)).max(0.0)
+    };
+    let duration = media_duration(job.path(), options).await.ok()?;
+    Some(match job {
+        PendingJob::Audio { append, .. } => new_secs(duration, append),
+        PendingJob::Video { tracks, .. } => tracks
+            .iter()
+            .map(|track| new_secs(duration, &track.append))
+            .sum(),
+    })
+}
+
+/// 读取媒体时长（秒）。
+///
+/// 部分流式容器的 `format=duration` 为 `N/A`，此时依次回退到各流时长的最大值、
+/// 首条音轨最后一个数据包的结束时间；都无法得到时才返回错误，由调用方按文本长度估算。
+async fn media_duration(path: &Path, options: &ScannerOptions) -> Result<f64> {
+    let format = probe_entries(path, options, &[], "format=duration").await?;
+    if let Some(duration) = parse_format_duration(&format) {
+        return Ok(duration);
+    }
+    let streams = probe_entries(path, options, &[], "stream=duration").await?;
+    if let Some(duration) = parse_max_duration(&streams) {
+        return Ok(duration);
+    }
+    let packets = probe_entries(
+        path,
+        options,
+        &["-select_streams", "a:0"],
+        "packet=pts_time,duration_time",
+    )
+    .await?;
+    parse_packet_end(&packets)
+        .ok_or_else(|| anyhow!(tr!("无法解析 {} 的时长", display_path(&path))))
+}
+
+/// 运行 ffprobe 读取 `entries` 指定的字段，每行一条记录、逗号分隔且不带字段名。
+async fn probe_entries(
+    path: &Path,
+    options: &ScannerOptions,
+    extra_args: &[&str],
+    entries: &str,
+) -> Result<String> {
+    let mut cmd = Command::new(options.ffprobe());
+    cmd.arg("-v")
+        .arg("error")
+        .args(extra_args)
+        .arg("-show_entries")
+        .arg(entries)
+        .arg("-of")
+        .arg("csv=p=0")
+        .arg(path);
+    let output = run_tool(cmd, options.tool_timeout(), path).await?;
+
+    if !output.status.success() {
+        return Err(anyhow!(tr!(
+            "ffprobe 读取 {} 时长失败，退出状态：{}",
+            display_path(&path),
+            output.status
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// 解析一个时长字段，`N/A`、负数与非有限值视为缺失。
+fn parse_duration_field(value: &str) -> Option<f64> {
+    value
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|secs| secs.is_finite() && *secs > 0.0)
+}
+
+/// `format=duration` 的输出取第一条有效值。
+fn parse_format_duration(stdout: &str) -> Option<f64> {
+    stdout.lines().find_map(parse_duration_field)
+}
+
+/// 各流 `duration` 中的最大值，音视频流长度略有差异时以最长者为准。
+fn parse_max_duration(stdout: &str) -> Option<f64> {
+    stdout
+        .lines()
+        .filter_map(parse_duration_field)
+        .max_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
+}
+
+/// 由数据包的 `pts_time,duration_time` 估算结束时间：取各包起点加时长的最大值。
+fn parse_packet_end(stdout: &str) -> Option<f64> {
+    stdout
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split(',');
+            let pts = fields.next()?.trim().parse::<f64>().ok()?;
+            let duration = fields
+                .next()
+                .and_then(|value| value.trim().parse::<f64>().ok())
+                .unwrap_or(0.0);
+            Some(pts + duration)
+        })
+        .filter(|end| end.is_finite() && *end > 0.0)
+        .max_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
+}
+
+fn track_suffix(track_index: Option<u32>, segment_index: Option<usize>) -> String {
+    match (track_index, segment_index) {
+        (Some(track), Some(segment)) => tr!("（音轨 {} · 片段 {}）", track, segment),
+        (Some(track), None) => tr!("（音轨 {}）", track),
+        (None, Some(segment)) => tr!("（片段 {}）", segment),
+        (None, None) => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn media_extension_detection() {
+        for ext in ["mp3", "wav", "ogg", "mp4", "mkv"] {
+            assert!(is_media_extension(ext));
+        }
+
+        for ext in ["flac", "aac", "opus", "webm", "mka"] {
+            assert!(is_media_extension(ext), "{}", ext);
+        }
+        assert!(has_media_extension(Path::new("D:/talks/Intro.MP3")));
+        assert!(!has_media_extension(Path::new("D:/talks/notes.txt")));
+        assert!(!has_media_extension(Path::new("D:/talks/README")));
+        assert!(is_video(Path::new("clip.WEBM")));
+        assert!(is_video(Path::new("album.mka")));
+        assert!(!is_video(Path::new("song.flac")));
+        assert!(!is_video(Path::new("voice.aac")));
+
+        for ext in ["txt", "rs", "json", "zip"] {
+            assert!(!is_media_extension(ext));
+        }
+    }
+
+    #[test]
+    fn intermediate_audio_is_not_watchable() {
+        for name in [
+            "talk.mp4-track1.mp3",
+            "talk.mp3-prepared.mp3",
+            "talk.mp4-track2-prepared.mp3",
+            "talk.wav-seg3.mp3",
+            "talk.mkv-track1-seg12.mp3",
+            "talk.mp4-track1-seg2.ogg",
+            "talk.mp4-vad.wav",
+        ] {
+            assert!(is_intermediate_audio(Path::new(name)), "{}", name);
+            assert!(!is_watchable_media(Path::new(name)), "{}", name);
+        }
+
+        for name in [
+            "talk.mp3",
+            "talk-prepared.mp3",
+            "talk-track1.mp3",
+            "talk.mp4",
+        ] {
+            assert!(!is_intermediate_audio(Path::new(name)), "{}", name);
+            assert!(is_watchable_media(Path::new(name)), "{}", name);
+        }
+        assert!(!is_watchable_media(Path::new("talk.mp4.vadcache.json")));
+    }
+
+    #[test]
+    fn video_detection() {
+        assert!(is_video(Path::new("C:/data/sample.MP4")));
+        assert!(!is_video(Path::new("C:/data/audio.mp3")));
+        assert!(!is_video(Path::new("C:/data/no_ext")));
+    }
+
+    #[test]
+    fn transcript_path_preserves_original_name() {
+        let path = Path::new("C:/tmp/input/video.mp4");
+        let txt = transcript_result_path(path, None);
+        assert_eq!(txt, PathBuf::from("C:/tmp/input/video.srt"));
+
+        let track_txt = transcript_result_path(path, Some("轨道2"));
+        assert_eq!(track_txt, PathBuf::from("C:/tmp/input/video.轨道2.srt"));
+
+        let no_ext = Path::new("/tmp/audio");
+        let txt2 = transcript_result_path(no_ext, None);
+        assert_eq!(txt2, PathBuf::from("/tmp/audio.srt"));
+    }
+
+    #[test]
+    fn sanitized_result_names_replace_unsafe_characters() {
+        assert_eq!(sanitize_output_filename("a:b?c*d", "_"), "a_b_c_d");
+        assert_eq!(sanitize_output_filename("问答|第1集. ", "-"), "问答-第1集");
+        assert_eq!(sanitize_output_filename("x\ty", "/"), "x_y");
+        assert_eq!(sanitize_output_filename("...", "_"), "_");
+
+        let media = Path::new("/media/Q&A: live?.mp4");
+        let mut options = ScannerOptions {
+            filename_substitute: "_".to_string(),
+            ..ScannerOptions::default()
+        };
+        assert_eq!(
+            options.transcript_path(media, Some("轨道2")),
+            Path::new("/media/Q&A: live?.轨道2.srt")
+        );
+        options.sanitize_filenames = true;
+        assert_eq!(
+            options.transcript_path(media, Some("轨道2")),
+            Path::new("/media/Q&A_ live_.轨道2.srt")
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn sanitized_names_that_collide_get_a_distinct_suffix() {
+        let dir = scratch_dir("sanitize_collision");
+        for name in ["a:b.mp3", "a?b.mp3", "a_b.mp3", "c:d.mp3"] {
+            std::fs::write(dir.join(name), b"").unwrap();
+        }
+        let options = ScannerOptions {
+            sanitize_filenames: true,
+            filename_substitute: "_".to_string(),
+            ..ScannerOptions::default()
+        };
+        let colon = options.transcript_path(&dir.join("a:b.mp3"), None);
+        let question = options.transcript_path(&dir.join("a?b.mp3"), None);
+        let clean = options.transcript_path(&dir.join("a_b.mp3"), None);
+        assert_eq!(clean, dir.join("a_b.srt"));
+        assert_ne!(colon, question);
+        assert_ne!(colon, clean);
+        assert!(colon
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .starts_with("a_b~"));
+        assert_eq!(colon, options.transcript_path(&dir.join("a:b.mp3"), None));
+        // 没有重名时仍使用清理后的原样主干。
+        assert_eq!(
+            options.transcript_path(&dir.join("c:d.mp3"), None),
+            dir.join("c_d.srt")
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn audio_track_path_includes_track_id() {
+        let path = Path::new("/media/sample.mkv");
+        let mp3 = audio_track_path(path, 1, "mp3");
+        assert_eq!(mp3, PathBuf::from("/media/sample.mkv-track1.mp3"));
+        let ogg = audio_track_path(path, 2, ExportCodec::Opus.extension());
+        assert_eq!(ogg, PathBuf::from("/media/sample.mkv-track2.ogg"));
+    }
+
+    #[test]
+    fn atempo_chain_splits_large_factors() {
+        assert_eq!(atempo_filter_chain(1.25), "atempo=1.2500");
+        assert_eq!(atempo_filter_chain(3.0), "atempo=2.0,atempo=1.5000");
+        assert_eq!(atempo_filter_chain(10.0), "atempo=2.0,atempo=2.0000");
+    }
+
+    #[test]
+    fn speedup_timestamps_rescale_to_original() {
+        assert!((rescale_to_original(80.0, Some(1.25)) - 100.0).abs() < 1e-9);
+        assert!((rescale_to_original(80.0, None) - 80.0).abs() < 1e-9);
+
+        let mut options = ScannerOptions {
+            speedup_factor: Some(1.0),
+            ..Default::default()
+        };
+        assert_eq!(options.speedup(), None);
+        options.speedup_factor = Some(8.0);
+        assert_eq!(options.speedup(), Some(4.0));
+    }
+
+    #[tokio::test]
+    async fn keep_intermediates_skips_cleanup() {
+        let dir = scratch_dir("keep_intermediates");
+        let temp = dir.join("talk.mp4-track1.mp3");
+        let audio = |path: &Path| MaterializedAudio {
+            path: path.to_path_buf(),
+            cleanup: true,
+        };
+        std::fs::write(&temp, b"mp3").unwrap();
+
+        let mut options = ScannerOptions {
+            keep_intermediates: true,
+            ..ScannerOptions::default()
+        };
+        let mut retained = Vec::new();
+        cleanup_materialized(audio(&temp), &options).await.unwrap();
+        release_intermediate(&options, temp.clone(), &mut retained).await;
+        assert!(temp.exists());
+        assert_eq!(retained, vec![temp.clone()]);
+
+        options.keep_intermediates = false;
+        cleanup_materialized(audio(&temp), &options).await.unwrap();
+        assert!(!temp.exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn export_codec_args_follow_codec_and_bitrate() {
+        let mut options = ScannerOptions::default();
+        assert_eq!(options.export_codec_args(), vec!["-c:a", "libmp3lame"]);
+
+        options.export_codec = ExportCodec::Opus;
+        options.export_bitrate = Some(" 48k ".to_string());
+        assert_eq!(
+            options.export_codec_args(),
+            vec!["-c:a", "libopus", "-b:a", "48k"]
+        );
+
+        for ok in ["", "64k", "128K", "96000"] {
+            assert!(validate_export_bitrate(ok).is_ok(), "{}", ok);
+        }
+        for bad in ["k", "64kb", "fast", "-64k"] {
+            assert!(validate_export_bitrate(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn audio_filter_puts_loudnorm_before_speedup() {
+        let mut options = ScannerOptions::default();
+        assert_eq!(options.audio_filter(true, None), None);
+
+        options.audio_normalize = true;
+        assert_eq!(
+            options.audio_filter(true, None).as_deref(),
+            Some(LOUDNORM_FILTER)
+        );
+
+        options.speedup_factor = Some(1.25);
+        assert_eq!(
+            options.audio_filter(true, None),
+            Some(format!("{},atempo=1.2500", LOUDNORM_FILTER))
+        );
+        assert_eq!(
+            options.audio_filter(false, None).as_deref(),
+            Some(LOUDNORM_FILTER)
+        );
+    }
+
+    #[test]
+    fn downmix_filter_precedes_loudnorm() {
+        let mut options = ScannerOptions {
+            downmix_filter: DownmixFilter::CenterDialogue,
+            ..ScannerOptions::default()
+        };
+        assert_eq!(
+            options.audio_filter(false, Some(6)).as_deref(),
+            Some("pan=mono|c0=0.6*FC+0.2*FL+0.2*FR")
+        );
+        // 立体声没有中置声道，声道数未知时也不应用预设。
+        assert_eq!(options.audio_filter(false, Some(2)), None);
+        assert_eq!(options.audio_filter(false, None), None);
+
+        options.downmix_filter = DownmixFilter::FrontLeft;
+        options.audio_normalize = true;
+        assert_eq!(
+            options.audio_filter(true, Some(2)),
+            Some(format!("pan=mono|c0=FL,{}", LOUDNORM_FILTER))
+        );
+        assert_eq!(
+            options.audio_filter(true, Some(1)).as_deref(),
+            Some(LOUDNORM_FILTER)
+        );
+        options.trim_segment_silence = true;
+        assert_eq!(
+            options.segment_audio_filter(SegmentKind::Speech, Some(2)),
+            Some(format!(
+                "pan=mono|c0=FL,{},{}",
+                LOUDNORM_FILTER, TRIM_SILENCE_FILTER
+            ))
+        );
+    }
+
+    #[test]
+    fn silence_trim_applies_only_to_speech_segments() {
+        let mut options = ScannerOptions {
+            trim_segment_silence: true,
+            ..ScannerOptions::default()
+        };
+        assert_eq!(
+            options
+                .segment_audio_filter(SegmentKind::Speech, None)
+                .as_deref(),
+            Some(TRIM_SILENCE_FILTER)
+        );
+        assert_eq!(options.segment_audio_filter(SegmentKind::Gap, None), None);
+
+        options.audio_normalize = true;
+        assert_eq!(
+            options.segment_audio_filter(SegmentKind::Speech, None),
+            Some(format!("{},{}", LOUDNORM_FILTER, TRIM_SILENCE_FILTER))
+        );
+        assert_eq!(
+            options
+                .segment_audio_filter(SegmentKind::Gap, None)
+                .as_deref(),
+            Some(LOUDNORM_FILTER)
+        );
+
+        options.word_timestamps = true;
+        assert_eq!(
+            options
+                .segment_audio_filter(SegmentKind::Speech, None)
+                .as_deref(),
+            Some(LOUDNORM_FILTER)
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn hanging_tool_is_killed_on_timeout() {
+        let mut cmd = Command::new("sleep");
+        cmd.arg("30");
+        let started = Instant::now();
+        let err = run_tool(
+            cmd,
+            Some(Duration::from_millis(200)),
+            Path::new("/media/hang.mkv"),
+        )
+        .await
+        .unwrap_err();
+        assert!(is_tool_timeout(&err));
+        assert!(err.to_string().contains("超时"));
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn cancel_aborts_running_tool_and_removes_output() {
+        let dir = scratch_dir("cancel_tool");
+        let output = dir.join("talk.mp3-seg3.mp3");
+        std::fs::write(&output, b"half written").unwrap();
+        let options = ScannerOptions::default();
+        let cancel = options.cancel.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            cancel.cancel();
+        });
+
+        let mut cmd = Command::new("sleep");
+        cmd.arg("30");
+        let started = Instant::now();
+        let err = run_tool_to_output(cmd, &options, Path::new("/media/talk.mp3"), &output, "失败")
+            .await
+            .unwrap_err();
+        assert!(is_cancelled(&err));
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert!(!output.exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn atomic_writes_replace_whole_file_and_leave_no_temp() {
+        let dir = scratch_dir("atomic_write");
+        let srt = dir.join("talk.srt");
+        std::fs::write(&srt, build_srt_entry(1, 0.0, 1.0, "旧")).unwrap();
+
+        write_atomic(&srt, build_srt_entry(1, 0.0, 1.0, "新"))
+            .await
+            .unwrap();
+        append_atomic(&srt, &build_srt_entry(2, 1.0, 2.0, "续"))
+            .await
+            .unwrap();
+        let content = std::fs::read_to_string(&srt).unwrap();
+        assert_eq!(count_srt_cues(&content), 2);
+        assert!(content.starts_with("1\n") && content.contains("新") && !content.contains("旧"));
+        assert!(!atomic_temp_path(&srt).exists());
+
+        // 目标目录不存在时写入失败，也不会残留临时文件。
+        let missing = dir.join("missing").join("talk.srt");
+        assert!(write_atomic(&missing, "x").await.is_err());
+        assert!(!atomic_temp_path(&missing).exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn custom_tool_paths_take_precedence() {
+        let custom = Path::new("/opt/ffmpeg/bin/ffmpeg");
+        assert_eq!(ffmpeg_program(Some(custom)), custom.as_os_str());
+        assert_eq!(
+            ffprobe_program(None),
+            resolve_tool_path("ffprobe").as_os_str()
+        );
+
+        let missing = env::temp_dir().join("auto_asr_missing_tool/ffmpeg");
+        let err = check_ffmpeg(Some(missing)).await.unwrap_err();
+        assert!(err.to_string().contains("无法运行"));
+    }
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("auto_asr_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[tokio::test]
+    async fn cancel_after_one_segment_discards_partial_outputs() {
+        let dir = scratch_dir("cancel_discard");
+        let srt = dir.join("talk.srt");
+        let partial = partial_transcript_path(&srt);
+        let segment = dir.join("talk.mp3-seg2.mp3");
+        append_srt_entry(&partial, &build_srt_entry(1, 0.0, 1.0, "第一段"))
+            .await
+            .unwrap();
+        std::fs::write(&segment, b"mp3").unwrap();
+
+        let removed =
+            rollback_cancelled_outputs(&partial, std::slice::from_ref(&segment), true).await;
+
+        assert_eq!(removed, vec![segment.clone(), partial.clone()]);
+        assert!(!partial.exists());
+        assert!(!segment.exists());
+        assert!(!srt.exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn cancel_without_discard_keeps_partial_for_resume() {
+        let dir = scratch_dir("cancel_keep");
+        let srt = dir.join("talk.srt");
+        let partial = partial_transcript_path(&srt);
+        assert_eq!(partial, dir.join("talk.srt.partial"));
+        append_srt_entry(&partial, &build_srt_entry(1, 0.0, 1.0, "第一段"))
+            .await
+            .unwrap();
+
+        let removed = rollback_cancelled_outputs(&partial, &[], false).await;
+
+        // 不完整的结果不能写成 `.srt`，否则下次扫描会把它当作已完成。
+        assert!(removed.is_empty());
+        assert!(!srt.exists());
+        assert!(std::fs::read_to_string(&partial)
+            .unwrap()
+            .contains("第一段"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    fn synthetic_tone(amplitude: f32, noise: f32) -> Vec<i16> {
+        (0..VAD_DEFAULT_SAMPLE_RATE as usize)
+            .map(|i| {
+                // 前半段为“噪声”，后半段为“语音”。
+                let level = if i < VAD_DEFAULT_SAMPLE_RATE as usize / 2 {
+                    noise
+                } else {
+                    amplitude
+                };
+                let phase =
+                    i as f32 * 2.0 * std::f32::consts::PI * 220.0 / VAD_DEFAULT_SAMPLE_RATE as f32;
+                (phase.sin() * level * i16::MAX as f32) as i16
+            })
+            .collect()
+    }
+
+    #[test]
+    fn adaptive_threshold_follows_recording_level() {
+        let base = 0.6;
+        let quiet = measure_levels(&synthetic_tone(0.01, 0.001), VAD_DEFAULT_CHUNK_SIZE).unwrap();
+        let loud = measure_levels(&synthetic_tone(0.9, 0.05), VAD_DEFAULT_CHUNK_SIZE).unwrap();
+        assert!(quiet.speech_level_db < loud.speech_level_db);
+        assert!(quiet.noise_floor_db < quiet.speech_level_db);
+
+        let quiet_threshold = adapt_threshold(base, &quiet);
+        let loud_threshold = adapt_threshold(base, &loud);
+        assert!(quiet_threshold < base);
+        assert!(loud_threshold > base);
+        assert!(base - quiet_threshold <= ADAPTIVE_MAX_OFFSET + 1e-6);
+        assert!(loud_threshold - base <= ADAPTIVE_MAX_OFFSET + 1e-6);
+
+        // 语音同样偏小声时，噪声底高的录音阈值更高，且高于手动阈值。
+        let clean = measure_levels(&synthetic_tone(0.05, 0.0005), VAD_DEFAULT_CHUNK_SIZE).unwrap();
+        let noisy = measure_levels(&synthetic_tone(0.05, 0.03), VAD_DEFAULT_CHUNK_SIZE).unwrap();
+        assert!((clean.speech_level_db - noisy.speech_level_db).abs() < 0.5);
+        assert!(noisy.noise_floor_db > clean.noise_floor_db);
+        let clean_threshold = adapt_threshold(base, &clean);
+        let noisy_threshold = adapt_threshold(base, &noisy);
+        assert!(clean_threshold < base);
+        assert!(noisy_threshold > base);
+        assert!(measure_levels(&[], VAD_DEFAULT_CHUNK_SIZE).is_none());
+    }
+
+    #[tokio::test]
+    async fn cloned_loggers_share_logs_across_tasks() {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let logger = ScanLogger::new(Some(tx));
+        let mut tasks = JoinSet::new();
+        for idx in 0..4 {
+            let mut task_logger = logger.clone();
+            tasks.spawn(async move { task_logger.info(format!("任务 {}", idx)) });
+        }
+        while tasks.join_next().await.is_some() {}
+        logger.progress(4, 4, None);
+
+        let logs = logger.finish().await;
+        assert_eq!(logs.len(), 4);
+        let mut forwarded = Vec::new();
+        let mut last_progress = None;
+        while let Ok(event) = rx.try_recv() {
+            match event {
+                ScanEvent::Log(log) => forwarded.push(log.id),
+                ScanEvent::Progress {
+                    completed, total, ..
+                } => last_progress = Some((completed, total)),
+                _ => {}
+            }
+        }
+        // 实时送达的日志与扫描结果中的日志按序号一一对应，且序号互不相同。
+        forwarded.sort_unstable();
+        forwarded.dedup();
+        let mut finished: Vec<u64> = logs.iter().map(|log| log.id).collect();
+        finished.sort_unstable();
+        assert_eq!(forwarded, finished);
+        assert_eq!(forwarded.len(), 4);
+        assert_eq!(last_progress, Some((4, 4)));
+    }
+
+    #[tokio::test]
+    async fn track_and_segment_events_are_not_logged() {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let logger = ScanLogger::new(Some(tx));
+        let media = Path::new("/media/talk.mp4");
+        logger.segment_progress(media, 3, 10);
+        logger.track_finished(media, Ok(Path::new("/media/talk.srt")));
+        logger.track_finished(media, Err("调用 API 失败"));
+
+        assert!(logger.finish().await.is_empty());
+        assert!(matches!(
+            rx.try_recv(),
+            Ok(ScanEvent::Segment {
+                done: 3,
+                total: 10,
+                ..
+            })
+        ));
+        assert!(matches!(
+            rx.try_recv(),
+            Ok(ScanEvent::TrackDone { output, .. }) if output == Path::new("/media/talk.srt")
+        ));
+        assert!(matches!(
+            rx.try_recv(),
+            Ok(ScanEvent::TrackFailed { error, .. }) if error == "调用 API 失败"
+        ));
+    }
+
+    #[test]
+    fn file_tracker_reports_once_all_tracks_finish() {
+        let video = PathBuf::from("/media/movie.mkv");
+        let audio = PathBuf::from("/media/talk.mp3");
+        let mut tracker = FileTracker::default();
+        tracker.add(video.clone(), 2);
+        tracker.add(audio.clone(), 1);
+        assert_eq!(tracker.tracks(&video), 2);
+
+        assert_eq!(tracker.finish_track(&video, false), None);
+        assert_eq!(tracker.finish_track(&video, true), Some(FileState::Failed));
+        assert_eq!(tracker.finish_track(&audio, true), Some(FileState::Done));
+        assert_eq!(tracker.finish_track(Path::new("/other.mp3"), true), None);
+
+        // 多音轨视频的每条音轨成功后，运行报告中只计一次。
+        assert!(tracker.first_success(&video));
+        assert!(!tracker.first_success(&video));
+        assert!(tracker.first_success(&audio));
+    }
+
+    #[tokio::test]
+    async fn growing_file_appends_offset_cues() {
+        let dir = scratch_dir("append_tail");
+        let media = dir.join("lecture.mp3");
+        let srt = transcript_result_path(&media, None);
+        let existing = format!(
+            "{}{}",
+            build_srt_entry(1, 0.0, 30.0, "开场"),
+            build_srt_entry(2, 30.0, 60.0, "第一节")
+        );
+        std::fs::write(&srt, &existing).unwrap();
+        write_transcript_state(&srt, 60.0).await.unwrap();
+        assert_eq!(
+            transcript_state_path(&srt),
+            dir.join("lecture.srt.state.json")
+        );
+
+        let state = read_transcript_state(&srt).await.unwrap();
+        let source = AudioSource::from_audio_file(media).with_append(Some(AppendState {
+            offset: state.duration_secs,
+            existing_cues: count_srt_cues(&existing),
+        }));
+        assert_eq!(source.existing_cues(), 2);
+
+        // 尾部音频里 5~12 秒的分段应落在原始时间轴的 65~72 秒，编号续接为 3。
+        let partial = partial_transcript_path(&srt);
+        let entry = build_srt_entry(
+            source.existing_cues() + 1,
+            source.start_offset() + 5.0,
+            source.start_offset() + 12.0,
+            "第二节",
+        );
+        append_srt_entry(&partial, &entry).await.unwrap();
+        promote_partial(&partial, &srt, true).await.unwrap();
+
+        let merged = std::fs::read_to_string(&srt).unwrap();
+        assert!(merged.starts_with(&existing));
+        assert!(merged.ends_with("3\n00:01:05,000 --> 00:01:12,000\n第二节\n\n"));
+        assert_eq!(count_srt_cues(&merged), 3);
+        assert!(!partial.exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn plain_text_output_drops_timestamps() {
+        let media = Path::new("/tmp/demo/lecture.mkv");
+        assert_eq!(
+            transcript_output_path(media, Some("轨道1"), OutputFormat::Txt),
+            Path::new("/tmp/demo/lecture.轨道1.txt")
+        );
+        assert_eq!(
+            transcript_output_path(media, None, OutputFormat::Srt),
+            transcript_result_path(media, None)
+        );
+
+        let text: String = [(0.0, 2.0, " 第一段 "), (2.0, 5.0, "第二段\n续")]
+            .iter()
+            .enumerate()
+            .map(|(idx, (start, end, text))| {
+                OutputFormat::Txt.render_entry(idx + 1, *start, *end, text.trim())
+            })
+            .collect();
+        assert_eq!(text, "第一段\n第二段\n续\n");
+    }
+
+    #[test]
+    fn ass_output_has_styles_and_dialogue_lines() {
+        let options = ScannerOptions {
+            output_format: OutputFormat::Ass,
+            ass_style: AssStyle {
+                font_name: "Noto Sans CJK SC".to_string(),
+                font_size: 60,
+            },
+            ..ScannerOptions::default()
+        };
+        assert_eq!(
+            options.transcript_path(Path::new("/tmp/demo/lecture.mkv"), None),
+            Path::new("/tmp/demo/lecture.ass")
+        );
+        assert_eq!(format_ass_timestamp(3725.456), "1:02:05.46");
+
+        let content = build_ass_file(
+            &options.ass_style,
+            &[
+                (0.0, 1.5, "你好".to_string()),
+                (61.0, 62.25, "第二行\n{续}".to_string()),
+            ],
+        );
+        assert!(content.starts_with("[Script Info]\n"));
+        assert!(content.starts_with(&options.result_header().unwrap()));
+        assert!(content.contains("\n[V4+ Styles]\n"));
+        assert!(content.contains("Style: Default,Noto Sans CJK SC,60,"));
+        assert!(content.ends_with(
+            "Dialogue: 0,0:00:00.00,0:00:01.50,Default,,0,0,0,,你好\n\
+             Dialogue: 0,0:01:01.00,0:01:02.25,Default,,0,0,0,,第二行\\N\\{续\\}\n"
+        ));
+    }
+
+    #[test]
+    fn json_transcript_serializes_segment_kinds() {
+        let segments = vec![
+            TranscriptSegment {
+                index: 1,
+                start_sec: 0.0,
+                end_sec: 2.5,
+                text: "你好".to_string(),
+                kind: SegmentKind::Speech,
+                speaker: None,
+            },
+            TranscriptSegment {
+                index: 2,
+                start_sec: 2.5,
+                end_sec: 4.0,
+                text: "嗯".to_string(),
+                kind: SegmentKind::Gap,
+                speaker: None,
+            },
+        ];
+        let json = build_json_transcript(&segments).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value[0]["kind"], "speech");
+        assert_eq!(value[1]["kind"], "gap");
+        assert_eq!(value[1]["start_sec"], 2.5);
+        assert_eq!(value[1]["text"], "嗯");
+        let parsed: Vec<TranscriptSegment> = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, segments);
+        assert_eq!(
+            transcript_json_path(Path::new("/tmp/demo/lecture.轨道1.srt")),
+            Path::new("/tmp/demo/lecture.轨道1.json")
+        );
+    }
+
+    #[tokio::test]
+    async fn multiple_directories_are_merged_and_missing_ones_skipped() {
+        let first = scratch_dir("multi_first");
+        let second = scratch_dir("multi_second");
+        std::fs::write(first.join("a.mp3"), b"").unwrap();
+        std::fs::write(second.join("b.mp3"), b"").unwrap();
+        std::fs::write(second.join("c.wav"), b"").unwrap();
+        let missing = first.join("missing");
+
+        let options = ScannerOptions {
+            dry_run: true,
+            ..ScannerOptions::default()
+        };
+        let report =
+            process_directories(vec![first.clone(), missing, second.clone()], options, None)
+                .await
+                .unwrap();
+        assert!(report.dry_run);
+        assert_eq!(report.pending, 3);
+        assert!(report
+            .logs
+            .iter()
+            .any(|log| matches!(log.level, ScanLogLevel::Error) && log.message.contains("已跳过")));
+        let _ = std::fs::remove_dir_all(&first);
+        let _ = std::fs::remove_dir_all(&second);
+    }
+
+    #[tokio::test]
+    async fn roots_sharing_output_dir_do_not_collide() {
+        let first = scratch_dir("shared_out_a").join("shows");
+        let second = scratch_dir("shared_out_b").join("shows");
+        let output = scratch_dir("shared_out_dir");
+        std::fs::create_dir_all(&first).unwrap();
+        std::fs::create_dir_all(&second).unwrap();
+        std::fs::write(first.join("ep1.mp3"), b"").unwrap();
+        std::fs::write(second.join("ep1.mp3"), b"").unwrap();
+        std::fs::create_dir_all(output.join("shows")).unwrap();
+        std::fs::write(
+            output.join("shows").join("ep1.srt"),
+            build_srt_entry(1, 0.0, 1.0, "好"),
+        )
+        .unwrap();
+        assert_eq!(
+            output_subdirs(&[first.clone(), second.clone()]),
+            vec!["shows".to_string(), "shows-2".to_string()]
+        );
+
+        let options = ScannerOptions {
+            dry_run: true,
+            output_dir: Some(output.clone()),
+            ..ScannerOptions::default()
+        };
+        let report = process_directories(vec![first.clone(), second.clone()], options, None)
+            .await
+            .unwrap();
+        assert_eq!(report.pending, 1);
+        assert_eq!(report.skipped, 1);
+        let _ = std::fs::remove_dir_all(first.parent().unwrap());
+        let _ = std::fs::remove_dir_all(second.parent().unwrap());
+        let _ = std::fs::remove_dir_all(&output);
+    }
+
+    #[tokio::test]
+    async fn dry_run_lists_pending_audio_without_api_key() {
+        let dir = scratch_dir("dry_run");
+        std::fs::write(dir.join("new.mp3"), b"").unwrap();
+        std::fs::write(dir.join("done.mp3"), b"").unwrap();
+        std::fs::write(dir.join("done.srt"), b"1\n").unwrap();
+        std::fs::write(dir.join("notes.txt"), b"").unwrap();
+
+        let options = ScannerOptions {
+            dry_run: true,
+            ..ScannerOptions::default()
+        };
+        let report = process_directory(dir.clone(), options, None).await.unwrap();
+        assert!(report.dry_run);
+        assert_eq!(report.pending, 1);
+        assert_eq!(report.skipped, 1);
+        assert!(report.succeeded.is_empty());
+        assert!(report
+            .logs
+            .iter()
+            .any(|log| log.message.contains("待转写音频") && log.message.contains("new.mp3")));
+        assert!(!dir.join("new.srt").exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn discovered_jobs_stream_through_bounded_channel() {
+        let dir = scratch_dir("stream_jobs");
+        for idx in 0..40 {
+            std::fs::write(dir.join(format!("ep{:02}.mp3", idx)), b"").unwrap();
+        }
+
+        // 通道容量等于并发数（此处为 1），遍历须随接收端逐个推进而不死锁。
+        let options = ScannerOptions {
+            dry_run: true,
+            ..ScannerOptions::default()
+        };
+        let report = process_directory(dir.clone(), options.clone(), None)
+            .await
+            .unwrap();
+        assert_eq!(report.pending, 40);
+        let listed = report
+            .logs
+            .iter()
+            .filter(|log| log.message.contains("待转写音频"))
+            .count();
+        assert_eq!(listed, 40);
+
+        // 已取消的扫描不再遍历目录。
+        options.cancel.cancel();
+        let report = process_directory(dir.clone(), options, None).await.unwrap();
+        assert_eq!((report.found, report.pending), (0, 0));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn cost_estimate_is_logged_before_first_job_is_handed_out() {
+        let dir = scratch_dir("estimate_first");
+        for idx in 0..3 {
+            std::fs::write(dir.join(format!("ep{:02}.mp3", idx)), b"").unwrap();
+        }
+        let options = ScannerOptions {
+            cost_per_minute: Some(0.5),
+            ffprobe_path: Some(dir.join("missing-ffprobe")),
+            ..ScannerOptions::default()
+        };
+        let (event_tx, mut event_rx) = mpsc::unbounded_channel();
+        let (job_tx, mut job_rx) = mpsc::channel(1);
+        let discovery = discover_jobs(&dir, &options, ScanLogger::new(Some(event_tx)), job_tx);
+        let receive = async {
+            // 收到第一个任务（即可开始转写）时，预估必须已经写入日志。
+            let first = job_rx.recv().await;
+            let mut logged = Vec::new();
+            while let Ok(event) = event_rx.try_recv() {
+                if let ScanEvent::Log(log) = event {
+                    logged.push(log.message);
+                }
+            }
+            let mut received = usize::from(first.is_some());
+            while job_rx.recv().await.is_some() {
+                received += 1;
+            }
+            (logged, received)
+        };
+        let (discovery, (logged, received)) = tokio::join!(discovery, receive);
+
+        assert_eq!(received, 3);
+        assert_eq!(discovery.unknown_durations, 3);
+        assert!(logged.iter().any(|m| m.contains("待处理音轨总数：3")));
+        assert!(logged.iter().any(|m| m.contains("估算费用约 0.00")));
+        assert!(logged.iter().any(|m| m.contains("3 个文件无法读取时长")));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn single_file_scan_only_handles_the_chosen_file() {
+        let dir = scratch_dir("single_file");
+        std::fs::write(dir.join("chosen.mp3"), b"").unwrap();
+        std::fs::write(dir.join("other.mp3"), b"").unwrap();
+        std::fs::write(dir.join("notes.txt"), b"").unwrap();
+
+        let options = ScannerOptions {
+            dry_run: true,
+            exclude_patterns: vec!["chosen.*".to_string()],
+            ..ScannerOptions::default()
+        };
+        let report = process_files(vec![dir.join("chosen.mp3")], options.clone(), None)
+            .await
+            .unwrap();
+        assert_eq!(report.found, 1);
+        assert_eq!(report.pending, 1);
+        assert!(report
+            .logs
+            .iter()
+            .any(|log| log.message.contains("chosen.mp3")));
+
+        let err = process_files(vec![dir.join("notes.txt")], options.clone(), None)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("不是支持的媒体文件"));
+        assert!(
+            process_files(vec![dir.join("missing.mp3")], options.clone(), None)
+                .await
+                .is_err()
+        );
+
+        // 多个文件（含其他目录中的文件）一起处理，重复列出的只算一次。
+        let elsewhere = scratch_dir("single_file_elsewhere");
+        std::fs::write(elsewhere.join("b.mp3"), b"").unwrap();
+        let files = vec![
+            dir.join("chosen.mp3"),
+            dir.join("other.mp3"),
+            elsewhere.join("b.mp3"),
+            dir.join("chosen.mp3"),
+        ];
+        let report = process_files(files, options, None).await.unwrap();
+        assert_eq!(report.pending, 3);
+        let _ = std::fs::remove_dir_all(&dir);
+        let _ = std::fs::remove_dir_all(&elsewhere);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn listed_files_match_the_scan_root_after_canonicalizing() {
+        let dir = scratch_dir("listed_files");
+        std::fs::create_dir_all(dir.join("real/sub")).unwrap();
+        std::fs::write(dir.join("real/a.mp3"), b"").unwrap();
+        std::fs::write(dir.join("real/sub/b.mp3"), b"").unwrap();
+        std::os::unix::fs::symlink(dir.join("real"), dir.join("link")).unwrap();
+        let root = dir.join("link");
+
+        // 经符号链接列出的扫描根与经真实路径给出的文件仍能对应，且结果以扫描根为前缀。
+        let files = vec![
+            dir.join("real/a.mp3"),
+            dir.join("link/a.mp3"),
+            dir.join("real/sub/b.mp3"),
+            dir.join("elsewhere.mp3"),
+        ];
+        assert_eq!(
+            listed_files_under(&root, &files, None),
+            vec![root.join("a.mp3"), root.join("sub/b.mp3")]
+        );
+        assert_eq!(
+            listed_files_under(&root, &files, Some(1)),
+            vec![root.join("a.mp3")]
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn describe_video_job_lists_tracks() {
+        let job = PendingJob::Video {
+            path: PathBuf::from("/media/movie.mkv"),
+            tracks: vec![
+                PendingTrack {
+                    stream: AudioStream {
+                        index: 1,
+                        language: None,
+                        title: None,
+                    },
+                    label: numbered_track_label(1),
+                    append: None,
+                },
+                PendingTrack {
+                    stream: AudioStream {
+                        index: 2,
+                        language: Some("jpn".to_string()),
+                        title: None,
+                    },
+                    label: numbered_track_label(2),
+                    append: Some(AppendState {
+                        offset: 65.0,
+                        existing_cues: 3,
+                    }),
+                },
+            ],
+        };
+        let text = describe_pending_job(&job);
+        assert!(text.contains("movie.mkv"));
+        assert!(text.contains("音轨 1、2（jpn）（从 01:05.000 起追加）"));
+    }
+
+    #[test]
+    fn max_depth_limits_recursion() {
+        let dir = scratch_dir("max_depth");
+        std::fs::create_dir_all(dir.join("season1/extras")).unwrap();
+        for file in ["top.mp3", "season1/ep1.mp3", "season1/extras/bts.mp3"] {
+            std::fs::write(dir.join(file), b"").unwrap();
+        }
+        let count = |depth| {
+            scan_walker(&dir, depth)
+                .into_iter()
+                .filter_map(|e| e.ok())
+                .filter(|e| e.path().is_file())
+                .count()
+        };
+        assert_eq!(count(Some(1)), 1);
+        assert_eq!(count(Some(2)), 2);
+        assert_eq!(count(None), 3);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn exclude_patterns_match_relative_paths() {
+        let mut logger = ScanLogger::new(None);
+        let patterns = vec![
+            "**/backup/**".to_string(),
+            "*.sample.mp4".to_string(),
+            "_raw/**".to_string(),
+            "[".to_string(),
+        ];
+        let excludes = build_glob_set(&patterns, "排除", &mut logger);
+        let logs = logger.finish().await;
+        assert_eq!(logs.len(), 1, "无效规则应记录一条错误");
+
+        let root = Path::new("/media");
+        for path in [
+            "/media/show/backup/ep1.mkv",
+            "/media/backup/ep1.mkv",
+            "/media/trailer.sample.mp4",
+            "/media/s1/trailer.sample.mp4",
+            "/media/_raw/take1.wav",
+        ] {
+            assert!(matches_relative(&excludes, root, Path::new(path)), "{path}");
+        }
+        for path in [
+            "/media/show/ep1.mkv",
+            "/media/s1/_raw.mp3",
+            "/media/backups/ep1.mkv",
+        ] {
+            assert!(
+                !matches_relative(&excludes, root, Path::new(path)),
+                "{path}"
+            );
+        }
+    }
+
+    #[test]
+    fn whole_transcript_splits_into_proportional_cues() {
+        let cues = split_text_into_cues("你好。今天天气不错！\n我们开始吧", 20.0);
+        let texts: Vec<&str> = cues.iter().map(|(_, _, text)| text.as_str()).collect();
+        assert_eq!(texts, vec!["你好。", "今天天气不错！", "我们开始吧"]);
+        // 共 15 个字：3 + 7 + 5。
+        assert!((cues[0].1 - 4.0).abs() < 1e-9);
+        assert!((cues[1].0 - 4.0).abs() < 1e-9);
+        assert!((cues[1].1 - 13.333_333).abs() < 1e-3);
+        assert!((cues[2].1 - 20.0).abs() < 1e-9);
+
+        let long = "字".repeat(MAX_CUE_CHARS * 2 + 5);
+        let cues = split_text_into_cues(&long, 10.0);
+        assert_eq!(cues.len(), 3);
+        assert_eq!(cues[2].2.chars().count(), 5);
+        assert!(split_text_into_cues("  ", 10.0).is_empty());
+
+        let english = split_text_into_cues("It costs 3.5 dollars. Fine", 10.0);
+        assert_eq!(english.len(), 2);
+        assert_eq!(english[0].2, "It costs 3.5 dollars.");
+    }
+
+    #[test]
+    fn word_cues_break_on_punctuation_and_pauses() {
+        let word = |start: f64, end: f64, text: &str| TimedWord {
+            start,
+            end,
+            word: text.to_string(),
+        };
+        let words = vec![
+            word(0.0, 0.3, "Hello,"),
+            word(0.3, 0.6, "world."),
+            word(0.7, 0.9, "It's"),
+            word(0.9, 1.2, "fine"),
+            word(2.5, 2.7, "你"),
+            word(2.7, 2.9, "好"),
+        ];
+        assert_eq!(
+            build_word_cues(&words),
+            vec![
+                (0.0, 0.6, "Hello, world.".to_string()),
+                (0.7, 1.2, "It's fine".to_string()),
+                (2.5, 2.9, "你好".to_string()),
+            ]
+        );
+
+        let long: Vec<TimedWord> = (0..MAX_CUE_CHARS + 3)
+            .map(|idx| word(idx as f64 * 0.1, idx as f64 * 0.1 + 0.1, "字"))
+            .collect();
+        let cues = build_word_cues(&long);
+        assert_eq!(cues.len(), 2);
+        assert_eq!(cues[1].2.chars().count(), 3);
+
+        let transcription = Transcription {
+            words: words[..2].to_vec(),
+            ..Transcription::default()
+        };
+        let mut options = ScannerOptions::default();
+        assert_eq!(word_level_cues(&options, &transcription, Some(2.0)), None);
+        options.word_timestamps = true;
+        assert_eq!(
+            word_level_cues(&options, &transcription, Some(2.0)),
+            Some(vec![(0.0, 1.2, "Hello, world.".to_string())])
+        );
+    }
+
+    #[test]
+    fn close_speech_segments_are_merged() {
+        let segments = vec![
+            SpeechSegment::new(0.0, 1.0, SegmentKind::Speech),
+            SpeechSegment::new(1.2, 2.0, SegmentKind::Speech),
+            SpeechSegment::new(2.1, 3.0, SegmentKind::Speech),
+            SpeechSegment::new(4.0, 5.0, SegmentKind::Speech),
+        ];
+        let merged = merge_close_segments(segments.clone(), 0.3, 0.0);
+        assert_eq!(merged.len(), 2);
+        assert!((merged[0].start_sec - 0.0).abs() < 1e-9);
+        assert!((merged[0].end_sec - 3.0).abs() < 1e-9);
+        assert!((merged[1].start_sec - 4.0).abs() < 1e-9);
+
+        assert_eq!(merge_close_segments(segments.clone(), 0.0, 0.0).len(), 4);
+        // 合并后超过长度上限时保持分开。
+        assert_eq!(merge_close_segments(segments, 0.3, 2.5).len(), 3);
+    }
+
+    #[test]
+    fn padding_extends_segments_without_overlap() {
+        let segments = vec![
+            SpeechSegment::new(0.1, 1.0, SegmentKind::Speech),
+            SpeechSegment::new(1.3, 2.0, SegmentKind::Speech),
+            SpeechSegment::new(4.0, 4.8, SegmentKind::Speech),
+        ];
+        let cfg = VadConfig {
+            lead_padding_secs: 0.25,
+            tail_padding_secs: 0.5,
+            ..VadConfig::default()
+        };
+        let padded = pad_segments(segments, &cfg, 5.0);
+        let bounds: Vec<(f64, f64)> = padded.iter().map(|s| (s.start_sec, s.end_sec)).collect();
+        // 开头截到 0；0.3 秒的间隔按 0.5:0.25 分给前后两段；结尾截到音频时长。
+        let expected = [(0.0, 1.2), (1.2, 2.5), (3.75, 5.0)];
+        for ((start, end), (want_start, want_end)) in bounds.iter().zip(expected) {
+            assert!((start - want_start).abs() < 1e-9, "{:?}", bounds);
+            assert!((end - want_end).abs() < 1e-9, "{:?}", bounds);
+        }
+
+        let none = VadConfig {
+            lead_padding_secs: 0.0,
+            tail_padding_secs: 0.0,
+            ..VadConfig::default()
+        };
+        let unchanged = pad_segments(padded.clone(), &none, 5.0);
+        assert!((unchanged[1].start_sec - 1.2).abs() < 1e-9);
+    }
+
+    #[test]
+    fn long_segments_split_at_lowest_probability() {
+        // 100 个分块的语音段，上限 40：先在 [10, 90) 内概率最低的 60 处切开，
+        // 前半段 [0, 60) 再在 [10, 50) 内最低的 25 处切开。
+        let mut probabilities = vec![0.9f32; 100];
+        probabilities[25] = 0.3;
+        probabilities[60] = 0.2;
+        probabilities[95] = 0.0;
+        let cfg = VadConfig::default();
+        let segment = SpeechSegment::from_chunks(0, 100, &cfg);
+        let pieces = split_long_segments(vec![segment.clone()], &probabilities, 40, &cfg);
+        let bounds: Vec<(usize, usize)> = pieces
+            .iter()
+            .map(|seg| {
+                (
+                    cfg.time_to_chunk(seg.start_sec),
+                    cfg.time_to_chunk(seg.end_sec),
+                )
+            })
+            .collect();
+        assert_eq!(bounds, vec![(0, 25), (25, 60), (60, 100)]);
+
+        assert_eq!(
+            split_long_segments(vec![segment], &probabilities, 0, &cfg).len(),
+            1
+        );
+    }
+
+    #[test]
     fn expand_segments_adds_gap_coverage() {
         let speech_segments = vec![
             SpeechSegment::new(0.0, 2.0, SegmentKind::Speech),
@@ -1034,4 +7286,928 @@ mod tests {
         assert!((expanded[3].start_sec - 6.0).abs() < 1e-6);
         assert!((expanded[3].end_sec - 8.0).abs() < 1e-6);
     }
+
+    #[test]
+    fn skip_gap_policy_drops_silence_coverage() {
+        let speech_segments = vec![
+            SpeechSegment::new(4.0, 6.0, SegmentKind::Speech),
+            SpeechSegment::new(0.0, 2.0, SegmentKind::Speech),
+        ];
+        let expanded = expand_segments_with_gaps(&speech_segments, 8.0);
+
+        let skipped = apply_gap_policy(expanded.clone(), GapPolicy::Skip);
+        let starts: Vec<f64> = skipped.iter().map(|seg| seg.start_sec).collect();
+        assert_eq!(starts, vec![0.0, 4.0]);
+        assert!(skipped.iter().all(|seg| seg.kind == SegmentKind::Speech));
+
+        assert_eq!(
+            apply_gap_policy(expanded.clone(), GapPolicy::MarkEmpty).len(),
+            4
+        );
+        assert_eq!(apply_gap_policy(expanded, GapPolicy::Transcribe).len(), 4);
+        assert_eq!(GapPolicy::default(), GapPolicy::Skip);
+    }
+
+    #[test]
+    fn empty_or_outdated_transcript_is_stale() {
+        let dir = scratch_dir("stale_transcript");
+        let media = dir.join("talk.mp3");
+        let srt = dir.join("talk.srt");
+        std::fs::write(&media, b"audio").unwrap();
+        std::fs::write(&srt, b"").unwrap();
+        assert_eq!(
+            stale_transcript_reason(&media, &srt, true),
+            Some("已有结果为空")
+        );
+
+        std::fs::write(&srt, build_srt_entry(1, 0.0, 1.0, "好")).unwrap();
+        assert_eq!(stale_transcript_reason(&media, &srt, true), None);
+
+        let later = std::time::SystemTime::now() + Duration::from_secs(60);
+        std::fs::File::options()
+            .write(true)
+            .open(&media)
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+        assert_eq!(
+            stale_transcript_reason(&media, &srt, true),
+            Some("源文件在上次转写后被修改")
+        );
+        assert_eq!(stale_transcript_reason(&media, &srt, false), None);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn rescan_after_replace_mode_muxing_is_not_stale() {
+        let dir = scratch_dir("mux_replace_mtime");
+        let video = dir.join("ep1.mkv");
+        let srt = dir.join("ep1.srt");
+        let muxed = tagged_video_path(&video, MUXING_TEMP_TAG);
+        std::fs::write(&video, b"video").unwrap();
+        let earlier = std::time::SystemTime::now() - Duration::from_secs(60);
+        std::fs::File::options()
+            .write(true)
+            .open(&video)
+            .unwrap()
+            .set_modified(earlier)
+            .unwrap();
+        std::fs::write(&srt, build_srt_entry(1, 0.0, 1.0, "好")).unwrap();
+        std::fs::write(&muxed, b"video+subs").unwrap();
+
+        replace_with_muxed(&muxed, &video).await.unwrap();
+        assert!(!muxed.exists());
+        assert_eq!(std::fs::read(&video).unwrap(), b"video+subs");
+        assert_eq!(stale_transcript_reason(&video, &srt, true), None);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn speakers_split_by_level_then_by_pauses() {
+        let with_level = |start: f64, end: f64, level: f32| SpeechSegment {
+            level_db: Some(level),
+            ..SpeechSegment::new(start, end, SegmentKind::Speech)
+        };
+        // 两人电平相差明显：按电平归类，首个说话人为 0，补间没有标签。
+        let segments = vec![
+            with_level(0.0, 2.0, -30.0),
+            SpeechSegment::new(2.0, 2.5, SegmentKind::Gap),
+            with_level(2.5, 4.0, -18.0),
+            with_level(4.2, 6.0, -29.0),
+            with_level(6.1, 8.0, -17.5),
+        ];
+        assert_eq!(
+            assign_speakers(&segments),
+            vec![Some(0), None, Some(1), Some(0), Some(1)]
+        );
+
+        // 电平接近（或未测量）时按长停顿轮换。
+        let segments = vec![
+            SpeechSegment::new(0.0, 2.0, SegmentKind::Speech),
+            SpeechSegment::new(2.3, 4.0, SegmentKind::Speech),
+            SpeechSegment::new(6.0, 7.0, SegmentKind::Speech),
+            SpeechSegment::new(9.0, 10.0, SegmentKind::Speech),
+        ];
+        assert_eq!(
+            assign_speakers(&segments),
+            vec![Some(0), Some(0), Some(1), Some(0)]
+        );
+
+        let labels = vec![
+            Some("SPEAKER_01".to_string()),
+            None,
+            Some("SPEAKER_00".to_string()),
+            Some("SPEAKER_01".to_string()),
+        ];
+        assert_eq!(
+            speakers_from_labels(&labels),
+            vec![Some(0), None, Some(1), Some(0)]
+        );
+        assert_eq!(label_speaker("你好", Some(1)), "[说话人 2] 你好");
+        assert_eq!(label_speaker("你好", None), "你好");
+    }
+
+    #[tokio::test]
+    async fn vad_cache_is_reused_only_with_matching_key() {
+        let dir = scratch_dir("vad_cache");
+        let media = dir.join("talk.wav");
+        std::fs::write(&media, b"RIFF").unwrap();
+        let source = AudioSource::from_audio_file(media.clone());
+        let options = ScannerOptions::default();
+        let cfg = VadConfig::default();
+
+        let key = vad_cache_key(&source, &options, &cfg).await.unwrap();
+        let cache_path = vad_cache_path(&media, None);
+        assert_eq!(cache_path, dir.join("talk.wav.vadcache.json"));
+        let cache = VadCache {
+            key: key.clone(),
+            total_duration: 8.0,
+            segments: vec![SpeechSegment::new(1.0, 3.0, SegmentKind::Speech)],
+        };
+        save_vad_cache(&cache_path, &cache).await.unwrap();
+
+        let loaded = load_vad_cache(&cache_path, &key).await.unwrap();
+        assert_eq!(loaded.segments.len(), 1);
+        assert!((loaded.total_duration - 8.0).abs() < 1e-9);
+
+        let stricter = VadConfig {
+            threshold: 0.8,
+            ..VadConfig::default()
+        };
+        let other_key = vad_cache_key(&source, &options, &stricter).await.unwrap();
+        assert_ne!(other_key, key);
+        assert_eq!(vad_cache_key(&source, &options, &cfg).await.unwrap(), key);
+        assert_eq!(key.len(), 64);
+        assert!(load_vad_cache(&cache_path, &other_key).await.is_none());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn picks_text_subtitle_streams_from_ffprobe_output() {
+        let streams = parse_subtitle_streams("2,hdmv_pgs_subtitle\n3,subrip\n\nbad\n4,ass\n");
+        assert_eq!(
+            streams,
+            vec![
+                (2, "hdmv_pgs_subtitle".to_string()),
+                (3, "subrip".to_string()),
+                (4, "ass".to_string()),
+            ]
+        );
+        let first_text = streams
+            .iter()
+            .find(|(_, codec)| is_text_subtitle_codec(codec))
+            .map(|(index, _)| *index);
+        assert_eq!(first_text, Some(3));
+        assert!(!is_text_subtitle_codec("dvd_subtitle"));
+    }
+
+    #[test]
+    fn detects_sidecar_subtitles_by_suffix() {
+        let dir = scratch_dir("sidecar");
+        let media = dir.join("Movie.mkv");
+        std::fs::write(&media, b"").unwrap();
+        let suffixes = vec!["zh".to_string(), "chi".to_string()];
+        let lookup = |suffixes: &[String]| {
+            has_existing_subtitle(&media, suffixes, &[], &mut SubtitleListing::default())
+        };
+        assert_eq!(lookup(&suffixes), None);
+
+        std::fs::write(dir.join("Movie.en.srt"), b"1").unwrap();
+        std::fs::write(dir.join("Movie.2.chi.srt"), b"1").unwrap();
+        let mut listing = SubtitleListing::default();
+        assert_eq!(
+            has_existing_subtitle(&media, &suffixes, &[], &mut listing),
+            None
+        );
+
+        let sidecar = dir.join("movie.CHI.srt");
+        std::fs::write(&sidecar, b"1").unwrap();
+        assert_eq!(lookup(&suffixes), Some(sidecar));
+        assert_eq!(lookup(&[]), None);
+        // 同一次遍历中目录只列出一次，之后新增的文件不会再触发读取。
+        assert_eq!(
+            has_existing_subtitle(&media, &suffixes, &[], &mut listing),
+            None
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn own_language_named_results_are_not_sidecars() {
+        let dir = scratch_dir("own_sidecar");
+        let media = dir.join("movie.mkv");
+        std::fs::write(&media, b"").unwrap();
+        let streams = parse_audio_streams(
+            r#"{"streams": [
+                {"index": 1, "tags": {"language": "chi"}},
+                {"index": 2, "tags": {"language": "jpn"}}
+            ]}"#,
+        )
+        .unwrap();
+        let options = ScannerOptions {
+            name_tracks_by_language: true,
+            subtitle_suffixes: vec!["zh".to_string(), "chi".to_string()],
+            ..ScannerOptions::default()
+        };
+        let labels = track_labels(&streams, options.name_tracks_by_language);
+        let own: Vec<PathBuf> = labels
+            .values()
+            .map(|label| options.transcript_path(&media, Some(label)))
+            .collect();
+
+        // 只有中文音轨转写完成时，其结果 `movie.chi.srt` 不应让日语音轨被跳过。
+        std::fs::write(dir.join("movie.chi.srt"), b"1").unwrap();
+        let suffixes = &options.subtitle_suffixes;
+        assert_eq!(
+            has_existing_subtitle(&media, suffixes, &own, &mut SubtitleListing::default()),
+            None
+        );
+        let third_party = dir.join("movie.zh.srt");
+        std::fs::write(&third_party, b"1").unwrap();
+        assert_eq!(
+            has_existing_subtitle(&media, suffixes, &own, &mut SubtitleListing::default()),
+            Some(third_party)
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn eta_waits_for_enough_completed_targets() {
+        let elapsed = Duration::from_secs(90);
+        assert_eq!(average_secs_per_target(elapsed, 0), None);
+        assert_eq!(average_secs_per_target(elapsed, 1), None);
+        assert_eq!(average_secs_per_target(elapsed, 3), Some(30.0));
+    }
+
+    #[test]
+    fn size_limit_only_rejects_larger_files() {
+        let dir = scratch_dir("size_limit");
+        let file = dir.join("big.mp3");
+        std::fs::write(&file, vec![0u8; 1024 * 1024 + 1]).unwrap();
+        let mut options = ScannerOptions::default();
+        assert_eq!(oversized_mb(&file, &options), None);
+        options.max_file_size_mb = Some(2);
+        assert_eq!(oversized_mb(&file, &options), None);
+        options.max_file_size_mb = Some(1);
+        assert_eq!(oversized_mb(&file, &options), Some(2));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn vad_format_must_be_supported_pair() {
+        assert!(validate_vad_format(16_000, 512).is_ok());
+        assert!(validate_vad_format(8_000, 256).is_ok());
+        assert!(validate_vad_format(16_000, 256).is_err());
+        assert!(validate_vad_format(44_100, 1024).is_err());
+        // 旧版 Silero 模型的更大窗口不受支持，界面只列出 32 毫秒的两种组合。
+        assert!(validate_vad_format(16_000, 1536).is_err());
+        assert_eq!(VadFormat::find(16_000, 512), Some(VadFormat::ALL[0]));
+        assert_eq!(VadFormat::ALL[1].to_string(), "8000 Hz / 256");
+
+        let narrow = VadConfig {
+            sample_rate: 8_000,
+            chunk_size: 256,
+            ..VadConfig::default()
+        };
+        let wide = VadConfig::default();
+        assert_eq!(narrow.chunk_to_time(10), wide.chunk_to_time(10));
+        assert_eq!(
+            narrow.secs_to_chunks(2.0),
+            wide.secs_to_chunks(VAD_DEFAULT_MIN_SEGMENT_SECS)
+        );
+    }
+
+    #[test]
+    fn probed_stream_types_decide_media_kind() {
+        assert_eq!(parse_media_kind("video,0\naudio,0\n"), Some(true));
+        assert_eq!(parse_media_kind("audio,0\nvideo,1\n"), Some(false));
+        assert_eq!(parse_media_kind("audio\n"), Some(false));
+        assert_eq!(parse_media_kind("video,0\n"), None);
+        assert_eq!(parse_media_kind(""), None);
+    }
+
+    #[test]
+    fn srt_cues_round_trip_with_offset() {
+        let content = "1\r\n00:00:01,500 --> 00:00:03,000\r\n第一行\r\n第二行\r\n\r\n\
+                       2\n00:01:02.250 --> 00:01:04,000\n下一句\n\nbroken block\n";
+        let cues = parse_srt_cues(content);
+        assert_eq!(
+            cues,
+            vec![
+                (1.5, 3.0, "第一行\n第二行".to_string()),
+                (62.25, 64.0, "下一句".to_string()),
+            ]
+        );
+        assert_eq!(
+            build_srt_entry(7, cues[0].0 + 60.0, cues[0].1 + 60.0, &cues[0].2),
+            "7\n00:01:01,500 --> 00:01:03,000\n第一行\n第二行\n\n"
+        );
+        assert_eq!(parse_srt_timestamp("1:2"), None);
+    }
+
+    #[test]
+    fn combine_prefers_own_then_first_track_then_sidecar() {
+        let dir = scratch_dir("combine_pick");
+        let media = dir.join("ep01.mkv");
+        std::fs::write(&media, b"").unwrap();
+        let options = ScannerOptions {
+            subtitle_suffixes: vec!["zh".to_string()],
+            ..ScannerOptions::default()
+        };
+        assert_eq!(
+            find_combinable_srt(&media, &options, &mut SubtitleListing::default()),
+            None
+        );
+
+        std::fs::write(dir.join("ep01.zh.srt"), b"1").unwrap();
+        assert_eq!(
+            find_combinable_srt(&media, &options, &mut SubtitleListing::default()),
+            Some(dir.join("ep01.zh.srt"))
+        );
+        std::fs::write(dir.join("ep01.轨道3.srt"), b"1").unwrap();
+        std::fs::write(dir.join("ep01.轨道1.srt"), b"1").unwrap();
+        assert_eq!(
+            find_combinable_srt(&media, &options, &mut SubtitleListing::default()),
+            Some(dir.join("ep01.轨道1.srt"))
+        );
+        std::fs::write(dir.join("ep01.srt"), b"1").unwrap();
+        assert_eq!(
+            find_combinable_srt(&media, &options, &mut SubtitleListing::default()),
+            Some(dir.join("ep01.srt"))
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn stderr_tail_keeps_last_non_empty_lines() {
+        let stderr =
+            b"ffmpeg version 6\n\n  built with gcc\nInput #0\n[mp3 @ 0x1] Invalid data\r\n\n";
+        assert_eq!(
+            stderr_tail(stderr, 2).as_deref(),
+            Some("Input #0\n[mp3 @ 0x1] Invalid data")
+        );
+        assert_eq!(stderr_tail(b"\n  \n", 8), None);
+    }
+
+    #[test]
+    fn output_dir_mirrors_relative_paths() {
+        let media = Path::new("/nas/shows/s1/ep01.mkv");
+        let mut options = ScannerOptions::default();
+        assert_eq!(
+            options.transcript_path(media, None),
+            PathBuf::from("/nas/shows/s1/ep01.srt")
+        );
+
+        options.output_dir = Some(PathBuf::from("/data/subtitles"));
+        options.scan_root = Some(PathBuf::from("/nas/shows"));
+        assert_eq!(
+            options.transcript_path(media, Some("轨道2")),
+            PathBuf::from("/data/subtitles/s1/ep01.轨道2.srt")
+        );
+        assert_eq!(
+            options.relocate(vad_cache_path(media, None)),
+            PathBuf::from("/data/subtitles/s1/ep01.mkv.vadcache.json")
+        );
+        // 不在扫描根目录下的路径保持原样。
+        assert_eq!(
+            options.relocate(PathBuf::from("/other/a.srt")),
+            PathBuf::from("/other/a.srt")
+        );
+    }
+
+    #[tokio::test]
+    async fn segment_cache_matches_index_and_time_range() {
+        let dir = scratch_dir("segment_cache");
+        let srt = dir.join("talk.srt");
+        let cache_path = segment_cache_path(&srt);
+        assert_eq!(cache_path, dir.join("talk.srt.partial.jsonl"));
+
+        let cache = SegmentCache {
+            key: "abc".to_string(),
+            segments: vec![CachedSegment {
+                index: 1,
+                start_sec: 2.0,
+                end_sec: 4.5,
+                text: "你好".to_string(),
+            }],
+        };
+        save_segment_cache(&cache_path, &cache).await.unwrap();
+        let appended = CachedSegment {
+            index: 2,
+            start_sec: 4.5,
+            end_sec: 6.0,
+            text: "世界".to_string(),
+        };
+        append_segment_cache(&cache_path, &appended).await.unwrap();
+        // 模拟中断时写了一半的末行。
+        append_srt_entry(&cache_path, "{\"index\":3,")
+            .await
+            .unwrap();
+
+        let loaded = load_segment_cache(&cache_path, "abc").await;
+        let hit = SpeechSegment::new(2.0, 4.5, SegmentKind::Speech);
+        assert_eq!(loaded.lookup(1, &hit), Some("你好"));
+        let next = SpeechSegment::new(4.5, 6.0, SegmentKind::Speech);
+        assert_eq!(loaded.lookup(2, &next), Some("世界"));
+        assert_eq!(loaded.segments.len(), 2);
+        assert_eq!(loaded.lookup(0, &hit), None);
+        let shifted = SpeechSegment::new(2.0, 5.0, SegmentKind::Speech);
+        assert_eq!(loaded.lookup(1, &shifted), None);
+
+        let stale = load_segment_cache(&cache_path, "other").await;
+        assert!(stale.segments.is_empty());
+        assert_eq!(stale.key, "other");
+
+        // 同一分段方式下切换模型或接口地址，分段缓存键随之变化。
+        let mut options = ScannerOptions::default();
+        let key = segment_cache_key("abc", &options).unwrap();
+        options.model_name = "another-model".to_string();
+        let model_key = segment_cache_key("abc", &options).unwrap();
+        assert_ne!(key, model_key);
+        options.api_url = "https://example.invalid/v1".to_string();
+        assert_ne!(segment_cache_key("abc", &options).unwrap(), model_key);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn zero_timeouts_mean_no_limit() {
+        let mut options = ScannerOptions::default();
+        assert_eq!(options.request_timeout(), None);
+        assert_eq!(options.tool_timeout(), None);
+        options.request_timeout_secs = 90;
+        options.tool_timeout_secs = 30;
+        assert_eq!(options.request_timeout(), Some(Duration::from_secs(90)));
+        assert_eq!(options.tool_timeout(), Some(Duration::from_secs(30)));
+    }
+
+    #[test]
+    fn rate_limiter_spaces_requests_evenly() {
+        let limiter = RateLimiter::new(30);
+        let start = Instant::now();
+        assert_eq!(limiter.reserve(start), Duration::ZERO);
+        assert_eq!(limiter.reserve(start), Duration::from_secs(2));
+        assert_eq!(limiter.reserve(start), Duration::from_secs(4));
+        // 空闲足够久后不再需要等待。
+        let later = start + Duration::from_secs(60);
+        assert_eq!(limiter.reserve(later), Duration::ZERO);
+        assert_eq!(
+            limiter.reserve(later + Duration::from_millis(500)),
+            Duration::from_millis(1500)
+        );
+    }
+
+    #[test]
+    fn ffmpeg_concurrency_adds_separate_conversion_and_upload_slots() {
+        let mut options = ScannerOptions {
+            max_concurrency: 2,
+            ..ScannerOptions::default()
+        };
+        options.ensure_limiters();
+        assert_eq!(options.track_concurrency(), 2);
+        assert!(options.ffmpeg_limiter.is_none() && options.upload_limiter.is_none());
+
+        options.ffmpeg_concurrency = 4;
+        options.ensure_limiters();
+        assert_eq!(options.track_concurrency(), 6);
+        let permits =
+            |limiter: &Option<Arc<Semaphore>>| limiter.as_ref().unwrap().available_permits();
+        assert_eq!(permits(&options.ffmpeg_limiter), 4);
+        assert_eq!(permits(&options.upload_limiter), 2);
+
+        // 不同文件、音轨与分段的中间音频互不重名，可并发转码。
+        let paths = [
+            vad_audio_path(Path::new("/media/a.mkv"), Some(1)),
+            vad_audio_path(Path::new("/media/a.mkv"), Some(2)),
+            vad_audio_path(Path::new("/media/b.mkv"), Some(1)),
+            segment_audio_path(Path::new("/media/a.mkv"), Some(1), 1, "mp3"),
+            segment_audio_path(Path::new("/media/a.mkv"), Some(1), 2, "mp3"),
+            segment_audio_path(Path::new("/media/b.mkv"), Some(1), 1, "mp3"),
+            segment_audio_path(Path::new("/other/a.mkv"), Some(1), 1, "mp3"),
+        ];
+        for (idx, path) in paths.iter().enumerate() {
+            assert!(!paths[idx + 1..].contains(path), "{}", path.display());
+        }
+    }
+
+    #[test]
+    fn track_selection_filters_stream_indices() {
+        let stream = |index| AudioStream {
+            index,
+            language: None,
+            title: None,
+        };
+        let indices =
+            |streams: Vec<AudioStream>| streams.iter().map(|s| s.index).collect::<Vec<_>>();
+        let streams = vec![stream(1), stream(2), stream(4)];
+        assert_eq!(TrackSelection::parse("").unwrap(), TrackSelection::All);
+        assert_eq!(
+            indices(
+                TrackSelection::parse(" ALL ")
+                    .unwrap()
+                    .apply(streams.clone())
+            ),
+            vec![1, 2, 4]
+        );
+        assert_eq!(
+            indices(
+                TrackSelection::parse("first")
+                    .unwrap()
+                    .apply(vec![stream(4), stream(2)])
+            ),
+            vec![2]
+        );
+        assert_eq!(
+            TrackSelection::parse("4, 1,4,").unwrap(),
+            TrackSelection::Indices(vec![4, 1])
+        );
+        assert_eq!(
+            indices(TrackSelection::parse("4,1").unwrap().apply(streams.clone())),
+            vec![1, 4]
+        );
+        assert!(TrackSelection::parse("3")
+            .unwrap()
+            .apply(streams)
+            .is_empty());
+        for bad in ["second", "1;2", ",", "-1"] {
+            assert!(validate_track_selection(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn text_cleanup_applies_rules_in_order() {
+        let rule = |pattern: &str, replacement: &str| TextReplacement {
+            pattern: pattern.to_string(),
+            replacement: replacement.to_string(),
+        };
+        assert_eq!(
+            TextCleanup::default().apply("  你好，  世界。\r\n"),
+            "你好，  世界。"
+        );
+        let cleanup = TextCleanup::new(
+            true,
+            true,
+            &[rule("(嗯|呃)+[，,]?", ""), rule(r"(\d+)号", "第$1号")],
+        )
+        .unwrap();
+        assert_eq!(cleanup.apply("嗯，今天  是\n3号。"), "今天 是 第3号");
+        assert_eq!(cleanup.apply("嗯嗯，"), "");
+        assert_eq!(cleanup.apply("Hello world?!"), "Hello world");
+        assert!(validate_text_replacements(&[rule("(", "")]).is_err());
+
+        // 宽松模式只跳过无效的规则，其余规则仍然生效。
+        let lenient = TextCleanup::lenient(false, false, &[rule("(", ""), rule("呃", "")]);
+        assert_eq!(lenient.apply("呃好的"), "好的");
+        assert_eq!(lenient.skipped_rules().len(), 1);
+        assert!(lenient.skipped_rules()[0].contains("\"(\""));
+    }
+
+    #[test]
+    fn names_tracks_by_unique_language_tag() {
+        let json = r#"{"streams": [
+            {"index": 1, "tags": {"language": "jpn", "title": "Main"}},
+            {"index": 2, "tags": {"LANGUAGE": "eng"}},
+            {"index": 3, "tags": {"language": "jpn", "title": "Commentary"}},
+            {"index": 4, "tags": {"language": "und"}},
+            {"index": 5}
+        ]}"#;
+        let streams = parse_audio_streams(json).unwrap();
+        assert_eq!(streams.len(), 5);
+        assert_eq!(streams[1].language.as_deref(), Some("eng"));
+        assert_eq!(streams[2].describe_tags(), "（jpn · Commentary）");
+        assert_eq!(streams[4].describe_tags(), "");
+
+        let labels = track_labels(&streams, true);
+        assert_eq!(labels[&2], "eng");
+        // 语言重复或缺失时回退为音轨编号。
+        assert_eq!(labels[&1], "轨道1");
+        assert_eq!(labels[&3], "轨道3");
+        assert_eq!(labels[&4], "轨道4");
+        assert_eq!(labels[&5], "轨道5");
+        assert_eq!(track_labels(&streams, false)[&2], "轨道2");
+
+        let media = Path::new("/media/movie.mkv");
+        assert_eq!(
+            transcript_result_path(media, Some(labels[&2].as_str())),
+            PathBuf::from("/media/movie.eng.srt")
+        );
+        assert!(parse_audio_streams("not json").is_err());
+    }
+
+    #[test]
+    fn smoothing_window_suppresses_spikes_and_dips() {
+        let cfg = VadConfig {
+            threshold: 0.5,
+            min_speech_secs: 0.0,
+            padding_chunks: 0,
+            ..VadConfig::default()
+        };
+        // 第 2 块是孤立的噪声尖峰，第 17 块是语音中的短暂下探。
+        let mut probabilities = vec![0.1, 0.1, 0.95, 0.1, 0.1];
+        probabilities.extend([0.9; 26]);
+        probabilities[17] = 0.1;
+        probabilities.extend([0.1; 6]);
+
+        let raw = segments_from_probabilities(&smooth_probabilities(&probabilities, 1), &cfg);
+        assert_eq!(raw.len(), 2);
+
+        let smoothed = smooth_probabilities(&probabilities, 3);
+        assert_eq!(smoothed.len(), probabilities.len());
+        assert!((smoothed[0] - 0.1).abs() < 1e-6);
+        assert!(smoothed[2] < 0.5);
+        assert!(smoothed[17] > 0.5);
+        let segments = segments_from_probabilities(&smoothed, &cfg);
+        assert_eq!(segments.len(), 1);
+        let expected = SpeechSegment::from_chunks(5, 31, &cfg);
+        assert!((segments[0].start_sec - expected.start_sec).abs() < 1e-9);
+        assert!((segments[0].end_sec - expected.end_sec).abs() < 1e-9);
+    }
+
+    #[test]
+    fn duration_falls_back_when_format_reports_na() {
+        assert_eq!(parse_format_duration("N/A\n"), None);
+        assert_eq!(parse_format_duration("12.500000\n"), Some(12.5));
+
+        assert_eq!(parse_max_duration("N/A\nN/A\n"), None);
+        assert_eq!(parse_max_duration("N/A\n61.2\n60.9\n"), Some(61.2));
+
+        let packets = "0.000000,0.021333\n0.021333,0.021333\n95.978667,0.021333\nN/A,N/A\n";
+        let end = parse_packet_end(packets).unwrap();
+        assert!((end - 96.0).abs() < 1e-6);
+        assert_eq!(parse_packet_end("N/A,N/A\n"), None);
+    }
+
+    #[tokio::test]
+    async fn pause_token_blocks_until_resumed_or_cancelled() {
+        let pause = PauseToken::default();
+        let cancel = CancellationToken::new();
+        assert!(pause.wait_if_paused(&cancel).await.is_ok());
+
+        pause.pause();
+        let waiter = {
+            let (pause, cancel) = (pause.clone(), cancel.clone());
+            tokio::spawn(async move { pause.wait_if_paused(&cancel).await })
+        };
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!waiter.is_finished());
+        pause.resume();
+        assert!(waiter.await.unwrap().is_ok());
+
+        pause.pause();
+        let waiter = {
+            let (pause, cancel) = (pause.clone(), cancel.clone());
+            tokio::spawn(async move { pause.wait_if_paused(&cancel).await })
+        };
+        cancel.cancel();
+        assert!(is_cancelled(&waiter.await.unwrap().unwrap_err()));
+    }
+
+    #[tokio::test]
+    async fn include_patterns_that_all_fail_to_compile_abort_the_scan() {
+        assert!(validate_include_patterns(&[]).is_ok());
+        assert!(validate_include_patterns(&["  ".to_string()]).is_ok());
+        assert!(validate_include_patterns(&["a{".to_string(), "*.mp3".to_string()]).is_ok());
+        assert!(validate_include_patterns(&["a{".to_string()]).is_err());
+
+        let dir = scratch_dir("include_all_invalid");
+        std::fs::write(dir.join("a.mp3"), b"").unwrap();
+        let options = ScannerOptions {
+            dry_run: true,
+            include_patterns: vec!["*.{mp3".to_string()],
+            ..ScannerOptions::default()
+        };
+        assert!(process_directories(vec![dir.clone()], options, None)
+            .await
+            .is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn include_patterns_limit_scan_before_excludes() {
+        let dir = scratch_dir("include_patterns");
+        std::fs::create_dir_all(dir.join("s1")).unwrap();
+        for name in ["a.interview.mp3", "s1/b.interview.mp3", "s1/c.mp3", "d.mp3"] {
+            std::fs::write(dir.join(name), b"").unwrap();
+        }
+        let options = ScannerOptions {
+            dry_run: true,
+            include_patterns: vec!["*.interview.mp3".to_string()],
+            exclude_patterns: vec!["s1/**".to_string()],
+            ..ScannerOptions::default()
+        };
+        let report = process_directory(dir.clone(), options, None).await.unwrap();
+        assert_eq!(report.pending, 1);
+        assert!(report.logs.iter().any(|log| log
+            .message
+            .contains("包含规则匹配了 2 个媒体文件，另有 2 个未匹配")));
+        assert!(report.logs.iter().any(
+            |log| log.message.contains("待转写音频") && log.message.contains("a.interview.mp3")
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn final_partial_chunk_uses_real_samples_and_stays_in_bounds() {
+        assert_eq!(chunk_ranges(1024, 512), vec![0..512, 512..1024]);
+        // 1300 不能被 512 整除：最后一块取末尾 512 个采样，而不是 1024..1300 补零。
+        assert_eq!(chunk_ranges(1300, 512), vec![0..512, 512..1024, 788..1300]);
+        // 不足一块时只能补零。
+        assert_eq!(chunk_ranges(300, 512), vec![0..300]);
+        assert!(chunk_ranges(0, 512).is_empty());
+
+        let cfg = VadConfig::default();
+        let total = 1300.0 / cfg.sample_rate as f64;
+        let segments = vec![
+            SpeechSegment::from_chunks(0, 2, &cfg),
+            SpeechSegment::from_chunks(2, 3, &cfg),
+        ];
+        assert!(segments[1].end_sec > total);
+        let clamped = clamp_segments_to_duration(segments, total);
+        assert_eq!(clamped.len(), 2);
+        assert!((clamped[1].end_sec - total).abs() < 1e-9);
+        assert!((clamped[0].end_sec - 1024.0 / 16_000.0).abs() < 1e-9);
+
+        // 完全落在音频之外的分段被丢弃。
+        let beyond = vec![SpeechSegment::new(total, total + 0.5, SegmentKind::Speech)];
+        assert!(clamp_segments_to_duration(beyond, total).is_empty());
+    }
+
+    #[test]
+    fn muxing_picks_codec_by_container_and_skips_muxed_outputs() {
+        assert_eq!(
+            subtitle_codec_for_container(Path::new("a.MKV")),
+            Some("srt")
+        );
+        assert_eq!(
+            subtitle_codec_for_container(Path::new("a.mp4")),
+            Some("mov_text")
+        );
+        assert_eq!(
+            subtitle_codec_for_container(Path::new("a.webm")),
+            Some("webvtt")
+        );
+        assert_eq!(subtitle_codec_for_container(Path::new("a.avi")), None);
+
+        let muxed = tagged_video_path(Path::new("/media/ep.01.mp4"), MUXED_VIDEO_TAG);
+        assert_eq!(muxed, PathBuf::from("/media/ep.01.subtitled.mp4"));
+        assert!(is_muxed_video(&muxed));
+        assert!(is_muxed_video(Path::new("/media/ep.01.muxing.mkv")));
+        assert!(!is_watchable_media(&muxed));
+        assert!(!is_muxed_video(Path::new("/media/ep.01.mp4")));
+        assert!(!is_muxed_video(Path::new("/media/ep.subtitled.srt")));
+    }
+
+    #[test]
+    fn vad_preview_lists_segments_with_totals() {
+        let preview = VadPreview {
+            path: PathBuf::from("talk.mp3"),
+            analyzed_secs: 60.0,
+            threshold: 0.55,
+            segments: vec![(1.0, 3.5), (10.0, 12.0)],
+        };
+        assert_eq!(
+            preview.describe(),
+            vec![
+                "VAD 预览 talk.mp3（前 60 秒，阈值 0.55）：检测到 2 段语音，共 4.5 秒。"
+                    .to_string(),
+                "  1. 00:01.000 - 00:03.500（2.5 秒）".to_string(),
+                "  2. 00:10.000 - 00:12.000（2.0 秒）".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn derived_paths_keep_cjk_names_with_spaces() {
+        let media = Path::new("/媒体 文件/第一集 会议 记录.mp4");
+        assert_eq!(
+            transcript_result_path(media, None),
+            PathBuf::from("/媒体 文件/第一集 会议 记录.srt")
+        );
+        assert_eq!(
+            transcript_result_path(media, Some("轨道2")),
+            PathBuf::from("/媒体 文件/第一集 会议 记录.轨道2.srt")
+        );
+        let segment = segment_audio_path(media, Some(1), 3, "mp3");
+        assert_eq!(
+            segment,
+            PathBuf::from("/媒体 文件/第一集 会议 记录.mp4-track1-seg3.mp3")
+        );
+        assert!(is_intermediate_audio(&segment));
+        assert_eq!(
+            display_path(&segment),
+            "/媒体 文件/第一集 会议 记录.mp4-track1-seg3.mp3"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn derived_paths_preserve_non_utf8_names() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let media = Path::new(OsStr::from_bytes(b"/media/caf\xe9.mp3"));
+        let srt = transcript_result_path(media, None);
+        assert_eq!(srt.as_os_str().as_bytes(), b"/media/caf\xe9.srt");
+        let vad = vad_audio_path(media, None);
+        assert_eq!(vad.as_os_str().as_bytes(), b"/media/caf\xe9.mp3-vad.wav");
+        assert_eq!(display_path(media), "/media/caf\u{fffd}.mp3");
+    }
+
+    #[test]
+    fn wrapped_api_errors_keep_their_category() {
+        let unauthorized = ApiError::Unauthorized {
+            message: "API 密钥无效".to_string(),
+        };
+        let err = api_failure(
+            unauthorized,
+            "调用 API 转写 a.mp3 失败：API 密钥无效".to_string(),
+        );
+        assert_eq!(err.to_string(), "调用 API 转写 a.mp3 失败：API 密钥无效");
+        assert!(is_auth_failure(&err));
+
+        let parse = api_failure(ApiError::Parse("bad".to_string()), "bad".to_string());
+        assert!(!is_auth_failure(&parse));
+        assert!(!is_auth_failure(&anyhow!("API 密钥无效")));
+    }
+
+    #[test]
+    fn failure_streak_aborts_on_auth_errors_and_repeated_failures() {
+        let network = api_failure(
+            ApiError::Parse("连接超时".to_string()),
+            "调用 API 转写 a.mp3 失败：连接超时".to_string(),
+        );
+        let corrupt = anyhow!("ffprobe 解析音轨失败");
+        let auth = api_failure(
+            ApiError::Unauthorized {
+                message: "API 密钥无效".to_string(),
+            },
+            "调用 API 转写 a.mp3 失败：API 密钥无效".to_string(),
+        );
+
+        let mut unlimited = FailureStreak::new(0);
+        for _ in 0..10 {
+            assert_eq!(unlimited.record(Some(&network)), None);
+        }
+        assert!(unlimited.record(Some(&auth)).unwrap().contains("认证失败"));
+
+        let mut streak = FailureStreak::new(3);
+        for _ in 0..5 {
+            assert_eq!(streak.record(Some(&corrupt)), None);
+        }
+        assert_eq!(streak.record(Some(&network)), None);
+        assert_eq!(streak.record(Some(&network)), None);
+        assert_eq!(streak.record(None), None);
+        assert_eq!(streak.record(Some(&network)), None);
+        assert_eq!(streak.record(Some(&network)), None);
+        assert_eq!(streak.record(Some(&corrupt)), None);
+        assert!(streak
+            .record(Some(&network))
+            .unwrap()
+            .contains("连续 3 个音轨转写失败"));
+    }
+
+    #[test]
+    fn fixed_interval_segments_cover_the_whole_duration() {
+        let bounds = |segments: Vec<SpeechSegment>| -> Vec<(f64, f64)> {
+            segments
+                .iter()
+                .map(|seg| (seg.start_sec, seg.end_sec))
+                .collect()
+        };
+        assert_eq!(
+            bounds(fixed_interval_segments(70.0, 30.0)),
+            vec![(0.0, 30.0), (30.0, 60.0), (60.0, 70.0)]
+        );
+        // 不足 1 秒的末尾并入前一段。
+        assert_eq!(
+            bounds(fixed_interval_segments(60.5, 30.0)),
+            vec![(0.0, 30.0), (30.0, 60.5)]
+        );
+        assert_eq!(
+            bounds(fixed_interval_segments(12.0, 30.0)),
+            vec![(0.0, 12.0)]
+        );
+        assert!(fixed_interval_segments(0.0, 30.0).is_empty());
+        assert!(fixed_interval_segments(10.0, 0.0).is_empty());
+    }
+
+    #[test]
+    fn cost_summary_combines_directory_reports() {
+        let mut report = RunReport::default();
+        assert_eq!(report.cost_summary(), None);
+
+        report.absorb(RunReport {
+            estimated_cost: Some(1.2),
+            ..RunReport::default()
+        });
+        assert_eq!(report.cost_summary().as_deref(), Some("预估费用 1.20"));
+
+        report.absorb(RunReport {
+            estimated_cost: Some(0.3),
+            actual_cost: Some(1.25),
+            ..RunReport::default()
+        });
+        assert_eq!(
+            report.cost_summary().as_deref(),
+            Some("预估费用 1.50，实际费用 1.25")
+        );
+    }
 }
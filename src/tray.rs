@@ -0,0 +1,158 @@
+//! 系统托盘图标，提供“显示窗口”“立即扫描”“退出”菜单并以颜色提示扫描状态。
+//!
+//! Linux 上的 `tray-icon` 需要独立的 GTK 主循环，无法与 Iced 的事件循环共存，
+//! 因此托盘仅在 Windows 与 macOS 上启用，其余平台 [`SUPPORTED`] 为 `false`。
+
+/// 托盘菜单或图标触发的操作。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(not(any(windows, target_os = "macos")), allow(dead_code))]
+pub enum TrayAction {
+    ShowWindow,
+    ScanNow,
+    Quit,
+}
+
+/// 当前平台是否支持系统托盘。
+pub const SUPPORTED: bool = cfg!(any(windows, target_os = "macos"));
+
+pub use imp::Tray;
+
+#[cfg(any(windows, target_os = "macos"))]
+mod imp {
+    use super::TrayAction;
+    use crate::tr;
+    use anyhow::Result;
+    use tray_icon::{
+        menu::{Menu, MenuEvent, MenuId, MenuItem},
+        Icon, MouseButton, TrayIcon, TrayIconBuilder, TrayIconEvent,
+    };
+
+    /// 托盘图标边长（像素）。
+    const ICON_SIZE: u32 = 32;
+
+    /// 已创建的托盘图标及其菜单项 ID。
+    pub struct Tray {
+        icon: TrayIcon,
+        show_id: MenuId,
+        scan_id: MenuId,
+        quit_id: MenuId,
+        busy: bool,
+    }
+
+    impl Tray {
+        /// 创建托盘图标与菜单，需在主线程（事件循环所在线程）调用。
+        pub fn new() -> Result<Self> {
+            let show = MenuItem::new(tr!("显示窗口"), true, None);
+            let scan = MenuItem::new(tr!("立即扫描"), true, None);
+            let quit = MenuItem::new(tr!("退出"), true, None);
+            let menu = Menu::new();
+            menu.append_items(&[&show, &scan, &quit])?;
+
+            let icon = TrayIconBuilder::new()
+                .with_menu(Box::new(menu))
+                .with_tooltip("AutoASR")
+                .with_icon(status_icon(false)?)
+                .build()?;
+
+            Ok(Self {
+                icon,
+                show_id: show.id().clone(),
+                scan_id: scan.id().clone(),
+                quit_id: quit.id().clone(),
+                busy: false,
+            })
+        }
+
+        /// 根据是否正在扫描切换图标颜色与提示文字，状态未变时不做任何事；
+        /// 更新失败只报告一次，直到状态再次变化。
+        pub fn set_busy(&mut self, busy: bool) -> Result<()> {
+            if self.busy == busy {
+                return Ok(());
+            }
+            self.busy = busy;
+            self.icon.set_icon(Some(status_icon(busy)?))?;
+            let tooltip = if busy {
+                tr!("AutoASR - 正在扫描")
+            } else {
+                "AutoASR"
+            };
+            self.icon.set_tooltip(Some(tooltip))?;
+            Ok(())
+        }
+
+        /// 取出一条待处理的托盘操作，没有时返回 `None`。
+        pub fn poll(&self) -> Option<TrayAction> {
+            if let Ok(event) = MenuEvent::receiver().try_recv() {
+                if event.id == self.show_id {
+                    return Some(TrayAction::ShowWindow);
+                }
+                if event.id == self.scan_id {
+                    return Some(TrayAction::ScanNow);
+                }
+                if event.id == self.quit_id {
+                    return Some(TrayAction::Quit);
+                }
+            }
+            // 双击托盘图标等同于“显示窗口”，其余鼠标事件直接丢弃。
+            while let Ok(event) = TrayIconEvent::receiver().try_recv() {
+                if let TrayIconEvent::DoubleClick {
+                    button: MouseButton::Left,
+                    ..
+                } = event
+                {
+                    return Some(TrayAction::ShowWindow);
+                }
+            }
+            None
+        }
+    }
+
+    /// 生成纯色圆形图标：空闲时为蓝色，扫描中为橙色。
+    fn status_icon(busy: bool) -> Result<Icon> {
+        let (r, g, b) = if busy {
+            (0xF5, 0x8A, 0x1F)
+        } else {
+            (0x2D, 0x7D, 0xD2)
+        };
+        let center = (ICON_SIZE as f32 - 1.0) / 2.0;
+        let radius = ICON_SIZE as f32 / 2.0 - 1.0;
+        let mut rgba = Vec::with_capacity((ICON_SIZE * ICON_SIZE * 4) as usize);
+        for y in 0..ICON_SIZE {
+            for x in 0..ICON_SIZE {
+                let dx = x as f32 - center;
+                let dy = y as f32 - center;
+                let alpha = if dx * dx + dy * dy <= radius * radius {
+                    0xFF
+                } else {
+                    0
+                };
+                rgba.extend_from_slice(&[r, g, b, alpha]);
+            }
+        }
+        Ok(Icon::from_rgba(rgba, ICON_SIZE, ICON_SIZE)?)
+    }
+}
+
+#[cfg(not(any(windows, target_os = "macos")))]
+mod imp {
+    use super::TrayAction;
+    use crate::tr;
+    use anyhow::{bail, Result};
+
+    /// 不支持托盘的平台上的占位实现。
+    pub struct Tray;
+
+    impl Tray {
+        pub fn new() -> Result<Self> {
+            bail!(tr!("当前平台暂不支持系统托盘"))
+        }
+
+        pub fn set_busy(&mut self, _busy: bool) -> Result<()> {
+            Ok(())
+        }
+
+        pub fn poll(&self) -> Option<TrayAction> {
+            None
+        }
+    }
+}
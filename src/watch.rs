@@ -0,0 +1,156 @@
+//! 监视目录中新出现或被修改的媒体文件，等文件大小稳定（写入完成）后交给扫描器处理。
+
+use crate::scanner::{display_path, is_watchable_media};
+use crate::tr;
+use futures::{never::Never, SinkExt};
+use iced::{subscription, Subscription};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+/// 检查待处理文件大小的间隔。
+const WATCH_POLL_INTERVAL: Duration = Duration::from_secs(1);
+/// 文件大小保持不变多久后视为写入完成。
+const WATCH_STABLE_FOR: Duration = Duration::from_secs(5);
+
+/// 监视订阅上报给界面的事件。
+#[derive(Debug, Clone)]
+pub enum WatchEvent {
+    /// 一批已写入完成、可以转写的媒体文件。
+    Ready(Vec<PathBuf>),
+    /// 无法开始监视，附带原因。
+    Failed(String),
+}
+
+/// 监视 `dir`（含子目录），目录不变时 Iced 会复用同一个订阅。
+pub fn watch_directory(dir: PathBuf) -> Subscription<WatchEvent> {
+    subscription::channel(("watch", dir.clone()), 16, move |mut output| async move {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let watcher = RecommendedWatcher::new(
+            move |res| {
+                let _ = tx.send(res);
+            },
+            notify::Config::default(),
+        )
+        .and_then(|mut watcher| {
+            watcher
+                .watch(&dir, RecursiveMode::Recursive)
+                .map(|_| watcher)
+        });
+        // 监视器需要在订阅存活期间一直持有，丢弃即停止监视。
+        let _watcher = match watcher {
+            Ok(watcher) => watcher,
+            Err(e) => {
+                let _ = output
+                    .send(WatchEvent::Failed(tr!(
+                        "监视目录 {} 失败：{}",
+                        display_path(&dir),
+                        e
+                    )))
+                    .await;
+                match futures::future::pending::<Never>().await {}
+            }
+        };
+
+        let mut pending = PendingFiles::default();
+        let mut ticker = tokio::time::interval(WATCH_POLL_INTERVAL);
+        loop {
+            tokio::select! {
+                Some(res) = rx.recv() => {
+                    let Ok(event) = res else { continue };
+                    if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+                        for path in event.paths {
+                            if is_watchable_media(&path) {
+                                pending.touch(path, Instant::now());
+                            }
+                        }
+                    }
+                }
+                _ = ticker.tick() => {
+                    let ready = pending.take_stable(Instant::now());
+                    if !ready.is_empty() {
+                        let _ = output.send(WatchEvent::Ready(ready)).await;
+                    }
+                }
+            }
+        }
+    })
+}
+
+/// 等待写入完成的文件：记录最近一次观察到的大小以及大小最后变化的时间。
+#[derive(Default)]
+struct PendingFiles {
+    files: HashMap<PathBuf, (Option<u64>, Instant)>,
+}
+
+impl PendingFiles {
+    /// 收到文件事件时重新计时，持续写入的文件会一直留在等待列表中。
+    fn touch(&mut self, path: PathBuf, now: Instant) {
+        self.files.insert(path, (None, now));
+    }
+
+    /// 取出大小已稳定 `WATCH_STABLE_FOR` 的文件；已被删除的文件直接丢弃。
+    fn take_stable(&mut self, now: Instant) -> Vec<PathBuf> {
+        let mut ready = Vec::new();
+        self.files.retain(|path, (size, changed_at)| {
+            let Ok(current) = std::fs::metadata(path).map(|meta| meta.len()) else {
+                return false;
+            };
+            if *size != Some(current) {
+                *size = Some(current);
+                *changed_at = now;
+                return true;
+            }
+            if now.duration_since(*changed_at) >= WATCH_STABLE_FOR {
+                ready.push(path.clone());
+                return false;
+            }
+            true
+        });
+        ready.sort();
+        ready
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn files_are_ready_only_after_size_stays_unchanged() {
+        let dir = std::env::temp_dir().join(format!("auto_asr_watch_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let media = dir.join("drop.mp3");
+        std::fs::write(&media, b"abc").unwrap();
+
+        let start = Instant::now();
+        let mut pending = PendingFiles::default();
+        pending.touch(media.clone(), start);
+        // 第一次检查只记录大小。
+        assert!(pending.take_stable(start).is_empty());
+
+        // 文件仍在增长：重新计时。
+        std::fs::write(&media, b"abcdef").unwrap();
+        let later = start + WATCH_STABLE_FOR;
+        assert!(pending.take_stable(later).is_empty());
+
+        assert!(pending
+            .take_stable(later + Duration::from_secs(1))
+            .is_empty());
+        assert_eq!(
+            pending.take_stable(later + WATCH_STABLE_FOR),
+            vec![media.clone()]
+        );
+        assert!(pending.take_stable(later + WATCH_STABLE_FOR * 2).is_empty());
+
+        // 等待期间被删除的文件直接丢弃。
+        pending.touch(dir.join("gone.mp3"), start);
+        assert!(pending.take_stable(start).is_empty());
+        assert!(pending.files.is_empty());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}